@@ -67,9 +67,34 @@ fn build_command() -> Command {
         .arg(
             Arg::new("input")
                 .value_parser(value_parser!(String))
-                .required_unless_present("module_names")
+                .required_unless_present_any(["module_names", "save_compiled_rules"])
                 .help("File or directory to scan"),
         )
+        .arg(
+            Arg::new("compiled_rules")
+                .short('C')
+                .long("compiled-rules")
+                .action(ArgAction::SetTrue)
+                .help("Load compiled rules")
+                .long_help(
+                    "Load compiled rules.\n\
+                     The rules file must have been generated with --save-compiled-rules.",
+                ),
+        )
+        .arg(
+            Arg::new("save_compiled_rules")
+                .long("save-compiled-rules")
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with("compiled_rules")
+                .help("Save the compiled rules in the given file")
+                .long_help(
+                    "Save the compiled rules in the given file.\n\
+                     The file can then be used with --compiled-rules to avoid \
+                     compiling the rules again.\n\
+                     If no input is given, the program exits after saving the rules.",
+                ),
+        )
         .arg(
             Arg::new("define")
                 .short('d')
@@ -213,7 +238,30 @@ fn build_command() -> Command {
                 .long("scan-list")
                 .action(ArgAction::SetTrue)
                 .help("Scan files listed in input, each line is a path to a file or directory"),
-        );
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about("Format rules files")
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .action(ArgAction::SetTrue)
+                        .help("Do not modify the files, list the ones that are not formatted")
+                        .long_help(
+                            "Do not modify the files, list the ones that are not formatted.\n\
+                             The command fails if at least one file is not formatted.",
+                        ),
+                )
+                .arg(
+                    Arg::new("files")
+                        .value_parser(value_parser!(PathBuf))
+                        .action(ArgAction::Append)
+                        .required(true)
+                        .help("Path to the yara files to format"),
+                ),
+        )
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true);
 
     if cfg!(feature = "memmap") {
         command = command.arg(
@@ -236,6 +284,10 @@ fn build_command() -> Command {
 fn main() -> ExitCode {
     let mut args = build_command().get_matches();
 
+    if let Some(("fmt", fmt_args)) = args.subcommand() {
+        return format_files(fmt_args);
+    }
+
     if args.get_flag("module_names") {
         let compiler = Compiler::new();
 
@@ -270,38 +322,58 @@ fn main() -> ExitCode {
             },
         )));
 
-        compiler.set_params(
-            boreal::compiler::CompilerParams::default()
-                .fail_on_warnings(args.get_flag("fail_on_warnings"))
-                .compute_statistics(args.get_flag("string_statistics")),
-        );
-
-        if let Some(defines) = args.remove_many::<(String, ExternalValue)>("define") {
-            for (name, value) in defines {
-                compiler.define_symbol(name, value);
+        if args.get_flag("compiled_rules") {
+            match load_compiled_rules(&rules_file, compiler, &mut args) {
+                Ok(scanner) => scanner,
+                Err(err) => {
+                    eprintln!("{err}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        } else {
+            compiler.set_params(
+                boreal::compiler::CompilerParams::default()
+                    .fail_on_warnings(args.get_flag("fail_on_warnings"))
+                    .compute_statistics(args.get_flag("string_statistics")),
+            );
+
+            if let Some(defines) = args.remove_many::<(String, ExternalValue)>("define") {
+                for (name, value) in defines {
+                    compiler.define_symbol(name, value);
+                }
             }
-        }
 
-        match compiler.add_rules_file(&rules_file) {
-            Ok(status) => {
-                if !args.get_flag("no_warnings") {
-                    for warn in status.warnings() {
-                        display_diagnostic(&rules_file, warn);
+            match compiler.add_rules_file(&rules_file) {
+                Ok(status) => {
+                    if !args.get_flag("no_warnings") {
+                        for warn in status.warnings() {
+                            display_diagnostic(&rules_file, warn);
+                        }
+                    }
+                    for rule_stat in status.statistics() {
+                        display_rule_stats(rule_stat);
                     }
                 }
-                for rule_stat in status.statistics() {
-                    display_rule_stats(rule_stat);
+                Err(err) => {
+                    display_diagnostic(&rules_file, &err);
+                    return ExitCode::FAILURE;
                 }
             }
-            Err(err) => {
-                display_diagnostic(&rules_file, &err);
-                return ExitCode::FAILURE;
-            }
-        }
 
-        compiler.into_scanner()
+            compiler.into_scanner()
+        }
     };
 
+    if let Some(path) = args.get_one::<PathBuf>("save_compiled_rules") {
+        if let Err(err) = std::fs::write(path, scanner.serialize()) {
+            eprintln!("Cannot save compiled rules to {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+        if !args.contains_id("input") {
+            return ExitCode::SUCCESS;
+        }
+    }
+
     let scan_options = ScanOptions::new(&args);
 
     let mut scan_params = scan_params_from_args(&args);
@@ -356,6 +428,68 @@ fn main() -> ExitCode {
     }
 }
 
+fn format_files(args: &ArgMatches) -> ExitCode {
+    let check = args.get_flag("check");
+    let mut success = true;
+
+    for path in args.get_many::<PathBuf>("files").unwrap() {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(v) => v,
+            Err(err) => {
+                eprintln!("Cannot read {}: {}", path.display(), err);
+                success = false;
+                continue;
+            }
+        };
+        let formatted = match boreal_parser::printer::format(&contents) {
+            Ok(v) => v,
+            Err(err) => {
+                display_parse_error(path, &contents, &err);
+                success = false;
+                continue;
+            }
+        };
+
+        if formatted == contents {
+            continue;
+        }
+        if check {
+            println!("{}", path.display());
+            success = false;
+        } else if let Err(err) = std::fs::write(path, formatted) {
+            eprintln!("Cannot write {}: {}", path.display(), err);
+            success = false;
+        }
+    }
+
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn load_compiled_rules(
+    path: &Path,
+    compiler: Compiler,
+    args: &mut ArgMatches,
+) -> Result<Scanner, String> {
+    let contents = std::fs::read(path)
+        .map_err(|err| format!("Cannot read compiled rules {}: {}", path.display(), err))?;
+    let mut scanner = Scanner::deserialize(&contents, compiler)
+        .map_err(|err| format!("Cannot load compiled rules {}: {}", path.display(), err))?;
+
+    if let Some(defines) = args.remove_many::<(String, ExternalValue)>("define") {
+        for (name, value) in defines {
+            scanner
+                .define_symbol(&name, value)
+                .map_err(|err| format!("Cannot define symbol {name}: {err}"))?;
+        }
+    }
+
+    Ok(scanner)
+}
+
 #[derive(Debug)]
 enum Input {
     Directory(PathBuf),
@@ -734,6 +868,17 @@ fn display_diagnostic(path: &Path, err: &boreal::compiler::AddRuleError) {
     }
 }
 
+fn display_parse_error(path: &Path, contents: &str, err: &boreal_parser::error::Error) {
+    let writer = StandardStream::stderr(ColorChoice::Auto);
+    let config = term::Config::default();
+
+    let files = SimpleFile::new(path.display().to_string(), contents);
+    let writer = &mut writer.lock();
+    if let Err(e) = term::emit(writer, &config, &files, &err.to_diagnostic()) {
+        eprintln!("cannot emit diagnostics: {e}");
+    }
+}
+
 fn display_rule_stats(stats: &statistics::CompiledRule) {
     print!(
         "{}:{}",
@@ -1195,6 +1195,139 @@ rule bad_symbols {
         .failure();
 }
 
+#[test]
+fn test_compiled_rules() {
+    let rule_file = test_file(
+        br#"
+import "math"
+
+rule is_a {
+    condition:
+        sym == "a" and math.to_number(true) == 1
+}
+
+rule my_rule {
+    strings:
+        $a = "abc"
+    condition:
+        any of them
+}"#,
+    );
+    let temp_dir = TempDir::new().unwrap();
+    let compiled_path = temp_dir.path().join("compiled");
+
+    // Only save the rules
+    cmd()
+        .arg("-d")
+        .arg("sym=a")
+        .arg("--save-compiled-rules")
+        .arg(&compiled_path)
+        .arg(rule_file.path())
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    let input = test_file(b"zeabce");
+    let path = input.path().display();
+
+    // Load compiled rules, with the symbol saved
+    cmd()
+        .arg("-C")
+        .arg(&compiled_path)
+        .arg(input.path())
+        .assert()
+        .stdout(format!("is_a {path}\nmy_rule {path}\n"))
+        .stderr("")
+        .success();
+
+    // The symbol can be redefined
+    cmd()
+        .arg("--compiled-rules")
+        .arg("-d")
+        .arg("sym=b")
+        .arg(&compiled_path)
+        .arg(input.path())
+        .assert()
+        .stdout(format!("my_rule {path}\n"))
+        .stderr("")
+        .success();
+
+    // But not with another type, or if it is not defined in the rules
+    cmd()
+        .arg("-C")
+        .arg("-d")
+        .arg("sym=3")
+        .arg(&compiled_path)
+        .arg(input.path())
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains("Cannot define symbol sym"))
+        .failure();
+    cmd()
+        .arg("-C")
+        .arg("-d")
+        .arg("other=3")
+        .arg(&compiled_path)
+        .arg(input.path())
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains("Cannot define symbol other"))
+        .failure();
+
+    // Save and scan at the same time
+    let compiled_path2 = temp_dir.path().join("compiled2");
+    cmd()
+        .arg("-d")
+        .arg("sym=a")
+        .arg("--save-compiled-rules")
+        .arg(&compiled_path2)
+        .arg(rule_file.path())
+        .arg(input.path())
+        .assert()
+        .stdout(format!("is_a {path}\nmy_rule {path}\n"))
+        .stderr("")
+        .success();
+    assert_eq!(
+        fs::read(&compiled_path).unwrap(),
+        fs::read(&compiled_path2).unwrap()
+    );
+
+    // Loading a rules file that is not compiled fails
+    cmd()
+        .arg("-C")
+        .arg(rule_file.path())
+        .arg(input.path())
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains(format!(
+            "Cannot load compiled rules {}: invalid header",
+            rule_file.path().display()
+        )))
+        .failure();
+
+    // As well as a file that does not exist
+    cmd()
+        .arg("-C")
+        .arg(temp_dir.path().join("do_not_exist"))
+        .arg(input.path())
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains("Cannot read compiled rules"))
+        .failure();
+
+    // Cannot save rules that are already compiled
+    cmd()
+        .arg("-C")
+        .arg("--save-compiled-rules")
+        .arg(&compiled_path2)
+        .arg(&compiled_path)
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains("cannot be used with"))
+        .failure();
+}
+
 #[test]
 fn test_scan_list() {
     let rule_file = test_file(b"rule a { condition: true }");
@@ -1306,6 +1439,70 @@ fn test_scan_list() {
     }
 }
 
+#[test]
+fn test_fmt() {
+    let formatted = "rule a\n{\n    condition:\n        true\n}\n";
+    let unformatted = test_file(b"rule a { condition: true }");
+    let valid = test_file(formatted.as_bytes());
+
+    // Check mode lists the unformatted files, without modifying them
+    cmd()
+        .arg("fmt")
+        .arg("--check")
+        .arg(unformatted.path())
+        .arg(valid.path())
+        .assert()
+        .stdout(format!("{}\n", unformatted.path().display()))
+        .stderr("")
+        .failure();
+    assert_eq!(
+        fs::read_to_string(unformatted.path()).unwrap(),
+        "rule a { condition: true }"
+    );
+
+    cmd()
+        .arg("fmt")
+        .arg(unformatted.path())
+        .arg(valid.path())
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    assert_eq!(fs::read_to_string(unformatted.path()).unwrap(), formatted);
+    assert_eq!(fs::read_to_string(valid.path()).unwrap(), formatted);
+
+    cmd()
+        .arg("fmt")
+        .arg("--check")
+        .arg(unformatted.path())
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    // Parse errors are reported
+    let invalid = test_file(b"rule a {");
+    cmd()
+        .arg("fmt")
+        .arg(invalid.path())
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains("syntax error"))
+        .failure();
+
+    // Missing file
+    cmd()
+        .arg("fmt")
+        .arg("do_not_exist")
+        .assert()
+        .stdout("")
+        .stderr(predicate::str::contains("Cannot read do_not_exist"))
+        .failure();
+
+    // Files are required
+    cmd().arg("fmt").assert().failure();
+}
+
 // Copied in `boreal/tests/it/utils.rs`. Not trivial to share, and won't be
 // modified too frequently.
 struct BinHelper {
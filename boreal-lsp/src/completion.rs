@@ -0,0 +1,311 @@
+//! Completion of module fields.
+use std::collections::HashMap;
+
+use boreal::module::{self, Module, StaticValue, Type};
+use boreal_parser::file::YaraFileComponent;
+use lsp_types::{CompletionItem, CompletionItemKind};
+
+use crate::Document;
+
+/// Types of the values exposed by each module, indexed by the name of the module.
+pub(crate) struct Modules(HashMap<&'static str, Type>);
+
+impl Default for Modules {
+    fn default() -> Self {
+        let mut this = Self(HashMap::new());
+
+        this.add(&module::Time);
+        this.add(&module::Math);
+        this.add(&module::Magic);
+        this.add(&module::String_);
+        this.add(&module::Dex);
+        this.add(&module::Cuckoo);
+        this.add(&module::Lnk);
+        this.add(&module::Console::with_callback(Box::new(|_: String| ())));
+        this.add(&module::Hash);
+        this.add(&module::Elf);
+        this.add(&module::Dotnet);
+        this.add(&module::MachO::default());
+        this.add(&module::Pe::default());
+
+        this
+    }
+}
+
+impl Modules {
+    fn add<M: Module>(&mut self, module: &M) {
+        // Static values take precedence over dynamic ones, as done when compiling rules.
+        let mut fields = module.get_dynamic_types();
+        fields.extend(
+            module
+                .get_static_values()
+                .iter()
+                .map(|(name, value)| (*name, static_value_type(value))),
+        );
+
+        let _r = self.0.insert(module.get_name(), Type::Object(fields));
+    }
+
+    /// List the fields that can complete the module expression ending at the given offset.
+    ///
+    /// The expression must end with a dot, optionally followed by the start of the field name,
+    /// for example `pe.sections[0].na`. The module must be imported in the document.
+    pub(crate) fn complete(&self, document: &Document, offset: usize) -> Vec<CompletionItem> {
+        let Some(path) = path_before(&document.text, offset) else {
+            return Vec::new();
+        };
+        let mut segments = parse_path(path).into_iter();
+
+        let Some(Segment::Field(module_name)) = segments.next() else {
+            return Vec::new();
+        };
+        let imported = document.file.components.iter().any(|component| {
+            matches!(component, YaraFileComponent::Import(import) if import.name == module_name)
+        });
+        if !imported {
+            return Vec::new();
+        }
+
+        let mut ty = self.0.get(module_name);
+        for segment in segments {
+            ty = match (ty, segment) {
+                (Some(Type::Object(fields)), Segment::Field(name)) => fields.get(name),
+                (
+                    Some(Type::Array { value_type } | Type::Dictionary { value_type }),
+                    Segment::Subscript,
+                ) => Some(&**value_type),
+                (Some(Type::Function { return_type, .. }), Segment::Call) => Some(&**return_type),
+                _ => None,
+            };
+        }
+
+        let Some(Type::Object(fields)) = ty else {
+            return Vec::new();
+        };
+        let mut items: Vec<_> = fields
+            .iter()
+            .map(|(name, ty)| CompletionItem {
+                label: (*name).to_owned(),
+                kind: Some(match ty {
+                    Type::Function { .. } => CompletionItemKind::FUNCTION,
+                    Type::Object(_) => CompletionItemKind::STRUCT,
+                    _ => CompletionItemKind::FIELD,
+                }),
+                detail: Some(describe_type(ty)),
+                ..CompletionItem::default()
+            })
+            .collect();
+        items.sort_by(|a, b| a.label.cmp(&b.label));
+        items
+    }
+}
+
+fn static_value_type(value: &StaticValue) -> Type {
+    match value {
+        StaticValue::Integer(_) => Type::Integer,
+        StaticValue::Float(_) => Type::Float,
+        StaticValue::Bytes(_) => Type::Bytes,
+        StaticValue::Boolean(_) => Type::Boolean,
+        StaticValue::Object(fields) => Type::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (*name, static_value_type(value)))
+                .collect(),
+        ),
+        StaticValue::Function {
+            arguments_types,
+            return_type,
+            ..
+        } => Type::function(arguments_types.clone(), return_type.clone()),
+    }
+}
+
+fn describe_type(ty: &Type) -> String {
+    match ty {
+        Type::Integer => "integer".to_owned(),
+        Type::Float => "float".to_owned(),
+        Type::Bytes => "string".to_owned(),
+        Type::Regex => "regex".to_owned(),
+        Type::Boolean => "boolean".to_owned(),
+        Type::Object(_) => "object".to_owned(),
+        Type::Array { value_type } => format!("array of {}", describe_type(value_type)),
+        Type::Dictionary { value_type } => format!("dictionary of {}", describe_type(value_type)),
+        Type::Function {
+            arguments_types,
+            return_type,
+        } => {
+            let overloads: Vec<_> = arguments_types
+                .iter()
+                .map(|types| {
+                    let types: Vec<_> = types.iter().map(describe_type).collect();
+                    format!("({})", types.join(", "))
+                })
+                .collect();
+            format!(
+                "function{} -> {}",
+                overloads.join(" | "),
+                describe_type(return_type)
+            )
+        }
+    }
+}
+
+/// An operation in a module expression.
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    /// Access to a field of an object: `.name`.
+    Field(&'a str),
+    /// Subscript of an array or dictionary: `[...]`.
+    Subscript,
+    /// Call of a function: `(...)`.
+    Call,
+}
+
+/// Retrieve the module expression that precedes the field being completed.
+///
+/// For example, with `pe.sections[0].na` before the offset, this returns `pe.sections[0]`.
+fn path_before(text: &str, offset: usize) -> Option<&str> {
+    let bytes = text.as_bytes().get(..offset)?;
+    let skip_identifier = |mut i: usize| {
+        while i > 0 && is_identifier_char(bytes[i - 1]) {
+            i -= 1;
+        }
+        i
+    };
+
+    let mut i = skip_identifier(bytes.len());
+    if i == 0 || bytes[i - 1] != b'.' {
+        return None;
+    }
+    i -= 1;
+    let end = i;
+
+    loop {
+        // Skip subscripts and calls, which can contain nested expressions.
+        while i > 0 && matches!(bytes[i - 1], b']' | b')') {
+            let mut depth = 0_u32;
+            loop {
+                i -= 1;
+                match bytes[i] {
+                    b']' | b')' => depth += 1,
+                    b'[' | b'(' => depth -= 1,
+                    _ => (),
+                }
+                if depth == 0 {
+                    break;
+                }
+                if i == 0 {
+                    return None;
+                }
+            }
+        }
+
+        let start = skip_identifier(i);
+        if start == i {
+            return None;
+        }
+        i = start;
+        if i > 0 && bytes[i - 1] == b'.' {
+            i -= 1;
+        } else {
+            break;
+        }
+    }
+
+    // Variables and numbers are not module expressions.
+    if bytes[i].is_ascii_digit() || (i > 0 && matches!(bytes[i - 1], b'$' | b'#' | b'@' | b'!')) {
+        return None;
+    }
+    text.get(i..end)
+}
+
+fn parse_path(path: &str) -> Vec<Segment> {
+    let bytes = path.as_bytes();
+    let mut segments = Vec::new();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'.' => i += 1,
+            b'[' | b'(' => {
+                segments.push(if bytes[i] == b'[' {
+                    Segment::Subscript
+                } else {
+                    Segment::Call
+                });
+                let mut depth = 0_u32;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'[' | b'(' => depth += 1,
+                        b']' | b')' => depth -= 1,
+                        _ => (),
+                    }
+                    i += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                let start = i;
+                while i < bytes.len() && is_identifier_char(bytes[i]) {
+                    i += 1;
+                }
+                if start == i {
+                    break;
+                }
+                segments.push(Segment::Field(&path[start..i]));
+            }
+        }
+    }
+
+    segments
+}
+
+fn is_identifier_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_before() {
+        let test = |text: &str, expected: Option<&str>| {
+            assert_eq!(path_before(text, text.len()), expected, "{text}");
+        };
+
+        test("pe.", Some("pe"));
+        test("pe.nu", Some("pe"));
+        test("  pe.sections[0].", Some("pe.sections[0]"));
+        test(
+            "a and pe.sections[pe.number_of_sections - 1].na",
+            Some("pe.sections[pe.number_of_sections - 1]"),
+        );
+        test(
+            "math.to_number(math.entropy(0, 1)).",
+            Some("math.to_number(math.entropy(0, 1))"),
+        );
+        test("pe", None);
+        test("pe..", None);
+        test("1.", None);
+        test("$a.", None);
+        test("a[0]].", None);
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("pe.sections[pe.a(1)].f(2, 3).name"),
+            vec![
+                Segment::Field("pe"),
+                Segment::Field("sections"),
+                Segment::Subscript,
+                Segment::Field("f"),
+                Segment::Call,
+                Segment::Field("name"),
+            ]
+        );
+    }
+}
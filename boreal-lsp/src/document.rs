@@ -0,0 +1,245 @@
+//! Analysis of an opened document.
+use std::ops::Range;
+
+use boreal::compiler::{AddRuleError, CompilerParams};
+use boreal::statistics::CompiledRule;
+use boreal::Compiler;
+use boreal_parser::file::YaraFile;
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Url};
+
+/// A document opened by the client.
+pub(crate) struct Document {
+    /// Contents of the document.
+    pub(crate) text: String,
+
+    /// Offsets of the start of each line.
+    line_starts: Vec<usize>,
+
+    /// Components parsed from the document.
+    ///
+    /// If the document contains errors, this only contains the components that could be
+    /// parsed.
+    pub(crate) file: YaraFile,
+
+    /// Diagnostics generated when parsing and compiling the document.
+    pub(crate) diagnostics: Vec<lsp_types::Diagnostic>,
+
+    /// Compilation statistics of the rules.
+    ///
+    /// Empty if the document does not compile.
+    pub(crate) statistics: Vec<CompiledRule>,
+}
+
+impl Document {
+    pub(crate) fn new(uri: &Url, text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let (file, errors) = boreal_parser::parse_with_recovery(&text);
+
+        let mut this = Self {
+            text,
+            line_starts,
+            file,
+            diagnostics: Vec::new(),
+            statistics: Vec::new(),
+        };
+
+        if errors.is_empty() {
+            this.compile(uri);
+        } else {
+            this.diagnostics = errors
+                .iter()
+                .map(|error| this.convert_diagnostic(uri, &error.to_diagnostic()))
+                .collect();
+        }
+
+        this
+    }
+
+    /// Compile the rules of the document, to retrieve the compilation diagnostics and
+    /// statistics.
+    fn compile(&mut self, uri: &Url) {
+        let mut compiler = Compiler::new();
+        compiler.set_params(CompilerParams::default().compute_statistics(true));
+
+        match compiler.add_rules_str(&self.text) {
+            Ok(status) => {
+                self.diagnostics = status
+                    .warnings()
+                    .map(|warning| self.convert_error(uri, warning))
+                    .collect();
+                self.statistics = status.statistics().cloned().collect();
+            }
+            Err(error) => self.diagnostics = vec![self.convert_error(uri, &error)],
+        }
+    }
+
+    fn convert_error(&self, uri: &Url, error: &AddRuleError) -> lsp_types::Diagnostic {
+        let diagnostic = error.to_diagnostic();
+
+        match &error.path {
+            // The error is located in an included file, the spans cannot be used.
+            Some(path) => lsp_types::Diagnostic {
+                severity: Some(convert_severity(diagnostic.severity)),
+                source: Some("boreal".to_owned()),
+                message: format!(
+                    "in included file {}: {}",
+                    path.display(),
+                    diagnostic.message
+                ),
+                ..lsp_types::Diagnostic::default()
+            },
+            None => self.convert_diagnostic(uri, &diagnostic),
+        }
+    }
+
+    /// Convert a diagnostic generated by boreal.
+    ///
+    /// The first primary label is used to locate the diagnostic, the other labels are
+    /// added as related information.
+    fn convert_diagnostic(&self, uri: &Url, diagnostic: &Diagnostic<()>) -> lsp_types::Diagnostic {
+        let primary = diagnostic
+            .labels
+            .iter()
+            .position(|label| label.style == LabelStyle::Primary);
+
+        let mut message = diagnostic.message.clone();
+        let mut range = lsp_types::Range::default();
+        if let Some(label) = primary.map(|i| &diagnostic.labels[i]) {
+            range = self.range(&label.range);
+            if !label.message.is_empty() {
+                message.push_str(": ");
+                message.push_str(&label.message);
+            }
+        }
+        for note in &diagnostic.notes {
+            message.push('\n');
+            message.push_str(note);
+        }
+
+        let related_information: Vec<_> = diagnostic
+            .labels
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != primary)
+            .map(|(_, label)| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.clone(),
+                    range: self.range(&label.range),
+                },
+                message: if label.message.is_empty() {
+                    diagnostic.message.clone()
+                } else {
+                    label.message.clone()
+                },
+            })
+            .collect();
+
+        lsp_types::Diagnostic {
+            range,
+            severity: Some(convert_severity(diagnostic.severity)),
+            source: Some("boreal".to_owned()),
+            message,
+            related_information: if related_information.is_empty() {
+                None
+            } else {
+                Some(related_information)
+            },
+            ..lsp_types::Diagnostic::default()
+        }
+    }
+
+    /// Convert an offset in the text into a LSP position.
+    ///
+    /// Positions count characters in UTF-16 code units.
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character = self
+            .text
+            .get(line_start..offset)
+            .map_or(0, |text| text.encode_utf16().count());
+
+        Position {
+            line: u32::try_from(line).unwrap_or(u32::MAX),
+            character: u32::try_from(character).unwrap_or(u32::MAX),
+        }
+    }
+
+    /// Convert a LSP position into an offset in the text.
+    ///
+    /// Positions out of the text are clamped to the end of the line or of the text.
+    pub(crate) fn offset(&self, position: Position) -> usize {
+        let Some(line_start) = usize::try_from(position.line)
+            .ok()
+            .and_then(|line| self.line_starts.get(line))
+        else {
+            return self.text.len();
+        };
+        let line = self.text[*line_start..]
+            .split('\n')
+            .next()
+            .unwrap_or_default();
+
+        let mut remaining = position.character as usize;
+        let mut offset = *line_start;
+        for c in line.chars() {
+            if remaining < c.len_utf16() {
+                break;
+            }
+            remaining -= c.len_utf16();
+            offset += c.len_utf8();
+        }
+        offset
+    }
+
+    pub(crate) fn range(&self, span: &Range<usize>) -> lsp_types::Range {
+        lsp_types::Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
+
+fn convert_severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Note => DiagnosticSeverity::INFORMATION,
+        Severity::Help => DiagnosticSeverity::HINT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let uri = Url::parse("file:///a.yar").unwrap();
+        let doc = Document::new(&uri, "rule a {\n condition: \"é𝄞\" == \"\"\n}".to_owned());
+
+        let pos = |line, character| Position { line, character };
+        for (offset, position) in [
+            (0, pos(0, 0)),
+            (8, pos(0, 8)),
+            (9, pos(1, 0)),
+            (22, pos(1, 13)),
+            (24, pos(1, 14)),
+            (28, pos(1, 16)),
+            (36, pos(2, 0)),
+            (37, pos(2, 1)),
+        ] {
+            assert_eq!(doc.position(offset), position);
+            assert_eq!(doc.offset(position), offset);
+        }
+
+        // Out of bounds positions are clamped.
+        assert_eq!(doc.offset(pos(0, 20)), 8);
+        assert_eq!(doc.offset(pos(5, 0)), 37);
+        assert_eq!(doc.position(50), pos(2, 1));
+    }
+}
@@ -0,0 +1,66 @@
+//! Description of the strings on hover.
+use std::fmt::Write;
+
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+
+use crate::{symbols, Document};
+
+/// Describe the string declared or used at the given offset.
+///
+/// If the document compiles, this lists the literals and atoms extracted from the string,
+/// to help writing efficient strings.
+pub(crate) fn hover(document: &Document, offset: usize) -> Option<Hover> {
+    let (rule, variable) = symbols::find_variable(&document.file, offset)?;
+    let declaration = document.text.get(variable.span.clone())?;
+
+    let mut value = format!("```yara\n{declaration}\n```\n");
+
+    let compiled_string = document
+        .statistics
+        .iter()
+        .find(|stats| stats.name == rule.name)
+        .and_then(|stats| stats.strings.iter().find(|s| s.name == variable.name));
+    if let Some(string) = compiled_string {
+        let literals: Vec<_> = string.literals.iter().map(|v| display_bytes(v)).collect();
+        let atoms: Vec<_> = string.atoms.iter().map(|v| display_bytes(v)).collect();
+
+        let _r = write!(
+            value,
+            "\n- Literals: {}\n- Atoms: {}\n- Atoms quality: {}\n- Matching algorithm: {}\n",
+            literals.join(", "),
+            atoms.join(", "),
+            string.atoms_quality,
+            string.matching_algo,
+        );
+    }
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: None,
+    })
+}
+
+/// Display bytes as a string if printable, or as a hex string otherwise.
+fn display_bytes(bytes: &[u8]) -> String {
+    if bytes.iter().all(|b| *b == b' ' || b.is_ascii_graphic()) {
+        format!("`{:?}`", String::from_utf8_lossy(bytes))
+    } else {
+        let hex: Vec<_> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        format!("`{{ {} }}`", hex.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_bytes() {
+        assert_eq!(display_bytes(b"ab\"c"), r#"`"ab\"c"`"#);
+        assert_eq!(display_bytes(b"\x01\xFF"), "`{ 01 ff }`");
+        assert_eq!(display_bytes(b"a\0b\0"), "`{ 61 00 62 00 }`");
+    }
+}
@@ -0,0 +1,284 @@
+//! Language server for YARA rules, powered by **boreal**.
+//!
+//! The server implements the [Language Server Protocol](https://microsoft.github.io/language-server-protocol/)
+//! and provides:
+//!
+//! - diagnostics, generated when parsing and compiling the rules.
+//! - completion of module fields.
+//! - go-to-definition on rule references and strings.
+//! - hover on strings, describing the atoms extracted from them.
+//! - renaming of strings.
+//!
+//! The server is started with [`run`], which takes a [`Connection`]. The `boreal-lsp`
+//! binary uses a connection over stdio, but an in-memory connection can also be used:
+//!
+//! ```no_run
+//! let (connection, io_threads) = lsp_server::Connection::stdio();
+//! boreal_lsp::run(&connection)?;
+//! drop(connection);
+//! io_threads.join()?;
+//! # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+//! ```
+
+// Deny most of allowed by default lints from rustc.
+#![deny(explicit_outlives_requirements)]
+#![deny(keyword_idents)]
+#![deny(macro_use_extern_crate)]
+#![deny(non_ascii_idents)]
+#![deny(noop_method_call)]
+#![deny(rust_2021_compatibility)]
+#![deny(single_use_lifetimes)]
+#![deny(trivial_casts)]
+#![deny(trivial_numeric_casts)]
+#![deny(unsafe_code)]
+#![deny(unused_crate_dependencies)]
+#![deny(unused_extern_crates)]
+#![deny(unused_import_braces)]
+#![deny(unused_lifetimes)]
+#![deny(unused_qualifications)]
+#![deny(unused_results)]
+// Do the same for clippy
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+// Allow some useless pedantic lints
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::too_many_lines)]
+#![allow(clippy::single_match_else)]
+#![deny(missing_docs)]
+#![deny(clippy::cargo)]
+// Handled by cargo-deny
+#![allow(clippy::multiple_crate_versions)]
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Rename, Request as LspRequest};
+use lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability, OneOf,
+    PublishDiagnosticsParams, RenameParams, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url, WorkspaceEdit,
+};
+
+mod completion;
+mod document;
+use document::Document;
+mod hover;
+mod symbols;
+
+/// Error returned when the server stops unexpectedly.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Run the language server on the given connection.
+///
+/// This handles the initialization of the connection, then answers the requests of the
+/// client until it asks the server to shut down.
+///
+/// # Errors
+///
+/// An error is returned if the initialization fails or if the connection is closed
+/// abruptly.
+pub fn run(connection: &Connection) -> Result<(), Error> {
+    let capabilities = serde_json::to_value(server_capabilities())?;
+    let _params = connection.initialize(capabilities)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    return Ok(());
+                }
+                connection
+                    .sender
+                    .send(Message::Response(server.handle_request(req)))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(params) = server.handle_notification(notification) {
+                    connection
+                        .sender
+                        .send(Message::Notification(Notification::new(
+                            PublishDiagnostics::METHOD.to_owned(),
+                            params,
+                        )))?;
+                }
+            }
+            Message::Response(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_owned()]),
+            ..CompletionOptions::default()
+        }),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    /// Documents opened by the client.
+    documents: HashMap<Url, Document>,
+
+    /// Fields of the modules, used for completion.
+    modules: completion::Modules,
+}
+
+impl Server {
+    fn handle_request(&self, req: Request) -> Response {
+        match req.method.as_str() {
+            Completion::METHOD => {
+                dispatch::<Completion>(req, |params| Ok(self.completion(&params)))
+            }
+            GotoDefinition::METHOD => {
+                dispatch::<GotoDefinition>(req, |params| Ok(self.definition(&params)))
+            }
+            HoverRequest::METHOD => dispatch::<HoverRequest>(req, |params| Ok(self.hover(&params))),
+            Rename::METHOD => dispatch::<Rename>(req, |params| self.rename(&params)),
+            _ => Response::new_err(
+                req.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported method {}", req.method),
+            ),
+        }
+    }
+
+    /// Handle a notification, returning the diagnostics to publish if a document was modified.
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Option<PublishDiagnosticsParams> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: <DidOpenTextDocument as LspNotification>::Params =
+                    serde_json::from_value(notification.params).ok()?;
+                let doc = params.text_document;
+                Some(self.update_document(doc.uri, doc.text, Some(doc.version)))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: <DidChangeTextDocument as LspNotification>::Params =
+                    serde_json::from_value(notification.params).ok()?;
+                // The full text is sent on every change, so only the last change is relevant.
+                let change = params.content_changes.into_iter().last()?;
+                let doc = params.text_document;
+                Some(self.update_document(doc.uri, change.text, Some(doc.version)))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: <DidCloseTextDocument as LspNotification>::Params =
+                    serde_json::from_value(notification.params).ok()?;
+                let uri = params.text_document.uri;
+                let _r = self.documents.remove(&uri);
+                Some(PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: Vec::new(),
+                    version: None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn update_document(
+        &mut self,
+        uri: Url,
+        text: String,
+        version: Option<i32>,
+    ) -> PublishDiagnosticsParams {
+        let document = Document::new(&uri, text);
+        let diagnostics = document.diagnostics.clone();
+        let _r = self.documents.insert(uri.clone(), document);
+
+        PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        }
+    }
+
+    fn completion(&self, params: &CompletionParams) -> Option<CompletionResponse> {
+        let position = &params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+
+        Some(CompletionResponse::Array(
+            self.modules.complete(document, offset),
+        ))
+    }
+
+    fn definition(&self, params: &GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = &params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+
+        let span = symbols::find_definition(&document.file, offset)?;
+        Some(GotoDefinitionResponse::Scalar(lsp_types::Location {
+            uri: position.text_document.uri.clone(),
+            range: document.range(&span),
+        }))
+    }
+
+    fn hover(&self, params: &HoverParams) -> Option<Hover> {
+        let position = &params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+
+        hover::hover(document, offset)
+    }
+
+    fn rename(&self, params: &RenameParams) -> Result<Option<WorkspaceEdit>, String> {
+        let position = &params.text_document_position;
+        let uri = &position.text_document.uri;
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(None);
+        };
+        let offset = document.offset(position.position);
+
+        let spans = symbols::rename_string(&document.file, offset, &params.new_name)?;
+        let new_name = params.new_name.trim_start_matches('$');
+        let edits = spans
+            .into_iter()
+            .map(|span| lsp_types::TextEdit {
+                range: document.range(&span),
+                new_text: new_name.to_owned(),
+            })
+            .collect();
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some([(uri.clone(), edits)].into()),
+            ..WorkspaceEdit::default()
+        }))
+    }
+}
+
+/// Deserialize the parameters of a request, and build the response from the handler result.
+///
+/// Errors returned by the handler are returned as invalid params errors.
+fn dispatch<R: LspRequest>(
+    req: Request,
+    handler: impl FnOnce(R::Params) -> Result<R::Result, String>,
+) -> Response {
+    let params = match serde_json::from_value(req.params) {
+        Ok(params) => params,
+        Err(err) => {
+            return Response::new_err(req.id, ErrorCode::InvalidParams as i32, err.to_string())
+        }
+    };
+
+    match handler(params) {
+        Ok(result) => Response::new_ok(req.id, result),
+        Err(message) => Response::new_err(req.id, ErrorCode::InvalidParams as i32, message),
+    }
+}
@@ -0,0 +1,22 @@
+use std::process::ExitCode;
+
+use lsp_server::Connection;
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+
+    if let Err(err) = boreal_lsp::run(&connection) {
+        eprintln!("language server error: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    // Close the connection so that the IO threads can terminate.
+    drop(connection);
+    match io_threads.join() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error on the connection: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Resolution of the symbols used in rules: strings and rule references.
+use std::ops::Range;
+
+use boreal_parser::expression::{
+    Expression, ExpressionKind, ForIterator, ForSelection, Identifier, IdentifierOperationType,
+    VariableSet,
+};
+use boreal_parser::file::{YaraFile, YaraFileComponent};
+use boreal_parser::rule::{Rule, VariableDeclaration};
+
+/// Find the declaration of the symbol at the given offset.
+///
+/// This returns the span of the name of the declared string or rule.
+pub(crate) fn find_definition(file: &YaraFile, offset: usize) -> Option<Range<usize>> {
+    if let Some((_, variable)) = find_variable(file, offset) {
+        return Some(declaration_name_span(variable));
+    }
+
+    let rule = enclosing_rule(file, offset)?;
+    let reference = references(rule)
+        .into_iter()
+        .find(|reference| reference.kind == Kind::Rule && reference.contains(offset))?;
+    rules(file)
+        .find(|rule| rule.name == reference.name)
+        .map(|rule| rule.name_span.clone())
+}
+
+/// Find the string declared or used at the given offset.
+pub(crate) fn find_variable(
+    file: &YaraFile,
+    offset: usize,
+) -> Option<(&Rule, &VariableDeclaration)> {
+    let rule = enclosing_rule(file, offset)?;
+
+    if let Some(variable) = rule.variables.iter().find(|variable| {
+        let span = declaration_name_span(variable);
+        span.start - 1 <= offset && offset <= span.end
+    }) {
+        return Some((rule, variable));
+    }
+
+    let reference = references(rule)
+        .into_iter()
+        .find(|reference| reference.kind == Kind::Variable && reference.contains(offset))?;
+    rule.variables
+        .iter()
+        .find(|variable| variable.name == reference.name)
+        .map(|variable| (rule, variable))
+}
+
+/// Compute the spans to modify to rename the string at the given offset.
+///
+/// The spans cover the name of the string, without its `$`, `#`, `@` or `!` prefix, in its
+/// declaration and in all its uses in the condition of the rule.
+///
+/// # Errors
+///
+/// An error is returned if there is no string at this offset, if the new name is invalid, or
+/// if the rename would change the strings selected by a wildcard in the condition.
+pub(crate) fn rename_string(
+    file: &YaraFile,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<Range<usize>>, String> {
+    let (rule, variable) =
+        find_variable(file, offset).ok_or_else(|| "no string to rename".to_owned())?;
+
+    let new_name = new_name.strip_prefix('$').unwrap_or(new_name);
+    if new_name.is_empty()
+        || !new_name
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'_')
+    {
+        return Err(format!("invalid string name ${new_name}"));
+    }
+    if rule.variables.iter().any(|v| v.name == new_name) {
+        return Err(format!("string ${new_name} is already declared"));
+    }
+
+    let references = references(rule);
+    if let Some(wildcard) = references.iter().find(|reference| {
+        reference.kind == Kind::VariableWildcard
+            && variable.name.starts_with(reference.name) != new_name.starts_with(reference.name)
+    }) {
+        return Err(format!(
+            "renaming would change the strings selected by ${}*",
+            wildcard.name
+        ));
+    }
+
+    let mut spans = vec![declaration_name_span(variable)];
+    spans.extend(
+        references
+            .into_iter()
+            .filter(|reference| reference.kind == Kind::Variable && reference.name == variable.name)
+            .map(|reference| reference.span),
+    );
+    Ok(spans)
+}
+
+fn rules(file: &YaraFile) -> impl Iterator<Item = &Rule> {
+    file.components
+        .iter()
+        .filter_map(|component| match component {
+            YaraFileComponent::Rule(rule) => Some(&**rule),
+            _ => None,
+        })
+}
+
+/// Find the rule containing the given offset.
+///
+/// Rules do not store their full span, so this is the last rule declared before the offset.
+fn enclosing_rule(file: &YaraFile, offset: usize) -> Option<&Rule> {
+    rules(file)
+        .take_while(|rule| rule.name_span.start <= offset)
+        .last()
+}
+
+/// Span of the name of a string in its declaration, without the `$` prefix.
+fn declaration_name_span(variable: &VariableDeclaration) -> Range<usize> {
+    let start = variable.span.start + 1;
+    start..(start + variable.name.len())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Kind {
+    Variable,
+    /// Prefix of the variables selected by a wildcard in a string set.
+    VariableWildcard,
+    Rule,
+}
+
+/// Use of a symbol in the condition of a rule.
+#[derive(Debug)]
+struct Reference<'a> {
+    kind: Kind,
+
+    name: &'a str,
+
+    /// Span of the name, without the prefix of variables.
+    span: Range<usize>,
+}
+
+impl Reference<'_> {
+    fn contains(&self, offset: usize) -> bool {
+        let start = match self.kind {
+            // Include the prefix
+            Kind::Variable | Kind::VariableWildcard => self.span.start - 1,
+            Kind::Rule => self.span.start,
+        };
+        start <= offset && offset <= self.span.end
+    }
+}
+
+/// List the symbols used in the condition of a rule.
+///
+/// Identifiers without any operations are listed as rule references, even though they might
+/// refer to a bound identifier or a module.
+fn references(rule: &Rule) -> Vec<Reference> {
+    let mut refs = Vec::new();
+    visit_expression(&rule.condition, &mut refs);
+    refs
+}
+
+fn add_variable<'a>(refs: &mut Vec<Reference<'a>>, name: &'a str, prefix_start: usize) {
+    // Anonymous uses in for bodies refer to the iterated string.
+    if !name.is_empty() {
+        let start = prefix_start + 1;
+        refs.push(Reference {
+            kind: Kind::Variable,
+            name,
+            span: start..(start + name.len()),
+        });
+    }
+}
+
+fn visit_expression<'a>(expr: &'a Expression, refs: &mut Vec<Reference<'a>>) {
+    match &expr.expr {
+        ExpressionKind::Filesize
+        | ExpressionKind::Entrypoint
+        | ExpressionKind::Integer(_)
+        | ExpressionKind::Double(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::Bytes(_)
+        | ExpressionKind::Regex(_) => (),
+
+        ExpressionKind::Count(name) | ExpressionKind::Variable(name) => {
+            add_variable(refs, name, expr.span.start);
+        }
+        ExpressionKind::Offset {
+            variable_name,
+            occurence_number,
+        }
+        | ExpressionKind::Length {
+            variable_name,
+            occurence_number,
+        } => {
+            add_variable(refs, variable_name, expr.span.start);
+            visit_expression(occurence_number, refs);
+        }
+        ExpressionKind::CountInRange {
+            variable_name,
+            variable_name_span,
+            from,
+            to,
+        }
+        | ExpressionKind::VariableIn {
+            variable_name,
+            variable_name_span,
+            from,
+            to,
+        } => {
+            add_variable(refs, variable_name, variable_name_span.start);
+            visit_expression(from, refs);
+            visit_expression(to, refs);
+        }
+        ExpressionKind::VariableAt {
+            variable_name,
+            variable_name_span,
+            offset,
+        } => {
+            add_variable(refs, variable_name, variable_name_span.start);
+            visit_expression(offset, refs);
+        }
+
+        ExpressionKind::ReadInteger { addr: expr, .. }
+        | ExpressionKind::Neg(expr)
+        | ExpressionKind::BitwiseNot(expr)
+        | ExpressionKind::Not(expr)
+        | ExpressionKind::Defined(expr)
+        | ExpressionKind::Matches(expr, _) => visit_expression(expr, refs),
+
+        ExpressionKind::Add(left, right)
+        | ExpressionKind::Sub(left, right)
+        | ExpressionKind::Mul(left, right)
+        | ExpressionKind::Div(left, right)
+        | ExpressionKind::Mod(left, right)
+        | ExpressionKind::BitwiseXor(left, right)
+        | ExpressionKind::BitwiseAnd(left, right)
+        | ExpressionKind::BitwiseOr(left, right)
+        | ExpressionKind::ShiftLeft(left, right)
+        | ExpressionKind::ShiftRight(left, right)
+        | ExpressionKind::Eq(left, right)
+        | ExpressionKind::NotEq(left, right)
+        | ExpressionKind::IEquals(left, right)
+        | ExpressionKind::Cmp { left, right, .. }
+        | ExpressionKind::Contains {
+            haystack: left,
+            needle: right,
+            ..
+        }
+        | ExpressionKind::StartsWith {
+            expr: left,
+            prefix: right,
+            ..
+        }
+        | ExpressionKind::EndsWith {
+            expr: left,
+            suffix: right,
+            ..
+        } => {
+            visit_expression(left, refs);
+            visit_expression(right, refs);
+        }
+
+        ExpressionKind::And(exprs) | ExpressionKind::Or(exprs) => {
+            for expr in exprs {
+                visit_expression(expr, refs);
+            }
+        }
+
+        ExpressionKind::For {
+            selection,
+            set,
+            body,
+        } => {
+            visit_selection(selection, refs);
+            visit_set(set, refs);
+            if let Some(body) = body {
+                visit_expression(body, refs);
+            }
+        }
+        ExpressionKind::ForIn {
+            selection,
+            set,
+            from,
+            to,
+        } => {
+            visit_selection(selection, refs);
+            visit_set(set, refs);
+            visit_expression(from, refs);
+            visit_expression(to, refs);
+        }
+        ExpressionKind::ForAt {
+            selection,
+            set,
+            offset,
+        } => {
+            visit_selection(selection, refs);
+            visit_set(set, refs);
+            visit_expression(offset, refs);
+        }
+        ExpressionKind::ForIdentifiers {
+            selection,
+            iterator,
+            body,
+            ..
+        } => {
+            visit_selection(selection, refs);
+            match iterator {
+                ForIterator::Identifier(identifier) => visit_identifier(identifier, refs),
+                ForIterator::Range { from, to } => {
+                    visit_expression(from, refs);
+                    visit_expression(to, refs);
+                }
+                ForIterator::List(exprs) => {
+                    for expr in exprs {
+                        visit_expression(expr, refs);
+                    }
+                }
+            }
+            visit_expression(body, refs);
+        }
+        ExpressionKind::ForRules { selection, set } => {
+            visit_selection(selection, refs);
+            refs.extend(
+                set.elements
+                    .iter()
+                    .filter(|element| !element.is_wildcard)
+                    .map(|element| Reference {
+                        kind: Kind::Rule,
+                        name: &element.name,
+                        span: element.span.clone(),
+                    }),
+            );
+        }
+        ExpressionKind::With { declarations, body } => {
+            for declaration in declarations {
+                visit_expression(&declaration.expr, refs);
+            }
+            visit_expression(body, refs);
+        }
+
+        ExpressionKind::Identifier(identifier) => visit_identifier(identifier, refs),
+    }
+}
+
+fn visit_selection<'a>(selection: &'a ForSelection, refs: &mut Vec<Reference<'a>>) {
+    if let ForSelection::Expr { expr, .. } = selection {
+        visit_expression(expr, refs);
+    }
+}
+
+fn visit_set<'a>(set: &'a VariableSet, refs: &mut Vec<Reference<'a>>) {
+    for element in &set.elements {
+        if element.is_wildcard {
+            let start = element.span.start + 1;
+            refs.push(Reference {
+                kind: Kind::VariableWildcard,
+                name: &element.name,
+                span: start..(start + element.name.len()),
+            });
+        } else {
+            add_variable(refs, &element.name, element.span.start);
+        }
+    }
+}
+
+fn visit_identifier<'a>(identifier: &'a Identifier, refs: &mut Vec<Reference<'a>>) {
+    if identifier.operations.is_empty() {
+        refs.push(Reference {
+            kind: Kind::Rule,
+            name: &identifier.name,
+            span: identifier.name_span.clone(),
+        });
+    }
+
+    for operation in &identifier.operations {
+        match &operation.op {
+            IdentifierOperationType::Subscript(expr) => visit_expression(expr, refs),
+            IdentifierOperationType::Subfield(_) => (),
+            IdentifierOperationType::FunctionCall(exprs) => {
+                for expr in exprs {
+                    visit_expression(expr, refs);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+rule first { condition: true }
+rule second {
+    strings:
+        $a = "a"
+        $b = "b"
+    condition:
+        $a and #a > 2 and @a[1] < !b[1] and first and
+        for any of ($a, $b*): ($ at 0) and
+        for all i in (1..#b): (@b[i] > 0) and
+        any of (first, sec*)
+}"#;
+
+    fn offset_of(needle: &str, nth: usize) -> usize {
+        RULES.match_indices(needle).nth(nth).unwrap().0
+    }
+
+    #[test]
+    fn test_find_definition() {
+        let file = boreal_parser::parse(RULES).unwrap();
+        let decl_a = offset_of("$a", 0) + 1;
+        let decl_b = offset_of("$b", 0) + 1;
+
+        // On the prefix, the name, and just after the name.
+        assert_eq!(
+            find_definition(&file, offset_of("$a", 1)),
+            Some(decl_a..(decl_a + 1))
+        );
+        assert_eq!(
+            find_definition(&file, offset_of("#a", 0) + 1),
+            Some(decl_a..(decl_a + 1))
+        );
+        assert_eq!(
+            find_definition(&file, offset_of("@a", 0) + 2),
+            Some(decl_a..(decl_a + 1))
+        );
+        assert_eq!(
+            find_definition(&file, offset_of("!b", 0)),
+            Some(decl_b..(decl_b + 1))
+        );
+        assert_eq!(
+            find_definition(&file, offset_of("$a", 2)),
+            Some(decl_a..(decl_a + 1))
+        );
+        assert_eq!(
+            find_definition(&file, offset_of("#b", 0)),
+            Some(decl_b..(decl_b + 1))
+        );
+
+        let first = offset_of("first", 0);
+        assert_eq!(
+            find_definition(&file, offset_of("first", 1) + 2),
+            Some(first..(first + 5))
+        );
+        assert_eq!(
+            find_definition(&file, offset_of("first", 2)),
+            Some(first..(first + 5))
+        );
+
+        assert_eq!(find_definition(&file, offset_of("$b*", 0)), None);
+        assert_eq!(find_definition(&file, offset_of("sec*", 0)), None);
+        assert_eq!(find_definition(&file, offset_of("true", 0)), None);
+        assert_eq!(find_definition(&file, 0), None);
+    }
+
+    #[test]
+    fn test_rename_string() {
+        let file = boreal_parser::parse(RULES).unwrap();
+
+        let spans = rename_string(&file, offset_of("@a", 0), "$c").unwrap();
+        let names: Vec<_> = spans.iter().map(|span| &RULES[span.clone()]).collect();
+        assert_eq!(names, ["a", "a", "a", "a", "a"]);
+        assert_eq!(spans[0].start, offset_of("$a", 0) + 1);
+
+        // Wildcards are not renamed, but must select the same strings.
+        let spans = rename_string(&file, offset_of("$b", 0), "bc").unwrap();
+        assert_eq!(spans.len(), 4);
+        assert!(rename_string(&file, offset_of("$b", 0), "c").is_err());
+        assert!(rename_string(&file, offset_of("$a", 0), "bc").is_err());
+
+        assert!(rename_string(&file, offset_of("$a", 1), "$b").is_err());
+        assert!(rename_string(&file, offset_of("$a", 1), "$").is_err());
+        assert!(rename_string(&file, offset_of("$a", 1), "a-b").is_err());
+        assert!(rename_string(&file, offset_of("first", 1), "c").is_err());
+    }
+}
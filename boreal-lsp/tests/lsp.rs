@@ -0,0 +1,362 @@
+use std::thread::JoinHandle;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, ResponseError};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Initialized,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, GotoDefinition, HoverRequest, Initialize, Rename, Request as LspRequest, Shutdown,
+};
+use lsp_types::{
+    CompletionItemKind, CompletionParams, CompletionResponse, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, HoverContents, HoverParams, InitializeParams,
+    InitializedParams, Position, PublishDiagnosticsParams, RenameParams,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, Url, VersionedTextDocumentIdentifier,
+};
+
+/// Client connected to a server running in another thread.
+struct Client {
+    connection: Connection,
+    server: Option<JoinHandle<()>>,
+    next_id: i32,
+    uri: Url,
+}
+
+impl Client {
+    fn new() -> Self {
+        let (server_connection, connection) = Connection::memory();
+        let server = std::thread::spawn(move || boreal_lsp::run(&server_connection).unwrap());
+
+        let mut this = Self {
+            connection,
+            server: Some(server),
+            next_id: 0,
+            uri: Url::parse("file:///rules.yar").unwrap(),
+        };
+        let _r = this
+            .request::<Initialize>(InitializeParams::default())
+            .unwrap();
+        this.notify::<Initialized>(InitializedParams {});
+        this
+    }
+
+    fn request<R: LspRequest>(&mut self, params: R::Params) -> Result<R::Result, ResponseError> {
+        self.next_id += 1;
+        let id = self.next_id.into();
+        self.connection
+            .sender
+            .send(Message::Request(Request::new(
+                id,
+                R::METHOD.to_owned(),
+                params,
+            )))
+            .unwrap();
+
+        loop {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) => {
+                    assert_eq!(response.id, RequestId::from(self.next_id));
+                    return match response.error {
+                        Some(err) => Err(err),
+                        None => Ok(serde_json::from_value(response.result.unwrap()).unwrap()),
+                    };
+                }
+                // Ignore diagnostics that were not checked.
+                Message::Notification(_) => (),
+                Message::Request(req) => panic!("unexpected request {req:?}"),
+            }
+        }
+    }
+
+    fn notify<N: LspNotification>(&self, params: N::Params) {
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                N::METHOD.to_owned(),
+                params,
+            )))
+            .unwrap();
+    }
+
+    fn diagnostics(&self) -> PublishDiagnosticsParams {
+        match self.connection.receiver.recv().unwrap() {
+            Message::Notification(notif) => {
+                assert_eq!(notif.method, PublishDiagnostics::METHOD);
+                serde_json::from_value(notif.params).unwrap()
+            }
+            msg => panic!("unexpected message {msg:?}"),
+        }
+    }
+
+    fn open(&self, text: &str) -> PublishDiagnosticsParams {
+        self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: self.uri.clone(),
+                language_id: "yara".to_owned(),
+                version: 0,
+                text: text.to_owned(),
+            },
+        });
+        self.diagnostics()
+    }
+
+    fn change(&self, text: &str, version: i32) -> PublishDiagnosticsParams {
+        self.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: self.uri.clone(),
+                version,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: text.to_owned(),
+            }],
+        });
+        self.diagnostics()
+    }
+
+    fn position_params(&self, position: Position) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: self.uri.clone(),
+            },
+            position,
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+
+        self.request::<Shutdown>(()).unwrap();
+        self.notify::<Exit>(());
+        self.server.take().unwrap().join().unwrap();
+    }
+}
+
+/// Position of the nth occurrence of `needle` in `text`, shifted by `shift` characters.
+fn position(text: &str, needle: &str, nth: usize, shift: usize) -> Position {
+    let offset = text.match_indices(needle).nth(nth).unwrap().0 + shift;
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+
+    Position {
+        line: text[..offset].matches('\n').count().try_into().unwrap(),
+        character: (offset - line_start).try_into().unwrap(),
+    }
+}
+
+#[test]
+fn test_diagnostics() {
+    let client = Client::new();
+
+    // All parse errors are reported
+    let rules = "rule a { condition: ( }\nrule b { condition: true }\nrule c { condition: 1 + }";
+    let params = client.open(rules);
+    assert_eq!(params.uri, client.uri);
+    assert_eq!(params.version, Some(0));
+    assert_eq!(params.diagnostics.len(), 2);
+    assert_eq!(params.diagnostics[0].range.start.line, 0);
+    assert_eq!(params.diagnostics[1].range.start.line, 2);
+    for diag in &params.diagnostics {
+        assert_eq!(diag.severity, Some(DiagnosticSeverity::ERROR));
+        assert!(diag.message.starts_with("syntax error"), "{}", diag.message);
+    }
+
+    let params = client.change("rule a { condition: true }", 1);
+    assert_eq!(params.version, Some(1));
+    assert!(params.diagnostics.is_empty());
+
+    // Compilation errors are reported
+    let rules = "rule a { condition: true }\nrule b { condition: c }";
+    let params = client.change(rules, 2);
+    assert_eq!(params.diagnostics.len(), 1);
+    let diag = &params.diagnostics[0];
+    assert_eq!(diag.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diag.range.start, position(rules, "c }", 0, 0));
+    assert_eq!(diag.range.end, position(rules, "c }", 0, 1));
+
+    client.notify::<DidCloseTextDocument>(DidCloseTextDocumentParams {
+        text_document: TextDocumentIdentifier {
+            uri: client.uri.clone(),
+        },
+    });
+    assert!(client.diagnostics().diagnostics.is_empty());
+}
+
+#[test]
+fn test_completion() {
+    let mut client = Client::new();
+
+    let rules = r#"import "math"
+import "pe"
+rule a {
+    condition: math. and pe.sections[pe.number_of_sections - 1].vi and
+        pe.number_of_sections. and elf.
+}"#;
+    let _r = client.open(rules);
+
+    let mut complete = |position| {
+        let res = client
+            .request::<Completion>(CompletionParams {
+                text_document_position: client.position_params(position),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            })
+            .unwrap();
+        match res {
+            Some(CompletionResponse::Array(items)) => items,
+            res => panic!("unexpected response {res:?}"),
+        }
+    };
+
+    let items = complete(position(rules, "math.", 0, 5));
+    let entropy = items.iter().find(|item| item.label == "entropy").unwrap();
+    assert_eq!(entropy.kind, Some(CompletionItemKind::FUNCTION));
+    assert!(items.iter().any(|item| item.label == "MEAN_BYTES"));
+
+    let items = complete(position(rules, ".vi", 0, 3));
+    let virtual_size = items
+        .iter()
+        .find(|item| item.label == "virtual_size")
+        .unwrap();
+    assert_eq!(virtual_size.kind, Some(CompletionItemKind::FIELD));
+    assert_eq!(virtual_size.detail.as_deref(), Some("integer"));
+    assert!(items.iter().any(|item| item.label == "name"));
+
+    // Fields of an integer
+    assert!(complete(position(rules, "sections.", 0, 9)).is_empty());
+    // Module not imported
+    assert!(complete(position(rules, "elf.", 0, 4)).is_empty());
+}
+
+#[test]
+fn test_definition() {
+    let mut client = Client::new();
+
+    let rules = r#"
+rule first { condition: true }
+rule second {
+    strings:
+        $a = "abc"
+    condition:
+        #a > 2 and first
+}"#;
+    let _r = client.open(rules);
+
+    let mut definition = |position| {
+        client
+            .request::<GotoDefinition>(GotoDefinitionParams {
+                text_document_position_params: client.position_params(position),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+    };
+
+    let res = definition(position(rules, "#a", 0, 1));
+    match res {
+        Some(GotoDefinitionResponse::Scalar(location)) => {
+            assert_eq!(location.range.start, position(rules, "a =", 0, 0));
+            assert_eq!(location.range.end, position(rules, "a =", 0, 1));
+        }
+        res => panic!("unexpected response {res:?}"),
+    }
+
+    let res = definition(position(rules, "first", 1, 3));
+    match res {
+        Some(GotoDefinitionResponse::Scalar(location)) => {
+            assert_eq!(location.range.start, position(rules, "first", 0, 0));
+            assert_eq!(location.range.end, position(rules, "first", 0, 5));
+        }
+        res => panic!("unexpected response {res:?}"),
+    }
+
+    assert!(definition(position(rules, "true", 0, 0)).is_none());
+}
+
+#[test]
+fn test_hover() {
+    let mut client = Client::new();
+
+    let rules = r#"
+rule a {
+    strings:
+        $a = "abcdef" wide
+        $b = { 01 02 03 04 }
+    condition:
+        $a and $b
+}"#;
+    let _r = client.open(rules);
+
+    let mut hover = |position| {
+        let res = client
+            .request::<HoverRequest>(HoverParams {
+                text_document_position_params: client.position_params(position),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap();
+        match res.map(|hover| hover.contents) {
+            Some(HoverContents::Markup(content)) => content.value,
+            res => panic!("unexpected response {res:?}"),
+        }
+    };
+
+    let value = hover(position(rules, "$a", 1, 1));
+    assert!(value.contains(r#"$a = "abcdef" wide"#), "{value}");
+    assert!(value.contains("Atoms quality: "), "{value}");
+
+    let value = hover(position(rules, "$b", 0, 0));
+    assert!(value.contains("$b = { 01 02 03 04 }"), "{value}");
+    assert!(value.contains("Atoms: `{ 01 02 03 04 }`"), "{value}");
+}
+
+#[test]
+fn test_rename() {
+    let mut client = Client::new();
+
+    let rules = r#"
+rule a {
+    strings:
+        $a = "abc"
+        $b = "def"
+    condition:
+        $a and for all i in (1..#a): (@a[i] > 10)
+}"#;
+    let _r = client.open(rules);
+    let uri = client.uri.clone();
+
+    let mut rename = |position, new_name: &str| {
+        client.request::<Rename>(RenameParams {
+            text_document_position: client.position_params(position),
+            new_name: new_name.to_owned(),
+            work_done_progress_params: Default::default(),
+        })
+    };
+
+    let edit = rename(position(rules, "@a", 0, 0), "$c").unwrap().unwrap();
+    let mut edits = edit.changes.unwrap().remove(&uri).unwrap();
+    edits.sort_by_key(|edit| edit.range.start);
+    let starts: Vec<_> = edits.iter().map(|edit| edit.range.start).collect();
+    assert_eq!(
+        starts,
+        vec![
+            position(rules, "$a", 0, 1),
+            position(rules, "$a", 1, 1),
+            position(rules, "#a", 0, 1),
+            position(rules, "@a", 0, 1),
+        ]
+    );
+    assert!(edits.iter().all(|edit| edit.new_text == "c"));
+
+    // The new name is already used
+    assert!(rename(position(rules, "$a", 0, 1), "$b").is_err());
+    // Not a string
+    assert!(rename(position(rules, "for", 0, 0), "$c").is_err());
+}
@@ -26,6 +26,10 @@ impl Error {
         Self { span, kind }
     }
 
+    pub(crate) fn span(&self) -> &Range<usize> {
+        &self.span
+    }
+
     /// Convert to a [`Diagnostic`].
     ///
     /// This can be used to display the error in a user-friendly manner.
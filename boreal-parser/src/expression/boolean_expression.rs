@@ -21,6 +21,7 @@ use super::{
     common::range,
     for_expression::{for_expression_non_ambiguous, for_expression_with_expr_selection},
     primary_expression::primary_expression,
+    with_expression::with_expression,
     Expression, ExpressionKind,
 };
 
@@ -140,6 +141,8 @@ fn expression_item(input: Input) -> ParseResult<Expression> {
     match alt((
         // all variants of for expressions with a non ambiguous first token
         for_expression_non_ambiguous,
+        // 'with' declarations ':' '(' body ')'
+        with_expression,
         // string_identifier ...
         variable_expression,
     ))(input)
@@ -8,6 +8,7 @@ mod identifier;
 mod primary_expression;
 mod read_integer;
 mod string_expression;
+mod with_expression;
 
 use crate::regex::Regex;
 
@@ -363,6 +364,19 @@ pub enum ExpressionKind {
         set: RuleSet,
     },
 
+    /// Bind identifiers to values, and evaluate an expression using those identifiers.
+    ///
+    /// For example: `with last = pe.sections[pe.number_of_sections - 1] : ( last.name == ".rsrc" )`
+    With {
+        /// Identifiers to bind, in order of declaration.
+        ///
+        /// A declaration can use the identifiers bound by the previous declarations.
+        declarations: Vec<WithDeclaration>,
+
+        /// Body to evaluate with the identifiers bound.
+        body: Box<Expression>,
+    },
+
     /// An identifier.
     Identifier(Identifier),
     /// A byte string.
@@ -415,6 +429,19 @@ pub enum ForIterator {
     List(Vec<Expression>),
 }
 
+/// Declaration of an identifier in a 'with' expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithDeclaration {
+    /// Name of the identifier.
+    pub name: String,
+
+    /// Span covering the name of the identifier.
+    pub name_span: Range<usize>,
+
+    /// Expression whose value is bound to the identifier.
+    pub expr: Expression,
+}
+
 /// Set of multiple variables.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VariableSet {
@@ -0,0 +1,196 @@
+//! Parsers for 'with' expressions.
+//!
+//! Those expressions bind identifiers to values, which can then be used in the body of the
+//! expression:
+//!
+//! `with a = expr, b = expr : ( body )`
+use nom::{
+    character::complete::char,
+    combinator::{cut, not},
+    multi::separated_list1,
+    sequence::{delimited, terminated},
+};
+
+use crate::{
+    nom_recipes::{rtrim, textual_tag as ttag},
+    string,
+    types::{Input, ParseResult},
+};
+
+use super::{boolean_expression::boolean_expression, Expression, ExpressionKind, WithDeclaration};
+
+/// Parse a with expression.
+///
+/// This parses `'with' declarations ':' '(' body ')'`.
+pub(super) fn with_expression(input: Input) -> ParseResult<Expression> {
+    let start = input.pos();
+    let (input, _) = rtrim(ttag("with"))(input)?;
+    let (input, declarations) = terminated(with_declarations, cut(rtrim(char(':'))))(input)?;
+    let (input, body) = cut(delimited(
+        rtrim(char('(')),
+        boolean_expression,
+        rtrim(char(')')),
+    ))(input)?;
+
+    Ok((
+        input,
+        Expression {
+            expr: ExpressionKind::With {
+                declarations,
+                body: Box::new(body),
+            },
+            span: input.get_span_from(start),
+        },
+    ))
+}
+
+/// Parse a list of declarations, separated by commas.
+///
+/// Equivalent to the `with_declarations` pattern in grammar.y in libyara.
+fn with_declarations(input: Input) -> ParseResult<Vec<WithDeclaration>> {
+    separated_list1(rtrim(char(',')), with_declaration)(input)
+}
+
+/// Parse a single declaration: `identifier '=' expression`.
+///
+/// Equivalent to the `with_declaration` pattern in grammar.y in libyara.
+fn with_declaration(input: Input) -> ParseResult<WithDeclaration> {
+    let start = input.pos();
+    let (input, name) = string::identifier(input)?;
+    let name_span = input.get_span_from(start);
+    let (input, _) = rtrim(terminated(char('='), not(char('='))))(input)?;
+    let (input, expr) = cut(boolean_expression)(input)?;
+
+    Ok((
+        input,
+        WithDeclaration {
+            name,
+            name_span,
+            expr,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{Identifier, IdentifierOperation, IdentifierOperationType};
+    use crate::test_helpers::{parse, parse_err, test_public_type};
+
+    #[test]
+    fn test_with_expression() {
+        parse(
+            with_expression,
+            "with a = 5, b = pe.sections[a] : (b) c",
+            "c",
+            Expression {
+                expr: ExpressionKind::With {
+                    declarations: vec![
+                        WithDeclaration {
+                            name: "a".to_owned(),
+                            name_span: 5..6,
+                            expr: Expression {
+                                expr: ExpressionKind::Integer(5),
+                                span: 9..10,
+                            },
+                        },
+                        WithDeclaration {
+                            name: "b".to_owned(),
+                            name_span: 12..13,
+                            expr: Expression {
+                                expr: ExpressionKind::Identifier(Identifier {
+                                    name: "pe".to_owned(),
+                                    name_span: 16..18,
+                                    operations: vec![
+                                        IdentifierOperation {
+                                            op: IdentifierOperationType::Subfield(
+                                                "sections".to_owned(),
+                                            ),
+                                            span: 18..27,
+                                        },
+                                        IdentifierOperation {
+                                            op: IdentifierOperationType::Subscript(Box::new(
+                                                Expression {
+                                                    expr: ExpressionKind::Identifier(Identifier {
+                                                        name: "a".to_owned(),
+                                                        name_span: 28..29,
+                                                        operations: vec![],
+                                                    }),
+                                                    span: 28..29,
+                                                },
+                                            )),
+                                            span: 27..30,
+                                        },
+                                    ],
+                                }),
+                                span: 16..30,
+                            },
+                        },
+                    ],
+                    body: Box::new(Expression {
+                        expr: ExpressionKind::Identifier(Identifier {
+                            name: "b".to_owned(),
+                            name_span: 34..35,
+                            operations: vec![],
+                        }),
+                        span: 34..35,
+                    }),
+                },
+                span: 0..36,
+            },
+        );
+
+        parse(
+            with_expression,
+            "with a = true and false: (a)",
+            "",
+            Expression {
+                expr: ExpressionKind::With {
+                    declarations: vec![WithDeclaration {
+                        name: "a".to_owned(),
+                        name_span: 5..6,
+                        expr: Expression {
+                            expr: ExpressionKind::And(vec![
+                                Expression {
+                                    expr: ExpressionKind::Boolean(true),
+                                    span: 9..13,
+                                },
+                                Expression {
+                                    expr: ExpressionKind::Boolean(false),
+                                    span: 18..23,
+                                },
+                            ]),
+                            span: 9..23,
+                        },
+                    }],
+                    body: Box::new(Expression {
+                        expr: ExpressionKind::Identifier(Identifier {
+                            name: "a".to_owned(),
+                            name_span: 26..27,
+                            operations: vec![],
+                        }),
+                        span: 26..27,
+                    }),
+                },
+                span: 0..28,
+            },
+        );
+
+        parse_err(with_expression, "");
+        parse_err(with_expression, "with");
+        parse_err(with_expression, "with a");
+        parse_err(with_expression, "with a =");
+        parse_err(with_expression, "with a == 1: (a)");
+        parse_err(with_expression, "with a = 1");
+        parse_err(with_expression, "with a = 1,: (a)");
+        parse_err(with_expression, "with a = 1: a");
+        parse_err(with_expression, "with a = 1: ()");
+        parse_err(with_expression, "with a = 1: (a");
+        parse_err(with_expression, "witha = 1: (a)");
+    }
+
+    #[test]
+    fn test_public_types() {
+        test_public_type(with_declaration(Input::new("a = 1")).unwrap());
+    }
+}
@@ -6,8 +6,10 @@ use nom::bytes::complete::take_till1;
 use nom::character::complete::char;
 use nom::combinator::map;
 use nom::sequence::delimited;
+use nom::Finish;
 use nom::{combinator::cut, sequence::preceded};
 
+use crate::error::Error;
 use crate::rule::Rule;
 
 use super::rule::rule;
@@ -26,6 +28,12 @@ pub struct YaraFile {
     /// or a module, or to properly use rules included by wildcard use of rule
     /// names in conditions.
     pub components: Vec<YaraFileComponent>,
+
+    /// List of comments contained in the file, in order of appearance.
+    ///
+    /// Comments are not part of the other elements of the AST, their spans can be
+    /// used to relocate them.
+    pub comments: Vec<Comment>,
 }
 
 /// A top-level component of a Yara file.
@@ -57,6 +65,17 @@ pub struct Include {
     pub span: Range<usize>,
 }
 
+/// A comment inside a Yara file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    /// Text of the comment, including the delimiters, ie `// foo` or `/* bar */`.
+    ///
+    /// The newline terminating a single line comment is not included.
+    pub text: String,
+    /// The span covering the comment.
+    pub span: Range<usize>,
+}
+
 /// Parse a full YARA file.
 ///
 /// # Errors
@@ -68,13 +87,10 @@ pub(crate) fn parse_yara_file(input: Input) -> ParseResult<YaraFile> {
 
     let mut file = YaraFile {
         components: Vec::new(),
+        comments: Vec::new(),
     };
     while !input.is_empty() {
-        let (i, component) = alt((
-            map(include_file, YaraFileComponent::Include),
-            map(import, YaraFileComponent::Import),
-            map(rule, |r| YaraFileComponent::Rule(Box::new(r))),
-        ))(input)?;
+        let (i, component) = component(input)?;
         file.components.push(component);
         input = i;
     }
@@ -82,6 +98,196 @@ pub(crate) fn parse_yara_file(input: Input) -> ParseResult<YaraFile> {
     Ok((input, file))
 }
 
+/// Parse a full YARA file, recovering from errors.
+///
+/// When a component cannot be parsed, the error is saved and the parsing resumes on the
+/// next `rule`, `import` or `include` keyword following the error.
+///
+/// The returned file contains all the components that were successfully parsed.
+pub(crate) fn parse_yara_file_with_recovery(mut input: Input) -> (YaraFile, Vec<Error>) {
+    let mut file = YaraFile {
+        components: Vec::new(),
+        comments: Vec::new(),
+    };
+    let mut errors = Vec::new();
+
+    loop {
+        match next_component(input).finish() {
+            Ok((_, None)) => break,
+            Ok((i, Some(component))) => {
+                file.components.push(component);
+                input = i;
+            }
+            Err(error) => {
+                // Resume the parsing after the error, and not before, to avoid resyncing on
+                // elements that were properly parsed, for example a `private` string modifier.
+                // Skipping the first character of the component also ensures the parsing
+                // always progresses.
+                let offset = input.get_position_offset();
+                let from = std::cmp::max(error.span().start.saturating_sub(offset), 1);
+                errors.push(error);
+
+                match find_component_start(input.cursor(), from) {
+                    Some(pos) => input.advance(pos),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    (file, errors)
+}
+
+/// Parse a top-level component of a YARA file.
+fn component(input: Input) -> ParseResult<YaraFileComponent> {
+    alt((
+        map(include_file, YaraFileComponent::Include),
+        map(import, YaraFileComponent::Import),
+        map(rule, |r| YaraFileComponent::Rule(Box::new(r))),
+    ))(input)
+}
+
+/// Parse the next component of a YARA file, or None if the end of the file is reached.
+fn next_component(input: Input) -> ParseResult<Option<YaraFileComponent>> {
+    let (input, ()) = ltrim(input)?;
+
+    if input.is_empty() {
+        Ok((input, None))
+    } else {
+        map(component, Some)(input)
+    }
+}
+
+/// Find the start of the next component in the input, ignoring any start before `from`.
+///
+/// Comments and strings are skipped, so that keywords contained in them are not used.
+fn find_component_start(input: &str, from: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if rest.starts_with(b"//") {
+            pos += rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        } else if rest.starts_with(b"/*") {
+            pos += rest[2..]
+                .windows(2)
+                .position(|w| w == b"*/")
+                .map_or(rest.len(), |v| v + 4);
+        } else if rest[0] == b'"' {
+            // Strings cannot span multiple lines, stop on a newline in case the string is
+            // not properly closed.
+            let mut len = 1;
+            while len < rest.len() && rest[len] != b'"' && rest[len] != b'\n' {
+                if rest[len] == b'\\' {
+                    len += 1;
+                }
+                len += 1;
+            }
+            pos += len + 1;
+        } else if is_identifier_byte(rest[0]) {
+            // Ignore words preceded by a sigil, e.g. `$rule` or `#rule`.
+            let is_sigil = pos > 0 && matches!(bytes[pos - 1], b'$' | b'#' | b'@' | b'!' | b'.');
+            if pos >= from && !is_sigil && is_component_start(&input[pos..]) {
+                return Some(pos);
+            }
+            pos += rest
+                .iter()
+                .position(|b| !is_identifier_byte(*b))
+                .unwrap_or(rest.len());
+        } else {
+            pos += 1;
+        }
+    }
+
+    None
+}
+
+/// Does the input start with the keywords of a component.
+fn is_component_start(mut input: &str) -> bool {
+    loop {
+        let len = input
+            .bytes()
+            .position(|b| !is_identifier_byte(b))
+            .unwrap_or(input.len());
+        match &input[..len] {
+            "rule" | "import" | "include" => return true,
+            "private" | "global" => input = input[len..].trim_start(),
+            _ => return false,
+        }
+    }
+}
+
+fn is_identifier_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Extract the comments of a file.
+///
+/// This is done in a lexing pass separate from the parsing. Text strings and regexes are
+/// skipped, as they can contain comment delimiters.
+pub(crate) fn build_comments(input: &str) -> Vec<Comment> {
+    let bytes = input.as_bytes();
+    let comment = |span: Range<usize>| Comment {
+        text: input[span.clone()].to_owned(),
+        span,
+    };
+
+    let mut comments = Vec::new();
+    let mut i = 0;
+    while let Some(&b) = bytes.get(i) {
+        i = match (b, bytes.get(i + 1)) {
+            (b'/', Some(b'/')) => {
+                let end = find(bytes, i, b"\n").unwrap_or(bytes.len());
+                // With CRLF line endings, the carriage return is not part of the comment.
+                let text_end = if bytes[end - 1] == b'\r' {
+                    end - 1
+                } else {
+                    end
+                };
+                comments.push(comment(i..text_end));
+                end
+            }
+            (b'/', Some(b'*')) => {
+                let end = find(bytes, i + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+                comments.push(comment(i..end));
+                end
+            }
+            (b'"' | b'/', _) => skip_literal(bytes, i + 1, b),
+            _ => i + 1,
+        };
+    }
+    comments
+}
+
+/// Find the offset of the first occurrence of `needle` in `bytes`, starting from `start`.
+fn find(bytes: &[u8], start: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(start..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| start + pos)
+}
+
+/// Skip a text string or a regex, returning the offset following its closing delimiter.
+///
+/// As neither can contain newlines, a newline also ends it, to limit the impact of an
+/// unterminated one.
+fn skip_literal(bytes: &[u8], mut i: usize, delimiter: u8) -> usize {
+    while let Some(&b) = bytes.get(i) {
+        if b == b'\\' {
+            i += 2;
+        } else if b == b'\n' {
+            return i;
+        } else if b == delimiter {
+            return i + 1;
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
 /// Parse an include declaration
 fn include_file(input: Input) -> ParseResult<Include> {
     let start = input.pos();
@@ -152,6 +358,7 @@ mod tests {
                     is_private: false,
                     is_global: true,
                 }))],
+                comments: vec![],
             },
         );
 
@@ -205,14 +412,26 @@ mod tests {
                         is_global: false,
                     })),
                 ],
+                comments: vec![],
+            },
+        );
+        parse(
+            parse_yara_file,
+            "",
+            "",
+            YaraFile {
+                components: vec![],
+                comments: vec![],
             },
         );
-        parse(parse_yara_file, "", "", YaraFile { components: vec![] });
         parse(
             parse_yara_file,
             " /* removed */ ",
             "",
-            YaraFile { components: vec![] },
+            YaraFile {
+                components: vec![],
+                comments: vec![],
+            },
         );
         parse(
             parse_yara_file,
@@ -229,6 +448,7 @@ mod tests {
                         span: 12..22,
                     }),
                 ],
+                comments: vec![],
             },
         );
 
@@ -237,17 +457,159 @@ mod tests {
         parse_err(parse_yara_file, " /*");
     }
 
+    #[track_caller]
+    fn test_recovery(input: &str, expected_components: &[&str], expected_nb_errors: usize) {
+        let (file, errors) = parse_yara_file_with_recovery(Input::new(input));
+
+        let names: Vec<_> = file
+            .components
+            .iter()
+            .map(|component| match component {
+                YaraFileComponent::Rule(rule) => &*rule.name,
+                YaraFileComponent::Import(import) => &*import.name,
+                YaraFileComponent::Include(include) => &*include.path,
+            })
+            .collect();
+        assert_eq!(names, expected_components);
+        assert_eq!(errors.len(), expected_nb_errors);
+
+        // The first error is the one returned when not recovering.
+        if let Some(error) = errors.first() {
+            assert_eq!(error, &crate::parse(input).unwrap_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_yara_file_with_recovery() {
+        test_recovery("", &[], 0);
+        test_recovery(" /* a */ ", &[], 0);
+        test_recovery(
+            "import \"a\" rule b { condition: true } include \"c\"",
+            &["a", "b", "c"],
+            0,
+        );
+
+        test_recovery("rule a {", &[], 1);
+        test_recovery(" /*", &[], 1);
+        test_recovery("foo\nimport \"pe\"", &["pe"], 1);
+        test_recovery(
+            "rule a { condition: }\nrule b { condition: true }",
+            &["b"],
+            1,
+        );
+        test_recovery(
+            "import pe\nrule a { condition: true }\nrule b { condition: 1 + }\ninclude \"c\"",
+            &["a", "c"],
+            2,
+        );
+
+        // Resync on private and global rules
+        test_recovery(
+            "rule a { condition: ( }\nprivate global rule b { condition: true }",
+            &["b"],
+            1,
+        );
+
+        // Keywords properly parsed before the error, or contained in strings, comments
+        // and identifiers are not used to resync.
+        test_recovery(
+            "rule a { strings: $a = \"a\" private $b = { ZZ } condition: all of them }\n\
+             rule b { condition: true }",
+            &["b"],
+            1,
+        );
+        test_recovery(
+            "rule a { strongs: $a = \"rule x\" // rule y\n\
+             condition: /* rule z */ $rule and #rule > 0 and a.rule }\n\
+             rule b { condition: true }",
+            &["b"],
+            1,
+        );
+        test_recovery("rule a { condition: ( }\nruleb { condition: true }", &[], 1);
+    }
+
+    #[test]
+    fn test_comments() {
+        let file =
+            crate::parse("// a\nrule /* b */ a { condition: true // c\n}\n/* d\n*/").unwrap();
+        assert_eq!(
+            file.comments,
+            vec![
+                Comment {
+                    text: "// a".to_owned(),
+                    span: 0..4,
+                },
+                Comment {
+                    text: "/* b */".to_owned(),
+                    span: 10..17,
+                },
+                Comment {
+                    text: "// c".to_owned(),
+                    span: 38..42,
+                },
+                Comment {
+                    text: "/* d\n*/".to_owned(),
+                    span: 45..52,
+                },
+            ]
+        );
+
+        // Comment delimiters in text strings and regexes are not comments.
+        let file = crate::parse(
+            r#"rule a {
+    strings:
+        $a = "/* \" //"
+        $b = /a\/\/b"/
+    condition:
+        $a and $b // c
+}"#,
+        )
+        .unwrap();
+        let comments: Vec<_> = file.comments.iter().map(|c| &*c.text).collect();
+        assert_eq!(comments, ["// c"]);
+
+        // An unterminated literal ends with its line, an unterminated comment with the input.
+        assert_eq!(
+            build_comments("\"a\n// b\n/* c"),
+            vec![
+                Comment {
+                    text: "// b".to_owned(),
+                    span: 3..7,
+                },
+                Comment {
+                    text: "/* c".to_owned(),
+                    span: 8..12,
+                },
+            ]
+        );
+
+        // The carriage return of a CRLF line ending is not part of the comment.
+        assert_eq!(
+            build_comments("// a\r\n// b\r"),
+            vec![
+                Comment {
+                    text: "// a".to_owned(),
+                    span: 0..4,
+                },
+                Comment {
+                    text: "// b".to_owned(),
+                    span: 6..10,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_public_types() {
         test_public_type(
-            parse_yara_file(Input::new(
+            crate::parse(
                 r#"
-import "a"
+import "a" // import
 include "b"
 
 rule a { condition: true }
 "#,
-            ))
+            )
             .unwrap(),
         );
     }
@@ -137,6 +137,7 @@ pub mod file;
 pub mod hex_string;
 mod nom_recipes;
 mod number;
+pub mod printer;
 pub mod regex;
 pub mod rule;
 mod string;
@@ -151,10 +152,26 @@ mod types;
 pub fn parse(input: &str) -> Result<file::YaraFile, error::Error> {
     use nom::Finish;
 
-    let input = types::Input::new(input);
-    let (_, rules) = file::parse_yara_file(input).finish()?;
+    let (_, mut file) = file::parse_yara_file(types::Input::new(input)).finish()?;
+    file.comments = file::build_comments(input);
 
-    Ok(rules)
+    Ok(file)
+}
+
+/// Parse a YARA file, recovering from errors.
+///
+/// Instead of stopping on the first error, the parsing resumes on the next `rule`, `import`
+/// or `include` keyword following the error. This allows reporting all the errors of a file
+/// at once.
+///
+/// The returned file contains all the components that were successfully parsed, and is
+/// identical to the one returned by [`parse`] if the list of errors is empty.
+#[must_use]
+pub fn parse_with_recovery(input: &str) -> (file::YaraFile, Vec<error::Error>) {
+    let (mut file, errors) = file::parse_yara_file_with_recovery(types::Input::new(input));
+    file.comments = file::build_comments(input);
+
+    (file, errors)
 }
 
 #[cfg(test)]
@@ -169,4 +186,17 @@ mod tests {
         assert!(parse("  global rule c { condition: false }").is_ok());
         assert!(parse("  global rule c { condtion: false }").is_err());
     }
+
+    #[test]
+    fn test_parse_with_recovery() {
+        let (file, errors) = parse_with_recovery("  global rule c { condition: false }");
+        assert_eq!(file.components.len(), 1);
+        assert!(errors.is_empty());
+
+        let (file, errors) = parse_with_recovery(
+            "rule a { condtion: false }\nimport \"pe\"\nrule b { condition: true }\nrule c {",
+        );
+        assert_eq!(file.components.len(), 2);
+        assert_eq!(errors.len(), 2);
+    }
 }
@@ -0,0 +1,1635 @@
+//! Printing of a parsed AST back into YARA source.
+//!
+//! The printer generates a canonical form of the rules: indentation, spacing, modifiers
+//! order and parenthesization are normalized, while comments are kept. A comment is printed
+//! at the end of the line of the element it follows if it ends a line in the source, and on
+//! its own line before the element it precedes otherwise.
+//!
+//! Some elements are not fully described by the AST, for example an integer can be written
+//! in decimal or hexadecimal form. Those elements are copied from the source, using the spans
+//! stored in the AST. This means the source given to the printing functions must be the input
+//! the AST was parsed from.
+//!
+//! ```rust
+//! use boreal_parser::printer::format;
+//!
+//! let formatted = format(r#"rule a { strings: $a="foo" condition:$a and #a>0x2 }"#)?;
+//! assert_eq!(
+//!     formatted,
+//!     r#"rule a
+//! {
+//!     strings:
+//!         $a = "foo"
+//!     condition:
+//!         $a and #a > 0x2
+//! }
+//! "#
+//! );
+//! # Ok::<(), boreal_parser::error::Error>(())
+//! ```
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::error::Error;
+use crate::expression::{
+    Expression, ExpressionKind, ForIterator, ForSelection, Identifier, IdentifierOperationType,
+    ReadIntegerType, SetElement, VariableSet,
+};
+use crate::file::{Comment, YaraFile, YaraFileComponent};
+use crate::hex_string::{Mask, Token};
+use crate::regex::Regex;
+use crate::rule::{MetadataValue, Rule, VariableDeclaration, VariableDeclarationValue};
+
+/// Maximum width of a line of a condition, before it is split on multiple lines.
+const MAX_WIDTH: usize = 100;
+
+/// Number of spaces for one indentation level.
+const INDENT_WIDTH: usize = 4;
+
+/// Parse a YARA file and print it back in its canonical form.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be parsed.
+pub fn format(input: &str) -> Result<String, Error> {
+    let file = crate::parse(input)?;
+
+    Ok(print_file(&file, input))
+}
+
+/// Print a parsed YARA file, including its comments.
+///
+/// `source` must be the input that was parsed to generate the file.
+#[must_use]
+pub fn print_file(file: &YaraFile, source: &str) -> String {
+    let mut printer = Printer::new(source, &file.comments);
+    printer.file(&file.components);
+    printer.out
+}
+
+/// Print a parsed rule.
+///
+/// `source` must be the input that was parsed to generate the rule. Comments are not
+/// printed, as they are only available on a [`YaraFile`].
+#[must_use]
+pub fn print_rule(rule: &Rule, source: &str) -> String {
+    let mut printer = Printer::new(source, &[]);
+    let _r = printer.rule(rule);
+    printer.out
+}
+
+/// Print a parsed expression on a single line.
+///
+/// `source` must be the input that was parsed to generate the expression.
+#[must_use]
+pub fn print_expression(expr: &Expression, source: &str) -> String {
+    Printer::new(source, &[]).inline(expr, Precedence::Or)
+}
+
+/// Precedence of an expression, from the loosest to the tightest binding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Or,
+    And,
+    /// Prefix operators `not` and `defined`, but also for, with and variable expressions,
+    /// which can only be used as operands of those operators or of boolean operators.
+    Not,
+    Eq,
+    Cmp,
+    /// `|` operator, this is the level of a primary expression.
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Add,
+    Mul,
+    /// Prefix operators `-` and `~`.
+    Unary,
+    /// Expressions that never need to be parenthesized.
+    Item,
+}
+
+impl Precedence {
+    /// Precedence required for the right operand of a left-associative operator.
+    fn next(self) -> Self {
+        match self {
+            Self::Or => Self::And,
+            Self::And => Self::Not,
+            Self::Not => Self::Eq,
+            Self::Eq => Self::Cmp,
+            Self::Cmp => Self::BitOr,
+            Self::BitOr => Self::BitXor,
+            Self::BitXor => Self::BitAnd,
+            Self::BitAnd => Self::Shift,
+            Self::Shift => Self::Add,
+            Self::Add => Self::Mul,
+            Self::Mul => Self::Unary,
+            Self::Unary | Self::Item => Self::Item,
+        }
+    }
+}
+
+fn precedence(expr: &ExpressionKind) -> Precedence {
+    match expr {
+        ExpressionKind::Or(_) => Precedence::Or,
+        ExpressionKind::And(_) => Precedence::And,
+        ExpressionKind::Not(_)
+        | ExpressionKind::Defined(_)
+        | ExpressionKind::Variable(_)
+        | ExpressionKind::VariableAt { .. }
+        | ExpressionKind::VariableIn { .. }
+        | ExpressionKind::For { .. }
+        | ExpressionKind::ForIn { .. }
+        | ExpressionKind::ForAt { .. }
+        | ExpressionKind::ForIdentifiers { .. }
+        | ExpressionKind::ForRules { .. }
+        | ExpressionKind::With { .. } => Precedence::Not,
+        ExpressionKind::Eq(..)
+        | ExpressionKind::NotEq(..)
+        | ExpressionKind::Contains { .. }
+        | ExpressionKind::StartsWith { .. }
+        | ExpressionKind::EndsWith { .. }
+        | ExpressionKind::IEquals(..)
+        | ExpressionKind::Matches(..) => Precedence::Eq,
+        ExpressionKind::Cmp { .. } => Precedence::Cmp,
+        ExpressionKind::BitwiseOr(..) => Precedence::BitOr,
+        ExpressionKind::BitwiseXor(..) => Precedence::BitXor,
+        ExpressionKind::BitwiseAnd(..) => Precedence::BitAnd,
+        ExpressionKind::ShiftLeft(..) | ExpressionKind::ShiftRight(..) => Precedence::Shift,
+        ExpressionKind::Add(..) | ExpressionKind::Sub(..) => Precedence::Add,
+        ExpressionKind::Mul(..) | ExpressionKind::Div(..) | ExpressionKind::Mod(..) => {
+            Precedence::Mul
+        }
+        ExpressionKind::Neg(_) | ExpressionKind::BitwiseNot(_) => Precedence::Unary,
+        ExpressionKind::Filesize
+        | ExpressionKind::Entrypoint
+        | ExpressionKind::ReadInteger { .. }
+        | ExpressionKind::Integer(_)
+        | ExpressionKind::Double(_)
+        | ExpressionKind::Count(_)
+        | ExpressionKind::CountInRange { .. }
+        | ExpressionKind::Offset { .. }
+        | ExpressionKind::Length { .. }
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::Identifier(_)
+        | ExpressionKind::Bytes(_)
+        | ExpressionKind::Regex(_) => Precedence::Item,
+    }
+}
+
+/// Can the expression be printed on multiple lines.
+fn is_breakable(expr: &ExpressionKind) -> bool {
+    matches!(
+        expr,
+        ExpressionKind::And(_)
+            | ExpressionKind::Or(_)
+            | ExpressionKind::For { body: Some(_), .. }
+            | ExpressionKind::ForIdentifiers { .. }
+            | ExpressionKind::With { .. }
+    )
+}
+
+struct Printer<'a> {
+    /// Input the printed AST was parsed from.
+    source: &'a str,
+
+    /// Comments of the input, ordered by position.
+    comments: &'a [Comment],
+
+    /// Index of the first comment that has not been printed yet.
+    next_comment: usize,
+
+    /// Generated output.
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn new(source: &'a str, comments: &'a [Comment]) -> Self {
+        Self {
+            source,
+            comments,
+            next_comment: 0,
+            out: String::new(),
+        }
+    }
+
+    fn file(&mut self, components: &[YaraFileComponent]) {
+        // End of the previous component, and whether it was a rule.
+        let mut previous: Option<(usize, bool)> = None;
+
+        for component in components {
+            let (start, is_rule) = match component {
+                YaraFileComponent::Rule(rule) => (rule.name_span.start, true),
+                YaraFileComponent::Import(import) => (import.span.start, false),
+                YaraFileComponent::Include(include) => (include.span.start, false),
+            };
+            // Rules are always separated from other components by an empty line. Imports and
+            // includes are only separated if they were in the source.
+            if let Some((end, previous_is_rule)) = previous {
+                if previous_is_rule || is_rule || self.has_blank_line(end, self.next_start(start)) {
+                    self.out.push('\n');
+                }
+            }
+
+            self.comments_before(start, 0);
+            let end = match component {
+                YaraFileComponent::Rule(rule) => self.rule(rule),
+                YaraFileComponent::Import(import) => {
+                    self.line(0, &format!("import \"{}\"", import.name));
+                    import.span.end
+                }
+                YaraFileComponent::Include(include) => {
+                    self.line(0, &format!("include \"{}\"", include.path));
+                    include.span.end
+                }
+            };
+            self.trailing_comments(end, "");
+            previous = Some((end, is_rule));
+        }
+
+        if let Some((end, _)) = previous {
+            if self.has_blank_line(end, self.next_start(self.source.len())) {
+                self.out.push('\n');
+            }
+        }
+        self.comments_before(self.source.len(), 0);
+    }
+
+    /// Print a rule, and return the end position of the rule in the source.
+    fn rule(&mut self, rule: &Rule) -> usize {
+        let mut header = String::new();
+        if rule.is_private {
+            header.push_str("private ");
+        }
+        if rule.is_global {
+            header.push_str("global ");
+        }
+        header.push_str("rule ");
+        header.push_str(&rule.name);
+        if !rule.tags.is_empty() {
+            header.push_str(" :");
+            for tag in &rule.tags {
+                header.push(' ');
+                header.push_str(&tag.tag);
+            }
+        }
+        let header_end = rule
+            .tags
+            .last()
+            .map_or(rule.name_span.end, |tag| tag.span.end);
+        // Comments inside the header are printed before it, and comments between the header
+        // and the opening brace before the brace.
+        self.comments_before(header_end, 0);
+        self.line(0, &header);
+        self.trailing_comments(header_end, "");
+
+        let mut lexer = Lexer::new(self.source, header_end);
+        lexer.skip_trivia();
+        self.comments_before(lexer.pos, 0);
+        let open_end = lexer.skip_char('{');
+        self.line(0, "{");
+        if let Some(pos) = open_end {
+            self.trailing_comments(pos, "");
+        }
+
+        // End of the last element of the rule that was printed, if known.
+        let mut last_end = open_end;
+
+        if !rule.metadatas.is_empty() {
+            let section_end = self.section_header(last_end, "meta:");
+            let spans = section_end
+                .and_then(|pos| Lexer::new(self.source, pos).metadatas_spans(rule.metadatas.len()));
+
+            match spans {
+                Some(spans) => {
+                    let mut previous_end = None;
+                    for (meta, span) in rule.metadatas.iter().zip(spans) {
+                        if let Some(end) = previous_end {
+                            if self.has_blank_line(end, self.next_start(span.start)) {
+                                self.out.push('\n');
+                            }
+                        }
+                        self.comments_before(span.end, 2);
+                        self.line(
+                            2,
+                            &format!("{} = {}", meta.name, metadata_value(&meta.value)),
+                        );
+                        self.trailing_comments(span.end, "");
+                        previous_end = Some(span.end);
+                    }
+                    last_end = previous_end;
+                }
+                None => {
+                    // Positions of the metadatas could not be found, print all the comments of
+                    // the section before them.
+                    let next_section_start = rule
+                        .variables
+                        .first()
+                        .map_or(rule.condition.span.start, |var| var.span.start);
+                    self.comments_before(next_section_start, 2);
+                    for meta in &rule.metadatas {
+                        self.line(
+                            2,
+                            &format!("{} = {}", meta.name, metadata_value(&meta.value)),
+                        );
+                    }
+                    last_end = None;
+                }
+            }
+        }
+
+        if !rule.variables.is_empty() {
+            let _r = self.section_header(last_end, "strings:");
+
+            let mut previous_end = None;
+            for var in &rule.variables {
+                if let Some(end) = previous_end {
+                    if self.has_blank_line(end, self.next_start(var.span.start)) {
+                        self.out.push('\n');
+                    }
+                }
+                self.comments_before(var.span.end, 2);
+                let decl = self.variable(var);
+                self.line(2, &decl);
+                self.trailing_comments(var.span.end, "");
+                previous_end = Some(var.span.end);
+            }
+            last_end = previous_end;
+        }
+
+        let _r = self.section_header(last_end, "condition:");
+        self.expr_lines(&rule.condition, 2, Precedence::Or, "");
+
+        let end = Lexer::new(self.source, rule.condition.span.end)
+            .skip_char('}')
+            .unwrap_or(rule.condition.span.end);
+        self.comments_before(end, 2);
+        self.line(0, "}");
+
+        end
+    }
+
+    /// Print the header of a section of a rule, e.g. `strings:`.
+    ///
+    /// `from` is the end of the last element preceding the section in the source, if known.
+    /// Returns the end of the header in the source.
+    fn section_header(&mut self, from: Option<usize>, header: &str) -> Option<usize> {
+        let span = from.and_then(|from| {
+            let mut lexer = Lexer::new(self.source, from);
+            lexer.skip_trivia();
+            let start = lexer.pos;
+            lexer.skip_word()?;
+            lexer.skip_char(':').map(|end| start..end)
+        });
+
+        if let Some(span) = &span {
+            self.comments_before(span.start, 1);
+        }
+        self.line(1, header);
+        if let Some(span) = &span {
+            self.trailing_comments(span.end, "");
+        }
+        span.map(|span| span.end)
+    }
+
+    fn variable(&self, var: &VariableDeclaration) -> String {
+        let mut out = format!("${} = ", var.name);
+        match &var.value {
+            VariableDeclarationValue::Bytes(bytes) => write_quoted(&mut out, bytes),
+            VariableDeclarationValue::Regex(regex) => self.write_regex(&mut out, regex),
+            VariableDeclarationValue::HexString(tokens) => {
+                out.push('{');
+                write_hex_tokens(&mut out, tokens);
+                out.push_str(" }");
+            }
+        }
+
+        let modifiers = &var.modifiers;
+        for (enabled, name) in [
+            (modifiers.ascii, "ascii"),
+            (modifiers.wide, "wide"),
+            (modifiers.nocase, "nocase"),
+            (modifiers.fullword, "fullword"),
+        ] {
+            if enabled {
+                out.push(' ');
+                out.push_str(name);
+            }
+        }
+        match modifiers.xor {
+            Some((0, 255)) => out.push_str(" xor"),
+            Some((from, to)) if from == to => {
+                let _r = write!(out, " xor({from})");
+            }
+            Some((from, to)) => {
+                let _r = write!(out, " xor({from}-{to})");
+            }
+            None => (),
+        }
+        if let Some(base64) = &modifiers.base64 {
+            for (enabled, name) in [(base64.ascii, "base64"), (base64.wide, "base64wide")] {
+                if enabled {
+                    out.push(' ');
+                    out.push_str(name);
+                    if let Some(alphabet) = &base64.alphabet {
+                        out.push('(');
+                        write_quoted(&mut out, alphabet);
+                        out.push(')');
+                    }
+                }
+            }
+        }
+        if modifiers.private {
+            out.push_str(" private");
+        }
+
+        out
+    }
+
+    /// Print an expression, on one or multiple lines.
+    ///
+    /// The suffix is appended to the last line of the expression.
+    fn expr_lines(&mut self, expr: &Expression, indent: usize, min: Precedence, suffix: &str) {
+        let inline = self.inline(expr, min);
+        let fits = indent * INDENT_WIDTH + inline.len() + suffix.len() <= MAX_WIDTH;
+
+        if !is_breakable(&expr.expr) || (fits && !self.has_comments_in(&expr.span)) {
+            self.comments_before(expr.span.end, indent);
+            self.line(indent, &format!("{inline}{suffix}"));
+            self.trailing_comments(expr.span.end, suffix.trim());
+            return;
+        }
+
+        if precedence(&expr.expr) < min {
+            self.comments_before(expr.span.start, indent);
+            self.line(indent, "(");
+            self.expr_lines(expr, indent + 1, Precedence::Or, "");
+            self.line(indent, &format!("){suffix}"));
+            self.trailing_comments(expr.span.end, suffix.trim());
+            return;
+        }
+
+        match &expr.expr {
+            ExpressionKind::And(ops) => self.operands_lines(ops, "and", indent, suffix),
+            ExpressionKind::Or(ops) => self.operands_lines(ops, "or", indent, suffix),
+            ExpressionKind::For {
+                body: Some(body), ..
+            }
+            | ExpressionKind::ForIdentifiers { body, .. }
+            | ExpressionKind::With { body, .. } => {
+                self.comments_before(expr.span.start, indent);
+                let mut header = String::new();
+                self.write_body_header(&mut header, &expr.expr);
+                header.push_str(" : (");
+                self.line(indent, &header);
+                self.expr_lines(body, indent + 1, Precedence::Or, "");
+                self.comments_before(expr.span.end, indent + 1);
+                self.line(indent, &format!("){suffix}"));
+                self.trailing_comments(expr.span.end, suffix.trim());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Print the operands of a `and` or `or` expression, one per line.
+    fn operands_lines(&mut self, ops: &[Expression], keyword: &str, indent: usize, suffix: &str) {
+        let separator = format!(" {keyword}");
+        for (i, op) in ops.iter().enumerate() {
+            let op_suffix = if i + 1 == ops.len() {
+                suffix
+            } else {
+                separator.as_str()
+            };
+            self.expr_lines(op, indent, Precedence::Not, op_suffix);
+        }
+    }
+
+    /// Print an expression on a single line.
+    ///
+    /// The expression is parenthesized if its precedence is lower than `min`.
+    fn inline(&self, expr: &Expression, min: Precedence) -> String {
+        let mut out = String::new();
+        self.write_expr(&mut out, expr, min);
+        out
+    }
+
+    fn write_expr(&self, out: &mut String, expr: &Expression, min: Precedence) {
+        let prec = precedence(&expr.expr);
+        if prec < min {
+            out.push('(');
+            self.write_expr(out, expr, Precedence::Or);
+            out.push(')');
+            return;
+        }
+
+        match &expr.expr {
+            ExpressionKind::Filesize => out.push_str("filesize"),
+            ExpressionKind::Entrypoint => out.push_str("entrypoint"),
+            ExpressionKind::ReadInteger { ty, addr } => {
+                out.push_str(read_integer_name(*ty));
+                out.push('(');
+                self.write_expr(out, addr, Precedence::BitOr);
+                out.push(')');
+            }
+            ExpressionKind::Integer(v) => self.write_literal(out, &expr.span, v),
+            ExpressionKind::Double(v) => self.write_literal(out, &expr.span, v),
+            ExpressionKind::Count(name) => {
+                out.push('#');
+                out.push_str(name);
+            }
+            ExpressionKind::CountInRange {
+                variable_name,
+                from,
+                to,
+                ..
+            } => {
+                out.push('#');
+                out.push_str(variable_name);
+                out.push_str(" in ");
+                self.write_range(out, from, to);
+            }
+            ExpressionKind::Offset {
+                variable_name,
+                occurence_number,
+            } => {
+                out.push('@');
+                out.push_str(variable_name);
+                self.write_occurence_number(out, expr, occurence_number);
+            }
+            ExpressionKind::Length {
+                variable_name,
+                occurence_number,
+            } => {
+                out.push('!');
+                out.push_str(variable_name);
+                self.write_occurence_number(out, expr, occurence_number);
+            }
+            ExpressionKind::Neg(v) => {
+                out.push('-');
+                self.write_expr(out, v, Precedence::Unary);
+            }
+            ExpressionKind::BitwiseNot(v) => {
+                out.push('~');
+                self.write_expr(out, v, Precedence::Unary);
+            }
+            ExpressionKind::Add(a, b) => self.write_binary(out, a, " + ", b, prec),
+            ExpressionKind::Sub(a, b) => self.write_binary(out, a, " - ", b, prec),
+            ExpressionKind::Mul(a, b) => self.write_binary(out, a, " * ", b, prec),
+            ExpressionKind::Div(a, b) => self.write_binary(out, a, " \\ ", b, prec),
+            ExpressionKind::Mod(a, b) => self.write_binary(out, a, " % ", b, prec),
+            ExpressionKind::BitwiseXor(a, b) => self.write_binary(out, a, " ^ ", b, prec),
+            ExpressionKind::BitwiseAnd(a, b) => self.write_binary(out, a, " & ", b, prec),
+            ExpressionKind::BitwiseOr(a, b) => self.write_binary(out, a, " | ", b, prec),
+            ExpressionKind::ShiftLeft(a, b) => self.write_binary(out, a, " << ", b, prec),
+            ExpressionKind::ShiftRight(a, b) => self.write_binary(out, a, " >> ", b, prec),
+            ExpressionKind::And(ops) => self.write_list(out, ops, " and ", Precedence::Not),
+            ExpressionKind::Or(ops) => self.write_list(out, ops, " or ", Precedence::Not),
+            ExpressionKind::Not(v) => {
+                out.push_str("not ");
+                self.write_expr(out, v, Precedence::Not);
+            }
+            ExpressionKind::Defined(v) => {
+                out.push_str("defined ");
+                self.write_expr(out, v, Precedence::Not);
+            }
+            ExpressionKind::Cmp {
+                left,
+                right,
+                less_than,
+                can_be_equal,
+            } => {
+                let op = match (less_than, can_be_equal) {
+                    (true, false) => " < ",
+                    (true, true) => " <= ",
+                    (false, false) => " > ",
+                    (false, true) => " >= ",
+                };
+                self.write_binary(out, left, op, right, prec);
+            }
+            ExpressionKind::Eq(a, b) => self.write_binary(out, a, " == ", b, prec),
+            ExpressionKind::NotEq(a, b) => self.write_binary(out, a, " != ", b, prec),
+            ExpressionKind::Contains {
+                haystack,
+                needle,
+                case_insensitive,
+            } => {
+                let op = if *case_insensitive {
+                    " icontains "
+                } else {
+                    " contains "
+                };
+                self.write_binary(out, haystack, op, needle, prec);
+            }
+            ExpressionKind::StartsWith {
+                expr,
+                prefix,
+                case_insensitive,
+            } => {
+                let op = if *case_insensitive {
+                    " istartswith "
+                } else {
+                    " startswith "
+                };
+                self.write_binary(out, expr, op, prefix, prec);
+            }
+            ExpressionKind::EndsWith {
+                expr,
+                suffix,
+                case_insensitive,
+            } => {
+                let op = if *case_insensitive {
+                    " iendswith "
+                } else {
+                    " endswith "
+                };
+                self.write_binary(out, expr, op, suffix, prec);
+            }
+            ExpressionKind::IEquals(a, b) => self.write_binary(out, a, " iequals ", b, prec),
+            ExpressionKind::Matches(v, regex) => {
+                self.write_expr(out, v, prec);
+                out.push_str(" matches ");
+                self.write_regex(out, regex);
+            }
+            ExpressionKind::Boolean(v) => out.push_str(if *v { "true" } else { "false" }),
+            ExpressionKind::Variable(name) => {
+                out.push('$');
+                out.push_str(name);
+            }
+            ExpressionKind::VariableAt {
+                variable_name,
+                offset,
+                ..
+            } => {
+                out.push('$');
+                out.push_str(variable_name);
+                out.push_str(" at ");
+                self.write_expr(out, offset, Precedence::BitOr);
+            }
+            ExpressionKind::VariableIn {
+                variable_name,
+                from,
+                to,
+                ..
+            } => {
+                out.push('$');
+                out.push_str(variable_name);
+                out.push_str(" in ");
+                self.write_range(out, from, to);
+            }
+            ExpressionKind::For {
+                selection,
+                set,
+                body: None,
+            } => {
+                self.write_selection(out, selection);
+                out.push_str(" of ");
+                write_variable_set(out, set);
+            }
+            ExpressionKind::ForIn {
+                selection,
+                set,
+                from,
+                to,
+            } => {
+                self.write_selection(out, selection);
+                out.push_str(" of ");
+                write_variable_set(out, set);
+                out.push_str(" in ");
+                self.write_range(out, from, to);
+            }
+            ExpressionKind::ForAt {
+                selection,
+                set,
+                offset,
+            } => {
+                self.write_selection(out, selection);
+                out.push_str(" of ");
+                write_variable_set(out, set);
+                out.push_str(" at ");
+                self.write_expr(out, offset, Precedence::BitOr);
+            }
+            ExpressionKind::For {
+                body: Some(body), ..
+            }
+            | ExpressionKind::ForIdentifiers { body, .. }
+            | ExpressionKind::With { body, .. } => {
+                self.write_body_header(out, &expr.expr);
+                out.push_str(" : (");
+                self.write_expr(out, body, Precedence::Or);
+                out.push(')');
+            }
+            ExpressionKind::ForRules { selection, set } => {
+                self.write_selection(out, selection);
+                out.push_str(" of (");
+                write_set_elements(out, &set.elements, "");
+                out.push(')');
+            }
+            ExpressionKind::Identifier(identifier) => self.write_identifier(out, identifier),
+            ExpressionKind::Bytes(v) => write_quoted(out, v),
+            ExpressionKind::Regex(regex) => self.write_regex(out, regex),
+        }
+    }
+
+    /// Write the part of an expression with a body that precedes the body, e.g.
+    /// `for any i in (0..3)`.
+    fn write_body_header(&self, out: &mut String, expr: &ExpressionKind) {
+        match expr {
+            ExpressionKind::For { selection, set, .. } => {
+                out.push_str("for ");
+                self.write_selection(out, selection);
+                out.push_str(" of ");
+                write_variable_set(out, set);
+            }
+            ExpressionKind::ForIdentifiers {
+                selection,
+                identifiers,
+                iterator,
+                ..
+            } => {
+                out.push_str("for ");
+                self.write_selection(out, selection);
+                out.push(' ');
+                out.push_str(&identifiers.join(", "));
+                out.push_str(" in ");
+                match iterator {
+                    ForIterator::Identifier(identifier) => self.write_identifier(out, identifier),
+                    ForIterator::Range { from, to } => self.write_range(out, from, to),
+                    ForIterator::List(exprs) => {
+                        out.push('(');
+                        self.write_list(out, exprs, ", ", Precedence::BitOr);
+                        out.push(')');
+                    }
+                }
+            }
+            ExpressionKind::With { declarations, .. } => {
+                out.push_str("with ");
+                for (i, decl) in declarations.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(&decl.name);
+                    out.push_str(" = ");
+                    self.write_expr(out, &decl.expr, Precedence::Or);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn write_binary(
+        &self,
+        out: &mut String,
+        left: &Expression,
+        op: &str,
+        right: &Expression,
+        prec: Precedence,
+    ) {
+        self.write_expr(out, left, prec);
+        out.push_str(op);
+        self.write_expr(out, right, prec.next());
+    }
+
+    fn write_list(&self, out: &mut String, exprs: &[Expression], separator: &str, min: Precedence) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                out.push_str(separator);
+            }
+            self.write_expr(out, expr, min);
+        }
+    }
+
+    fn write_range(&self, out: &mut String, from: &Expression, to: &Expression) {
+        out.push('(');
+        self.write_expr(out, from, Precedence::BitOr);
+        out.push_str("..");
+        self.write_expr(out, to, Precedence::BitOr);
+        out.push(')');
+    }
+
+    fn write_occurence_number(&self, out: &mut String, expr: &Expression, number: &Expression) {
+        // When no occurrence number is specified, the parser generates one with the same span
+        // as the whole expression.
+        if number.span != expr.span {
+            out.push('[');
+            self.write_expr(out, number, Precedence::BitOr);
+            out.push(']');
+        }
+    }
+
+    fn write_selection(&self, out: &mut String, selection: &ForSelection) {
+        match selection {
+            ForSelection::Any => out.push_str("any"),
+            ForSelection::All => out.push_str("all"),
+            ForSelection::None => out.push_str("none"),
+            ForSelection::Expr { expr, as_percent } => {
+                self.write_expr(out, expr, Precedence::Item);
+                if *as_percent {
+                    out.push('%');
+                }
+            }
+        }
+    }
+
+    fn write_identifier(&self, out: &mut String, identifier: &Identifier) {
+        out.push_str(&identifier.name);
+        for op in &identifier.operations {
+            match &op.op {
+                IdentifierOperationType::Subscript(expr) => {
+                    out.push('[');
+                    self.write_expr(out, expr, Precedence::BitOr);
+                    out.push(']');
+                }
+                IdentifierOperationType::Subfield(name) => {
+                    out.push('.');
+                    out.push_str(name);
+                }
+                IdentifierOperationType::FunctionCall(args) => {
+                    out.push('(');
+                    self.write_list(out, args, ", ", Precedence::Or);
+                    out.push(')');
+                }
+            }
+        }
+    }
+
+    /// Write a literal as written in the source, or using its value if not available.
+    fn write_literal<T: ToString>(&self, out: &mut String, span: &Range<usize>, value: &T) {
+        match self.source.get(span.clone()) {
+            Some(v) if !v.is_empty() => out.push_str(v),
+            _ => out.push_str(&value.to_string()),
+        }
+    }
+
+    fn write_regex(&self, out: &mut String, regex: &Regex) {
+        out.push_str(self.source.get(regex.span.clone()).unwrap_or_default());
+    }
+
+    /// Write a line at the given indentation level.
+    fn line(&mut self, indent: usize, content: &str) {
+        for _ in 0..(indent * INDENT_WIDTH) {
+            self.out.push(' ');
+        }
+        self.out.push_str(content);
+        self.out.push('\n');
+    }
+
+    /// Print, one per line, all the comments not printed yet that start before `pos`.
+    fn comments_before(&mut self, pos: usize, indent: usize) {
+        let comments = self.comments;
+        while let Some(comment) = comments.get(self.next_comment) {
+            if comment.span.start >= pos {
+                break;
+            }
+            self.next_comment += 1;
+            self.line(indent, &comment.text);
+            if self.has_blank_line(comment.span.end, self.next_start(pos)) {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    /// Append to the last printed line the comments following `end` on the same line.
+    ///
+    /// Only comments separated from `end` by whitespaces, parentheses and the given separator,
+    /// and ending the line, are appended. Other comments belong to the next element, and are
+    /// printed before it.
+    fn trailing_comments(&mut self, mut end: usize, mut separator: &str) {
+        let comments = self.comments;
+        while let Some(comment) = comments.get(self.next_comment) {
+            match self.source.get(end..comment.span.start) {
+                Some(between) if !between.contains('\n') && is_separator(between, separator) => (),
+                _ => break,
+            }
+            if !self.ends_line(self.next_comment) {
+                break;
+            }
+            self.next_comment += 1;
+            let _r = self.out.pop();
+            self.out.push(' ');
+            self.out.push_str(&comment.text);
+            self.out.push('\n');
+            end = comment.span.end;
+            separator = "";
+        }
+    }
+
+    /// Is the comment at the given index only followed by whitespaces and comments on its line.
+    fn ends_line(&self, mut index: usize) -> bool {
+        while let Some(comment) = self.comments.get(index) {
+            let rest = self.source.get(comment.span.end..).unwrap_or_default();
+            let trimmed = rest.trim_start_matches(|c: char| c != '\n' && c.is_whitespace());
+            if trimmed.is_empty() || trimmed.starts_with('\n') {
+                return true;
+            }
+
+            // Something follows the comment on the same line, which is fine only if it is
+            // another comment.
+            let next_start = comment.span.end + (rest.len() - trimmed.len());
+            index += 1;
+            match self.comments.get(index) {
+                Some(next) if next.span.start == next_start => (),
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Are there comments not printed yet in the given span.
+    fn has_comments_in(&self, span: &Range<usize>) -> bool {
+        self.comments[self.next_comment..]
+            .iter()
+            .any(|comment| span.contains(&comment.span.start))
+    }
+
+    /// Position of the next element to print, which is either `pos` or the next comment.
+    fn next_start(&self, pos: usize) -> usize {
+        self.comments
+            .get(self.next_comment)
+            .map_or(pos, |comment| comment.span.start.min(pos))
+    }
+
+    /// Is there an empty line in the source between the two positions.
+    fn has_blank_line(&self, start: usize, end: usize) -> bool {
+        let Some(text) = self.source.get(start..end) else {
+            return false;
+        };
+
+        let mut nb_newlines = 0;
+        for c in text.chars() {
+            if c == '\n' {
+                nb_newlines += 1;
+                if nb_newlines >= 2 {
+                    return true;
+                }
+            } else if !c.is_whitespace() {
+                nb_newlines = 0;
+            }
+        }
+        false
+    }
+}
+
+/// Minimal lexer on the source, used to find elements whose positions are not stored in the
+/// AST.
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str, pos: usize) -> Self {
+        Self { source, pos }
+    }
+
+    fn rest(&self) -> &'a str {
+        self.source.get(self.pos..).unwrap_or_default()
+    }
+
+    /// Skip whitespaces and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            let comment_len = if trimmed.starts_with("//") {
+                trimmed.find('\n').unwrap_or(trimmed.len())
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                comment.find("*/").map_or(trimmed.len(), |v| v + 4)
+            } else {
+                return;
+            };
+            self.pos += comment_len;
+        }
+    }
+
+    /// Skip the given character, and return the position following it.
+    fn skip_char(&mut self, c: char) -> Option<usize> {
+        self.skip_trivia();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            Some(self.pos)
+        } else {
+            None
+        }
+    }
+
+    /// Skip a word, ie an identifier, keyword or number.
+    fn skip_word(&mut self) -> Option<()> {
+        self.skip_trivia();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        if len == 0 {
+            None
+        } else {
+            self.pos += len;
+            Some(())
+        }
+    }
+
+    /// Skip a quoted string.
+    fn skip_quoted(&mut self) -> Option<()> {
+        let _r = self.skip_char('"')?;
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    let _r = chars.next();
+                }
+                '"' => {
+                    self.pos += i + 1;
+                    return Some(());
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Retrieve the spans of the metadatas of a rule, the lexer being placed just after the
+    /// `meta:` header.
+    fn metadatas_spans(&mut self, nb_metadatas: usize) -> Option<Vec<Range<usize>>> {
+        let mut spans = Vec::with_capacity(nb_metadatas);
+        for _ in 0..nb_metadatas {
+            self.skip_trivia();
+            let start = self.pos;
+            self.skip_word()?;
+            let _r = self.skip_char('=')?;
+            self.skip_trivia();
+            if self.rest().starts_with('"') {
+                self.skip_quoted()?;
+            } else {
+                let _r = self.skip_char('-');
+                self.skip_word()?;
+            }
+            spans.push(start..self.pos);
+        }
+        Some(spans)
+    }
+}
+
+/// Is the text only made of whitespaces, parentheses and the given separator.
+fn is_separator(text: &str, separator: &str) -> bool {
+    let rest: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '(' && *c != ')')
+        .collect();
+    rest.is_empty() || rest == separator
+}
+
+fn read_integer_name(ty: ReadIntegerType) -> &'static str {
+    match ty {
+        ReadIntegerType::Int8 => "int8",
+        ReadIntegerType::Uint8 => "uint8",
+        ReadIntegerType::Int16 => "int16",
+        ReadIntegerType::Int16BE => "int16be",
+        ReadIntegerType::Uint16 => "uint16",
+        ReadIntegerType::Uint16BE => "uint16be",
+        ReadIntegerType::Int32 => "int32",
+        ReadIntegerType::Int32BE => "int32be",
+        ReadIntegerType::Uint32 => "uint32",
+        ReadIntegerType::Uint32BE => "uint32be",
+    }
+}
+
+fn metadata_value(value: &MetadataValue) -> String {
+    match value {
+        MetadataValue::Bytes(v) => {
+            let mut out = String::new();
+            write_quoted(&mut out, v);
+            out
+        }
+        MetadataValue::Integer(v) => v.to_string(),
+        MetadataValue::Boolean(v) => v.to_string(),
+    }
+}
+
+fn write_variable_set(out: &mut String, set: &VariableSet) {
+    if set.elements.is_empty() {
+        out.push_str("them");
+    } else {
+        out.push('(');
+        write_set_elements(out, &set.elements, "$");
+        out.push(')');
+    }
+}
+
+fn write_set_elements(out: &mut String, elements: &[SetElement], prefix: &str) {
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(prefix);
+        out.push_str(&element.name);
+        if element.is_wildcard {
+            out.push('*');
+        }
+    }
+}
+
+/// Write bytes as a quoted string.
+///
+/// Valid UTF-8 text is kept as is, other bytes and control characters are escaped.
+fn write_quoted(out: &mut String, bytes: &[u8]) {
+    out.push('"');
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(v) => (v, &[][..]),
+            Err(err) => {
+                let (valid, invalid) = rest.split_at(err.valid_up_to());
+                (std::str::from_utf8(valid).unwrap_or_default(), invalid)
+            }
+        };
+
+        for c in valid.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\t' => out.push_str("\\t"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                c if c.is_control() => {
+                    let mut buf = [0; 4];
+                    for b in c.encode_utf8(&mut buf).bytes() {
+                        let _r = write!(out, "\\x{b:02X}");
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+
+        match invalid.split_first() {
+            Some((b, tail)) => {
+                let _r = write!(out, "\\x{b:02X}");
+                rest = tail;
+            }
+            None => break,
+        }
+    }
+    out.push('"');
+}
+
+/// Write the tokens of a hex string, each token being preceded by a space.
+fn write_hex_tokens(out: &mut String, tokens: &[Token]) {
+    for token in tokens {
+        out.push(' ');
+        match token {
+            Token::Byte(b) => {
+                let _r = write!(out, "{b:02X}");
+            }
+            Token::NotByte(b) => {
+                let _r = write!(out, "~{b:02X}");
+            }
+            Token::MaskedByte(b, mask) => write_masked_byte(out, *b, mask),
+            Token::NotMaskedByte(b, mask) => {
+                out.push('~');
+                write_masked_byte(out, *b, mask);
+            }
+            Token::Jump(jump) => match jump.to {
+                Some(to) if to == jump.from => {
+                    let _r = write!(out, "[{to}]");
+                }
+                Some(to) => {
+                    let _r = write!(out, "[{}-{}]", jump.from, to);
+                }
+                None => {
+                    let _r = write!(out, "[{}-]", jump.from);
+                }
+            },
+            Token::Alternatives(alts) => {
+                out.push('(');
+                for (i, alt) in alts.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(" |");
+                    }
+                    write_hex_tokens(out, alt);
+                }
+                out.push_str(" )");
+            }
+        }
+    }
+}
+
+fn write_masked_byte(out: &mut String, b: u8, mask: &Mask) {
+    match mask {
+        Mask::Left => {
+            let _r = write!(out, "?{b:X}");
+        }
+        Mask::Right => {
+            let _r = write!(out, "{b:X}?");
+        }
+        Mask::All => out.push_str("??"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test(input: &str, expected: &str) {
+        let formatted = format(input).unwrap();
+        assert_eq!(formatted, expected);
+
+        // Formatting must be idempotent
+        assert_eq!(format(&formatted).unwrap(), formatted);
+    }
+
+    #[track_caller]
+    fn test_cond(cond: &str, expected: &str) {
+        test(
+            &format!("rule a {{ condition: {cond} }}"),
+            &format!("rule a\n{{\n    condition:\n        {expected}\n}}\n"),
+        );
+    }
+
+    #[test]
+    fn test_format_file() {
+        test(
+            r#"import "pe"
+import "math"
+include "a.yar"
+private global rule foo:tag1 tag2{meta:author="me" score=-5 ok=true
+strings:$a="abc"wide ascii $b={4d 5a ?? ?1 c? ~00 ~?2 [2] [3-] [1-4] (01|02 03)}private
+$c=/a+b/is nocase condition:$a and $b or $c}
+rule bar { condition: foo }"#,
+            r#"import "pe"
+import "math"
+include "a.yar"
+
+private global rule foo : tag1 tag2
+{
+    meta:
+        author = "me"
+        score = -5
+        ok = true
+    strings:
+        $a = "abc" ascii wide
+        $b = { 4D 5A ?? ?1 C? ~00 ~?2 [2] [3-] [1-4] ( 01 | 02 03 ) } private
+        $c = /a+b/is nocase
+    condition:
+        ($a and $b) or $c
+}
+
+rule bar
+{
+    condition:
+        foo
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_format_modifiers() {
+        test(
+            r#"rule a { strings:
+                $a = "a" private fullword xor(1-3)
+                $b = "b" base64wide("!@#$%^&*(){}[].,|ABCDEFGHIJ\x09LMNOPQRSTUVWXYZabcdefghijklmnopqrstu")
+                $c = "c" base64 wide base64wide
+                $d = "d" wide nocase ascii
+                $e = "e" xor(0-255)
+                $f = "f" xor(2)
+                condition: all of them }"#,
+            r#"rule a
+{
+    strings:
+        $a = "a" fullword xor(1-3) private
+        $b = "b" base64wide("!@#$%^&*(){}[].,|ABCDEFGHIJ\tLMNOPQRSTUVWXYZabcdefghijklmnopqrstu")
+        $c = "c" wide base64 base64wide
+        $d = "d" ascii wide nocase
+        $e = "e" xor
+        $f = "f" xor(2)
+    condition:
+        all of them
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_format_quoted() {
+        test(
+            r#"rule a { meta: a = "\x41\"\\\t\r\n\x01\xff é" strings: $ = "\x7Fà\x00" condition: true }"#,
+            r#"rule a
+{
+    meta:
+        a = "A\"\\\t\r\n\x01\xFF é"
+    strings:
+        $ = "\x7Fà\x00"
+    condition:
+        true
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_format_expressions() {
+        test_cond(
+            "filesize>0x10 and entrypoint< 1KB",
+            "filesize > 0x10 and entrypoint < 1KB",
+        );
+        test_cond("(((true)))", "true");
+        test_cond("(true or false) and true", "(true or false) and true");
+        test_cond("true or (false and true)", "true or (false and true)");
+        test_cond(
+            "true or false or (true or false)",
+            "true or false or (true or false)",
+        );
+        test_cond("not (true and false)", "not (true and false)");
+        test_cond("not not defined a", "not not defined a");
+        test_cond("1 + 2 * 3 == (1 + 2) * 3", "1 + 2 * 3 == (1 + 2) * 3");
+        test_cond("1 - (2 - 3) == 1 - 2 - 3", "1 - (2 - 3) == 1 - 2 - 3");
+        test_cond("-(-1) == ~ - 1", "--1 == ~-1");
+        test_cond("(1 | 2) ^ 3 & 4 << 5 % 6", "(1 | 2) ^ 3 & 4 << 5 % 6");
+        test_cond("1 \\ 2 >> 1 > 3.5", "1 \\ 2 >> 1 > 3.5");
+        test_cond("(1 < 2) == (3 >= 4)", "1 < 2 == 3 >= 4");
+        test_cond(
+            r#""a" contains "b" != ("c" icontains "d")"#,
+            r#""a" contains "b" != ("c" icontains "d")"#,
+        );
+        test_cond(
+            r#""a" startswith "b" and "a" istartswith "b" and "a" endswith "b""#,
+            r#""a" startswith "b" and "a" istartswith "b" and "a" endswith "b""#,
+        );
+        test_cond(
+            r#""a" iendswith "b" and "a" iequals "b" and "a" matches /a\/b/i"#,
+            r#""a" iendswith "b" and "a" iequals "b" and "a" matches /a\/b/i"#,
+        );
+        test_cond(
+            "uint8(0) + int16be(uint32(0)) + uint32be(1 + 2)",
+            "uint8(0) + int16be(uint32(0)) + uint32be(1 + 2)",
+        );
+        test_cond(
+            "pe.sections[1 + 2].name(true and false, /a/) == a.b",
+            "pe.sections[1 + 2].name(true and false, /a/) == a.b",
+        );
+        test_cond(
+            "#a in (0..10) == @a[2] + !a + @b + !b[(1)]",
+            "#a in (0..10) == @a[2] + !a + @b + !b[1]",
+        );
+        test_cond(
+            "$ and $a at 5 and $b in (1 + 2 .. filesize)",
+            "$ and $a at 5 and $b in (1 + 2..filesize)",
+        );
+    }
+
+    #[test]
+    fn test_format_for_expressions() {
+        test_cond(
+            "any of them and all of ($a*, $b) and none of them in (0..5)",
+            "any of them and all of ($a*, $b) and none of them in (0..5)",
+        );
+        test_cond(
+            "2 of them at 5 and 50% of them and (1 + 1) of (a, b*)",
+            "2 of them at 5 and 50% of them and (1 + 1) of (a, b*)",
+        );
+        test_cond("for any of ($a) : ( $ )", "for any of ($a) : ($)");
+        test_cond(
+            "for 2 i in (1, 2, 3) : (i > 1) and for all k, v in tests.dict: (k == v)",
+            "for 2 i in (1, 2, 3) : (i > 1) and for all k, v in tests.dict : (k == v)",
+        );
+        test_cond(
+            "(for any i in (0 .. 3): (true)) == true",
+            "(for any i in (0..3) : (true)) == true",
+        );
+        test_cond(
+            "with a = 1, b = a + 1: (b == 2)",
+            "with a = 1, b = a + 1 : (b == 2)",
+        );
+        test_cond("not $a", "not $a");
+        test_cond("($a) == true", "($a) == true");
+    }
+
+    #[test]
+    fn test_format_long_condition() {
+        test_cond(
+            "uint16(0) == 0x5A4D and filesize < 200KB and (pe.number_of_sections > 3 or \
+             pe.is_dll()) and for any i in (0..pe.number_of_sections - 1): (pe.sections[i].name \
+             == \".text\" and pe.sections[i].raw_data_size > 0x1000)",
+            "uint16(0) == 0x5A4D and
+        filesize < 200KB and
+        (pe.number_of_sections > 3 or pe.is_dll()) and
+        for any i in (0..pe.number_of_sections - 1) : (
+            pe.sections[i].name == \".text\" and pe.sections[i].raw_data_size > 0x1000
+        )",
+        );
+
+        test_cond(
+            "with aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa = 1, bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb = 2: \
+             (aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa == 1 and bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb == 2) \
+             and true",
+            "with aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa = 1, bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb = 2 : (
+            aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa == 1 and bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb == 2
+        ) and
+        true",
+        );
+    }
+
+    #[test]
+    fn test_format_comments() {
+        test(
+            r#"// Header
+
+/* Imports */
+import "pe" // pe
+import "math"
+
+import "cuckoo"
+/* foo */ rule a // a
+{ // brace
+  meta: // meta
+    a = 1 // one
+
+    /* b */ b = "//" c = true // c
+  strings:
+    // a
+    $a = "a" // a
+
+    $b = { 01 /* b */ 02 } // b
+  condition:
+    // Comment
+    $a and // a
+    $b // b
+    /* end */ } // end rule
+
+// footer
+"#,
+            r#"// Header
+
+/* Imports */
+import "pe" // pe
+import "math"
+
+import "cuckoo"
+
+/* foo */
+rule a // a
+{ // brace
+    meta: // meta
+        a = 1 // one
+
+        /* b */
+        b = "//"
+        c = true // c
+    strings:
+        // a
+        $a = "a" // a
+
+        /* b */
+        $b = { 01 02 } // b
+    condition:
+        // Comment
+        $a and // a
+        $b // b
+        /* end */
+} // end rule
+
+// footer
+"#,
+        );
+
+        // Comments inside a line are moved before it.
+        test(
+            "rule a /* a */ : t { condition: true }",
+            r"/* a */
+rule a : t
+{
+    condition:
+        true
+}
+",
+        );
+        test(
+            "rule a { condition: true and /* c */ false or true }",
+            r"rule a
+{
+    condition:
+        (
+            true and
+            /* c */
+            false
+        ) or
+        true
+}
+",
+        );
+
+        // CRLF line endings are normalized, including after comments.
+        test(
+            "// a\r\nrule a { // b\r\n    condition: /* c */ true // d\r\n}\r\n",
+            r"// a
+rule a
+{ // b
+    condition:
+        /* c */
+        true // d
+}
+",
+        );
+    }
+
+    /// List the comments of a source, each one with the tokens preceding and following it.
+    fn comments_neighbors(source: &str) -> Vec<(String, String, String)> {
+        fn tokenize(text: &str, tokens: &mut Vec<String>) {
+            let is_word = |c: char| c.is_alphanumeric() || c == '_';
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                let mut token = String::from(c);
+                while is_word(c) {
+                    match chars.next_if(|c| is_word(*c)) {
+                        Some(c) => token.push(c),
+                        None => break,
+                    }
+                }
+                tokens.push(token);
+            }
+        }
+
+        let comments = crate::parse(source).unwrap().comments;
+        let mut tokens = Vec::new();
+        let mut indexes = Vec::new();
+        let mut pos = 0;
+        for comment in &comments {
+            tokenize(&source[pos..comment.span.start], &mut tokens);
+            indexes.push(tokens.len());
+            tokens.push(comment.text.clone());
+            pos = comment.span.end;
+        }
+        tokenize(&source[pos..], &mut tokens);
+
+        indexes
+            .into_iter()
+            .map(|i| {
+                let previous = i.checked_sub(1).map(|i| tokens[i].clone());
+                let next = tokens.get(i + 1).cloned();
+                (
+                    previous.unwrap_or_default(),
+                    tokens[i].clone(),
+                    next.unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_format_comments_roundtrip() {
+        for input in [
+            "// only a comment\n",
+            "/* a */ /* b */\n\n// c\n",
+            r#"import "pe" /* a */ // b
+/* c */ include "a.yar" /* d */
+/* e */
+rule a : t /* g */
+{ /* h */
+    meta: /* i */ a = 1 /* j */
+    b = "/* k */" // l
+    /* m */ strings: /* n */ $a = "//" /* o */
+    $b = /a\/* /
+    /* p */ condition: /* q */ ($a and /* r */
+    // s
+    $b) or /* t */ true /* u */ } /* v */
+/* w */ rule b { condition: for any i in (0..1): ( /* x */ true ) } // y
+"#,
+            "rule a { condition: true and /* c */ false or true }",
+        ] {
+            let formatted = format(input).unwrap();
+            assert_eq!(format(&formatted).unwrap(), formatted, "input: {input}");
+            assert_eq!(
+                comments_neighbors(&formatted),
+                comments_neighbors(input),
+                "input: {input}\nformatted: {formatted}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_print_rule_and_expression() {
+        let source = "rule a : t { condition: 1 + (2 * 3) == 0x7 and /* c */ true }";
+        let file = crate::parse(source).unwrap();
+        let YaraFileComponent::Rule(rule) = &file.components[0] else {
+            panic!()
+        };
+
+        assert_eq!(
+            print_rule(rule, source),
+            "rule a : t\n{\n    condition:\n        1 + 2 * 3 == 0x7 and true\n}\n"
+        );
+        assert_eq!(
+            print_expression(&rule.condition, source),
+            "1 + 2 * 3 == 0x7 and true"
+        );
+    }
+
+    #[test]
+    fn test_format_err() {
+        assert!(format("rule a {").is_err());
+    }
+}
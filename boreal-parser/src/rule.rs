@@ -66,9 +66,10 @@ pub struct RuleTag {
 
 /// Value associated with a metadata key.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MetadataValue {
     /// Bytestring variant.
-    Bytes(Vec<u8>),
+    Bytes(#[cfg_attr(feature = "serde", serde(with = "serde_bytes"))] Vec<u8>),
     /// Integer variant.
     Integer(i64),
     /// Boolean variant.
@@ -77,6 +78,7 @@ pub enum MetadataValue {
 
 /// A metadata key-value, associated with a rule.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metadata {
     /// Name of the metadata.
     pub name: String,
@@ -94,6 +94,30 @@ impl Iterator for Iter {
     }
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::wire::{Deserialize, DeserializeContext, Serialize};
+
+    use super::Bitmap;
+
+    impl Serialize for Bitmap {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.low.serialize(writer);
+            self.high.serialize(writer);
+        }
+    }
+
+    impl Deserialize for Bitmap {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            let low = u128::deserialize_reader(ctx, reader)?;
+            let high = u128::deserialize_reader(ctx, reader)?;
+            Ok(Self { low, high })
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Bitmap;
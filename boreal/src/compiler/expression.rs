@@ -338,6 +338,20 @@ pub enum Expression {
         set: RuleSet,
     },
 
+    /// Bind identifiers to values, and evaluate an expression using those identifiers.
+    ///
+    /// For example: `with s = pe.sections[0] : ( s.virtual_size > 0x1000 )`
+    With {
+        /// Values to bind, in order of declaration.
+        ///
+        /// Each value is pushed on the bounded identifiers stack before the next one is
+        /// evaluated, so that declarations can use the previous ones.
+        declarations: Vec<WithValue>,
+
+        /// Body to evaluate with the identifiers bound.
+        body: Box<Expression>,
+    },
+
     /// Call into a module
     Module(ModuleExpression),
 
@@ -870,6 +884,33 @@ pub(super) fn compile_expression(
             span,
         }),
 
+        parser::ExpressionKind::With { declarations, body } => {
+            let mut names = Vec::with_capacity(declarations.len());
+            let mut values = Vec::with_capacity(declarations.len());
+            for declaration in declarations {
+                values.push(compile_with_declaration(
+                    compiler,
+                    declaration.expr,
+                    &declaration.name,
+                    &declaration.name_span,
+                )?);
+                names.push(declaration.name);
+            }
+            let body = compile_expression(compiler, *body)?;
+            for name in &names {
+                compiler.remove_bounded_identifier(name);
+            }
+
+            Ok(Expr {
+                expr: Expression::With {
+                    declarations: values,
+                    body: Box::new(to_bool_expr(compiler, body)?),
+                },
+                ty: Type::Boolean,
+                span,
+            })
+        }
+
         parser::ExpressionKind::Identifier(identifier) => {
             let (expr, ty) = compile_identifier(compiler, identifier, &span)?;
 
@@ -1217,6 +1258,59 @@ fn compile_for_iterator(
     }
 }
 
+/// Value bound to an identifier in a 'with' expression.
+#[derive(Debug)]
+pub enum WithValue {
+    /// A primitive value, computed by evaluating the expression.
+    Expression(Expression),
+
+    /// A module value, which can be a primitive, but also an object, an array, etc.
+    Module(ModuleExpression),
+}
+
+/// Compile the value of a declaration in a 'with' expression, and bind it to its identifier.
+fn compile_with_declaration(
+    compiler: &mut RuleCompiler<'_>,
+    expr: parser::Expression,
+    name: &str,
+    name_span: &Range<usize>,
+) -> Result<WithValue, CompilationError> {
+    let (value, value_type) = match expr.expr {
+        // Identifiers are handled separately, as they may refer to module values that
+        // are not primitives, such as `pe.sections[0]`.
+        parser::ExpressionKind::Identifier(identifier) => {
+            compile_identifier_as_with_value(compiler, identifier, &expr.span)?
+        }
+        expr_kind => {
+            let expr = compile_expression(
+                compiler,
+                parser::Expression {
+                    expr: expr_kind,
+                    span: expr.span,
+                },
+            )?;
+            let value_type = match expr.ty {
+                Type::Integer => ModuleType::Integer,
+                Type::Float => ModuleType::Float,
+                Type::Bytes => ModuleType::Bytes,
+                Type::Boolean => ModuleType::Boolean,
+                Type::Regex => {
+                    return Err(CompilationError::ExpressionInvalidType {
+                        ty: expr.ty.to_string(),
+                        expected_type: "integer, floating-point number, bytes or boolean"
+                            .to_owned(),
+                        span: expr.span,
+                    })
+                }
+            };
+            (WithValue::Expression(expr.expr), value_type)
+        }
+    };
+
+    compiler.add_bounded_identifier(name, value_type, name_span)?;
+    Ok(value)
+}
+
 fn compile_regex(
     compiler: &mut RuleCompiler<'_>,
     regex: boreal_parser::regex::Regex,
@@ -1326,6 +1420,524 @@ fn compile_identifier_as_iterator(
         })
 }
 
+fn compile_identifier_as_with_value(
+    compiler: &mut RuleCompiler<'_>,
+    identifier: parser::Identifier,
+    identifier_span: &Range<usize>,
+) -> Result<(WithValue, ModuleType), CompilationError> {
+    // First, try to resolve to a bound identifier.
+    let res = compiler.bounded_identifiers.get(&identifier.name).cloned();
+    let module_use = if let Some((identifier_type, index)) = res.as_deref() {
+        module::compile_bounded_identifier_use(compiler, identifier_type, identifier, *index)?
+    // Then, try to resolve to a module. This has precedence over rule names.
+    } else if let Some(module) = compiler.namespace.imported_modules.get(&identifier.name) {
+        module::compile_identifier(compiler, module, identifier, identifier_span)?
+    // Otherwise, this can only be a primitive value.
+    } else {
+        let (expr, ty) = compile_identifier(compiler, identifier, identifier_span)?;
+        let value_type = match ty {
+            Type::Integer => ModuleType::Integer,
+            Type::Float => ModuleType::Float,
+            Type::Bytes => ModuleType::Bytes,
+            Type::Regex => ModuleType::Regex,
+            Type::Boolean => ModuleType::Boolean,
+        };
+        return Ok((WithValue::Expression(expr), value_type));
+    };
+
+    module_use
+        .into_with_value()
+        .ok_or_else(|| CompilationError::InvalidIdentifierUse {
+            span: identifier_span.clone(),
+        })
+}
+
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use boreal_parser::expression::ReadIntegerType;
+
+    use crate::compiler::module::ModuleExpression;
+    use crate::regex::Regex;
+    use crate::wire::{
+        invalid_data, read_bytes, write_bytes, Deserialize, DeserializeContext, Serialize,
+    };
+
+    use super::{
+        Expression, ForIterator, ForSelection, RuleSet, VariableIndex, VariableSet, WithValue,
+    };
+
+    impl Serialize for Expression {
+        fn serialize(&self, w: &mut Vec<u8>) {
+            match self {
+                Self::Filesize => w.push(0),
+                Self::Entrypoint => w.push(1),
+                Self::ReadInteger { ty, addr } => {
+                    w.push(2);
+                    ty.serialize(w);
+                    addr.serialize(w);
+                }
+                Self::Integer(v) => {
+                    w.push(3);
+                    v.serialize(w);
+                }
+                Self::Double(v) => {
+                    w.push(4);
+                    v.serialize(w);
+                }
+                Self::Count(v) => {
+                    w.push(5);
+                    v.serialize(w);
+                }
+                Self::CountInRange {
+                    variable_index,
+                    from,
+                    to,
+                } => {
+                    w.push(6);
+                    variable_index.serialize(w);
+                    from.serialize(w);
+                    to.serialize(w);
+                }
+                Self::Offset {
+                    variable_index,
+                    occurence_number,
+                } => {
+                    w.push(7);
+                    variable_index.serialize(w);
+                    occurence_number.serialize(w);
+                }
+                Self::Length {
+                    variable_index,
+                    occurence_number,
+                } => {
+                    w.push(8);
+                    variable_index.serialize(w);
+                    occurence_number.serialize(w);
+                }
+                Self::Neg(v) => {
+                    w.push(9);
+                    v.serialize(w);
+                }
+                Self::Add(a, b) => serialize_binary(w, 10, a, b),
+                Self::Sub(a, b) => serialize_binary(w, 11, a, b),
+                Self::Mul(a, b) => serialize_binary(w, 12, a, b),
+                Self::Div(a, b) => serialize_binary(w, 13, a, b),
+                Self::Mod(a, b) => serialize_binary(w, 14, a, b),
+                Self::BitwiseXor(a, b) => serialize_binary(w, 15, a, b),
+                Self::BitwiseAnd(a, b) => serialize_binary(w, 16, a, b),
+                Self::BitwiseOr(a, b) => serialize_binary(w, 17, a, b),
+                Self::BitwiseNot(v) => {
+                    w.push(18);
+                    v.serialize(w);
+                }
+                Self::ShiftLeft(a, b) => serialize_binary(w, 19, a, b),
+                Self::ShiftRight(a, b) => serialize_binary(w, 20, a, b),
+                Self::And(v) => {
+                    w.push(21);
+                    v.serialize(w);
+                }
+                Self::Or(v) => {
+                    w.push(22);
+                    v.serialize(w);
+                }
+                Self::Not(v) => {
+                    w.push(23);
+                    v.serialize(w);
+                }
+                Self::Cmp {
+                    left,
+                    right,
+                    less_than,
+                    can_be_equal,
+                } => {
+                    w.push(24);
+                    left.serialize(w);
+                    right.serialize(w);
+                    less_than.serialize(w);
+                    can_be_equal.serialize(w);
+                }
+                Self::Eq(a, b) => serialize_binary(w, 25, a, b),
+                Self::NotEq(a, b) => serialize_binary(w, 26, a, b),
+                Self::Contains {
+                    haystack,
+                    needle,
+                    case_insensitive,
+                } => {
+                    serialize_binary(w, 27, haystack, needle);
+                    case_insensitive.serialize(w);
+                }
+                Self::StartsWith {
+                    expr,
+                    prefix,
+                    case_insensitive,
+                } => {
+                    serialize_binary(w, 28, expr, prefix);
+                    case_insensitive.serialize(w);
+                }
+                Self::EndsWith {
+                    expr,
+                    suffix,
+                    case_insensitive,
+                } => {
+                    serialize_binary(w, 29, expr, suffix);
+                    case_insensitive.serialize(w);
+                }
+                Self::IEquals(a, b) => serialize_binary(w, 30, a, b),
+                Self::Matches(expr, regex) => {
+                    w.push(31);
+                    expr.serialize(w);
+                    regex.serialize(w);
+                }
+                Self::Defined(v) => {
+                    w.push(32);
+                    v.serialize(w);
+                }
+                Self::Boolean(v) => {
+                    w.push(33);
+                    v.serialize(w);
+                }
+                Self::Variable(v) => {
+                    w.push(34);
+                    v.serialize(w);
+                }
+                Self::VariableAt {
+                    variable_index,
+                    offset,
+                } => {
+                    w.push(35);
+                    variable_index.serialize(w);
+                    offset.serialize(w);
+                }
+                Self::VariableIn {
+                    variable_index,
+                    from,
+                    to,
+                } => {
+                    w.push(36);
+                    variable_index.serialize(w);
+                    from.serialize(w);
+                    to.serialize(w);
+                }
+                Self::For {
+                    selection,
+                    set,
+                    body,
+                } => {
+                    w.push(37);
+                    selection.serialize(w);
+                    set.elements.serialize(w);
+                    body.serialize(w);
+                }
+                Self::ForIdentifiers {
+                    selection,
+                    iterator,
+                    body,
+                } => {
+                    w.push(38);
+                    selection.serialize(w);
+                    iterator.serialize(w);
+                    body.serialize(w);
+                }
+                Self::ForRules { selection, set } => {
+                    w.push(39);
+                    selection.serialize(w);
+                    set.elements.serialize(w);
+                    set.already_matched.serialize(w);
+                }
+                Self::Module(v) => {
+                    w.push(40);
+                    v.serialize(w);
+                }
+                Self::Rule(v) => {
+                    w.push(41);
+                    v.serialize(w);
+                }
+                Self::ExternalSymbol(v) => {
+                    w.push(42);
+                    v.serialize(w);
+                }
+                Self::Bytes(v) => {
+                    w.push(43);
+                    write_bytes(v, w);
+                }
+                Self::Regex(v) => {
+                    w.push(44);
+                    v.serialize(w);
+                }
+                Self::With { declarations, body } => {
+                    w.push(45);
+                    declarations.serialize(w);
+                    body.serialize(w);
+                }
+            }
+        }
+    }
+
+    fn serialize_binary(w: &mut Vec<u8>, discriminant: u8, a: &Expression, b: &Expression) {
+        w.push(discriminant);
+        a.serialize(w);
+        b.serialize(w);
+    }
+
+    impl Deserialize for Expression {
+        fn deserialize_reader(ctx: &DeserializeContext, r: &mut &[u8]) -> io::Result<Self> {
+            let boxed = |r: &mut &[u8]| Box::<Expression>::deserialize_reader(ctx, r);
+            let var_index = |r: &mut &[u8]| VariableIndex::deserialize_reader(ctx, r);
+            let boolean = |r: &mut &[u8]| bool::deserialize_reader(ctx, r);
+
+            Ok(match u8::deserialize_reader(ctx, r)? {
+                0 => Self::Filesize,
+                1 => Self::Entrypoint,
+                2 => Self::ReadInteger {
+                    ty: ReadIntegerType::deserialize_reader(ctx, r)?,
+                    addr: boxed(r)?,
+                },
+                3 => Self::Integer(i64::deserialize_reader(ctx, r)?),
+                4 => Self::Double(f64::deserialize_reader(ctx, r)?),
+                5 => Self::Count(var_index(r)?),
+                6 => Self::CountInRange {
+                    variable_index: var_index(r)?,
+                    from: boxed(r)?,
+                    to: boxed(r)?,
+                },
+                7 => Self::Offset {
+                    variable_index: var_index(r)?,
+                    occurence_number: boxed(r)?,
+                },
+                8 => Self::Length {
+                    variable_index: var_index(r)?,
+                    occurence_number: boxed(r)?,
+                },
+                9 => Self::Neg(boxed(r)?),
+                10 => Self::Add(boxed(r)?, boxed(r)?),
+                11 => Self::Sub(boxed(r)?, boxed(r)?),
+                12 => Self::Mul(boxed(r)?, boxed(r)?),
+                13 => Self::Div(boxed(r)?, boxed(r)?),
+                14 => Self::Mod(boxed(r)?, boxed(r)?),
+                15 => Self::BitwiseXor(boxed(r)?, boxed(r)?),
+                16 => Self::BitwiseAnd(boxed(r)?, boxed(r)?),
+                17 => Self::BitwiseOr(boxed(r)?, boxed(r)?),
+                18 => Self::BitwiseNot(boxed(r)?),
+                19 => Self::ShiftLeft(boxed(r)?, boxed(r)?),
+                20 => Self::ShiftRight(boxed(r)?, boxed(r)?),
+                21 => Self::And(Vec::deserialize_reader(ctx, r)?),
+                22 => Self::Or(Vec::deserialize_reader(ctx, r)?),
+                23 => Self::Not(boxed(r)?),
+                24 => Self::Cmp {
+                    left: boxed(r)?,
+                    right: boxed(r)?,
+                    less_than: boolean(r)?,
+                    can_be_equal: boolean(r)?,
+                },
+                25 => Self::Eq(boxed(r)?, boxed(r)?),
+                26 => Self::NotEq(boxed(r)?, boxed(r)?),
+                27 => Self::Contains {
+                    haystack: boxed(r)?,
+                    needle: boxed(r)?,
+                    case_insensitive: boolean(r)?,
+                },
+                28 => Self::StartsWith {
+                    expr: boxed(r)?,
+                    prefix: boxed(r)?,
+                    case_insensitive: boolean(r)?,
+                },
+                29 => Self::EndsWith {
+                    expr: boxed(r)?,
+                    suffix: boxed(r)?,
+                    case_insensitive: boolean(r)?,
+                },
+                30 => Self::IEquals(boxed(r)?, boxed(r)?),
+                31 => Self::Matches(boxed(r)?, Regex::deserialize_reader(ctx, r)?),
+                32 => Self::Defined(boxed(r)?),
+                33 => Self::Boolean(boolean(r)?),
+                34 => Self::Variable(var_index(r)?),
+                35 => Self::VariableAt {
+                    variable_index: var_index(r)?,
+                    offset: boxed(r)?,
+                },
+                36 => Self::VariableIn {
+                    variable_index: var_index(r)?,
+                    from: boxed(r)?,
+                    to: boxed(r)?,
+                },
+                37 => Self::For {
+                    selection: ForSelection::deserialize_reader(ctx, r)?,
+                    set: VariableSet {
+                        elements: Vec::deserialize_reader(ctx, r)?,
+                    },
+                    body: boxed(r)?,
+                },
+                38 => Self::ForIdentifiers {
+                    selection: ForSelection::deserialize_reader(ctx, r)?,
+                    iterator: ForIterator::deserialize_reader(ctx, r)?,
+                    body: boxed(r)?,
+                },
+                39 => Self::ForRules {
+                    selection: ForSelection::deserialize_reader(ctx, r)?,
+                    set: RuleSet {
+                        elements: Vec::deserialize_reader(ctx, r)?,
+                        already_matched: usize::deserialize_reader(ctx, r)?,
+                    },
+                },
+                40 => Self::Module(ModuleExpression::deserialize_reader(ctx, r)?),
+                41 => Self::Rule(usize::deserialize_reader(ctx, r)?),
+                42 => Self::ExternalSymbol(usize::deserialize_reader(ctx, r)?),
+                43 => Self::Bytes(read_bytes(ctx, r)?),
+                44 => Self::Regex(Regex::deserialize_reader(ctx, r)?),
+                45 => Self::With {
+                    declarations: Vec::deserialize_reader(ctx, r)?,
+                    body: boxed(r)?,
+                },
+                _ => return invalid_data("invalid expression discriminant"),
+            })
+        }
+    }
+
+    impl Serialize for VariableIndex {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.0.serialize(writer);
+        }
+    }
+
+    impl Deserialize for VariableIndex {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self(Option::deserialize_reader(ctx, reader)?))
+        }
+    }
+
+    impl Serialize for ForSelection {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Any => writer.push(0),
+                Self::All => writer.push(1),
+                Self::None => writer.push(2),
+                Self::Expr { expr, as_percent } => {
+                    writer.push(3);
+                    expr.serialize(writer);
+                    as_percent.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for ForSelection {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Any),
+                1 => Ok(Self::All),
+                2 => Ok(Self::None),
+                3 => Ok(Self::Expr {
+                    expr: Box::deserialize_reader(ctx, reader)?,
+                    as_percent: bool::deserialize_reader(ctx, reader)?,
+                }),
+                _ => invalid_data("invalid for selection discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for ForIterator {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::ModuleIterator(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Range { from, to } => {
+                    writer.push(1);
+                    from.serialize(writer);
+                    to.serialize(writer);
+                }
+                Self::List(v) => {
+                    writer.push(2);
+                    v.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for ForIterator {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::ModuleIterator(ModuleExpression::deserialize_reader(
+                    ctx, reader,
+                )?)),
+                1 => Ok(Self::Range {
+                    from: Box::deserialize_reader(ctx, reader)?,
+                    to: Box::deserialize_reader(ctx, reader)?,
+                }),
+                2 => Ok(Self::List(Vec::deserialize_reader(ctx, reader)?)),
+                _ => invalid_data("invalid for iterator discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for WithValue {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Expression(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Module(v) => {
+                    writer.push(1);
+                    v.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for WithValue {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Expression(Expression::deserialize_reader(
+                    ctx, reader,
+                )?)),
+                1 => Ok(Self::Module(ModuleExpression::deserialize_reader(
+                    ctx, reader,
+                )?)),
+                _ => invalid_data("invalid with value discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for ReadIntegerType {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            writer.push(match self {
+                Self::Int8 => 0,
+                Self::Uint8 => 1,
+                Self::Int16 => 2,
+                Self::Int16BE => 3,
+                Self::Uint16 => 4,
+                Self::Uint16BE => 5,
+                Self::Int32 => 6,
+                Self::Int32BE => 7,
+                Self::Uint32 => 8,
+                Self::Uint32BE => 9,
+            });
+        }
+    }
+
+    impl Deserialize for ReadIntegerType {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(match u8::deserialize_reader(ctx, reader)? {
+                0 => Self::Int8,
+                1 => Self::Uint8,
+                2 => Self::Int16,
+                3 => Self::Int16BE,
+                4 => Self::Uint16,
+                5 => Self::Uint16BE,
+                6 => Self::Int32,
+                7 => Self::Int32BE,
+                8 => Self::Uint32,
+                9 => Self::Uint32BE,
+                _ => return invalid_data("invalid read integer type discriminant"),
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1350,5 +1962,6 @@ mod tests {
         test_type_traits_non_clonable(Expression::Boolean(true));
         test_type_traits_non_clonable(ForSelection::Any);
         test_type_traits_non_clonable(ForIterator::List(Vec::new()));
+        test_type_traits_non_clonable(WithValue::Expression(Expression::Boolean(true)));
     }
 }
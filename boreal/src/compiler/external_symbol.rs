@@ -85,6 +85,52 @@ impl_into_bytes!(&[u8]);
 impl_into_bytes!(String);
 impl_into_bytes!(&str);
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::wire::{
+        invalid_data, read_bytes, write_bytes, Deserialize, DeserializeContext, Serialize,
+    };
+
+    use super::ExternalValue;
+
+    impl Serialize for ExternalValue {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Integer(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Float(v) => {
+                    writer.push(1);
+                    v.serialize(writer);
+                }
+                Self::Bytes(v) => {
+                    writer.push(2);
+                    write_bytes(v, writer);
+                }
+                Self::Boolean(v) => {
+                    writer.push(3);
+                    v.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for ExternalValue {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Integer(i64::deserialize_reader(ctx, reader)?)),
+                1 => Ok(Self::Float(f64::deserialize_reader(ctx, reader)?)),
+                2 => Ok(Self::Bytes(read_bytes(ctx, reader)?)),
+                3 => Ok(Self::Boolean(bool::deserialize_reader(ctx, reader)?)),
+                _ => invalid_data("invalid external value discriminant"),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
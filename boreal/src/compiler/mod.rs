@@ -5,7 +5,7 @@ use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use boreal_parser::file::YaraFileComponent;
+use boreal_parser::file::{Include, YaraFileComponent};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::files::SimpleFile;
 use codespan_reporting::term;
@@ -54,6 +54,32 @@ pub struct Compiler {
 
     /// Compilation parameters
     params: CompilerParams,
+
+    /// Callback used to retrieve the contents of included files.
+    ///
+    /// If not set, includes are resolved on the filesystem.
+    include_callback: Option<IncludeCallbackBox>,
+}
+
+/// Type of the callback used to retrieve the contents of included files.
+///
+/// See [`Compiler::set_include_callback`].
+pub type IncludeCallback =
+    dyn Fn(&str, Option<&Path>, Option<&str>) -> Result<String, std::io::Error> + Send + Sync;
+
+struct IncludeCallbackBox(Box<IncludeCallback>);
+
+/// Identifier of an included file, used to detect include cycles.
+///
+/// Paths resolved on the filesystem are canonical, and identify the file on their own. Paths
+/// given to the include callback are not resolved, so the same path can refer to different files
+/// depending on the file containing the include: they are identified by both paths.
+type IncludeId = (Option<PathBuf>, PathBuf);
+
+impl std::fmt::Debug for IncludeCallbackBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncludeCallback").finish_non_exhaustive()
+    }
 }
 
 #[derive(Debug)]
@@ -89,15 +115,22 @@ impl Compiler {
     /// Modules enabled by default:
     /// - `time`
     /// - `math`
+    /// - `magic`
     /// - `string`
+    /// - `dex`
+    /// - `cuckoo`
+    /// - `lnk`
     /// - `hash` if the `hash` feature is enabled
-    /// - `elf`, `macho` and `pe` if the `object` feature is enabled
+    /// - `elf`, `macho`, `pe` and `dotnet` if the `object` feature is enabled
     ///
     /// Modules disabled by default:
     /// - `console`
     ///
-    /// However, the pe module does not include signatures handling. To include it, you should have
-    /// the `authenticode` feature enabled, and use [`Compiler::new_with_pe_signatures`]
+    /// However, the pe and macho modules only include signatures handling if the
+    /// `authenticode-pure` feature is enabled. This parser only checks that the digests of the
+    /// signatures are consistent with the file, and does not verify their cryptographic
+    /// signatures. To use the OpenSSL parser instead, which verifies them, you should have the
+    /// `authenticode` feature enabled, and use [`Compiler::new_with_pe_signatures`]
     ///
     /// To create a compiler without some or all of those modules, use [`Compiler::default`] to
     /// create a [`Compiler`] without any modules, then add back only the desired modules.
@@ -113,6 +146,8 @@ impl Compiler {
 
     /// Create a new object to compile YARA rules, including the pe module with signatures.
     ///
+    /// The certificates of the code signatures are also parsed in the macho module.
+    ///
     /// # Safety
     ///
     /// The authenticode parsing requires creating OpenSSL objects, which is not thread-safe and
@@ -144,8 +179,12 @@ impl Compiler {
     #[cfg(all(feature = "object", feature = "authenticode"))]
     #[must_use]
     pub unsafe fn new_with_pe_signatures() -> Self {
-        let mut this = Self::new_without_pe_module();
+        let mut this = Self::new_with_base_modules();
 
+        let _r = this.add_module(
+            // Safety: guaranteed by the safety contract of this function
+            unsafe { crate::module::MachO::new_with_signatures() },
+        );
         let _r = this.add_module(
             // Safety: guaranteed by the safety contract of this function
             unsafe { crate::module::Pe::new_with_signatures() },
@@ -160,11 +199,26 @@ impl Compiler {
     /// [`crate::module::Pe::new_with_signatures`]
     #[must_use]
     pub fn new_without_pe_module() -> Self {
+        let mut this = Self::new_with_base_modules();
+
+        #[cfg(feature = "object")]
+        let _r = this.add_module(crate::module::MachO::default());
+
+        this
+    }
+
+    /// Create a new object with all the default modules except the ones that can handle
+    /// signatures.
+    fn new_with_base_modules() -> Self {
         let mut this = Self::default();
 
         let _r = this.add_module(crate::module::Time);
         let _r = this.add_module(crate::module::Math);
+        let _r = this.add_module(crate::module::Magic);
         let _r = this.add_module(crate::module::String_);
+        let _r = this.add_module(crate::module::Dex);
+        let _r = this.add_module(crate::module::Cuckoo);
+        let _r = this.add_module(crate::module::Lnk);
 
         #[cfg(feature = "hash")]
         let _r = this.add_module(crate::module::Hash);
@@ -172,7 +226,7 @@ impl Compiler {
         #[cfg(feature = "object")]
         let _r = this.add_module(crate::module::Elf);
         #[cfg(feature = "object")]
-        let _r = this.add_module(crate::module::MachO);
+        let _r = this.add_module(crate::module::Dotnet);
 
         this
     }
@@ -241,7 +295,13 @@ impl Compiler {
             path: Some(path.to_path_buf()),
             kind: AddRuleErrorKind::IO(error),
         })?;
-        self.add_rules_str_inner(&contents, namespace, Some(path), status)
+
+        // The file is the root of the include stack, so that it cannot include itself.
+        let mut include_stack = vec![(
+            None,
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+        )];
+        self.add_rules_str_inner(&contents, namespace, Some(path), status, &mut include_stack)
     }
 
     /// Add rules to compile from a string.
@@ -256,7 +316,7 @@ impl Compiler {
         rules: T,
     ) -> Result<AddRuleStatus, AddRuleError> {
         let mut status = AddRuleStatus::default();
-        self.add_rules_str_inner(rules.as_ref(), None, None, &mut status)?;
+        self.add_rules_str_inner(rules.as_ref(), None, None, &mut status, &mut Vec::new())?;
         Ok(status)
     }
 
@@ -271,7 +331,13 @@ impl Compiler {
         namespace: S,
     ) -> Result<AddRuleStatus, AddRuleError> {
         let mut status = AddRuleStatus::default();
-        self.add_rules_str_inner(rules.as_ref(), Some(namespace.as_ref()), None, &mut status)?;
+        self.add_rules_str_inner(
+            rules.as_ref(),
+            Some(namespace.as_ref()),
+            None,
+            &mut status,
+            &mut Vec::new(),
+        )?;
         Ok(status)
     }
 
@@ -281,13 +347,24 @@ impl Compiler {
         namespace: Option<&str>,
         current_filepath: Option<&Path>,
         status: &mut AddRuleStatus,
+        include_stack: &mut Vec<IncludeId>,
     ) -> Result<(), AddRuleError> {
-        let file = boreal_parser::parse(s).map_err(|error| AddRuleError {
-            path: current_filepath.map(Path::to_path_buf),
-            kind: AddRuleErrorKind::Parse(error),
-        })?;
+        let (file, errors) = boreal_parser::parse_with_recovery(s);
+        if !errors.is_empty() {
+            return Err(AddRuleError {
+                path: current_filepath.map(Path::to_path_buf),
+                kind: AddRuleErrorKind::Parse(errors),
+            });
+        }
         for component in file.components {
-            self.add_component(component, namespace, current_filepath, s, status)?;
+            self.add_component(
+                component,
+                namespace,
+                current_filepath,
+                s,
+                status,
+                include_stack,
+            )?;
         }
         Ok(())
     }
@@ -299,6 +376,7 @@ impl Compiler {
         current_filepath: Option<&Path>,
         parsed_contents: &str,
         status: &mut AddRuleStatus,
+        include_stack: &mut Vec<IncludeId>,
     ) -> Result<(), AddRuleError> {
         let namespace = match namespace_name {
             Some(name) => self
@@ -313,23 +391,39 @@ impl Compiler {
 
         match component {
             YaraFileComponent::Include(include) => {
-                // Resolve the given path relative to the current one
-                let path = match current_filepath {
-                    None => PathBuf::from(include.path),
-                    Some(current_path) => current_path
-                        .parent()
-                        .unwrap_or(current_path)
-                        .join(include.path),
+                if self.params.disable_includes {
+                    return Err(AddRuleError {
+                        path: current_filepath.map(Path::to_path_buf),
+                        kind: AddRuleErrorKind::IncludeDisabled { span: include.span },
+                    });
+                }
+
+                let (path, contents) =
+                    self.read_include(&include, namespace_name, current_filepath)?;
+                let id = if self.include_callback.is_some() {
+                    (current_filepath.map(Path::to_path_buf), path.clone())
+                } else {
+                    (None, path.clone())
                 };
-                let path = path.canonicalize().map_err(|error| AddRuleError {
-                    path: current_filepath.map(Path::to_path_buf),
-                    kind: AddRuleErrorKind::InvalidInclude {
-                        path,
-                        span: include.span,
-                        error,
-                    },
-                })?;
-                self.add_rules_file_inner(&path, namespace_name, status)?;
+                if include_stack.contains(&id) {
+                    return Err(AddRuleError {
+                        path: current_filepath.map(Path::to_path_buf),
+                        kind: AddRuleErrorKind::IncludeCycle {
+                            path,
+                            span: include.span,
+                        },
+                    });
+                }
+
+                include_stack.push(id);
+                self.add_rules_str_inner(
+                    &contents,
+                    namespace_name,
+                    Some(&path),
+                    status,
+                    include_stack,
+                )?;
+                let _r = include_stack.pop();
             }
             YaraFileComponent::Import(import) => {
                 match self.available_modules.get_mut(&import.name) {
@@ -459,6 +553,53 @@ impl Compiler {
         Ok(())
     }
 
+    /// Retrieve the contents of an included file.
+    ///
+    /// Returns the path identifying the included file, and its contents.
+    fn read_include(
+        &self,
+        include: &Include,
+        namespace_name: Option<&str>,
+        current_filepath: Option<&Path>,
+    ) -> Result<(PathBuf, String), AddRuleError> {
+        if let Some(IncludeCallbackBox(callback)) = &self.include_callback {
+            let path = PathBuf::from(&include.path);
+            return match callback(&include.path, current_filepath, namespace_name) {
+                Ok(contents) => Ok((path, contents)),
+                Err(error) => Err(AddRuleError {
+                    path: current_filepath.map(Path::to_path_buf),
+                    kind: AddRuleErrorKind::InvalidInclude {
+                        path,
+                        span: include.span.clone(),
+                        error,
+                    },
+                }),
+            };
+        }
+
+        // Resolve the given path relative to the current one
+        let path = match current_filepath {
+            None => PathBuf::from(&include.path),
+            Some(current_path) => current_path
+                .parent()
+                .unwrap_or(current_path)
+                .join(&include.path),
+        };
+        let path = path.canonicalize().map_err(|error| AddRuleError {
+            path: current_filepath.map(Path::to_path_buf),
+            kind: AddRuleErrorKind::InvalidInclude {
+                path,
+                span: include.span.clone(),
+                error,
+            },
+        })?;
+        let contents = std::fs::read_to_string(&path).map_err(|error| AddRuleError {
+            path: Some(path.clone()),
+            kind: AddRuleErrorKind::IO(error),
+        })?;
+        Ok((path, contents))
+    }
+
     /// Define a symbol that can be used in compiled rules.
     ///
     /// Any rules compiled after the addition can use the symbol name, which will be replaced
@@ -495,11 +636,78 @@ impl Compiler {
         self.params = params;
     }
 
+    /// Set a callback to retrieve the contents of included files.
+    ///
+    /// By default, the path in an `include` clause is resolved on the filesystem, relative to
+    /// the file containing the clause. When this callback is set, it is called instead with:
+    ///
+    /// - the path in the `include` clause.
+    /// - the path of the file containing the clause, which is the path given to
+    ///   [`Compiler::add_rules_file`], or the path of the include for nested includes. This
+    ///   is `None` for rules added with [`Compiler::add_rules_str`].
+    /// - the namespace in which the rules are added, `None` for the default namespace.
+    ///
+    /// This allows retrieving rules from other sources than the filesystem:
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// let mut files = HashMap::new();
+    /// files.insert("common.yar", "rule common { condition: true }");
+    ///
+    /// let mut compiler = boreal::Compiler::new();
+    /// compiler.set_include_callback(move |path, _current_path, _namespace| {
+    ///     files.get(path).map(|s| s.to_string()).ok_or_else(|| {
+    ///         std::io::Error::new(std::io::ErrorKind::NotFound, "unknown file")
+    ///     })
+    /// });
+    /// compiler.add_rules_str(r#"
+    /// include "common.yar"
+    /// rule a { condition: common }
+    /// "#)?;
+    /// # Ok::<(), boreal::compiler::AddRuleError>(())
+    /// ```
+    ///
+    /// See [`CompilerParams::disable_includes`] to reject all includes instead.
+    pub fn set_include_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&str, Option<&Path>, Option<&str>) -> Result<String, std::io::Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.include_callback = Some(IncludeCallbackBox(Box::new(callback)));
+    }
+
     /// Names of modules that are available for use in rules.
     pub fn available_modules(&self) -> impl Iterator<Item = &str> {
         self.available_modules.keys().map(|v| &**v)
     }
 
+    /// Consume the compiler to retrieve all its modules, by name.
+    ///
+    /// This is used to reattach modules when deserializing a [`Scanner`].
+    #[cfg(feature = "serialize")]
+    pub(crate) fn into_modules(
+        self,
+    ) -> HashMap<&'static str, (Arc<module::Module>, Box<dyn crate::module::Module>)> {
+        let mut imported_modules: Vec<_> = self.imported_modules.into_iter().map(Some).collect();
+
+        self.available_modules
+            .into_values()
+            .filter_map(|module| {
+                let module_obj = match module.location {
+                    ModuleLocation::Module(m) => m,
+                    ModuleLocation::ImportedIndex(i) => imported_modules.get_mut(i)?.take()?,
+                };
+                Some((
+                    module.compiled_module.name,
+                    (module.compiled_module, module_obj),
+                ))
+            })
+            .collect()
+    }
+
     /// Finalize the compiler and generate a [`Scanner`].
     ///
     /// # Errors
@@ -606,8 +814,27 @@ enum AddRuleErrorKind {
         error: std::io::Error,
     },
 
-    /// Error while parsing a rule.
-    Parse(boreal_parser::error::Error),
+    /// An include clause was found while includes are disabled.
+    ///
+    /// See [`CompilerParams::disable_includes`].
+    IncludeDisabled {
+        /// Span of the include.
+        span: Range<usize>,
+    },
+
+    /// A file includes itself, directly or through other includes.
+    IncludeCycle {
+        /// Path of the included file, which is already being included.
+        path: PathBuf,
+
+        /// Span of the include.
+        span: Range<usize>,
+    },
+
+    /// Errors while parsing the rules.
+    ///
+    /// All the parsing errors of the file are reported, this list is never empty.
+    Parse(Vec<boreal_parser::error::Error>),
 
     /// Error while compiling a rule.
     Compilation(CompilationError),
@@ -639,6 +866,9 @@ impl AddRuleError {
     ///
     /// This can be used to display the error in a more user-friendly manner than the
     /// simple `Self::to_short_description`.
+    ///
+    /// If the rules contain multiple parsing errors, the diagnostic describes the first one,
+    /// and the other ones are added as secondary labels.
     #[must_use]
     pub fn to_diagnostic(&self) -> Diagnostic<()> {
         self.kind.to_diagnostic()
@@ -652,11 +882,50 @@ impl AddRuleErrorKind {
             Self::InvalidInclude { path, span, error } => Diagnostic::error()
                 .with_message(format!("cannot include `{}`: {error}", path.display()))
                 .with_labels(vec![Label::primary((), span.clone())]),
-            Self::Parse(err) => err.to_diagnostic(),
+            Self::IncludeDisabled { span } => Diagnostic::error()
+                .with_message("includes are disabled")
+                .with_labels(vec![Label::primary((), span.clone())]),
+            Self::IncludeCycle { path, span } => Diagnostic::error()
+                .with_message(format!(
+                    "include cycle: `{}` is already being included",
+                    path.display()
+                ))
+                .with_labels(vec![Label::primary((), span.clone())]),
+            Self::Parse(errors) => parse_errors_to_diagnostic(errors),
             Self::Compilation(err) => err.to_diagnostic(),
         }
     }
 }
 
+/// Build a single diagnostic describing all the errors of a parsed file.
+fn parse_errors_to_diagnostic(errors: &[boreal_parser::error::Error]) -> Diagnostic<()> {
+    let Some((first, others)) = errors.split_first() else {
+        return Diagnostic::error();
+    };
+
+    let mut diagnostic = first.to_diagnostic();
+    for error in others {
+        let Diagnostic {
+            message, labels, ..
+        } = error.to_diagnostic();
+        diagnostic.labels.extend(labels.into_iter().map(|label| {
+            let label_message = if label.message.is_empty() {
+                message.clone()
+            } else {
+                format!("{message}: {}", label.message)
+            };
+            Label::secondary((), label.range).with_message(label_message)
+        }));
+    }
+    if !others.is_empty() {
+        diagnostic.notes.push(format!(
+            "{} errors were found while parsing the rules",
+            errors.len()
+        ));
+    }
+
+    diagnostic
+}
+
 #[cfg(test)]
 mod tests;
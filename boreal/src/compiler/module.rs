@@ -2,7 +2,7 @@ use std::{collections::HashMap, ops::Range};
 
 use boreal_parser::expression::{Identifier, IdentifierOperation, IdentifierOperationType};
 
-use super::expression::{compile_expression, Expr, Expression, Type};
+use super::expression::{compile_expression, Expr, Expression, Type, WithValue};
 use super::rule::RuleCompiler;
 use super::{CompilationError, ImportedModule};
 use crate::module::{self, EvalContext, StaticValue, Type as ValueType, Value};
@@ -51,6 +51,11 @@ pub enum ModuleExpressionKind {
     StaticFunction {
         /// The function to call.
         fun: fn(&mut EvalContext, Vec<Value>) -> Option<Value>,
+
+        /// Path to the function in the module static values, starting with the module name.
+        ///
+        /// For example, `["math", "entropy"]`.
+        path: Vec<&'static str>,
     },
 }
 
@@ -94,9 +99,10 @@ impl std::fmt::Debug for ModuleExpressionKind {
                 .debug_struct("BoundedModuleValueUse")
                 .field("index", index)
                 .finish(),
-            Self::StaticFunction { fun } => f
+            Self::StaticFunction { fun, path } => f
                 .debug_struct("Function")
                 .field("fun", &(*fun as usize))
+                .field("path", path)
                 .finish(),
         }
     }
@@ -148,6 +154,7 @@ pub(super) fn compile_bounded_identifier_use<'a, 'b>(
         operations: Vec::with_capacity(identifier.operations.len()),
         current_span: identifier.name_span,
         bounded_value_index: Some(BoundedValueIndex::BoundedStack(identifier_stack_index)),
+        static_path: Vec::new(),
     };
 
     for op in identifier.operations {
@@ -196,8 +203,8 @@ pub(super) fn compile_identifier<'a, 'b>(
     };
 
     // First try to get from the static values
-    let mut module_use = match module.module.static_values.get(&**subfield) {
-        Some(value) => ModuleUse {
+    let mut module_use = match module.module.static_values.get_key_value(&**subfield) {
+        Some((key, value)) => ModuleUse {
             compiler,
             last_immediate_value: Some(value),
             current_value: ValueOrType::Value(value),
@@ -205,6 +212,7 @@ pub(super) fn compile_identifier<'a, 'b>(
             operations: Vec::with_capacity(nb_ops),
             current_span: identifier.name_span,
             bounded_value_index: None,
+            static_path: vec![module.module.name, key],
         },
         None => {
             // otherwise, use dynamic types, and apply the first operation (so that it will be
@@ -217,6 +225,7 @@ pub(super) fn compile_identifier<'a, 'b>(
                 operations: Vec::with_capacity(nb_ops),
                 current_span: identifier.name_span,
                 bounded_value_index: Some(BoundedValueIndex::Module(module.module_index)),
+                static_path: Vec::new(),
             };
             module_use.add_operation(first_op)?;
             module_use
@@ -250,13 +259,20 @@ pub(super) struct ModuleUse<'a, 'b> {
 
     // Index for dynamic bounded value that will be used on evaluation.
     bounded_value_index: Option<BoundedValueIndex>,
+
+    // Path of the current value in the module static values, if using a static value.
+    static_path: Vec<&'static str>,
 }
 
 impl ModuleUse<'_, '_> {
     fn add_operation(&mut self, op: IdentifierOperation) -> Result<(), CompilationError> {
         let res = match op.op {
             IdentifierOperationType::Subfield(subfield) => {
-                let res = self.current_value.subfield(&subfield);
+                let res = self.current_value.subfield(&subfield).map(|key| {
+                    if let Some(key) = key {
+                        self.static_path.push(key);
+                    }
+                });
                 match self.current_value {
                     ValueOrType::Value(v) => self.last_immediate_value = Some(v),
                     ValueOrType::Type(_) => {
@@ -353,7 +369,10 @@ impl ModuleUse<'_, '_> {
                     StaticValue::Object(_) => return None,
 
                     StaticValue::Function { fun, .. } => Expression::Module(ModuleExpression {
-                        kind: ModuleExpressionKind::StaticFunction { fun: *fun },
+                        kind: ModuleExpressionKind::StaticFunction {
+                            fun: *fun,
+                            path: self.static_path,
+                        },
                         operations: ModuleOperations {
                             expressions: self.operations_expressions,
                             operations: self.operations,
@@ -380,6 +399,27 @@ impl ModuleUse<'_, '_> {
         Some((expr, ty))
     }
 
+    /// Generate the value to bind to an identifier in a 'with' expression.
+    ///
+    /// Values that can be computed during compilation are bound as primitive expressions,
+    /// while module values are bound as is, so that objects or arrays can be bound as well.
+    pub(super) fn into_with_value(self) -> Option<(WithValue, ValueType)> {
+        if self.last_immediate_value.is_some() {
+            let (expr, ty) = self.into_expression()?;
+            let ty = match ty {
+                Type::Integer => ValueType::Integer,
+                Type::Float => ValueType::Float,
+                Type::Bytes => ValueType::Bytes,
+                Type::Regex => ValueType::Regex,
+                Type::Boolean => ValueType::Boolean,
+            };
+            Some((WithValue::Expression(expr), ty))
+        } else {
+            let (expr, ty) = self.into_module_expression()?;
+            Some((WithValue::Module(expr), ty))
+        }
+    }
+
     pub(super) fn into_iterator_expression(self) -> Option<(ModuleExpression, IteratorType)> {
         let (expr, ty) = self.into_module_expression()?;
         let ty = match ty {
@@ -423,14 +463,17 @@ enum TypeError {
 }
 
 impl ValueOrType<'_> {
-    fn subfield(&mut self, subfield: &str) -> Result<(), TypeError> {
+    /// Apply a subfield operation.
+    ///
+    /// If the current value is a static one, the static name of the subfield is returned.
+    fn subfield(&mut self, subfield: &str) -> Result<Option<&'static str>, TypeError> {
         match self {
             Self::Value(value) => {
                 if let StaticValue::Object(map) = value {
-                    match map.get(subfield) {
-                        Some(v) => {
+                    match map.get_key_value(subfield) {
+                        Some((key, v)) => {
                             *self = Self::Value(v);
-                            return Ok(());
+                            return Ok(Some(key));
                         }
                         None => return Err(TypeError::UnknownSubfield(subfield.to_string())),
                     }
@@ -441,7 +484,7 @@ impl ValueOrType<'_> {
                     match map.get(subfield) {
                         Some(v) => {
                             *self = Self::Type(v);
-                            return Ok(());
+                            return Ok(None);
                         }
                         None => return Err(TypeError::UnknownSubfield(subfield.to_string())),
                     }
@@ -616,6 +659,146 @@ fn module_type_to_expr_type(v: &ValueType) -> Option<Type> {
     }
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::module::StaticValue;
+    use crate::wire::{invalid_data, Deserialize, DeserializeContext, Serialize};
+
+    use super::{
+        BoundedValueIndex, ModuleExpression, ModuleExpressionKind, ModuleOperations, ValueOperation,
+    };
+
+    impl Serialize for ModuleExpression {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.kind.serialize(writer);
+            self.operations.expressions.serialize(writer);
+            self.operations.operations.serialize(writer);
+        }
+    }
+
+    impl Deserialize for ModuleExpression {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self {
+                kind: ModuleExpressionKind::deserialize_reader(ctx, reader)?,
+                operations: ModuleOperations {
+                    expressions: Vec::deserialize_reader(ctx, reader)?,
+                    operations: Vec::deserialize_reader(ctx, reader)?,
+                },
+            })
+        }
+    }
+
+    impl Serialize for ModuleExpressionKind {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::BoundedModuleValueUse {
+                    index: BoundedValueIndex::Module(index),
+                } => {
+                    writer.push(0);
+                    index.serialize(writer);
+                }
+                Self::BoundedModuleValueUse {
+                    index: BoundedValueIndex::BoundedStack(index),
+                } => {
+                    writer.push(1);
+                    index.serialize(writer);
+                }
+                Self::StaticFunction { fun: _, path } => {
+                    // The function pointer cannot be serialized, but it can be retrieved from
+                    // the module when deserializing.
+                    writer.push(2);
+                    path.len().serialize(writer);
+                    for elem in path {
+                        (*elem).to_owned().serialize(writer);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Deserialize for ModuleExpressionKind {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::BoundedModuleValueUse {
+                    index: BoundedValueIndex::Module(usize::deserialize_reader(ctx, reader)?),
+                }),
+                1 => Ok(Self::BoundedModuleValueUse {
+                    index: BoundedValueIndex::BoundedStack(usize::deserialize_reader(ctx, reader)?),
+                }),
+                2 => {
+                    let names = Vec::<String>::deserialize_reader(ctx, reader)?;
+                    resolve_static_function(ctx, &names)
+                }
+                _ => invalid_data("invalid module expression discriminant"),
+            }
+        }
+    }
+
+    /// Find the function designated by the given path in the static values of a module.
+    fn resolve_static_function(
+        ctx: &DeserializeContext,
+        names: &[String],
+    ) -> io::Result<ModuleExpressionKind> {
+        let Some((module_name, names)) = names.split_first() else {
+            return invalid_data("empty path for a module function");
+        };
+        let Some(module) = ctx.modules.get(&**module_name) else {
+            return invalid_data("unknown module");
+        };
+
+        let mut path = Vec::with_capacity(names.len() + 1);
+        path.push(module.name);
+
+        let mut values = &module.static_values;
+        let mut names = names.iter().peekable();
+        while let Some(name) = names.next() {
+            let Some((key, value)) = values.get_key_value(&**name) else {
+                return invalid_data("unknown module static value");
+            };
+            path.push(*key);
+
+            match value {
+                StaticValue::Object(map) => values = map,
+                StaticValue::Function { fun, .. } if names.peek().is_none() => {
+                    return Ok(ModuleExpressionKind::StaticFunction { fun: *fun, path });
+                }
+                _ => break,
+            }
+        }
+
+        invalid_data("module static value is not a function")
+    }
+
+    impl Serialize for ValueOperation {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Subfield(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Subscript => writer.push(1),
+                Self::FunctionCall(v) => {
+                    writer.push(2);
+                    v.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for ValueOperation {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Subfield(String::deserialize_reader(ctx, reader)?)),
+                1 => Ok(Self::Subscript),
+                2 => Ok(Self::FunctionCall(usize::deserialize_reader(ctx, reader)?)),
+                _ => invalid_data("invalid value operation discriminant"),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -648,7 +831,10 @@ mod tests {
         test_type_traits_non_clonable(ModuleExpressionKind::BoundedModuleValueUse {
             index: BoundedValueIndex::Module(0),
         });
-        test_type_traits_non_clonable(ModuleExpressionKind::StaticFunction { fun: test_fun });
+        test_type_traits_non_clonable(ModuleExpressionKind::StaticFunction {
+            fun: test_fun,
+            path: vec!["a", "b"],
+        });
         test_type_traits_non_clonable(IteratorType::Array(ValueType::Integer));
         test_type_traits_non_clonable(TypeError::UnknownSubfield("a".to_owned()));
         test_type_traits_non_clonable(ValueOrType::Type(&ValueType::Integer));
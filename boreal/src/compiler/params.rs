@@ -11,6 +11,9 @@ pub struct CompilerParams {
 
     /// Compute statistics when compiling rules.
     pub(crate) compute_statistics: bool,
+
+    /// Reject rules containing includes.
+    pub(crate) disable_includes: bool,
 }
 
 impl Default for CompilerParams {
@@ -19,6 +22,7 @@ impl Default for CompilerParams {
             max_condition_depth: 40,
             fail_on_warnings: false,
             compute_statistics: false,
+            disable_includes: false,
         }
     }
 }
@@ -67,4 +71,17 @@ impl CompilerParams {
         self.compute_statistics = compute_statistics;
         self
     }
+
+    /// Disable includes.
+    ///
+    /// If set, adding rules that contain an `include` clause fails. This should be used
+    /// when compiling rules from untrusted sources, as includes can otherwise read any file
+    /// accessible to the process.
+    ///
+    /// Default value is false.
+    #[must_use]
+    pub fn disable_includes(mut self, disable_includes: bool) -> Self {
+        self.disable_includes = disable_includes;
+        self
+    }
 }
@@ -266,6 +266,79 @@ pub(super) struct CompiledRule {
     pub rule_wildcard_uses: Vec<String>,
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use boreal_parser::rule::{Metadata, MetadataValue};
+
+    use crate::compiler::expression::Expression;
+    use crate::wire::{
+        invalid_data, read_bytes, write_bytes, Deserialize, DeserializeContext, Serialize,
+    };
+
+    use super::Rule;
+
+    impl Serialize for Rule {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.name.serialize(writer);
+            self.namespace.serialize(writer);
+            self.tags.serialize(writer);
+            self.metadatas.serialize(writer);
+            self.nb_variables.serialize(writer);
+            self.condition.serialize(writer);
+            self.is_private.serialize(writer);
+        }
+    }
+
+    impl Deserialize for Rule {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self {
+                name: String::deserialize_reader(ctx, reader)?,
+                namespace: Option::deserialize_reader(ctx, reader)?,
+                tags: Vec::deserialize_reader(ctx, reader)?,
+                metadatas: Vec::deserialize_reader(ctx, reader)?,
+                nb_variables: usize::deserialize_reader(ctx, reader)?,
+                condition: Expression::deserialize_reader(ctx, reader)?,
+                is_private: bool::deserialize_reader(ctx, reader)?,
+            })
+        }
+    }
+
+    impl Serialize for Metadata {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.name.serialize(writer);
+            match &self.value {
+                MetadataValue::Bytes(v) => {
+                    writer.push(0);
+                    write_bytes(v, writer);
+                }
+                MetadataValue::Integer(v) => {
+                    writer.push(1);
+                    v.serialize(writer);
+                }
+                MetadataValue::Boolean(v) => {
+                    writer.push(2);
+                    v.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for Metadata {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            let name = String::deserialize_reader(ctx, reader)?;
+            let value = match u8::deserialize_reader(ctx, reader)? {
+                0 => MetadataValue::Bytes(read_bytes(ctx, reader)?),
+                1 => MetadataValue::Integer(i64::deserialize_reader(ctx, reader)?),
+                2 => MetadataValue::Boolean(bool::deserialize_reader(ctx, reader)?),
+                _ => return invalid_data("invalid metadata value discriminant"),
+            };
+            Ok(Self { name, value })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test_helpers::test_type_traits_non_clonable;
@@ -5,7 +5,7 @@ use super::module::compile_module;
 use super::rule::RuleCompiler;
 use super::{
     AddRuleError, AddRuleErrorKind, AddRuleStatus, AvailableModule, CompilationError, Compiler,
-    CompilerParams, ImportedModule, ModuleLocation, Namespace,
+    CompilerParams, ImportedModule, IncludeCallbackBox, ModuleLocation, Namespace,
 };
 use crate::test_helpers::{test_type_traits, test_type_traits_non_clonable};
 use boreal_parser::parse;
@@ -268,6 +268,7 @@ fn test_compilation_variables() {
 #[test]
 fn test_types_traits() {
     test_type_traits_non_clonable(Compiler::new());
+    test_type_traits_non_clonable(IncludeCallbackBox(Box::new(|_, _, _| Ok(String::new()))));
     test_type_traits_non_clonable(Namespace::default());
     test_type_traits_non_clonable(AvailableModule {
         compiled_module: Arc::new(compile_module(&crate::module::Time)),
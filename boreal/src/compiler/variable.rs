@@ -147,6 +147,34 @@ impl std::fmt::Display for VariableCompilationError {
     }
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::matcher::Matcher;
+    use crate::wire::{Deserialize, DeserializeContext, Serialize};
+
+    use super::Variable;
+
+    impl Serialize for Variable {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.name.serialize(writer);
+            self.is_private.serialize(writer);
+            self.matcher.serialize(writer);
+        }
+    }
+
+    impl Deserialize for Variable {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self {
+                name: String::deserialize_reader(ctx, reader)?,
+                is_private: bool::deserialize_reader(ctx, reader)?,
+                matcher: Matcher::deserialize_reader(ctx, reader)?,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
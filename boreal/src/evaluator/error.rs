@@ -11,6 +11,9 @@ pub enum EvalError {
 
     /// Timeout while scanning.
     Timeout,
+
+    /// The scan was cancelled.
+    Cancelled,
 }
 
 #[cfg(test)]
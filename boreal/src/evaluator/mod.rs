@@ -35,11 +35,14 @@
 //! - `defined`
 //!
 //! For all of those, an undefined value is considered to be equivalent to a false boolean value.
-use crate::compiler::expression::{Expression, ForIterator, ForSelection, VariableIndex};
+use crate::compiler::expression::{
+    Expression, ForIterator, ForSelection, VariableIndex, WithValue,
+};
 use crate::compiler::rule::Rule;
 use crate::memory::Memory;
 use crate::regex::Regex;
 use crate::scanner::ScanData;
+use crate::timeout::Interruption;
 use memchr::memmem;
 
 use crate::compiler::ExternalValue;
@@ -125,6 +128,7 @@ pub(crate) fn evaluate_rule<'scan, 'rule>(
         Err(PoisonKind::Undefined) => Ok(false),
         Err(PoisonKind::VarNeeded) => Err(EvalError::Undecidable),
         Err(PoisonKind::Timeout) => Err(EvalError::Timeout),
+        Err(PoisonKind::Cancelled) => Err(EvalError::Cancelled),
     }
 }
 
@@ -172,6 +176,11 @@ enum PoisonKind {
     ///
     /// This value should always be rethrown, no matter what, to end the execution asap.
     Timeout,
+
+    /// Scan was cancelled.
+    ///
+    /// As with [`PoisonKind::Timeout`], this value should always be rethrown.
+    Cancelled,
 }
 
 macro_rules! arith_op_num_and_float {
@@ -244,8 +253,10 @@ impl Evaluator<'_, '_, '_> {
     }
 
     fn evaluate_expr(&mut self, expr: &Expression) -> Result<Value, PoisonKind> {
-        if self.scan_data.check_timeout() {
-            return Err(PoisonKind::Timeout);
+        match self.scan_data.check_timeout() {
+            Some(Interruption::Timeout) => return Err(PoisonKind::Timeout),
+            Some(Interruption::Cancelled) => return Err(PoisonKind::Cancelled),
+            None => (),
         }
 
         match expr {
@@ -448,6 +459,7 @@ impl Evaluator<'_, '_, '_> {
                         Err(PoisonKind::Undefined) => return Ok(Value::Boolean(false)),
                         Err(PoisonKind::VarNeeded) => var_needed = true,
                         Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                        Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
                     };
                 }
                 if var_needed {
@@ -469,6 +481,7 @@ impl Evaluator<'_, '_, '_> {
                         Err(PoisonKind::Undefined) => (),
                         Err(PoisonKind::VarNeeded) => var_needed = true,
                         Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                        Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
                     };
                 }
                 if var_needed {
@@ -661,6 +674,13 @@ impl Evaluator<'_, '_, '_> {
                 selection.end(0)
             }
 
+            Expression::With { declarations, body } => {
+                let prev_stack_len = self.bounded_identifiers_stack.len();
+                let res = self.evaluate_with(declarations, body);
+                self.bounded_identifiers_stack.truncate(prev_stack_len);
+                res
+            }
+
             Expression::Module(module_expr) => module::evaluate_expr(self, module_expr)
                 .and_then(module::module_value_to_expr_value),
 
@@ -728,6 +748,31 @@ impl Evaluator<'_, '_, '_> {
         }
     }
 
+    fn evaluate_with(
+        &mut self,
+        declarations: &[WithValue],
+        body: &Expression,
+    ) -> Result<Value, PoisonKind> {
+        for declaration in declarations {
+            let value = match declaration {
+                WithValue::Expression(expr) => self
+                    .evaluate_expr(expr)
+                    .map(module::expr_value_to_module_value),
+                WithValue::Module(expr) => module::evaluate_expr(self, expr),
+            };
+            // An undefined value is still bound, and makes the uses of the identifier
+            // undefined.
+            let value = match value {
+                Ok(v) => v,
+                Err(PoisonKind::Undefined) => ModuleValue::Undefined,
+                Err(e) => return Err(e),
+            };
+            self.bounded_identifiers_stack.push(value);
+        }
+
+        self.evaluate_expr(body)
+    }
+
     fn evaluate_for_var<I>(
         &mut self,
         mut selection: ForSelectionEvaluator,
@@ -749,6 +794,7 @@ impl Evaluator<'_, '_, '_> {
                     continue;
                 }
                 Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
             };
 
             if let Some(result) = selection.add_result_and_check(v) {
@@ -786,6 +832,7 @@ impl Evaluator<'_, '_, '_> {
                                     continue;
                                 }
                                 Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                                Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
                             };
 
                             if let Some(result) = selection.add_result_and_check(v) {
@@ -807,6 +854,7 @@ impl Evaluator<'_, '_, '_> {
                                     continue;
                                 }
                                 Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                                Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
                             };
 
                             if let Some(result) = selection.add_result_and_check(v) {
@@ -840,6 +888,7 @@ impl Evaluator<'_, '_, '_> {
                             continue;
                         }
                         Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                        Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
                     };
 
                     if let Some(result) = selection.add_result_and_check(v) {
@@ -872,6 +921,7 @@ impl Evaluator<'_, '_, '_> {
                             continue;
                         }
                         Err(PoisonKind::Timeout) => return Err(PoisonKind::Timeout),
+                        Err(PoisonKind::Cancelled) => return Err(PoisonKind::Cancelled),
                     };
 
                     if let Some(result) = selection.add_result_and_check(v) {
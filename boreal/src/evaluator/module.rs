@@ -1,4 +1,5 @@
 //! Provides methods to evaluate module values during scanning.
+use std::any::Any;
 use std::collections::HashMap;
 use std::iter::Peekable;
 use std::slice::Iter;
@@ -11,6 +12,9 @@ use crate::module::{EvalContext, Module, ModuleDataMap, ScanContext, Value as Mo
 
 use super::{Evaluator, PoisonKind, Value};
 
+/// Data provided by the user for a scan, indexed by module name.
+pub type ModuleUserData<'a> = HashMap<&'a str, Box<dyn Any + Send + Sync>>;
+
 #[derive(Debug)]
 pub struct EvalData {
     pub values: Vec<(&'static str, ModuleValue)>,
@@ -18,12 +22,15 @@ pub struct EvalData {
 }
 
 impl EvalData {
-    pub fn new(modules: &[Box<dyn Module>]) -> Self {
+    pub fn new(modules: &[Box<dyn Module>], mut user_data: ModuleUserData) -> Self {
         let mut data_map = ModuleDataMap::default();
 
         let values = modules
             .iter()
             .map(|module| {
+                if let Some(data) = user_data.remove(module.get_name()) {
+                    data_map.insert_user_data(module.get_name(), data);
+                }
                 module.setup_new_scan(&mut data_map);
 
                 (module.get_name(), ModuleValue::Object(HashMap::new()))
@@ -98,7 +105,7 @@ pub(super) fn evaluate_expr(
             };
             evaluate_ops(&mut eval_ctx, value, ops, expressions)
         }
-        ModuleExpressionKind::StaticFunction { fun } => {
+        ModuleExpressionKind::StaticFunction { fun, .. } => {
             let Some(ValueOperation::FunctionCall(nb_arguments)) = ops.next() else {
                 return Err(PoisonKind::Undefined);
             };
@@ -196,7 +203,7 @@ pub(super) fn module_value_to_expr_value(value: ModuleValue) -> Result<Value, Po
     }
 }
 
-fn expr_value_to_module_value(v: Value) -> ModuleValue {
+pub(super) fn expr_value_to_module_value(v: Value) -> ModuleValue {
     match v {
         Value::Integer(v) => ModuleValue::Integer(v),
         Value::Float(v) => ModuleValue::Float(v),
@@ -96,7 +96,8 @@ impl<'a> VarMatches<'a> {
 }
 
 /// Details on a match on a string during a scan.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StringMatch {
     /// Base offset of the region containing the match.
     ///
@@ -120,7 +121,34 @@ pub struct StringMatch {
     /// The matched data.
     ///
     /// The length of this field is capped.
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pub data: Vec<u8>,
+
+    /// Form of the string that matched.
+    pub encoding: MatchEncoding,
+
+    /// Xor key applied on the string to get the matched data.
+    ///
+    /// This is only set for strings with the `xor` modifier.
+    pub xor_key: Option<u8>,
+}
+
+/// Form of a string that produced a match.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchEncoding {
+    /// The string matched as is, or with its ascii form for strings with the `ascii` modifier.
+    Ascii,
+
+    /// The string matched with its wide form, for strings with the `wide` modifier.
+    Wide,
+
+    /// The string matched with its base64 form, for strings with the `base64` modifier.
+    Base64,
+
+    /// The string matched with the wide form of its base64 form, for strings with the
+    /// `base64wide` modifier.
+    Base64Wide,
 }
 
 impl StringMatch {
@@ -128,6 +156,7 @@ impl StringMatch {
         region: &Region,
         mat: std::ops::Range<usize>,
         match_max_length: usize,
+        (encoding, xor_key): (MatchEncoding, Option<u8>),
     ) -> Self {
         let length = mat.end - mat.start;
         let capped_length = std::cmp::min(length, match_max_length);
@@ -141,6 +170,8 @@ impl StringMatch {
                 .collect(),
             offset: mat.start,
             length,
+            encoding,
+            xor_key,
         }
     }
 }
@@ -159,6 +190,9 @@ mod tests {
             offset: 0,
             length: 0,
             data: Vec::new(),
+            encoding: MatchEncoding::Ascii,
+            xor_key: None,
         });
+        test_type_traits(MatchEncoding::Ascii);
     }
 }
@@ -80,6 +80,8 @@ use base64 as _;
 #[cfg(test)]
 use glob as _;
 #[cfg(test)]
+use serde_json as _;
+#[cfg(test)]
 use tempfile as _;
 #[cfg(test)]
 use yara as _;
@@ -97,6 +99,8 @@ pub mod scanner;
 pub use scanner::Scanner;
 pub mod statistics;
 mod timeout;
+#[cfg(feature = "serialize")]
+mod wire;
 
 // Re-exports those symbols since they are exposed in the results of a scan. This avoids
 // having to depend on boreal-parser simply to match on those metadatas.
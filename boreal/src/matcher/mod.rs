@@ -2,6 +2,7 @@ use std::{collections::HashSet, ops::Range};
 
 use boreal_parser::rule::VariableModifiers;
 
+use crate::evaluator::variable::MatchEncoding;
 use crate::regex::Hir;
 
 mod analysis;
@@ -27,6 +28,12 @@ pub(crate) struct Matcher {
     /// would be buggy.
     pub(crate) literals: Vec<Vec<u8>>,
 
+    /// Encoding and xor key of each literal.
+    ///
+    /// This is only filled for strings with the `xor` or `base64` modifiers. Otherwise, the
+    /// encoding of a match only depends on its [`MatchType`].
+    literals_details: Vec<(MatchEncoding, Option<u8>)>,
+
     kind: MatcherKind,
 
     /// Modifiers related to matching.
@@ -78,6 +85,14 @@ impl MatchType {
             MatchType::WideStandard | MatchType::WideAlternate => true,
         }
     }
+
+    pub fn encoding(self) -> MatchEncoding {
+        if self.is_wide() {
+            MatchEncoding::Wide
+        } else {
+            MatchEncoding::Ascii
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -100,7 +115,15 @@ enum MatcherKind {
     /// The literals cover entirely the variable.
     Literals,
     /// The regex can confirm matches from AC literal matches.
-    Atomized { validator: validator::Validator },
+    Atomized {
+        validator: validator::Validator,
+
+        /// HIRs the validator was built from: pre, post and full.
+        ///
+        /// The validator cannot be serialized, those are used to rebuild it instead.
+        #[cfg(feature = "serialize")]
+        hirs: Box<(Option<Hir>, Option<Hir>, Hir)>,
+    },
 
     /// The regex cannot confirm matches from AC literal matches.
     Raw(raw::RawMatcher),
@@ -116,6 +139,7 @@ impl Matcher {
             let kind = MatcherKind::Raw(raw::RawMatcher::new(hir, &analysis, modifiers)?);
             return Ok(Self {
                 literals: Vec::new(),
+                literals_details: Vec::new(),
                 kind,
                 modifiers,
             });
@@ -128,6 +152,7 @@ impl Matcher {
                 if let Some(literals) = only_literals::hir_to_only_literals(hir) {
                     return Ok(Self {
                         literals,
+                        literals_details: Vec::new(),
                         kind: MatcherKind::Literals,
                         modifiers,
                     });
@@ -163,11 +188,14 @@ impl Matcher {
                     hir,
                     modifiers,
                 )?,
+                #[cfg(feature = "serialize")]
+                hirs: Box::new((pre_hir, post_hir, hir.clone())),
             }
         };
 
         Ok(Self {
             literals,
+            literals_details: Vec::new(),
             kind,
             modifiers,
         })
@@ -192,16 +220,25 @@ impl Matcher {
             let xor_range = xor_range.0..=xor_range.1;
             let xor_range_len = xor_range.len(); // modifiers.xor_range.1.saturating_sub(modifiers.xor_range.0) + 1;
             let mut new_literals: Vec<Vec<u8>> = Vec::with_capacity(literals.len() * xor_range_len);
+            let mut literals_details = Vec::with_capacity(new_literals.capacity());
 
             // Ascii literals must be first, then wide literals. Since the "literals" var
             // is the ascii literals then the wide ones, the order is preserved.
-            for lit in literals {
+            let wide_only = modifiers.wide && !modifiers.ascii;
+            for (i, lit) in literals.into_iter().enumerate() {
+                let encoding = if i == 0 && !wide_only {
+                    MatchEncoding::Ascii
+                } else {
+                    MatchEncoding::Wide
+                };
                 for xor_byte in xor_range.clone() {
                     new_literals.push(lit.iter().map(|c| c ^ xor_byte).collect());
+                    literals_details.push((encoding, Some(xor_byte)));
                 }
             }
             return Self {
                 literals: new_literals,
+                literals_details,
                 kind: MatcherKind::Literals,
                 modifiers: Modifiers {
                     fullword: modifiers.fullword,
@@ -213,6 +250,7 @@ impl Matcher {
             };
         }
 
+        let mut literals_details = Vec::new();
         if let Some(base64) = &modifiers.base64 {
             let mut old_literals = Vec::with_capacity(literals.len() * 3);
             std::mem::swap(&mut old_literals, &mut literals);
@@ -225,8 +263,10 @@ impl Matcher {
                             // literals is not required.
                             if base64.wide {
                                 literals.push(string_to_wide(&lit));
+                                literals_details.push((MatchEncoding::Base64Wide, None));
                             }
                             literals.push(lit);
+                            literals_details.push((MatchEncoding::Base64, None));
                         }
                     }
                 }
@@ -235,6 +275,7 @@ impl Matcher {
                     for offset in 0..=2 {
                         if let Some(lit) = base64::encode_base64(lit, &base64.alphabet, offset) {
                             literals.push(string_to_wide(&lit));
+                            literals_details.push((MatchEncoding::Base64Wide, None));
                         }
                     }
                 }
@@ -243,6 +284,7 @@ impl Matcher {
 
         Matcher {
             literals,
+            literals_details,
             kind: MatcherKind::Literals,
             modifiers: Modifiers {
                 fullword: modifiers.fullword,
@@ -286,6 +328,18 @@ impl Matcher {
         }
     }
 
+    /// Encoding and xor key of a match on the literal with the given index.
+    pub fn literal_details(
+        &self,
+        literal_index: usize,
+        match_type: MatchType,
+    ) -> (MatchEncoding, Option<u8>) {
+        match self.literals_details.get(literal_index) {
+            Some(details) => *details,
+            None => (match_type.encoding(), None),
+        }
+    }
+
     pub fn process_ac_match(
         &self,
         mem: &[u8],
@@ -301,7 +355,7 @@ impl Matcher {
                     AcMatchStatus::None
                 }
             }
-            MatcherKind::Atomized { validator } => {
+            MatcherKind::Atomized { validator, .. } => {
                 match validator.validate_match(mem, mat, start_position, match_type) {
                     Matches::None => AcMatchStatus::None,
                     Matches::Single(m) => {
@@ -326,7 +380,11 @@ impl Matcher {
         }
     }
 
-    pub fn find_next_match_at(&self, mem: &[u8], mut offset: usize) -> Option<Range<usize>> {
+    pub fn find_next_match_at(
+        &self,
+        mem: &[u8],
+        mut offset: usize,
+    ) -> Option<(Range<usize>, MatchType)> {
         let MatcherKind::Raw(regex) = &self.kind else {
             // This variable should have been covered by the AC pass, so we should
             // not be able to reach this code.
@@ -338,7 +396,7 @@ impl Matcher {
             let (mat, match_type) = regex.find_next_match_at(mem, offset, self.modifiers)?;
 
             if self.validate_fullword(mem, &mat, match_type) {
-                return Some(mat);
+                return Some((mat, match_type));
             }
 
             offset = mat.start + 1;
@@ -349,7 +407,7 @@ impl Matcher {
     pub fn to_desc(&self) -> String {
         match &self.kind {
             MatcherKind::Literals => "Literals".to_owned(),
-            MatcherKind::Atomized { validator } => format!("Atomized {{ {validator} }}"),
+            MatcherKind::Atomized { validator, .. } => format!("Atomized {{ {validator} }}"),
             MatcherKind::Raw(_) => "Raw".to_owned(),
         }
     }
@@ -420,6 +478,133 @@ fn string_to_wide(s: &[u8]) -> Vec<u8> {
     res
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::regex::Hir;
+    use crate::wire::{
+        invalid_data, read_bytes, write_bytes, Deserialize, DeserializeContext, Serialize,
+    };
+
+    use super::{raw, validator, MatchEncoding, Matcher, MatcherKind, Modifiers};
+
+    impl Serialize for Matcher {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.literals.len().serialize(writer);
+            for lit in &self.literals {
+                write_bytes(lit, writer);
+            }
+            self.literals_details.serialize(writer);
+            self.modifiers.serialize(writer);
+            match &self.kind {
+                MatcherKind::Literals => writer.push(0),
+                MatcherKind::Atomized { hirs, .. } => {
+                    writer.push(1);
+                    hirs.serialize(writer);
+                }
+                MatcherKind::Raw(raw) => {
+                    writer.push(2);
+                    raw.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for Matcher {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            let nb_literals = usize::deserialize_reader(ctx, reader)?;
+            let mut literals = Vec::with_capacity(std::cmp::min(nb_literals, reader.len()));
+            for _ in 0..nb_literals {
+                literals.push(read_bytes(ctx, reader)?);
+            }
+            let literals_details = Vec::deserialize_reader(ctx, reader)?;
+            let modifiers = Modifiers::deserialize_reader(ctx, reader)?;
+            let kind = match u8::deserialize_reader(ctx, reader)? {
+                0 => MatcherKind::Literals,
+                1 => {
+                    let hirs =
+                        Box::<(Option<Hir>, Option<Hir>, Hir)>::deserialize_reader(ctx, reader)?;
+                    let validator = validator::Validator::new(
+                        hirs.0.as_ref(),
+                        hirs.1.as_ref(),
+                        &hirs.2,
+                        modifiers,
+                    )
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+                    MatcherKind::Atomized { validator, hirs }
+                }
+                2 => MatcherKind::Raw(raw::RawMatcher::deserialize_reader(ctx, reader, modifiers)?),
+                _ => return invalid_data("invalid matcher kind discriminant"),
+            };
+
+            Ok(Self {
+                literals,
+                literals_details,
+                kind,
+                modifiers,
+            })
+        }
+    }
+
+    impl Serialize for MatchEncoding {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            writer.push(match self {
+                MatchEncoding::Ascii => 0,
+                MatchEncoding::Wide => 1,
+                MatchEncoding::Base64 => 2,
+                MatchEncoding::Base64Wide => 3,
+            });
+        }
+    }
+
+    impl Deserialize for MatchEncoding {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(MatchEncoding::Ascii),
+                1 => Ok(MatchEncoding::Wide),
+                2 => Ok(MatchEncoding::Base64),
+                3 => Ok(MatchEncoding::Base64Wide),
+                _ => invalid_data("invalid match encoding"),
+            }
+        }
+    }
+
+    impl Serialize for Modifiers {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            let Self {
+                fullword,
+                wide,
+                ascii,
+                nocase,
+                dot_all,
+            } = *self;
+            let flags = u8::from(fullword)
+                | (u8::from(wide) << 1)
+                | (u8::from(ascii) << 2)
+                | (u8::from(nocase) << 3)
+                | (u8::from(dot_all) << 4);
+            writer.push(flags);
+        }
+    }
+
+    impl Deserialize for Modifiers {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            let flags = u8::deserialize_reader(ctx, reader)?;
+            if flags >= (1 << 5) {
+                return invalid_data("invalid modifiers");
+            }
+            Ok(Self {
+                fullword: flags & 1 != 0,
+                wide: flags & (1 << 1) != 0,
+                ascii: flags & (1 << 2) != 0,
+                nocase: flags & (1 << 3) != 0,
+                dot_all: flags & (1 << 4) != 0,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -429,6 +614,7 @@ mod tests {
     fn test_types_traits() {
         test_type_traits_non_clonable(Matcher {
             literals: vec![],
+            literals_details: vec![],
             modifiers: Modifiers {
                 dot_all: false,
                 fullword: false,
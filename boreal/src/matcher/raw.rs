@@ -19,6 +19,10 @@ pub(super) struct RawMatcher {
     /// In this case, the regex expression cannot be "widened", and this regex is used to post
     /// check matches.
     pub non_wide_regex: Option<Regex>,
+
+    /// HIR the regex was built from, kept to rebuild the matcher on deserialization.
+    #[cfg(feature = "serialize")]
+    hir: Hir,
 }
 
 impl RawMatcher {
@@ -59,6 +63,8 @@ impl RawMatcher {
         Ok(Self {
             regex: res.map_err(crate::regex::Error::from)?,
             non_wide_regex,
+            #[cfg(feature = "serialize")]
+            hir: hir.clone(),
         })
     }
 
@@ -148,6 +154,37 @@ fn unwide(mem: &[u8]) -> Vec<u8> {
 
     res
 }
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::matcher::analysis::analyze_hir;
+    use crate::matcher::Modifiers;
+    use crate::regex::Hir;
+    use crate::wire::{Deserialize, DeserializeContext, Serialize};
+
+    use super::RawMatcher;
+
+    impl Serialize for RawMatcher {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.hir.serialize(writer);
+        }
+    }
+
+    impl RawMatcher {
+        pub(in crate::matcher) fn deserialize_reader(
+            ctx: &DeserializeContext,
+            reader: &mut &[u8],
+            modifiers: Modifiers,
+        ) -> io::Result<Self> {
+            let hir = Hir::deserialize_reader(ctx, reader)?;
+            let analysis = analyze_hir(&hir, modifiers.dot_all);
+            Self::new(&hir, &analysis, modifiers)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
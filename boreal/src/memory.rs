@@ -21,6 +21,11 @@ pub enum Memory<'a> {
 pub struct Fragmented<'a> {
     pub(crate) obj: Box<dyn FragmentedMemory + 'a>,
     pub(crate) params: MemoryParams,
+
+    /// Total size of the memory, if known.
+    ///
+    /// This is only known when scanning a stream, once it has been read entirely.
+    pub(crate) filesize: Option<usize>,
 }
 
 /// Parameters related to listing and fetching memory to scan.
@@ -50,7 +55,11 @@ impl<'a> Memory<'a> {
         obj: Box<dyn FragmentedMemory + 'a>,
         params: MemoryParams,
     ) -> Memory {
-        Memory::Fragmented(Fragmented { obj, params })
+        Memory::Fragmented(Fragmented {
+            obj,
+            params,
+            filesize: None,
+        })
     }
 }
 
@@ -58,7 +67,7 @@ impl Memory<'_> {
     pub(crate) fn filesize(&self) -> Option<usize> {
         match self {
             Self::Direct(mem) => Some(mem.len()),
-            Self::Fragmented { .. } => None,
+            Self::Fragmented(fragmented) => fragmented.filesize,
         }
     }
 
@@ -341,6 +350,7 @@ mod tests {
                 memory_chunk_size: None,
                 can_refetch_regions: false,
             },
+            filesize: None,
         });
     }
 
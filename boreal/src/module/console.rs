@@ -1,5 +1,6 @@
+use std::collections::HashMap;
 use std::fmt::Write;
-use std::{collections::HashMap, sync::Arc};
+use std::sync::{Arc, Mutex};
 
 use super::{EvalContext, Module, ModuleData, ModuleDataMap, StaticValue, Type, Value};
 
@@ -48,12 +49,32 @@ impl Module for Console {
     fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
         data_map.insert::<Self>(Data {
             callback: Arc::clone(&self.callback),
+            captured_logs: None,
         });
     }
 }
 
 pub struct Data {
     callback: Arc<Box<LogCallback>>,
+
+    /// Logs captured during the scan, instead of being passed to the callback.
+    ///
+    /// This is used when scanning with [`crate::Scanner::scan_mem_with_callback`], so that
+    /// logs are reported as scan events.
+    captured_logs: Option<Mutex<Vec<String>>>,
+}
+
+impl Data {
+    fn log(&self, msg: String) {
+        match &self.captured_logs {
+            Some(logs) => {
+                if let Ok(mut logs) = logs.lock() {
+                    logs.push(msg);
+                }
+            }
+            None => (self.callback)(msg),
+        }
+    }
 }
 
 impl ModuleData for Console {
@@ -72,6 +93,25 @@ impl Console {
         }
     }
 
+    /// Capture the logs of the current scan instead of passing them to the callback.
+    ///
+    /// The captured logs can be retrieved with [`Console::take_captured_logs`].
+    pub(crate) fn capture_logs(data_map: &mut ModuleDataMap) {
+        if let Some(data) = data_map.get_mut::<Self>() {
+            data.captured_logs = Some(Mutex::new(Vec::new()));
+        }
+    }
+
+    /// Retrieve the logs captured since the last call.
+    pub(crate) fn take_captured_logs(data_map: &ModuleDataMap) -> Vec<String> {
+        data_map
+            .get::<Self>()
+            .and_then(|data| data.captured_logs.as_ref())
+            .and_then(|logs| logs.lock().ok())
+            .map(|mut logs| std::mem::take(&mut *logs))
+            .unwrap_or_default()
+    }
+
     fn log(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
         let mut args = args.into_iter();
         let mut res = String::new();
@@ -81,7 +121,7 @@ impl Console {
         }
 
         let data = ctx.module_data.get::<Console>()?;
-        (data.callback)(res);
+        data.log(res);
 
         Some(Value::Integer(1))
     }
@@ -100,7 +140,7 @@ impl Console {
         };
 
         let data = ctx.module_data.get::<Console>()?;
-        (data.callback)(res);
+        data.log(res);
 
         Some(Value::Integer(1))
     }
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use crate::regex::Regex;
+
+use super::{EvalContext, Module, ModuleData, ModuleDataMap, StaticValue, Type, Value};
+
+mod json;
+
+/// `cuckoo` module. Exposes the behavior of the scanned input, as reported by a sandbox.
+///
+/// The behavior report is not computed by this module. It must be provided for each scan, as
+/// the JSON string of a Cuckoo report, through
+/// [`crate::Scanner::scan_mem_with_module_data`]:
+///
+/// ```
+/// use std::any::Any;
+/// use std::collections::HashMap;
+///
+/// let mut compiler = boreal::Compiler::new();
+/// compiler.add_rules_str(r#"
+/// import "cuckoo"
+///
+/// rule a {
+///     condition:
+///         cuckoo.sync.mutex(/^Global\\evil/)
+/// }"#)?;
+/// let scanner = compiler.into_scanner();
+///
+/// let report = r#"{"behavior": {"summary": {"mutexes": ["Global\\evil_mutex"]}}}"#;
+/// let mut module_data: HashMap<&str, Box<dyn Any + Send + Sync>> = HashMap::new();
+/// module_data.insert("cuckoo", Box::new(report.to_owned()));
+/// let scan_result = scanner.scan_mem_with_module_data(b"", module_data).unwrap();
+/// assert_eq!(scan_result.matched_rules.len(), 1);
+/// # Ok::<(), boreal::compiler::AddRuleError>(())
+/// ```
+///
+/// As in libyara, all the functions return false if no report is provided, or if the report
+/// is not valid JSON.
+#[derive(Debug)]
+pub struct Cuckoo;
+
+impl Module for Cuckoo {
+    fn get_name(&self) -> &'static str {
+        "cuckoo"
+    }
+
+    fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+        [
+            (
+                "network",
+                StaticValue::object([
+                    (
+                        "dns_lookup",
+                        StaticValue::function(
+                            Self::network_dns_lookup,
+                            vec![vec![Type::Regex]],
+                            Type::Integer,
+                        ),
+                    ),
+                    (
+                        "http_get",
+                        StaticValue::function(
+                            Self::network_http_get,
+                            vec![vec![Type::Regex]],
+                            Type::Integer,
+                        ),
+                    ),
+                    (
+                        "http_post",
+                        StaticValue::function(
+                            Self::network_http_post,
+                            vec![vec![Type::Regex]],
+                            Type::Integer,
+                        ),
+                    ),
+                    (
+                        "http_request",
+                        StaticValue::function(
+                            Self::network_http_request,
+                            vec![vec![Type::Regex]],
+                            Type::Integer,
+                        ),
+                    ),
+                    (
+                        "http_user_agent",
+                        StaticValue::function(
+                            Self::network_http_user_agent,
+                            vec![vec![Type::Regex]],
+                            Type::Integer,
+                        ),
+                    ),
+                    (
+                        "host",
+                        StaticValue::function(
+                            Self::network_host,
+                            vec![vec![Type::Regex]],
+                            Type::Integer,
+                        ),
+                    ),
+                ]),
+            ),
+            (
+                "registry",
+                StaticValue::object([(
+                    "key_access",
+                    StaticValue::function(
+                        Self::registry_key_access,
+                        vec![vec![Type::Regex]],
+                        Type::Integer,
+                    ),
+                )]),
+            ),
+            (
+                "filesystem",
+                StaticValue::object([(
+                    "file_access",
+                    StaticValue::function(
+                        Self::filesystem_file_access,
+                        vec![vec![Type::Regex]],
+                        Type::Integer,
+                    ),
+                )]),
+            ),
+            (
+                "sync",
+                StaticValue::object([(
+                    "mutex",
+                    StaticValue::function(Self::sync_mutex, vec![vec![Type::Regex]], Type::Integer),
+                )]),
+            ),
+            (
+                "process",
+                StaticValue::object([(
+                    "executed_command",
+                    StaticValue::function(
+                        Self::process_executed_command,
+                        vec![vec![Type::Regex]],
+                        Type::Integer,
+                    ),
+                )]),
+            ),
+        ]
+        .into()
+    }
+
+    fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
+        let report = data_map
+            .get_user_data::<String>(self.get_name())
+            .and_then(|report| json::parse(report.as_bytes()));
+        data_map.insert::<Self>(Data { report });
+    }
+}
+
+impl ModuleData for Cuckoo {
+    type Data = Data;
+}
+
+pub struct Data {
+    /// Report provided for the scan, if any.
+    report: Option<json::Value>,
+}
+
+impl Cuckoo {
+    fn network_dns_lookup(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        // Older reports use "hostname", newer ones use "request".
+        any_item_matches(ctx, args, &["network", "dns"], |dns, regex| {
+            ["hostname", "request"]
+                .iter()
+                .any(|key| str_matches(dns.get(key), regex))
+        })
+    }
+
+    fn network_http_get(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        http_request_matches(ctx, args, Some("GET"))
+    }
+
+    fn network_http_post(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        http_request_matches(ctx, args, Some("POST"))
+    }
+
+    fn network_http_request(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        http_request_matches(ctx, args, None)
+    }
+
+    fn network_http_user_agent(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        any_item_matches(ctx, args, &["network", "http"], |http, regex| {
+            str_matches(http.get("user-agent"), regex)
+        })
+    }
+
+    fn network_host(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        any_item_matches(ctx, args, &["network", "hosts"], |host, regex| {
+            str_matches(Some(host), regex)
+        })
+    }
+
+    fn registry_key_access(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        any_summary_item_matches(ctx, args, "keys")
+    }
+
+    fn filesystem_file_access(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        any_summary_item_matches(ctx, args, "files")
+    }
+
+    fn sync_mutex(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        any_summary_item_matches(ctx, args, "mutexes")
+    }
+
+    fn process_executed_command(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        any_summary_item_matches(ctx, args, "executed_commands")
+    }
+}
+
+/// Check if an HTTP request in the report has an uri matching the regex.
+///
+/// If a method is provided, only the requests using this method are considered.
+fn http_request_matches(
+    ctx: &mut EvalContext,
+    args: Vec<Value>,
+    method: Option<&str>,
+) -> Option<Value> {
+    any_item_matches(ctx, args, &["network", "http"], |http, regex| {
+        let method_matches = match method {
+            Some(method) => http
+                .get("method")
+                .and_then(json::Value::as_str)
+                .map_or(false, |v| v.eq_ignore_ascii_case(method)),
+            None => true,
+        };
+        method_matches && str_matches(http.get("uri"), regex)
+    })
+}
+
+/// Check if a string in a list of the behavior summary matches the regex.
+fn any_summary_item_matches(ctx: &mut EvalContext, args: Vec<Value>, list: &str) -> Option<Value> {
+    any_item_matches(ctx, args, &["behavior", "summary", list], |item, regex| {
+        str_matches(Some(item), regex)
+    })
+}
+
+/// Check if an item of the array found at the given path in the report matches the regex.
+///
+/// The regex is the only argument of the module functions.
+fn any_item_matches<F>(
+    ctx: &mut EvalContext,
+    args: Vec<Value>,
+    path: &[&str],
+    f: F,
+) -> Option<Value>
+where
+    F: Fn(&json::Value, &Regex) -> bool,
+{
+    let regex: Regex = args.into_iter().next()?.try_into().ok()?;
+    let data = ctx.module_data.get::<Cuckoo>()?;
+
+    let res = data
+        .report
+        .as_ref()
+        .and_then(|report| report.get_path(path))
+        .and_then(json::Value::as_array)
+        .map_or(false, |items| items.iter().any(|item| f(item, &regex)));
+
+    Some(Value::Integer(res.into()))
+}
+
+fn str_matches(value: Option<&json::Value>, regex: &Regex) -> bool {
+    value
+        .and_then(json::Value::as_str)
+        .map_or(false, |s| regex.is_match(s.as_bytes()))
+}
@@ -0,0 +1,336 @@
+//! Minimal JSON parser, used to read the sandbox reports.
+//!
+//! Only what is needed to navigate the report is kept: numbers are not decoded, and objects
+//! keep their members in order, as a report only has a handful of keys on each level.
+
+/// Maximum depth of nested arrays and objects.
+///
+/// Parsing is recursive, so this limit prevents stack overflows on malicious reports.
+const MAX_DEPTH: u32 = 128;
+
+/// A JSON value.
+#[derive(Debug, PartialEq)]
+pub(super) enum Value {
+    Null,
+    Bool(bool),
+    Number,
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Return the value of a member, if this value is an object that contains it.
+    pub(super) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Return the value at the end of a path of members.
+    pub(super) fn get_path(&self, path: &[&str]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    pub(super) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a JSON document.
+///
+/// None is returned if the document is not valid JSON.
+pub(super) fn parse(input: &[u8]) -> Option<Value> {
+    let mut parser = Parser { input, pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespaces();
+    if parser.pos == input.len() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn keyword(&mut self, keyword: &[u8], value: Value) -> Option<Value> {
+        if self.input[self.pos..].starts_with(keyword) {
+            self.pos += keyword.len();
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self, depth: u32) -> Option<Value> {
+        self.skip_whitespaces();
+        match self.peek()? {
+            b'n' => self.keyword(b"null", Value::Null),
+            b't' => self.keyword(b"true", Value::Bool(true)),
+            b'f' => self.keyword(b"false", Value::Bool(false)),
+            b'"' => self.string().map(Value::String),
+            b'[' if depth < MAX_DEPTH => self.array(depth + 1),
+            b'{' if depth < MAX_DEPTH => self.object(depth + 1),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => None,
+        }
+    }
+
+    fn array(&mut self, depth: u32) -> Option<Value> {
+        self.pos += 1;
+        let mut values = Vec::new();
+
+        self.skip_whitespaces();
+        if self.eat(b']') {
+            return Some(Value::Array(values));
+        }
+        loop {
+            values.push(self.value(depth)?);
+            self.skip_whitespaces();
+            match self.bump()? {
+                b',' => (),
+                b']' => return Some(Value::Array(values)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn object(&mut self, depth: u32) -> Option<Value> {
+        self.pos += 1;
+        let mut members = Vec::new();
+
+        self.skip_whitespaces();
+        if self.eat(b'}') {
+            return Some(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespaces();
+            if self.peek()? != b'"' {
+                return None;
+            }
+            let key = self.string()?;
+            self.skip_whitespaces();
+            if !self.eat(b':') {
+                return None;
+            }
+            members.push((key, self.value(depth)?));
+            self.skip_whitespaces();
+            match self.bump()? {
+                b',' => (),
+                b'}' => return Some(Value::Object(members)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let _ = self.eat(b'-');
+        if !self.eat(b'0') {
+            self.digits()?;
+        }
+        if self.eat(b'.') {
+            self.digits()?;
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            let _ = self.eat(b'+') || self.eat(b'-');
+            self.digits()?;
+        }
+        Some(Value::Number)
+    }
+
+    /// Consume at least one digit.
+    fn digits(&mut self) -> Option<()> {
+        if !self.peek()?.is_ascii_digit() {
+            return None;
+        }
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        Some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        // Skip the opening quote.
+        self.pos += 1;
+        let mut res = Vec::new();
+
+        loop {
+            match self.bump()? {
+                b'"' => return String::from_utf8(res).ok(),
+                b'\\' => {
+                    let c = match self.bump()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\x08',
+                        b'f' => '\x0C',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return None,
+                    };
+                    let mut buf = [0; 4];
+                    res.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c if c < 0x20 => return None,
+                c => res.push(c),
+            }
+        }
+    }
+
+    /// Parse the value of a `\u` escape, which can be followed by a second one for characters
+    /// encoded with a surrogate pair.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            // An unpaired low surrogate is not a valid char, and is rejected here.
+            return char::from_u32(high);
+        }
+        if !self.input[self.pos..].starts_with(b"\\u") {
+            return None;
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.input.get(self.pos..self.pos + 4)?;
+        let digits = std::str::from_utf8(digits).ok()?;
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test_string(input: &str, expected: &str) {
+        assert_eq!(
+            parse(input.as_bytes()),
+            Some(Value::String(expected.to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(b" null "), Some(Value::Null));
+        assert_eq!(parse(b"true"), Some(Value::Bool(true)));
+        assert_eq!(parse(b"false"), Some(Value::Bool(false)));
+        for number in ["0", "-0", "12", "-1.5", "3e10", "2.5E-3", "1e+2"] {
+            assert_eq!(parse(number.as_bytes()), Some(Value::Number), "{number}");
+        }
+        assert_eq!(
+            parse(br#"[1, "a", [], {}]"#),
+            Some(Value::Array(vec![
+                Value::Number,
+                Value::String("a".to_owned()),
+                Value::Array(Vec::new()),
+                Value::Object(Vec::new()),
+            ]))
+        );
+
+        let value = parse(br#"{"a": {"b": ["c"]}, "d": null}"#).unwrap();
+        assert_eq!(value.get("d"), Some(&Value::Null));
+        assert_eq!(value.get("e"), None);
+        let array = value.get_path(&["a", "b"]).and_then(Value::as_array);
+        assert_eq!(array.unwrap()[0].as_str(), Some("c"));
+    }
+
+    #[test]
+    fn test_parse_string() {
+        test_string(r#""""#, "");
+        test_string(r#""abc""#, "abc");
+        test_string(r#""a\"b\\c\/d""#, "a\"b\\c/d");
+        test_string(r#""\b\f\n\r\t""#, "\x08\x0C\n\r\t");
+        test_string(r#""\u0041\u00e9""#, "Aé");
+        test_string(r#""\ud83d\ude00""#, "\u{1F600}");
+        test_string("\"é\"", "é");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for input in [
+            "",
+            "nul",
+            "[1,]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{\"a\": 1,}",
+            "{1: 2}",
+            "\"abc",
+            "\"\\x\"",
+            "\"\\u12\"",
+            "\"\\ud83d\"",
+            "\"\\ude00\"",
+            "\"a\nb\"",
+            "01",
+            "-",
+            "1.",
+            "1e",
+            "[] []",
+        ] {
+            assert_eq!(parse(input.as_bytes()), None, "{input:?}");
+        }
+
+        // Invalid UTF-8
+        assert_eq!(parse(b"\"\xFF\""), None);
+
+        // Too deep
+        let mut input = "[".repeat(200);
+        input.push_str(&"]".repeat(200));
+        assert_eq!(parse(input.as_bytes()), None);
+        let mut input = "[".repeat(100);
+        input.push_str(&"]".repeat(100));
+        assert!(parse(input.as_bytes()).is_some());
+    }
+}
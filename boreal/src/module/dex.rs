@@ -0,0 +1,893 @@
+use std::collections::HashMap;
+
+use crate::regex::Regex;
+
+use super::{
+    EvalContext, Module, ModuleData, ModuleDataMap, ScanContext, StaticValue, Type, Value,
+};
+
+const HEADER_SIZE: usize = 0x70;
+const CODE_ITEM_SIZE: usize = 16;
+
+const DEX_FILE_MAGICS: [&[u8]; 5] = [
+    b"dex\n035\0",
+    b"dex\n036\0",
+    b"dex\n037\0",
+    b"dex\n038\0",
+    b"dex\n039\0",
+];
+
+/// `dex` module. Allows inspecting Android DEX files.
+#[derive(Debug)]
+pub struct Dex;
+
+impl Module for Dex {
+    fn get_name(&self) -> &'static str {
+        "dex"
+    }
+
+    fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+        [
+            // Magics
+            ("DEX_FILE_MAGIC_035", StaticValue::bytes(DEX_FILE_MAGICS[0])),
+            ("DEX_FILE_MAGIC_036", StaticValue::bytes(DEX_FILE_MAGICS[1])),
+            ("DEX_FILE_MAGIC_037", StaticValue::bytes(DEX_FILE_MAGICS[2])),
+            ("DEX_FILE_MAGIC_038", StaticValue::bytes(DEX_FILE_MAGICS[3])),
+            ("DEX_FILE_MAGIC_039", StaticValue::bytes(DEX_FILE_MAGICS[4])),
+            // Endianness
+            ("ENDIAN_CONSTANT", StaticValue::Integer(0x1234_5678)),
+            ("REVERSE_ENDIAN_CONSTANT", StaticValue::Integer(0x7856_3412)),
+            ("NO_INDEX", StaticValue::Integer(0xFFFF_FFFF)),
+            // Access flags
+            ("ACC_PUBLIC", StaticValue::Integer(0x1)),
+            ("ACC_PRIVATE", StaticValue::Integer(0x2)),
+            ("ACC_PROTECTED", StaticValue::Integer(0x4)),
+            ("ACC_STATIC", StaticValue::Integer(0x8)),
+            ("ACC_FINAL", StaticValue::Integer(0x10)),
+            ("ACC_SYNCHRONIZED", StaticValue::Integer(0x20)),
+            ("ACC_VOLATILE", StaticValue::Integer(0x40)),
+            ("ACC_BRIDGE", StaticValue::Integer(0x40)),
+            ("ACC_TRANSIENT", StaticValue::Integer(0x80)),
+            ("ACC_VARARGS", StaticValue::Integer(0x80)),
+            ("ACC_NATIVE", StaticValue::Integer(0x100)),
+            ("ACC_INTERFACE", StaticValue::Integer(0x200)),
+            ("ACC_ABSTRACT", StaticValue::Integer(0x400)),
+            ("ACC_STRICT", StaticValue::Integer(0x800)),
+            ("ACC_SYNTHETIC", StaticValue::Integer(0x1000)),
+            ("ACC_ANNOTATION", StaticValue::Integer(0x2000)),
+            ("ACC_ENUM", StaticValue::Integer(0x4000)),
+            ("ACC_CONSTRUCTOR", StaticValue::Integer(0x1_0000)),
+            ("ACC_DECLARED_SYNCHRONIZED", StaticValue::Integer(0x2_0000)),
+            // Map item types
+            ("TYPE_HEADER_ITEM", StaticValue::Integer(0x0000)),
+            ("TYPE_STRING_ID_ITEM", StaticValue::Integer(0x0001)),
+            ("TYPE_TYPE_ID_ITEM", StaticValue::Integer(0x0002)),
+            ("TYPE_PROTO_ID_ITEM", StaticValue::Integer(0x0003)),
+            ("TYPE_FIELD_ID_ITEM", StaticValue::Integer(0x0004)),
+            ("TYPE_METHOD_ID_ITEM", StaticValue::Integer(0x0005)),
+            ("TYPE_CLASS_DEF_ITEM", StaticValue::Integer(0x0006)),
+            ("TYPE_CALL_SITE_ID_ITEM", StaticValue::Integer(0x0007)),
+            ("TYPE_METHOD_HANDLE_ITEM", StaticValue::Integer(0x0008)),
+            ("TYPE_MAP_LIST", StaticValue::Integer(0x1000)),
+            ("TYPE_TYPE_LIST", StaticValue::Integer(0x1001)),
+            ("TYPE_ANNOTATION_SET_REF_LIST", StaticValue::Integer(0x1002)),
+            ("TYPE_ANNOTATION_SET_ITEM", StaticValue::Integer(0x1003)),
+            ("TYPE_CLASS_DATA_ITEM", StaticValue::Integer(0x2000)),
+            ("TYPE_CODE_ITEM", StaticValue::Integer(0x2001)),
+            ("TYPE_STRING_DATA_ITEM", StaticValue::Integer(0x2002)),
+            ("TYPE_DEBUG_INFO_ITEM", StaticValue::Integer(0x2003)),
+            ("TYPE_ANNOTATION_ITEM", StaticValue::Integer(0x2004)),
+            ("TYPE_ENCODED_ARRAY_ITEM", StaticValue::Integer(0x2005)),
+            (
+                "TYPE_ANNOTATIONS_DIRECTORY_ITEM",
+                StaticValue::Integer(0x2006),
+            ),
+            (
+                "TYPE_HIDDENAPI_CLASS_DATA_ITEM",
+                StaticValue::Integer(0xF000),
+            ),
+            // Functions
+            (
+                "has_method",
+                StaticValue::function(
+                    Self::has_method,
+                    vec![
+                        vec![Type::Bytes],
+                        vec![Type::Bytes, Type::Bytes],
+                        vec![Type::Regex],
+                        vec![Type::Regex, Type::Regex],
+                    ],
+                    Type::Integer,
+                ),
+            ),
+            (
+                "has_class",
+                StaticValue::function(
+                    Self::has_class,
+                    vec![vec![Type::Bytes], vec![Type::Regex]],
+                    Type::Integer,
+                ),
+            ),
+        ]
+        .into()
+    }
+
+    fn get_dynamic_types(&self) -> HashMap<&'static str, Type> {
+        [
+            (
+                "header",
+                Type::object([
+                    ("magic", Type::Bytes),
+                    ("checksum", Type::Integer),
+                    ("signature", Type::Bytes),
+                    ("file_size", Type::Integer),
+                    ("header_size", Type::Integer),
+                    ("endian_tag", Type::Integer),
+                    ("link_size", Type::Integer),
+                    ("link_offset", Type::Integer),
+                    ("map_offset", Type::Integer),
+                    ("string_ids_size", Type::Integer),
+                    ("string_ids_offset", Type::Integer),
+                    ("type_ids_size", Type::Integer),
+                    ("type_ids_offset", Type::Integer),
+                    ("proto_ids_size", Type::Integer),
+                    ("proto_ids_offset", Type::Integer),
+                    ("field_ids_size", Type::Integer),
+                    ("field_ids_offset", Type::Integer),
+                    ("method_ids_size", Type::Integer),
+                    ("method_ids_offset", Type::Integer),
+                    ("class_defs_size", Type::Integer),
+                    ("class_defs_offset", Type::Integer),
+                    ("data_size", Type::Integer),
+                    ("data_offset", Type::Integer),
+                ]),
+            ),
+            (
+                "string_ids",
+                Type::array(Type::object([
+                    ("offset", Type::Integer),
+                    ("size", Type::Integer),
+                    ("value", Type::Bytes),
+                ])),
+            ),
+            (
+                "type_ids",
+                Type::array(Type::object([("descriptor_idx", Type::Integer)])),
+            ),
+            (
+                "proto_ids",
+                Type::array(Type::object([
+                    ("shorty_idx", Type::Integer),
+                    ("return_type_idx", Type::Integer),
+                    ("parameters_offset", Type::Integer),
+                ])),
+            ),
+            (
+                "field_ids",
+                Type::array(Type::object([
+                    ("class_idx", Type::Integer),
+                    ("type_idx", Type::Integer),
+                    ("name_idx", Type::Integer),
+                ])),
+            ),
+            (
+                "method_ids",
+                Type::array(Type::object([
+                    ("class_idx", Type::Integer),
+                    ("proto_idx", Type::Integer),
+                    ("name_idx", Type::Integer),
+                ])),
+            ),
+            (
+                "class_defs",
+                Type::array(Type::object([
+                    ("class_idx", Type::Integer),
+                    ("access_flags", Type::Integer),
+                    ("super_class_idx", Type::Integer),
+                    ("interfaces_offset", Type::Integer),
+                    ("source_file_idx", Type::Integer),
+                    ("annotations_offset", Type::Integer),
+                    ("class_data_offset", Type::Integer),
+                    ("static_values_offset", Type::Integer),
+                ])),
+            ),
+            (
+                "class_data_item",
+                Type::array(Type::object([
+                    ("static_fields_size", Type::Integer),
+                    ("instance_fields_size", Type::Integer),
+                    ("direct_methods_size", Type::Integer),
+                    ("virtual_methods_size", Type::Integer),
+                ])),
+            ),
+            (
+                "map_list",
+                Type::object([
+                    ("size", Type::Integer),
+                    (
+                        "map_item",
+                        Type::array(Type::object([
+                            ("type", Type::Integer),
+                            ("unused", Type::Integer),
+                            ("size", Type::Integer),
+                            ("offset", Type::Integer),
+                        ])),
+                    ),
+                ]),
+            ),
+            ("number_of_fields", Type::Integer),
+            (
+                "field",
+                Type::array(Type::object([
+                    ("class_name", Type::Bytes),
+                    ("name", Type::Bytes),
+                    ("proto", Type::Bytes),
+                    ("static", Type::Integer),
+                    ("instance", Type::Integer),
+                    ("field_idx_diff", Type::Integer),
+                    ("access_flags", Type::Integer),
+                ])),
+            ),
+            ("number_of_methods", Type::Integer),
+            (
+                "method",
+                Type::array(Type::object([
+                    ("class_name", Type::Bytes),
+                    ("name", Type::Bytes),
+                    ("proto", Type::Bytes),
+                    ("direct", Type::Integer),
+                    ("virtual", Type::Integer),
+                    ("method_idx_diff", Type::Integer),
+                    ("access_flags", Type::Integer),
+                    ("code_off", Type::Integer),
+                    (
+                        "code_item",
+                        Type::object([
+                            ("registers_size", Type::Integer),
+                            ("ins_size", Type::Integer),
+                            ("outs_size", Type::Integer),
+                            ("tries_size", Type::Integer),
+                            ("debug_info_off", Type::Integer),
+                            ("insns_size", Type::Integer),
+                            ("insns", Type::Bytes),
+                        ]),
+                    ),
+                ])),
+            ),
+        ]
+        .into()
+    }
+
+    fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
+        data_map.insert::<Self>(Data::default());
+    }
+
+    fn get_dynamic_values(&self, ctx: &mut ScanContext, out: &mut HashMap<&'static str, Value>) {
+        let Some(data) = ctx.module_data.get_mut::<Self>() else {
+            return;
+        };
+        if data.found_dex {
+            // We already found a dex file in a scanned region, so ignore the others.
+            return;
+        }
+
+        let mem = ctx.region.mem;
+        if mem.len() < HEADER_SIZE || !DEX_FILE_MAGICS.contains(&&mem[..8]) {
+            return;
+        }
+
+        data.found_dex = true;
+        *out = Parser::new(mem, data).parse();
+    }
+}
+
+impl ModuleData for Dex {
+    type Data = Data;
+}
+
+#[derive(Default)]
+pub struct Data {
+    found_dex: bool,
+    methods: Vec<DataMethod>,
+}
+
+struct DataMethod {
+    class_name: Option<Vec<u8>>,
+    name: Option<Vec<u8>>,
+}
+
+impl Dex {
+    fn has_method(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let first = args.next()?;
+        let second = args.next();
+
+        let data = ctx.module_data.get::<Self>()?;
+        if !data.found_dex {
+            return None;
+        }
+
+        let res = match (first, second) {
+            (Value::Bytes(name), None) => data
+                .methods
+                .iter()
+                .any(|method| method.name.as_ref() == Some(&name)),
+            (Value::Bytes(class_name), Some(Value::Bytes(name))) => {
+                data.methods.iter().any(|method| {
+                    method.class_name.as_ref() == Some(&class_name)
+                        && method.name.as_ref() == Some(&name)
+                })
+            }
+            (Value::Regex(name), None) => data
+                .methods
+                .iter()
+                .any(|method| regex_match(&name, method.name.as_deref())),
+            (Value::Regex(class_name), Some(Value::Regex(name))) => {
+                data.methods.iter().any(|method| {
+                    regex_match(&class_name, method.class_name.as_deref())
+                        && regex_match(&name, method.name.as_deref())
+                })
+            }
+            _ => return None,
+        };
+
+        Some(Value::Integer(res.into()))
+    }
+
+    fn has_class(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let arg = args.next()?;
+
+        let data = ctx.module_data.get::<Self>()?;
+        if !data.found_dex {
+            return None;
+        }
+
+        let res = match arg {
+            Value::Bytes(class_name) => data
+                .methods
+                .iter()
+                .any(|method| method.class_name.as_ref() == Some(&class_name)),
+            Value::Regex(class_name) => data
+                .methods
+                .iter()
+                .any(|method| regex_match(&class_name, method.class_name.as_deref())),
+            _ => return None,
+        };
+
+        Some(Value::Integer(res.into()))
+    }
+}
+
+fn regex_match(regex: &Regex, value: Option<&[u8]>) -> bool {
+    value.map_or(false, |v| regex.is_match(v))
+}
+
+struct Parser<'a> {
+    mem: &'a [u8],
+    data: &'a mut Data,
+
+    /// Values of the strings, indexed by their id.
+    strings: Vec<Option<&'a [u8]>>,
+    /// Index into the strings of the type descriptors.
+    type_ids: Vec<u32>,
+    /// Index into the strings of the shorty descriptors of the prototypes.
+    proto_shorty_ids: Vec<u32>,
+    /// Class, type and name indexes of the fields.
+    field_ids: Vec<(u16, u16, u32)>,
+    /// Class, prototype and name indexes of the methods.
+    method_ids: Vec<(u16, u16, u32)>,
+
+    fields: Vec<Value>,
+    methods: Vec<Value>,
+}
+
+/// Location and number of items of a table listed in the header.
+#[derive(Copy, Clone)]
+struct Table {
+    size: u32,
+    offset: u32,
+}
+
+impl<'a> Parser<'a> {
+    fn new(mem: &'a [u8], data: &'a mut Data) -> Self {
+        Self {
+            mem,
+            data,
+            strings: Vec::new(),
+            type_ids: Vec::new(),
+            proto_shorty_ids: Vec::new(),
+            field_ids: Vec::new(),
+            method_ids: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> HashMap<&'static str, Value> {
+        let mut out = HashMap::new();
+
+        let table = |offset| Table {
+            size: read_u32(self.mem, offset).unwrap_or(0),
+            offset: read_u32(self.mem, offset + 4).unwrap_or(0),
+        };
+        let string_ids = table(56);
+        let type_ids = table(64);
+        let proto_ids = table(72);
+        let field_ids = table(80);
+        let method_ids = table(88);
+        let class_defs = table(96);
+        let map_offset = read_u32(self.mem, 52).unwrap_or(0);
+
+        let _r = out.insert("header", self.parse_header());
+
+        // As in libyara, the parsing stops on the first table that does not fit in the file.
+        let _r = self.parse_tables(
+            &mut out, string_ids, type_ids, proto_ids, field_ids, method_ids, map_offset,
+            class_defs,
+        );
+
+        let _r = out.insert("number_of_fields", self.fields.len().into());
+        let _r = out.insert("field", Value::Array(self.fields));
+        let _r = out.insert("number_of_methods", self.methods.len().into());
+        let _r = out.insert("method", Value::Array(self.methods));
+
+        out
+    }
+
+    fn parse_header(&self) -> Value {
+        let mem = self.mem;
+        let u32_value = |offset| read_u32(mem, offset).into();
+
+        Value::object([
+            ("magic", mem[..8].to_vec().into()),
+            ("checksum", u32_value(8)),
+            ("signature", mem[12..32].to_vec().into()),
+            ("file_size", u32_value(32)),
+            ("header_size", u32_value(36)),
+            ("endian_tag", u32_value(40)),
+            ("link_size", u32_value(44)),
+            ("link_offset", u32_value(48)),
+            ("map_offset", u32_value(52)),
+            ("string_ids_size", u32_value(56)),
+            ("string_ids_offset", u32_value(60)),
+            ("type_ids_size", u32_value(64)),
+            ("type_ids_offset", u32_value(68)),
+            ("proto_ids_size", u32_value(72)),
+            ("proto_ids_offset", u32_value(76)),
+            ("field_ids_size", u32_value(80)),
+            ("field_ids_offset", u32_value(84)),
+            ("method_ids_size", u32_value(88)),
+            ("method_ids_offset", u32_value(92)),
+            ("class_defs_size", u32_value(96)),
+            ("class_defs_offset", u32_value(100)),
+            ("data_size", u32_value(104)),
+            ("data_offset", u32_value(108)),
+        ])
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn parse_tables(
+        &mut self,
+        out: &mut HashMap<&'static str, Value>,
+        string_ids: Table,
+        type_ids: Table,
+        proto_ids: Table,
+        field_ids: Table,
+        method_ids: Table,
+        map_offset: u32,
+        class_defs: Table,
+    ) -> Option<()> {
+        let _r = out.insert(
+            "string_ids",
+            Value::Array(self.parse_string_ids(string_ids)?),
+        );
+        let _r = out.insert("type_ids", Value::Array(self.parse_type_ids(type_ids)?));
+        let _r = out.insert("proto_ids", Value::Array(self.parse_proto_ids(proto_ids)?));
+        let _r = out.insert("field_ids", Value::Array(self.parse_field_ids(field_ids)?));
+        let _r = out.insert(
+            "method_ids",
+            Value::Array(self.parse_method_ids(method_ids)?),
+        );
+        let _r = out.insert("map_list", self.parse_map_list(map_offset)?);
+
+        let (class_defs, class_data_items) = self.parse_class_defs(class_defs)?;
+        let _r = out.insert("class_defs", Value::Array(class_defs));
+        let _r = out.insert("class_data_item", Value::Array(class_data_items));
+
+        Some(())
+    }
+
+    /// Returns the items of a table, None if the table does not fit in the file.
+    fn table_items(&self, table: Table, item_size: usize) -> Option<std::slice::Chunks<'a, u8>> {
+        let start = usize::try_from(table.offset).ok()?;
+        let len = usize::try_from(table.size).ok()?.checked_mul(item_size)?;
+        let mem: &'a [u8] = self.mem;
+        Some(mem.get(start..start.checked_add(len)?)?.chunks(item_size))
+    }
+
+    fn parse_string_ids(&mut self, table: Table) -> Option<Vec<Value>> {
+        let mut values = Vec::new();
+
+        for item in self.table_items(table, 4)? {
+            let offset = read_u32(item, 0)?;
+            let mut string_id = HashMap::new();
+            let _r = string_id.insert("offset", offset.into());
+
+            let value = self.read_string_data(offset as usize);
+            if let Some(value) = value {
+                let _r = string_id.insert("size", value.len().into());
+                let _r = string_id.insert("value", value.to_vec().into());
+            }
+            self.strings.push(value);
+            values.push(Value::Object(string_id));
+        }
+
+        Some(values)
+    }
+
+    /// Read a `string_data_item`: its size in UTF-16 code units, followed by its MUTF-8 encoded
+    /// value.
+    fn read_string_data(&self, offset: usize) -> Option<&'a [u8]> {
+        let mem: &'a [u8] = self.mem;
+        // libyara checks that at least 4 bytes are available.
+        let _ = mem.get(offset..offset.checked_add(4)?)?;
+
+        let mut cursor = mem.get(offset..)?;
+        let size = read_uleb128(&mut cursor)? as usize;
+        cursor.get(..size)
+    }
+
+    fn parse_type_ids(&mut self, table: Table) -> Option<Vec<Value>> {
+        let mut values = Vec::new();
+
+        for item in self.table_items(table, 4)? {
+            let descriptor_idx = read_u32(item, 0)?;
+            self.type_ids.push(descriptor_idx);
+            values.push(Value::object([("descriptor_idx", descriptor_idx.into())]));
+        }
+
+        Some(values)
+    }
+
+    fn parse_proto_ids(&mut self, table: Table) -> Option<Vec<Value>> {
+        let mut values = Vec::new();
+
+        for item in self.table_items(table, 12)? {
+            let shorty_idx = read_u32(item, 0)?;
+            self.proto_shorty_ids.push(shorty_idx);
+            values.push(Value::object([
+                ("shorty_idx", shorty_idx.into()),
+                ("return_type_idx", read_u32(item, 4).into()),
+                ("parameters_offset", read_u32(item, 8).into()),
+            ]));
+        }
+
+        Some(values)
+    }
+
+    fn parse_field_ids(&mut self, table: Table) -> Option<Vec<Value>> {
+        let mut values = Vec::new();
+
+        for item in self.table_items(table, 8)? {
+            let class_idx = read_u16(item, 0)?;
+            let type_idx = read_u16(item, 2)?;
+            let name_idx = read_u32(item, 4)?;
+            self.field_ids.push((class_idx, type_idx, name_idx));
+            values.push(Value::object([
+                ("class_idx", class_idx.into()),
+                ("type_idx", type_idx.into()),
+                ("name_idx", name_idx.into()),
+            ]));
+        }
+
+        Some(values)
+    }
+
+    fn parse_method_ids(&mut self, table: Table) -> Option<Vec<Value>> {
+        let mut values = Vec::new();
+
+        for item in self.table_items(table, 8)? {
+            let class_idx = read_u16(item, 0)?;
+            let proto_idx = read_u16(item, 2)?;
+            let name_idx = read_u32(item, 4)?;
+            self.method_ids.push((class_idx, proto_idx, name_idx));
+            values.push(Value::object([
+                ("class_idx", class_idx.into()),
+                ("proto_idx", proto_idx.into()),
+                ("name_idx", name_idx.into()),
+            ]));
+        }
+
+        Some(values)
+    }
+
+    fn parse_map_list(&self, map_offset: u32) -> Option<Value> {
+        let size = read_u32(self.mem, map_offset as usize)?;
+        let table = Table {
+            size,
+            offset: map_offset.checked_add(4)?,
+        };
+
+        let items = self
+            .table_items(table, 12)?
+            .map(|item| {
+                Value::object([
+                    ("type", read_u16(item, 0).into()),
+                    ("unused", read_u16(item, 2).into()),
+                    ("size", read_u32(item, 4).into()),
+                    ("offset", read_u32(item, 8).into()),
+                ])
+            })
+            .collect();
+
+        Some(Value::object([
+            ("size", size.into()),
+            ("map_item", Value::Array(items)),
+        ]))
+    }
+
+    fn parse_class_defs(&mut self, table: Table) -> Option<(Vec<Value>, Vec<Value>)> {
+        let mut class_defs = Vec::new();
+        let mut class_data_items = Vec::new();
+
+        for item in self.table_items(table, 32)? {
+            let class_data_offset = read_u32(item, 24)?;
+            class_defs.push(Value::object([
+                ("class_idx", read_u32(item, 0).into()),
+                ("access_flags", read_u32(item, 4).into()),
+                ("super_class_idx", read_u32(item, 8).into()),
+                ("interfaces_offset", read_u32(item, 12).into()),
+                ("source_file_idx", read_u32(item, 16).into()),
+                ("annotations_offset", read_u32(item, 20).into()),
+                ("class_data_offset", class_data_offset.into()),
+                ("static_values_offset", read_u32(item, 28).into()),
+            ]));
+
+            if class_data_offset != 0 {
+                if let Some(class_data_item) = self.parse_class_data(class_data_offset as usize) {
+                    class_data_items.push(class_data_item);
+                }
+            }
+        }
+
+        Some((class_defs, class_data_items))
+    }
+
+    /// Parse a `class_data_item`, adding its fields and methods to the module values.
+    fn parse_class_data(&mut self, offset: usize) -> Option<Value> {
+        // libyara checks that at least 16 bytes are available.
+        let mem: &'a [u8] = self.mem;
+        let _ = mem.get(offset..offset.checked_add(16)?)?;
+        let mut cursor = &mem[offset..];
+
+        let static_fields_size = read_uleb128(&mut cursor)?;
+        let instance_fields_size = read_uleb128(&mut cursor)?;
+        let direct_methods_size = read_uleb128(&mut cursor)?;
+        let virtual_methods_size = read_uleb128(&mut cursor)?;
+
+        // The sizes are not trusted: as in libyara, the parsing of a list stops on the first
+        // element that cannot be parsed. Each element also takes at least two bytes, which bounds
+        // the number of elements that can be parsed.
+        for (size, is_static) in [(static_fields_size, true), (instance_fields_size, false)] {
+            let mut field_idx = 0;
+            for _ in 0..bound_size(size, cursor) {
+                match self.parse_encoded_field(&mut cursor, &mut field_idx, is_static) {
+                    Some(field) => self.fields.push(field),
+                    None => break,
+                }
+            }
+        }
+        for (size, is_direct) in [(direct_methods_size, true), (virtual_methods_size, false)] {
+            let mut method_idx = 0;
+            for _ in 0..bound_size(size, cursor) {
+                match self.parse_encoded_method(&mut cursor, &mut method_idx, is_direct) {
+                    Some(method) => self.methods.push(method),
+                    None => break,
+                }
+            }
+        }
+
+        Some(Value::object([
+            ("static_fields_size", static_fields_size.into()),
+            ("instance_fields_size", instance_fields_size.into()),
+            ("direct_methods_size", direct_methods_size.into()),
+            ("virtual_methods_size", virtual_methods_size.into()),
+        ]))
+    }
+
+    fn get_string(&self, index: u32) -> Option<&'a [u8]> {
+        self.strings.get(index as usize).copied().flatten()
+    }
+
+    fn get_type_name(&self, type_idx: u16) -> Option<&'a [u8]> {
+        self.type_ids
+            .get(usize::from(type_idx))
+            .and_then(|descriptor_idx| self.get_string(*descriptor_idx))
+    }
+
+    fn parse_encoded_field(
+        &self,
+        cursor: &mut &[u8],
+        field_idx: &mut u32,
+        is_static: bool,
+    ) -> Option<Value> {
+        // libyara checks that at least 8 bytes are available.
+        if cursor.len() < 8 {
+            return None;
+        }
+        let field_idx_diff = read_uleb128(cursor)?;
+        let access_flags = read_uleb128(cursor)?;
+
+        // The index is encoded as the difference from the index of the previous element.
+        *field_idx = field_idx.wrapping_add(field_idx_diff);
+        let (class_idx, type_idx, name_idx) = *self.field_ids.get(*field_idx as usize)?;
+
+        Some(Value::object([
+            (
+                "class_name",
+                self.get_type_name(class_idx).map(<[u8]>::to_vec).into(),
+            ),
+            ("name", self.get_string(name_idx).map(<[u8]>::to_vec).into()),
+            (
+                "proto",
+                self.get_type_name(type_idx).map(<[u8]>::to_vec).into(),
+            ),
+            ("static", Value::Integer(is_static.into())),
+            ("instance", Value::Integer((!is_static).into())),
+            ("field_idx_diff", field_idx_diff.into()),
+            ("access_flags", access_flags.into()),
+        ]))
+    }
+
+    fn parse_encoded_method(
+        &mut self,
+        cursor: &mut &[u8],
+        method_idx: &mut u32,
+        is_direct: bool,
+    ) -> Option<Value> {
+        // libyara checks that at least 12 bytes are available.
+        if cursor.len() < 12 {
+            return None;
+        }
+        let method_idx_diff = read_uleb128(cursor)?;
+        let access_flags = read_uleb128(cursor)?;
+        let code_off = read_uleb128(cursor)?;
+
+        // The index is encoded as the difference from the index of the previous element.
+        *method_idx = method_idx.wrapping_add(method_idx_diff);
+        let (class_idx, proto_idx, name_idx) = *self.method_ids.get(*method_idx as usize)?;
+
+        let class_name = self.get_type_name(class_idx).map(<[u8]>::to_vec);
+        let name = self.get_string(name_idx).map(<[u8]>::to_vec);
+        let proto = self
+            .proto_shorty_ids
+            .get(usize::from(proto_idx))
+            .and_then(|shorty_idx| self.get_string(*shorty_idx));
+
+        let mut method: HashMap<_, _> = [
+            ("class_name", class_name.clone().into()),
+            ("name", name.clone().into()),
+            ("proto", proto.map(<[u8]>::to_vec).into()),
+            ("direct", Value::Integer(is_direct.into())),
+            ("virtual", Value::Integer((!is_direct).into())),
+            ("method_idx_diff", method_idx_diff.into()),
+            ("access_flags", access_flags.into()),
+            ("code_off", code_off.into()),
+        ]
+        .into();
+        if code_off != 0 {
+            if let Some(code_item) = self.parse_code_item(code_off as usize) {
+                let _r = method.insert("code_item", code_item);
+            }
+        }
+
+        self.data.methods.push(DataMethod { class_name, name });
+
+        Some(Value::Object(method))
+    }
+
+    fn parse_code_item(&self, offset: usize) -> Option<Value> {
+        let item = self.mem.get(offset..offset.checked_add(CODE_ITEM_SIZE)?)?;
+        let insns_size = read_u32(item, 12)?;
+
+        let insns_start = offset + CODE_ITEM_SIZE;
+        let insns = (insns_size as usize)
+            .checked_mul(2)
+            .and_then(|len| insns_start.checked_add(len))
+            .and_then(|end| self.mem.get(insns_start..end));
+
+        Some(Value::object([
+            ("registers_size", read_u16(item, 0).into()),
+            ("ins_size", read_u16(item, 2).into()),
+            ("outs_size", read_u16(item, 4).into()),
+            ("tries_size", read_u16(item, 6).into()),
+            ("debug_info_off", read_u32(item, 8).into()),
+            ("insns_size", insns_size.into()),
+            ("insns", insns.map(<[u8]>::to_vec).into()),
+        ]))
+    }
+}
+
+/// Read an unsigned LEB128 value, as used in the DEX format.
+///
+/// The value is encoded on at most 5 bytes. None is returned if the value is truncated.
+/// Bound the declared number of elements of a list by the number of elements that can fit in the
+/// remaining data.
+fn bound_size(size: u32, cursor: &[u8]) -> usize {
+    (size as usize).min(cursor.len() / 2)
+}
+
+fn read_uleb128(cursor: &mut &[u8]) -> Option<u32> {
+    let mut value: u32 = 0;
+
+    for i in 0..5 {
+        let (byte, rest) = cursor.split_first()?;
+        *cursor = rest;
+        value |= u32::from(byte & 0x7F).wrapping_shl(7 * i);
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    Some(value)
+}
+
+fn read_u16(mem: &[u8], offset: usize) -> Option<u16> {
+    let v = mem.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([v[0], v[1]]))
+}
+
+fn read_u32(mem: &[u8], offset: usize) -> Option<u32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_uleb128() {
+        // Examples from the DEX format documentation.
+        let check = |mut bytes: &[u8], expected: u32| {
+            assert_eq!(read_uleb128(&mut bytes), Some(expected));
+            assert!(bytes.is_empty());
+        };
+        check(b"\x00", 0);
+        check(b"\x01", 1);
+        check(b"\x7F", 127);
+        check(b"\x80\x7F", 16256);
+        check(b"\x81\x80\x04", 0x1_0001);
+        check(b"\xFF\xFF\xFF\xFF\x0F", u32::MAX);
+
+        let mut bytes: &[u8] = b"\x80\x80";
+        assert_eq!(read_uleb128(&mut bytes), None);
+    }
+
+    #[test]
+    fn test_class_data_huge_sizes() {
+        let mut mem = std::fs::read("tests/assets/dex/classes.dex").unwrap();
+
+        // Replace the class_data_item of the first class by one declaring huge sizes, and
+        // truncate the file right after it. The remaining bytes reference unknown field and
+        // method ids.
+        let class_data_offset = read_u32(&mem, 0xE0 + 24).unwrap() as usize;
+        mem.truncate(class_data_offset);
+        for _ in 0..4 {
+            mem.extend_from_slice(b"\xFF\xFF\xFF\xFF\x0F");
+        }
+        mem.extend_from_slice(&[0x7F; 32]);
+
+        let mut data = Data::default();
+        let values = Parser::new(&mem, &mut data).parse();
+        let Some(Value::Array(fields)) = values.get("field") else {
+            panic!("missing fields");
+        };
+        assert!(fields.is_empty());
+        let Some(Value::Array(methods)) = values.get("method") else {
+            panic!("missing methods");
+        };
+        assert!(methods.is_empty());
+    }
+}
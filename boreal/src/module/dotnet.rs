@@ -0,0 +1,1248 @@
+use std::collections::HashMap;
+
+use object::{
+    coff::SectionTable,
+    pe::{self, ImageDosHeader, ImageNtHeaders32, ImageNtHeaders64},
+    read::pe::{DataDirectories, ImageNtHeaders},
+    FileKind, LittleEndian as LE,
+};
+
+use crate::memory::Region;
+
+use super::pe::va_to_file_offset;
+use super::{Module, ModuleData, ModuleDataMap, ScanContext, StaticValue, Type, Value};
+
+mod classes;
+
+const NET_METADATA_MAGIC: u32 = 0x424A_5342;
+const CLI_HEADER_SIZE: usize = 72;
+const NET_METADATA_SIZE: usize = 16;
+const STREAM_HEADER_SIZE: usize = 8;
+const STREAM_NAME_SIZE: usize = 32;
+const TILDE_HEADER_SIZE: usize = 24;
+
+const MAX_GUIDS_SIZE: u32 = 256;
+const MAX_TABLE_ROWS: u32 = 15_000;
+
+// Heap sizes flags of the #~ stream header.
+const HEAP_STRING_4: u8 = 0x01;
+const HEAP_GUID_4: u8 = 0x02;
+const HEAP_BLOB_4: u8 = 0x04;
+const HEAP_EXTRA_DATA: u8 = 0x40;
+
+const ELEMENT_TYPE_STRING: u8 = 0x0E;
+
+// Metadata tables, with the value being the bit set in the `Valid` field of the #~ stream
+// header. See ECMA-335 II.22.
+const TABLE_MODULE: usize = 0x00;
+const TABLE_TYPEREF: usize = 0x01;
+const TABLE_TYPEDEF: usize = 0x02;
+const TABLE_FIELDPTR: usize = 0x03;
+const TABLE_FIELD: usize = 0x04;
+const TABLE_METHODDEFPTR: usize = 0x05;
+const TABLE_METHODDEF: usize = 0x06;
+const TABLE_PARAMPTR: usize = 0x07;
+const TABLE_PARAM: usize = 0x08;
+const TABLE_INTERFACEIMPL: usize = 0x09;
+const TABLE_MEMBERREF: usize = 0x0A;
+const TABLE_CONSTANT: usize = 0x0B;
+const TABLE_CUSTOMATTRIBUTE: usize = 0x0C;
+const TABLE_FIELDMARSHAL: usize = 0x0D;
+const TABLE_DECLSECURITY: usize = 0x0E;
+const TABLE_CLASSLAYOUT: usize = 0x0F;
+const TABLE_FIELDLAYOUT: usize = 0x10;
+const TABLE_STANDALONESIG: usize = 0x11;
+const TABLE_EVENTMAP: usize = 0x12;
+const TABLE_EVENTPTR: usize = 0x13;
+const TABLE_EVENT: usize = 0x14;
+const TABLE_PROPERTYMAP: usize = 0x15;
+const TABLE_PROPERTYPTR: usize = 0x16;
+const TABLE_PROPERTY: usize = 0x17;
+const TABLE_METHODSEMANTICS: usize = 0x18;
+const TABLE_METHODIMPL: usize = 0x19;
+const TABLE_MODULEREF: usize = 0x1A;
+const TABLE_TYPESPEC: usize = 0x1B;
+const TABLE_IMPLMAP: usize = 0x1C;
+const TABLE_FIELDRVA: usize = 0x1D;
+const TABLE_ENCLOG: usize = 0x1E;
+const TABLE_ENCMAP: usize = 0x1F;
+const TABLE_ASSEMBLY: usize = 0x20;
+const TABLE_ASSEMBLYPROCESSOR: usize = 0x21;
+const TABLE_ASSEMBLYOS: usize = 0x22;
+const TABLE_ASSEMBLYREF: usize = 0x23;
+const TABLE_ASSEMBLYREFPROCESSOR: usize = 0x24;
+const TABLE_ASSEMBLYREFOS: usize = 0x25;
+const TABLE_FILE: usize = 0x26;
+const TABLE_EXPORTEDTYPE: usize = 0x27;
+const TABLE_MANIFESTRESOURCE: usize = 0x28;
+const TABLE_NESTEDCLASS: usize = 0x29;
+const TABLE_GENERICPARAM: usize = 0x2A;
+const TABLE_METHODSPEC: usize = 0x2B;
+const TABLE_GENERICPARAMCONSTRAINT: usize = 0x2C;
+
+// Tables that can be the target of the coded indexes. See ECMA-335 II.24.2.6.
+const TYPE_DEF_OR_REF: &[usize] = &[TABLE_TYPEDEF, TABLE_TYPEREF, TABLE_TYPESPEC];
+const HAS_CONSTANT: &[usize] = &[TABLE_PARAM, TABLE_FIELD, TABLE_PROPERTY];
+const HAS_CUSTOM_ATTRIBUTE: &[usize] = &[
+    TABLE_METHODDEF,
+    TABLE_FIELD,
+    TABLE_TYPEREF,
+    TABLE_TYPEDEF,
+    TABLE_PARAM,
+    TABLE_INTERFACEIMPL,
+    TABLE_MEMBERREF,
+    TABLE_MODULE,
+    TABLE_PROPERTY,
+    TABLE_EVENT,
+    TABLE_STANDALONESIG,
+    TABLE_MODULEREF,
+    TABLE_TYPESPEC,
+    TABLE_ASSEMBLY,
+    TABLE_ASSEMBLYREF,
+    TABLE_FILE,
+    TABLE_EXPORTEDTYPE,
+    TABLE_MANIFESTRESOURCE,
+    TABLE_GENERICPARAM,
+    TABLE_GENERICPARAMCONSTRAINT,
+    TABLE_METHODSPEC,
+];
+const HAS_FIELD_MARSHAL: &[usize] = &[TABLE_FIELD, TABLE_PARAM];
+const HAS_DECL_SECURITY: &[usize] = &[TABLE_TYPEDEF, TABLE_METHODDEF, TABLE_ASSEMBLY];
+const MEMBER_REF_PARENT: &[usize] = &[
+    TABLE_METHODDEF,
+    TABLE_MODULEREF,
+    TABLE_TYPEREF,
+    TABLE_TYPESPEC,
+];
+const HAS_SEMANTICS: &[usize] = &[TABLE_EVENT, TABLE_PROPERTY];
+const METHOD_DEF_OR_REF: &[usize] = &[TABLE_METHODDEF, TABLE_MEMBERREF];
+const MEMBER_FORWARDED: &[usize] = &[TABLE_FIELD, TABLE_METHODDEF];
+const IMPLEMENTATION: &[usize] = &[TABLE_FILE, TABLE_ASSEMBLYREF, TABLE_EXPORTEDTYPE];
+const RESOLUTION_SCOPE: &[usize] = &[
+    TABLE_MODULE,
+    TABLE_MODULEREF,
+    TABLE_ASSEMBLYREF,
+    TABLE_TYPEREF,
+];
+const TYPE_OR_METHOD_DEF: &[usize] = &[TABLE_TYPEDEF, TABLE_METHODDEF];
+
+/// `dotnet` module. Allows inspecting .NET assemblies.
+#[derive(Debug)]
+pub struct Dotnet;
+
+impl Module for Dotnet {
+    fn get_name(&self) -> &'static str {
+        "dotnet"
+    }
+
+    fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+        HashMap::new()
+    }
+
+    fn get_dynamic_types(&self) -> HashMap<&'static str, Type> {
+        let version = || {
+            Type::object([
+                ("major", Type::Integer),
+                ("minor", Type::Integer),
+                ("build_number", Type::Integer),
+                ("revision_number", Type::Integer),
+            ])
+        };
+
+        [
+            ("is_dotnet", Type::Integer),
+            ("version", Type::Bytes),
+            ("module_name", Type::Bytes),
+            (
+                "streams",
+                Type::array(Type::object([
+                    ("name", Type::Bytes),
+                    ("offset", Type::Integer),
+                    ("size", Type::Integer),
+                ])),
+            ),
+            ("number_of_streams", Type::Integer),
+            ("guids", Type::array(Type::Bytes)),
+            ("number_of_guids", Type::Integer),
+            (
+                "resources",
+                Type::array(Type::object([
+                    ("offset", Type::Integer),
+                    ("length", Type::Integer),
+                    ("name", Type::Bytes),
+                ])),
+            ),
+            ("number_of_resources", Type::Integer),
+            (
+                "classes",
+                Type::array(Type::object([
+                    ("fullname", Type::Bytes),
+                    ("name", Type::Bytes),
+                    ("namespace", Type::Bytes),
+                    ("visibility", Type::Bytes),
+                    ("type", Type::Bytes),
+                    ("abstract", Type::Integer),
+                    ("sealed", Type::Integer),
+                    ("number_of_generic_parameters", Type::Integer),
+                    ("generic_parameters", Type::array(Type::Bytes)),
+                    ("number_of_base_types", Type::Integer),
+                    ("base_types", Type::array(Type::Bytes)),
+                    ("number_of_methods", Type::Integer),
+                    (
+                        "methods",
+                        Type::array(Type::object([
+                            ("generic_parameters", Type::array(Type::Bytes)),
+                            ("number_of_generic_parameters", Type::Integer),
+                            (
+                                "parameters",
+                                Type::array(Type::object([
+                                    ("name", Type::Bytes),
+                                    ("type", Type::Bytes),
+                                ])),
+                            ),
+                            ("number_of_parameters", Type::Integer),
+                            ("return_type", Type::Bytes),
+                            ("abstract", Type::Integer),
+                            ("final", Type::Integer),
+                            ("virtual", Type::Integer),
+                            ("static", Type::Integer),
+                            ("visibility", Type::Bytes),
+                            ("name", Type::Bytes),
+                        ])),
+                    ),
+                ])),
+            ),
+            ("number_of_classes", Type::Integer),
+            (
+                "assembly_refs",
+                Type::array(Type::object([
+                    ("version", version()),
+                    ("public_key_or_token", Type::Bytes),
+                    ("name", Type::Bytes),
+                ])),
+            ),
+            ("number_of_assembly_refs", Type::Integer),
+            (
+                "assembly",
+                Type::object([
+                    ("version", version()),
+                    ("name", Type::Bytes),
+                    ("culture", Type::Bytes),
+                ]),
+            ),
+            ("modulerefs", Type::array(Type::Bytes)),
+            ("number_of_modulerefs", Type::Integer),
+            ("user_strings", Type::array(Type::Bytes)),
+            ("number_of_user_strings", Type::Integer),
+            ("typelib", Type::Bytes),
+            ("constants", Type::array(Type::Bytes)),
+            ("number_of_constants", Type::Integer),
+            ("field_offsets", Type::array(Type::Integer)),
+            ("number_of_field_offsets", Type::Integer),
+        ]
+        .into()
+    }
+
+    fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
+        data_map.insert::<Self>(Data::default());
+    }
+
+    fn get_dynamic_values(&self, ctx: &mut ScanContext, out: &mut HashMap<&'static str, Value>) {
+        let Some(data) = ctx.module_data.get_mut::<Self>() else {
+            return;
+        };
+
+        if data.found_pe {
+            // We already found a PE in a region, so ignore the others
+            return;
+        }
+
+        let res = match FileKind::parse(ctx.region.mem) {
+            Ok(FileKind::Pe32) => parse_file::<ImageNtHeaders32>(ctx.region, ctx.process_memory),
+            Ok(FileKind::Pe64) => parse_file::<ImageNtHeaders64>(ctx.region, ctx.process_memory),
+            _ => None,
+        };
+
+        if let Some(values) = res {
+            *out = values;
+            data.found_pe = true;
+        }
+    }
+}
+
+impl ModuleData for Dotnet {
+    type Data = Data;
+}
+
+#[derive(Default)]
+pub struct Data {
+    found_pe: bool,
+}
+
+fn parse_file<HEADERS: ImageNtHeaders>(
+    region: &Region,
+    process_memory: bool,
+) -> Option<HashMap<&'static str, Value>> {
+    let dos_header = ImageDosHeader::parse(region.mem).ok()?;
+    let mut offset = dos_header.nt_headers_offset().into();
+    let (nt_headers, data_dirs) = HEADERS::parse(region.mem, &mut offset).ok()?;
+
+    // Ignore DLLs while scanning a process
+    let characteristics = nt_headers.file_header().characteristics.get(LE);
+    if process_memory && (characteristics & pe::IMAGE_FILE_DLL) != 0 {
+        return None;
+    }
+
+    let parser = Parser {
+        mem: region.mem,
+        sections: nt_headers.sections(region.mem, offset).ok(),
+    };
+    let mut out = HashMap::new();
+    parser.parse_com(&data_dirs, &mut out);
+    Some(out)
+}
+
+struct Parser<'data> {
+    mem: &'data [u8],
+    sections: Option<SectionTable<'data>>,
+}
+
+/// Location of a stream, relative to the metadata root.
+#[derive(Copy, Clone, Debug)]
+struct StreamHeader {
+    offset: u32,
+    size: u32,
+}
+
+#[derive(Default)]
+struct Streams {
+    tilde: Option<StreamHeader>,
+    strings: Option<StreamHeader>,
+    blob: Option<StreamHeader>,
+    guid: Option<StreamHeader>,
+    us: Option<StreamHeader>,
+}
+
+/// Location of a heap in the file.
+#[derive(Copy, Clone, Debug)]
+struct Heap {
+    offset: usize,
+    size: u32,
+}
+
+/// Size of indexes into the heaps and the tables.
+#[derive(Debug)]
+struct IndexSizes {
+    string: u8,
+    guid: u8,
+    blob: u8,
+    tables: [u8; 64],
+}
+
+/// Location of a table in the file.
+#[derive(Copy, Clone, Debug, Default)]
+struct TableInfo {
+    offset: usize,
+    row_count: u32,
+    row_size: usize,
+}
+
+/// Tables needed to parse the types defined by the assembly.
+#[derive(Debug, Default)]
+struct Tables {
+    typeref: TableInfo,
+    typedef: TableInfo,
+    methoddef: TableInfo,
+    param: TableInfo,
+    interfaceimpl: TableInfo,
+    typespec: TableInfo,
+    nestedclass: TableInfo,
+    genericparam: TableInfo,
+}
+
+impl Parser<'_> {
+    fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        let sections = self.sections.as_ref()?;
+        va_to_file_offset(self.mem, sections, rva).map(|v| v as usize)
+    }
+
+    fn parse_com(&self, data_dirs: &DataDirectories, out: &mut HashMap<&'static str, Value>) {
+        let Some((cli_header, metadata_root, md_len)) = self.get_metadata(data_dirs) else {
+            let _r = out.insert("is_dotnet", 0.into());
+            return;
+        };
+        let _r = out.insert("is_dotnet", 1.into());
+
+        let version = &self.mem[(metadata_root + NET_METADATA_SIZE)..][..md_len];
+        // The length includes the NUL terminator and is rounded up to a multiple of 4, so
+        // only keep what is before the first NUL byte.
+        if let Some(end) = memchr::memchr(b'\0', version) {
+            let _r = out.insert("version", version[..end].to_vec().into());
+        }
+
+        // Flags are 2 bytes, followed by the number of streams on 2 bytes.
+        let offset = metadata_root + NET_METADATA_SIZE + md_len + 2;
+        let Some(nb_streams) = self.mem.get(offset..offset + 2) else {
+            return;
+        };
+        // libyara only reads the first byte of the number of streams.
+        let nb_streams = nb_streams[0];
+
+        let streams = self.parse_stream_headers(offset + 2, metadata_root, nb_streams, out);
+
+        if let Some(guid) = streams.guid {
+            self.parse_guids(metadata_root, guid, out);
+        }
+
+        if let (Some(tilde), Some(strings), Some(blob)) =
+            (streams.tilde, streams.strings, streams.blob)
+        {
+            let resource_base = self
+                .rva_to_offset(read_u32(self.mem, cli_header + 24).unwrap_or(0))
+                .and_then(|v| i64::try_from(v).ok())
+                .unwrap_or(-1);
+            let strings = heap(metadata_root, strings);
+            let blob = heap(metadata_root, blob);
+            if let (Some(strings), Some(blob)) = (strings, blob) {
+                self.parse_tilde(metadata_root, tilde, strings, blob, resource_base, out);
+            }
+        }
+
+        if let Some(us) = streams.us {
+            self.parse_user_strings(metadata_root, us, out);
+        }
+    }
+
+    /// Find and validate the metadata root.
+    ///
+    /// Returns the offset of the CLI header, the offset of the metadata root and the length
+    /// of the version string.
+    fn get_metadata(&self, data_dirs: &DataDirectories) -> Option<(usize, usize, usize)> {
+        let dir = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR)?;
+        let cli_header = self.rva_to_offset(dir.virtual_address.get(LE))?;
+        let _ = self
+            .mem
+            .get(cli_header..cli_header.checked_add(CLI_HEADER_SIZE)?)?;
+
+        let metadata_root = self.rva_to_offset(read_u32(self.mem, cli_header + 8)?)?;
+        let _ = self
+            .mem
+            .get(metadata_root..metadata_root.checked_add(NET_METADATA_SIZE)?)?;
+        if read_u32(self.mem, metadata_root)? != NET_METADATA_MAGIC {
+            return None;
+        }
+
+        // Version length must be between 1 and 255, and be a multiple of 4.
+        let md_len = read_u32(self.mem, metadata_root + 12)? as usize;
+        if md_len == 0 || md_len > 255 || md_len % 4 != 0 {
+            return None;
+        }
+        let _ = self.mem.get(
+            (metadata_root + NET_METADATA_SIZE)..(metadata_root + NET_METADATA_SIZE + md_len),
+        )?;
+
+        Some((cli_header, metadata_root, md_len))
+    }
+
+    fn parse_stream_headers(
+        &self,
+        mut offset: usize,
+        metadata_root: usize,
+        nb_streams: u8,
+        out: &mut HashMap<&'static str, Value>,
+    ) -> Streams {
+        let mut streams = Streams::default();
+        let mut values = Vec::new();
+
+        for _ in 0..nb_streams {
+            let (Some(stream_offset), Some(size)) =
+                (read_u32(self.mem, offset), read_u32(self.mem, offset + 4))
+            else {
+                break;
+            };
+            let Some(name) = self.mem.get(
+                (offset + STREAM_HEADER_SIZE)..(offset + STREAM_HEADER_SIZE + STREAM_NAME_SIZE),
+            ) else {
+                break;
+            };
+            let Some(name_len) = memchr::memchr(b'\0', name) else {
+                break;
+            };
+            let name = &name[..name_len];
+
+            values.push(Value::object([
+                ("name", name.to_vec().into()),
+                (
+                    "offset",
+                    (metadata_root as u64 + u64::from(stream_offset)).into(),
+                ),
+                ("size", size.into()),
+            ]));
+
+            // Only the first occurrence of each stream is used, except for the #GUID one.
+            // The #- stream is an unoptimized version of the #~ stream, which can contain
+            // additional tables but is parsed the same way.
+            let header = Some(StreamHeader {
+                offset: stream_offset,
+                size,
+            });
+            if name.starts_with(b"#~") || name.starts_with(b"#-") {
+                if streams.tilde.is_none() {
+                    streams.tilde = header;
+                }
+            } else if name.starts_with(b"#GUID") {
+                streams.guid = header;
+            } else if name.starts_with(b"#Strings") {
+                if streams.strings.is_none() {
+                    streams.strings = header;
+                }
+            } else if name.starts_with(b"#Blob") {
+                if streams.blob.is_none() {
+                    streams.blob = header;
+                }
+            } else if name.starts_with(b"#US") && streams.us.is_none() {
+                streams.us = header;
+            }
+
+            // Stream names are padded to a multiple of 4.
+            offset += STREAM_HEADER_SIZE + name_len + 4 - (name_len % 4);
+        }
+
+        let _r = out.insert("number_of_streams", values.len().into());
+        let _r = out.insert("streams", Value::Array(values));
+
+        streams
+    }
+
+    fn parse_guids(
+        &self,
+        metadata_root: usize,
+        header: StreamHeader,
+        out: &mut HashMap<&'static str, Value>,
+    ) {
+        let mut guids = Vec::new();
+
+        if let Some(offset) = metadata_root.checked_add(header.offset as usize) {
+            // Limit the number of GUIDs to 16.
+            let size = std::cmp::min(header.size, MAX_GUIDS_SIZE) as usize;
+            let mut offset = offset;
+            let end = offset.saturating_add(size);
+
+            while offset + 16 <= end {
+                let Some(guid) = self.mem.get(offset..(offset + 16)) else {
+                    break;
+                };
+                guids.push(Value::Bytes(
+                    format!(
+                        "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+                        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+                        u16::from_le_bytes([guid[4], guid[5]]),
+                        u16::from_le_bytes([guid[6], guid[7]]),
+                        guid[8],
+                        guid[9],
+                        guid[10],
+                        guid[11],
+                        guid[12],
+                        guid[13],
+                        guid[14],
+                        guid[15],
+                    )
+                    .into_bytes(),
+                ));
+                offset += 16;
+            }
+        }
+
+        let _r = out.insert("number_of_guids", guids.len().into());
+        let _r = out.insert("guids", Value::Array(guids));
+    }
+
+    fn parse_user_strings(
+        &self,
+        metadata_root: usize,
+        header: StreamHeader,
+        out: &mut HashMap<&'static str, Value>,
+    ) {
+        let Some(mut offset) = metadata_root.checked_add(header.offset as usize) else {
+            return;
+        };
+        let end = offset.saturating_add(header.size as usize);
+
+        // The stream must not be empty, and its first entry must be a single NUL byte.
+        if header.size == 0 || end > self.mem.len() || self.mem[offset] != 0 {
+            return;
+        }
+        offset += 1;
+
+        let mut strings = Vec::new();
+        while offset < end {
+            let Some((mut length, size)) = parse_blob_entry(self.mem, offset) else {
+                break;
+            };
+            offset += size;
+
+            // The string is followed by an additional terminal byte, see ECMA-335 II.24.2.4.
+            length = length.saturating_sub(1);
+
+            // Avoid empty strings, which are usually the trailing padding.
+            if length > 0 {
+                if let Some(s) = self.mem.get(offset..offset.saturating_add(length)) {
+                    strings.push(Value::Bytes(s.to_vec()));
+                    offset += length;
+                }
+            }
+        }
+
+        let _r = out.insert("number_of_user_strings", strings.len().into());
+        let _r = out.insert("user_strings", Value::Array(strings));
+    }
+
+    fn parse_tilde(
+        &self,
+        metadata_root: usize,
+        tilde: StreamHeader,
+        strings: Heap,
+        blob: Heap,
+        resource_base: i64,
+        out: &mut HashMap<&'static str, Value>,
+    ) {
+        let Some(tilde_offset) = metadata_root.checked_add(tilde.offset as usize) else {
+            return;
+        };
+        let Some(header) = self
+            .mem
+            .get(tilde_offset..tilde_offset.saturating_add(TILDE_HEADER_SIZE))
+        else {
+            return;
+        };
+        let heap_sizes = header[6];
+        let valid = u64::from_le_bytes(header[8..16].try_into().unwrap());
+
+        // The header is followed by the number of rows of each present table.
+        let rows_offset = tilde_offset + TILDE_HEADER_SIZE;
+        let mut rows = [0_u32; 64];
+        for (i, table) in (0..64).filter(|i| (valid >> i) & 1 != 0).enumerate() {
+            match read_u32(self.mem, rows_offset + i * 4) {
+                Some(v) => rows[table] = v,
+                None => return,
+            }
+        }
+
+        let index_size = |flag: u8| if heap_sizes & flag != 0 { 4 } else { 2 };
+        let mut index_sizes = IndexSizes {
+            string: index_size(HEAP_STRING_4),
+            guid: index_size(HEAP_GUID_4),
+            blob: index_size(HEAP_BLOB_4),
+            tables: [2; 64],
+        };
+        for (size, nb_rows) in index_sizes.tables.iter_mut().zip(rows.iter()) {
+            if *nb_rows > 0xFFFF {
+                *size = 4;
+            }
+        }
+
+        let mut table_offset = rows_offset + 4 * valid.count_ones() as usize;
+        // Some files have 4 additional bytes after the number of rows, signaled by this
+        // undocumented flag.
+        if heap_sizes & HEAP_EXTRA_DATA != 0 {
+            table_offset += 4;
+        }
+
+        let mut tilde_parser = TildeParser {
+            parser: self,
+            rows,
+            index_sizes,
+            strings,
+            blob,
+            tables: Tables::default(),
+            typeref: None,
+            memberref: None,
+        };
+
+        for table in (0..64).filter(|i| (valid >> i) & 1 != 0) {
+            let nb_rows = tilde_parser.rows[table];
+            // Avoid spending too much time on corrupted files.
+            if nb_rows > MAX_TABLE_ROWS {
+                return;
+            }
+            let Some(row_size) = tilde_parser.row_size(table) else {
+                return;
+            };
+            let info = TableInfo {
+                offset: table_offset,
+                row_count: nb_rows,
+                row_size,
+            };
+
+            match table {
+                TABLE_MODULE => {
+                    // The table is ignored if its first row does not fit.
+                    if !tilde_parser.parse_module(info, out) {
+                        continue;
+                    }
+                }
+                TABLE_TYPEREF => {
+                    tilde_parser.tables.typeref = info;
+                    tilde_parser.typeref = Some(info);
+                }
+                TABLE_TYPEDEF => tilde_parser.tables.typedef = info,
+                TABLE_METHODDEF => tilde_parser.tables.methoddef = info,
+                TABLE_PARAM => tilde_parser.tables.param = info,
+                TABLE_INTERFACEIMPL => tilde_parser.tables.interfaceimpl = info,
+                TABLE_MEMBERREF => tilde_parser.memberref = Some(info),
+                TABLE_CONSTANT => tilde_parser.parse_constants(info, out),
+                TABLE_CUSTOMATTRIBUTE => tilde_parser.parse_custom_attributes(info, out),
+                TABLE_MODULEREF => tilde_parser.parse_modulerefs(info, out),
+                TABLE_TYPESPEC => tilde_parser.tables.typespec = info,
+                TABLE_FIELDRVA => tilde_parser.parse_field_rvas(info, out),
+                TABLE_ASSEMBLY => {
+                    // The table is ignored if its first row does not fit.
+                    if !tilde_parser.parse_assembly(info, out) {
+                        continue;
+                    }
+                }
+                TABLE_ASSEMBLYREF => tilde_parser.parse_assembly_refs(info, out),
+                TABLE_MANIFESTRESOURCE => {
+                    tilde_parser.parse_manifest_resources(info, resource_base, out);
+                }
+                TABLE_NESTEDCLASS => tilde_parser.tables.nestedclass = info,
+                TABLE_GENERICPARAM => tilde_parser.tables.genericparam = info,
+                _ => (),
+            }
+
+            table_offset += row_size * nb_rows as usize;
+        }
+
+        classes::parse_user_types(&tilde_parser, out);
+    }
+}
+
+struct TildeParser<'a, 'data> {
+    parser: &'a Parser<'data>,
+
+    /// Number of rows of each table.
+    rows: [u32; 64],
+
+    index_sizes: IndexSizes,
+
+    strings: Heap,
+    blob: Heap,
+
+    tables: Tables,
+
+    // Those two tables are used to resolve the type of custom attributes.
+    typeref: Option<TableInfo>,
+    memberref: Option<TableInfo>,
+}
+
+impl TildeParser<'_, '_> {
+    /// Size of a coded index into one of the given tables.
+    fn coded_index_size(&self, tables: &[usize]) -> usize {
+        // Number of bits needed to encode the table.
+        let tag_bits = match tables.len() {
+            0..=2 => 1,
+            3..=4 => 2,
+            5..=8 => 3,
+            9..=16 => 4,
+            _ => 5,
+        };
+        let max_rows = tables.iter().map(|t| self.rows[*t]).max().unwrap_or(0);
+
+        if max_rows > (0xFFFF >> tag_bits) {
+            4
+        } else {
+            2
+        }
+    }
+
+    fn table_index_size(&self, table: usize) -> usize {
+        usize::from(self.index_sizes.tables[table])
+    }
+
+    /// Size of a row of a table, None if the table is unknown.
+    fn row_size(&self, table: usize) -> Option<usize> {
+        let string = usize::from(self.index_sizes.string);
+        let guid = usize::from(self.index_sizes.guid);
+        let blob = usize::from(self.index_sizes.blob);
+        let index = |table| self.table_index_size(table);
+        let coded = |tables| self.coded_index_size(tables);
+
+        Some(match table {
+            TABLE_MODULE => 2 + string + guid * 3,
+            TABLE_TYPEREF => coded(RESOLUTION_SCOPE) + string * 2,
+            TABLE_TYPEDEF => {
+                4 + string * 2
+                    + coded(TYPE_DEF_OR_REF)
+                    + index(TABLE_FIELD)
+                    + index(TABLE_METHODDEF)
+            }
+            TABLE_FIELDPTR => index(TABLE_FIELD),
+            TABLE_FIELD => 2 + string + blob,
+            TABLE_METHODDEFPTR => index(TABLE_METHODDEF),
+            TABLE_METHODDEF => 4 + 2 + 2 + string + blob + index(TABLE_PARAM),
+            TABLE_PARAMPTR => index(TABLE_PARAM),
+            TABLE_PARAM => 2 + 2 + string,
+            TABLE_INTERFACEIMPL => index(TABLE_TYPEDEF) + coded(TYPE_DEF_OR_REF),
+            TABLE_MEMBERREF => coded(MEMBER_REF_PARENT) + string + blob,
+            TABLE_CONSTANT => 1 + 1 + coded(HAS_CONSTANT) + blob,
+            TABLE_CUSTOMATTRIBUTE => coded(HAS_CUSTOM_ATTRIBUTE) + coded(METHOD_DEF_OR_REF) + blob,
+            TABLE_FIELDMARSHAL => coded(HAS_FIELD_MARSHAL) + blob,
+            TABLE_DECLSECURITY => 2 + coded(HAS_DECL_SECURITY) + blob,
+            TABLE_CLASSLAYOUT => 2 + 4 + index(TABLE_TYPEDEF),
+            TABLE_FIELDLAYOUT => 4 + index(TABLE_FIELD),
+            TABLE_STANDALONESIG => blob,
+            TABLE_EVENTMAP => index(TABLE_TYPEDEF) + index(TABLE_EVENT),
+            TABLE_EVENTPTR => index(TABLE_EVENT),
+            TABLE_EVENT => 2 + string + coded(TYPE_DEF_OR_REF),
+            TABLE_PROPERTYMAP => index(TABLE_TYPEDEF) + index(TABLE_PROPERTY),
+            TABLE_PROPERTYPTR => index(TABLE_PROPERTY),
+            TABLE_PROPERTY => 2 + string + blob,
+            TABLE_METHODSEMANTICS => 2 + index(TABLE_METHODDEF) + coded(HAS_SEMANTICS),
+            TABLE_METHODIMPL => index(TABLE_TYPEDEF) + coded(METHOD_DEF_OR_REF) * 2,
+            TABLE_MODULEREF => string,
+            TABLE_TYPESPEC => blob,
+            TABLE_IMPLMAP => 2 + coded(MEMBER_FORWARDED) + string + index(TABLE_MODULEREF),
+            TABLE_FIELDRVA => 4 + index(TABLE_FIELD),
+            TABLE_ENCLOG => 4 + 4,
+            TABLE_ENCMAP => 4,
+            TABLE_ASSEMBLY => 4 + 2 + 2 + 2 + 2 + 4 + blob + string * 2,
+            TABLE_ASSEMBLYPROCESSOR => 4,
+            TABLE_ASSEMBLYOS => 4 + 4 + 4,
+            TABLE_ASSEMBLYREF => 2 + 2 + 2 + 2 + 4 + blob * 2 + string * 2,
+            // libyara uses the size of an index into the AssemblyRefProcessor table instead
+            // of the AssemblyRef table.
+            TABLE_ASSEMBLYREFPROCESSOR => 4 + index(TABLE_ASSEMBLYREFPROCESSOR),
+            TABLE_ASSEMBLYREFOS => 4 + 4 + 4 + index(TABLE_ASSEMBLYREF),
+            TABLE_FILE => 4 + string + blob,
+            TABLE_EXPORTEDTYPE => 4 + 4 + string * 2 + coded(IMPLEMENTATION),
+            // The Implementation coded index can only point to the File and AssemblyRef
+            // tables here.
+            TABLE_MANIFESTRESOURCE => {
+                4 + 4 + string + coded(&[TABLE_FILE, TABLE_ASSEMBLYREF, TABLE_ASSEMBLYREF])
+            }
+            TABLE_NESTEDCLASS => index(TABLE_TYPEDEF) * 2,
+            TABLE_GENERICPARAM => 2 + 2 + coded(TYPE_OR_METHOD_DEF) + string,
+            TABLE_METHODSPEC => coded(METHOD_DEF_OR_REF) + blob,
+            TABLE_GENERICPARAMCONSTRAINT => index(TABLE_GENERICPARAM) + coded(TYPE_DEF_OR_REF),
+            _ => return None,
+        })
+    }
+
+    fn mem(&self) -> &[u8] {
+        self.parser.mem
+    }
+
+    fn read_index(&self, offset: usize, size: usize) -> Option<u32> {
+        if size == 4 {
+            read_u32(self.mem(), offset)
+        } else {
+            read_u16(self.mem(), offset).map(u32::from)
+        }
+    }
+
+    fn read_string_index(&self, offset: usize) -> Option<u32> {
+        self.read_index(offset, usize::from(self.index_sizes.string))
+    }
+
+    fn read_blob_index(&self, offset: usize) -> Option<u32> {
+        self.read_index(offset, usize::from(self.index_sizes.blob))
+    }
+
+    fn get_string(&self, index: u32) -> Option<&[u8]> {
+        get_string(self.mem(), self.strings, index)
+    }
+
+    /// Get the contents of an entry in the blob heap.
+    fn get_blob(&self, index: u32) -> Option<&[u8]> {
+        let offset = self.blob.offset.checked_add(index as usize)?;
+        let (length, size) = parse_blob_entry(self.mem(), offset)?;
+        let offset = offset + size;
+        self.mem().get(offset..offset.checked_add(length)?)
+    }
+
+    fn row_fits(&self, offset: usize, row_size: usize) -> bool {
+        offset
+            .checked_add(row_size)
+            .map_or(false, |end| end <= self.mem().len())
+    }
+
+    fn parse_module(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) -> bool {
+        // libyara checks for the size of its (packed) representation of a row.
+        if !self.row_fits(info.offset, 18) {
+            return false;
+        }
+        if let Some(name) = self
+            .read_string_index(info.offset + 2)
+            .and_then(|v| self.get_string(v))
+        {
+            let _r = out.insert("module_name", name.to_vec().into());
+        }
+        true
+    }
+
+    fn parse_constants(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) {
+        let value_offset = 2 + self.coded_index_size(HAS_CONSTANT);
+        let mut constants = Vec::new();
+
+        for i in 0..(info.row_count as usize) {
+            let row = info.offset + i * info.row_size;
+            if !self.row_fits(row, info.row_size) {
+                break;
+            }
+
+            // Only look for constants of type string.
+            if self.mem()[row] != ELEMENT_TYPE_STRING {
+                continue;
+            }
+
+            let Some(blob_index) = self.read_blob_index(row + value_offset) else {
+                continue;
+            };
+            if blob_index == 0 {
+                continue;
+            }
+            let Some(blob_offset) = self.blob.offset.checked_add(blob_index as usize) else {
+                continue;
+            };
+            let Some((length, size)) = parse_blob_entry(self.mem(), blob_offset) else {
+                continue;
+            };
+            let start = blob_offset + size;
+            // libyara requires the blob to end strictly before the end of the file.
+            if let Some(end) = start.checked_add(length) {
+                if end < self.mem().len() {
+                    constants.push(Value::Bytes(self.mem()[start..end].to_vec()));
+                }
+            }
+        }
+
+        let _r = out.insert("number_of_constants", constants.len().into());
+        let _r = out.insert("constants", Value::Array(constants));
+    }
+
+    fn parse_custom_attributes(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) {
+        let (Some(typeref), Some(memberref)) = (self.typeref, self.memberref) else {
+            return;
+        };
+        if !self.row_fits(info.offset, info.row_size) {
+            return;
+        }
+
+        let parent_size = self.coded_index_size(HAS_CUSTOM_ATTRIBUTE);
+        let type_size = self.coded_index_size(METHOD_DEF_OR_REF);
+        let memberref_class_size = self.table_index_size(TABLE_MEMBERREF);
+        let resolution_scope_size = self.coded_index_size(RESOLUTION_SCOPE);
+
+        for i in 0..(info.row_count as usize) {
+            let row = info.offset + i * info.row_size;
+            if !self.row_fits(row, info.row_size) {
+                break;
+            }
+
+            // The parent must be an index into the Assembly table.
+            let Some(parent) = self.read_index(row, parent_size) else {
+                break;
+            };
+            if parent & 0x1F != 0x0E {
+                continue;
+            }
+
+            // The type must be an index into the MemberRef table.
+            let Some(type_) = self.read_index(row + parent_size, type_size) else {
+                break;
+            };
+            if type_ & 0x07 != 0x03 {
+                continue;
+            }
+            let type_index = (type_ >> 3).saturating_sub(1) as usize;
+
+            // Follow the index into the MemberRef table, whose class must be an index into the
+            // TypeRef table.
+            let memberref_row = memberref.offset + memberref.row_size * type_index;
+            if !self.row_fits(memberref_row, memberref.row_size) {
+                break;
+            }
+            let Some(class) = self.read_index(memberref_row, memberref_class_size) else {
+                break;
+            };
+            if class & 0x07 != 0x01 {
+                continue;
+            }
+            let class_index = (class >> 3).saturating_sub(1) as usize;
+
+            // Then follow the index into the TypeRef table, and check its name.
+            let typeref_row = typeref.offset + typeref.row_size * class_index;
+            if !self.row_fits(typeref_row, typeref.row_size) {
+                break;
+            }
+            let name = self
+                .read_string_index(typeref_row + resolution_scope_size)
+                .and_then(|v| self.get_string(v));
+            if let Some(name) = name {
+                if !name.starts_with(b"GuidAttribute") {
+                    continue;
+                }
+            }
+
+            let Some(blob_index) = self.read_blob_index(row + parent_size + type_size) else {
+                continue;
+            };
+            if blob_index == 0 {
+                continue;
+            }
+            let Some(blob_offset) = self.blob.offset.checked_add(blob_index as usize) else {
+                continue;
+            };
+            let Some((length, size)) = parse_blob_entry(self.mem(), blob_offset) else {
+                continue;
+            };
+            let start = blob_offset + size;
+
+            // The value must contain at least the 16 bits prolog and the length of the
+            // string.
+            if length < 3 || start.saturating_add(length) >= self.mem().len() {
+                continue;
+            }
+            // Custom attributes must start with a 16 bits prolog of 0x0001.
+            if read_u16(self.mem(), start) != Some(0x0001) {
+                continue;
+            }
+            let str_len = usize::from(self.mem()[start + 2]);
+            let Some(typelib) = self.mem().get((start + 3)..(start + 3 + str_len)) else {
+                continue;
+            };
+
+            let typelib = match typelib.first() {
+                None | Some(0xFF | 0x00) => Vec::new(),
+                Some(_) => {
+                    let end = memchr::memchr(b'\0', typelib).unwrap_or(typelib.len());
+                    typelib[..end].to_vec()
+                }
+            };
+            let _r = out.insert("typelib", typelib.into());
+        }
+    }
+
+    fn parse_modulerefs(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) {
+        let mut modulerefs = Vec::new();
+
+        for i in 0..(info.row_count as usize) {
+            let row = info.offset + i * info.row_size;
+            // libyara checks for the size of its representation of a row.
+            if !self.row_fits(row, 4) {
+                break;
+            }
+            if let Some(name) = self.read_string_index(row).and_then(|v| self.get_string(v)) {
+                modulerefs.push(Value::Bytes(name.to_vec()));
+            }
+        }
+
+        let _r = out.insert("number_of_modulerefs", modulerefs.len().into());
+        let _r = out.insert("modulerefs", Value::Array(modulerefs));
+    }
+
+    fn parse_field_rvas(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) {
+        let mut offsets = Vec::new();
+
+        for i in 0..(info.row_count as usize) {
+            let row = info.offset + i * info.row_size;
+            if !self.row_fits(row, info.row_size) {
+                break;
+            }
+            if let Some(offset) =
+                read_u32(self.mem(), row).and_then(|v| self.parser.rva_to_offset(v))
+            {
+                offsets.push(offset.into());
+            }
+        }
+
+        let _r = out.insert("number_of_field_offsets", offsets.len().into());
+        let _r = out.insert("field_offsets", Value::Array(offsets));
+    }
+
+    fn parse_assembly(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) -> bool {
+        let row = info.offset;
+        if !self.row_fits(row, info.row_size) {
+            return false;
+        }
+
+        let mut assembly = HashMap::new();
+        let _r = assembly.insert("version", self.read_version(row + 4));
+
+        let name_offset = row + 16 + usize::from(self.index_sizes.blob);
+        if let Some(name) = self
+            .read_string_index(name_offset)
+            .and_then(|v| self.get_string(v))
+        {
+            let _r = assembly.insert("name", name.to_vec().into());
+        }
+
+        // The culture is sometimes an empty string, which is against the specification.
+        let culture_offset = name_offset + usize::from(self.index_sizes.string);
+        if let Some(culture) = self
+            .read_string_index(culture_offset)
+            .and_then(|v| self.get_string(v))
+        {
+            if !culture.is_empty() {
+                let _r = assembly.insert("culture", culture.to_vec().into());
+            }
+        }
+
+        let _r = out.insert("assembly", Value::Object(assembly));
+        true
+    }
+
+    fn read_version(&self, offset: usize) -> Value {
+        let mem = self.mem();
+        Value::object([
+            ("major", read_u16(mem, offset).into()),
+            ("minor", read_u16(mem, offset + 2).into()),
+            ("build_number", read_u16(mem, offset + 4).into()),
+            ("revision_number", read_u16(mem, offset + 6).into()),
+        ])
+    }
+
+    fn parse_assembly_refs(&self, info: TableInfo, out: &mut HashMap<&'static str, Value>) {
+        let mut refs = Vec::new();
+
+        // libyara only checks that the first row fits.
+        if self.row_fits(info.offset, info.row_size) {
+            for i in 0..(info.row_count as usize) {
+                let row = info.offset + i * info.row_size;
+                if !self.row_fits(row, info.row_size) {
+                    break;
+                }
+
+                let mut assembly_ref = HashMap::new();
+                let _r = assembly_ref.insert("version", self.read_version(row));
+
+                let Some(public_key) = self
+                    .read_blob_index(row + 8 + 4)
+                    .and_then(|v| self.get_blob(v))
+                else {
+                    refs.push(Value::Object(assembly_ref));
+                    continue;
+                };
+                if !public_key.is_empty() {
+                    let _r = assembly_ref.insert("public_key_or_token", public_key.to_vec().into());
+                }
+
+                let name_offset = row + 12 + usize::from(self.index_sizes.blob);
+                if let Some(name) = self
+                    .read_string_index(name_offset)
+                    .and_then(|v| self.get_string(v))
+                {
+                    let _r = assembly_ref.insert("name", name.to_vec().into());
+                }
+
+                refs.push(Value::Object(assembly_ref));
+            }
+        }
+
+        let _r = out.insert("number_of_assembly_refs", refs.len().into());
+        let _r = out.insert("assembly_refs", Value::Array(refs));
+    }
+
+    fn parse_manifest_resources(
+        &self,
+        info: TableInfo,
+        resource_base: i64,
+        out: &mut HashMap<&'static str, Value>,
+    ) {
+        let implementation_size =
+            self.coded_index_size(&[TABLE_FILE, TABLE_ASSEMBLYREF, TABLE_ASSEMBLYREF]);
+        let mut resources = Vec::new();
+
+        for i in 0..(info.row_count as usize) {
+            let row = info.offset + i * info.row_size;
+            if !self.row_fits(row, info.row_size) {
+                break;
+            }
+
+            let mut resource = HashMap::new();
+
+            let name_offset = row + 8;
+            if let Some(name) = self
+                .read_string_index(name_offset)
+                .and_then(|v| self.get_string(v))
+            {
+                let _r = resource.insert("name", name.to_vec().into());
+            }
+
+            // Only set the offset and length if the resource is in this file.
+            let implementation = self.read_index(
+                name_offset + usize::from(self.index_sizes.string),
+                implementation_size,
+            );
+            if implementation == Some(0) {
+                let offset = read_u32(self.mem(), row)
+                    .and_then(|v| resource_base.checked_add(i64::from(v)))
+                    .and_then(|v| usize::try_from(v).ok());
+                if let Some(offset) = offset {
+                    if let Some(length) = read_u32(self.mem(), offset) {
+                        // Skip the size of the resource.
+                        let _r = resource.insert("offset", (offset + 4).into());
+                        let _r = resource.insert("length", length.into());
+                    }
+                }
+            }
+
+            resources.push(Value::Object(resource));
+        }
+
+        let _r = out.insert("number_of_resources", resources.len().into());
+        let _r = out.insert("resources", Value::Array(resources));
+    }
+}
+
+/// Compute the location of a heap from its stream header.
+fn heap(metadata_root: usize, header: StreamHeader) -> Option<Heap> {
+    Some(Heap {
+        offset: metadata_root.checked_add(header.offset as usize)?,
+        size: header.size,
+    })
+}
+
+/// Get a NUL-terminated string from the strings heap.
+fn get_string(mem: &[u8], heap: Heap, index: u32) -> Option<&[u8]> {
+    if index >= heap.size {
+        return None;
+    }
+    let start = heap.offset.checked_add(index as usize)?;
+    let s = mem.get(start..)?;
+    let end = memchr::memchr(b'\0', s)?;
+    Some(&s[..end])
+}
+
+/// Parse the header of a blob entry, returning the length of the blob and the size of the
+/// header.
+///
+/// See ECMA-335 II.24.2.4.
+fn parse_blob_entry(mem: &[u8], offset: usize) -> Option<(usize, usize)> {
+    let first = *mem.get(offset)?;
+
+    if first & 0x80 == 0x00 {
+        Some((usize::from(first), 1))
+    } else if first & 0xC0 == 0x80 {
+        let second = *mem.get(offset + 1)?;
+        Some(((usize::from(first & 0x3F) << 8) | usize::from(second), 2))
+    } else if first & 0xE0 == 0xC0 && offset + 4 < mem.len() {
+        let length = (usize::from(first & 0x1F) << 24)
+            | (usize::from(mem[offset + 1]) << 16)
+            | (usize::from(mem[offset + 2]) << 8)
+            | usize::from(mem[offset + 3]);
+        Some((length, 4))
+    } else {
+        None
+    }
+}
+
+fn read_u16(mem: &[u8], offset: usize) -> Option<u16> {
+    let v = mem.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([v[0], v[1]]))
+}
+
+fn read_u32(mem: &[u8], offset: usize) -> Option<u32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+}
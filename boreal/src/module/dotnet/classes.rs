@@ -0,0 +1,907 @@
+//! Parsing of the types defined in the assembly, with their methods.
+use std::collections::HashMap;
+
+use super::{
+    read_u16, read_u32, TableInfo, TildeParser, RESOLUTION_SCOPE, TABLE_TYPEDEF, TYPE_DEF_OR_REF,
+    TYPE_OR_METHOD_DEF,
+};
+use crate::module::Value;
+
+const MAX_ARRAY_RANK: u32 = 50;
+const MAX_PARAM_COUNT: u32 = 2000;
+const MAX_GEN_PARAM_COUNT: u32 = 1000;
+const MAX_METHOD_COUNT: u32 = 20_000;
+const MAX_NAMESPACE_DEPTH: u32 = 10;
+const MAX_TYPE_DEPTH: u32 = 16;
+
+// TypeAttributes, see ECMA-335 II.23.1.15
+const TYPE_ATTR_VISIBILITY_MASK: u32 = 0x07;
+const TYPE_ATTR_NOT_PUBLIC: u32 = 0x00;
+const TYPE_ATTR_PUBLIC: u32 = 0x01;
+const TYPE_ATTR_NESTED_PUBLIC: u32 = 0x02;
+const TYPE_ATTR_NESTED_FAMILY: u32 = 0x04;
+const TYPE_ATTR_NESTED_ASSEMBLY: u32 = 0x05;
+const TYPE_ATTR_NESTED_FAM_AND_ASSEM: u32 = 0x06;
+const TYPE_ATTR_NESTED_FAM_OR_ASSEM: u32 = 0x07;
+const TYPE_ATTR_INTERFACE: u32 = 0x20;
+const TYPE_ATTR_ABSTRACT: u32 = 0x80;
+const TYPE_ATTR_SEALED: u32 = 0x100;
+
+// MethodAttributes, see ECMA-335 II.23.1.10
+const METHOD_ATTR_ACCESS_MASK: u16 = 0x07;
+const METHOD_ATTR_FAM_AND_ASSEM: u16 = 0x02;
+const METHOD_ATTR_ASSEM: u16 = 0x03;
+const METHOD_ATTR_FAMILY: u16 = 0x04;
+const METHOD_ATTR_FAM_OR_ASSEM: u16 = 0x05;
+const METHOD_ATTR_PUBLIC: u16 = 0x06;
+const METHOD_ATTR_STATIC: u16 = 0x10;
+const METHOD_ATTR_FINAL: u16 = 0x20;
+const METHOD_ATTR_VIRTUAL: u16 = 0x40;
+const METHOD_ATTR_ABSTRACT: u16 = 0x400;
+
+// Flag of a method signature, see ECMA-335 II.23.2.1
+const SIG_FLAG_GENERIC: u8 = 0x10;
+
+// Element types used in signatures, see ECMA-335 II.23.1.16
+const TYPE_VOID: u8 = 0x01;
+const TYPE_BOOL: u8 = 0x02;
+const TYPE_CHAR: u8 = 0x03;
+const TYPE_I1: u8 = 0x04;
+const TYPE_U1: u8 = 0x05;
+const TYPE_I2: u8 = 0x06;
+const TYPE_U2: u8 = 0x07;
+const TYPE_I4: u8 = 0x08;
+const TYPE_U4: u8 = 0x09;
+const TYPE_I8: u8 = 0x0A;
+const TYPE_U8: u8 = 0x0B;
+const TYPE_R4: u8 = 0x0C;
+const TYPE_R8: u8 = 0x0D;
+const TYPE_STRING: u8 = 0x0E;
+const TYPE_PTR: u8 = 0x0F;
+const TYPE_BYREF: u8 = 0x10;
+const TYPE_VALUETYPE: u8 = 0x11;
+const TYPE_CLASS: u8 = 0x12;
+const TYPE_VAR: u8 = 0x13;
+const TYPE_ARRAY: u8 = 0x14;
+const TYPE_GENERICINST: u8 = 0x15;
+const TYPE_TYPEDREF: u8 = 0x16;
+const TYPE_I: u8 = 0x18;
+const TYPE_U: u8 = 0x19;
+const TYPE_FNPTR: u8 = 0x1B;
+const TYPE_OBJECT: u8 = 0x1C;
+const TYPE_SZARRAY: u8 = 0x1D;
+const TYPE_MVAR: u8 = 0x1E;
+const TYPE_CMOD_REQD: u8 = 0x1F;
+const TYPE_CMOD_OPT: u8 = 0x20;
+
+struct TypeDefRow {
+    flags: u32,
+    name: u32,
+    namespace: u32,
+    extends: u32,
+    method_list: u32,
+}
+
+struct MethodDefRow {
+    flags: u16,
+    name: u32,
+    signature: u32,
+    param_list: u32,
+}
+
+/// Walk the `TypeDef` table and add the `classes` values.
+pub(super) fn parse_user_types(parser: &TildeParser, out: &mut HashMap<&'static str, Value>) {
+    let nb_types = parser.tables.typedef.row_count;
+    let mut classes = Vec::new();
+
+    for idx in 1..=nb_types {
+        let Some(row) = parser.read_typedef(idx) else {
+            continue;
+        };
+
+        // Ignore the module pseudo class
+        let name = match parser.get_string(row.name) {
+            Some(name) if name != b"<Module>" => name,
+            _ => continue,
+        };
+
+        let namespace = parser.get_string(row.namespace);
+        let namespace = if is_nested(row.flags) {
+            let enclosing = parser.parse_enclosing_types(idx, 1);
+            create_full_name(namespace, enclosing.as_deref())
+        } else {
+            namespace.map(<[u8]>::to_vec)
+        };
+        let fullname = create_full_name(Some(name), namespace.as_deref());
+
+        let gen_params = parser.parse_generic_params(false, idx);
+        let base_types = parser.parse_type_parents(row.extends, idx, &gen_params);
+
+        // The methods of a type go from its MethodList to the MethodList of the next type,
+        // or to the end of the MethodDef table for the last type.
+        let mut method_count = 0;
+        if idx < nb_types {
+            if let Some(next_row) = parser.read_typedef(idx + 1) {
+                if next_row.method_list >= row.method_list {
+                    method_count = next_row.method_list - row.method_list;
+                }
+            }
+        } else if parser.tables.methoddef.row_count >= row.method_list {
+            method_count = parser.tables.methoddef.row_count + 1 - row.method_list;
+        }
+
+        let mut class: HashMap<_, _> = [
+            ("fullname", fullname.into()),
+            ("name", name.to_vec().into()),
+            ("namespace", namespace.into()),
+            ("visibility", type_visibility(row.flags).to_vec().into()),
+            ("type", typedef_type(row.flags).to_vec().into()),
+            ("abstract", flag_value(row.flags & TYPE_ATTR_ABSTRACT != 0)),
+            ("sealed", flag_value(row.flags & TYPE_ATTR_SEALED != 0)),
+            ("number_of_generic_parameters", gen_params.len().into()),
+            (
+                "generic_parameters",
+                Value::Array(
+                    gen_params
+                        .iter()
+                        .map(|v| Value::Bytes(v.to_vec()))
+                        .collect(),
+                ),
+            ),
+            ("number_of_base_types", base_types.len().into()),
+            ("base_types", Value::Array(base_types)),
+        ]
+        .into();
+
+        // Sanity check for corrupted files
+        if method_count <= MAX_METHOD_COUNT {
+            if let Some(methods) = parser.parse_methods(row.method_list, method_count, &gen_params)
+            {
+                let _r = class.insert("number_of_methods", methods.len().into());
+                let _r = class.insert("methods", Value::Array(methods));
+            }
+        }
+
+        classes.push(Value::Object(class));
+    }
+
+    let _r = out.insert("number_of_classes", classes.len().into());
+    let _r = out.insert("classes", Value::Array(classes));
+}
+
+impl TildeParser<'_, '_> {
+    /// Get the offset of a row from its index, which starts at 1.
+    fn get_row(&self, table: TableInfo, index: u32) -> Option<usize> {
+        if !(1..=table.row_count).contains(&index) {
+            return None;
+        }
+        let offset = table.offset + table.row_size * (index - 1) as usize;
+        if self.row_fits(offset, table.row_size) {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    fn read_typedef(&self, index: u32) -> Option<TypeDefRow> {
+        let mut offset = self.get_row(self.tables.typedef, index)?;
+
+        let flags = read_u32(self.mem(), offset)?;
+        offset += 4;
+        let name = self.read_string_index(offset)?;
+        offset += usize::from(self.index_sizes.string);
+        let namespace = self.read_string_index(offset)?;
+        offset += usize::from(self.index_sizes.string);
+        let extends_size = self.coded_index_size(TYPE_DEF_OR_REF);
+        let extends = self.read_index(offset, extends_size)?;
+        offset += extends_size + self.table_index_size(super::TABLE_FIELD);
+        let method_list = self.read_index(offset, self.table_index_size(super::TABLE_METHODDEF))?;
+
+        Some(TypeDefRow {
+            flags,
+            name,
+            namespace,
+            extends,
+            method_list,
+        })
+    }
+
+    /// Read a row of the `TypeRef` table, returning its name and namespace.
+    fn read_typeref(&self, index: u32) -> Option<(u32, u32)> {
+        let offset = self.get_row(self.tables.typeref, index)?;
+        let offset = offset + self.coded_index_size(RESOLUTION_SCOPE);
+
+        let name = self.read_string_index(offset)?;
+        let namespace = self.read_string_index(offset + usize::from(self.index_sizes.string))?;
+        Some((name, namespace))
+    }
+
+    /// Read a row of the `TypeSpec` table, returning its signature.
+    fn read_typespec(&self, index: u32) -> Option<u32> {
+        let offset = self.get_row(self.tables.typespec, index)?;
+        self.read_blob_index(offset)
+    }
+
+    /// Read a row of the `InterfaceImpl` table, returning its class and interface.
+    fn read_interfaceimpl(&self, index: u32) -> Option<(u32, u32)> {
+        let offset = self.get_row(self.tables.interfaceimpl, index)?;
+        let class_size = self.table_index_size(TABLE_TYPEDEF);
+
+        let class = self.read_index(offset, class_size)?;
+        let interface =
+            self.read_index(offset + class_size, self.coded_index_size(TYPE_DEF_OR_REF))?;
+        Some((class, interface))
+    }
+
+    /// Read a row of the `NestedClass` table, returning the nested and enclosing classes.
+    fn read_nestedclass(&self, index: u32) -> Option<(u32, u32)> {
+        let offset = self.get_row(self.tables.nestedclass, index)?;
+        let size = self.table_index_size(TABLE_TYPEDEF);
+
+        let nested = self.read_index(offset, size)?;
+        let enclosing = self.read_index(offset + size, size)?;
+        Some((nested, enclosing))
+    }
+
+    /// Read a row of the `GenericParam` table, returning its owner and name.
+    fn read_genericparam(&self, index: u32) -> Option<(u32, u32)> {
+        let offset = self.get_row(self.tables.genericparam, index)?;
+        let owner_size = self.coded_index_size(TYPE_OR_METHOD_DEF);
+
+        let owner = self.read_index(offset + 4, owner_size)?;
+        let name = self.read_string_index(offset + 4 + owner_size)?;
+        Some((owner, name))
+    }
+
+    fn read_methoddef(&self, index: u32) -> Option<MethodDefRow> {
+        let offset = self.get_row(self.tables.methoddef, index)?;
+
+        let flags = read_u16(self.mem(), offset + 6)?;
+        let mut offset = offset + 8;
+        let name = self.read_string_index(offset)?;
+        offset += usize::from(self.index_sizes.string);
+        let signature = self.read_blob_index(offset)?;
+        offset += usize::from(self.index_sizes.blob);
+        let param_list = self.read_index(offset, self.table_index_size(super::TABLE_PARAM))?;
+
+        Some(MethodDefRow {
+            flags,
+            name,
+            signature,
+            param_list,
+        })
+    }
+
+    /// Read a row of the Param table, returning its name.
+    fn read_param(&self, index: u32) -> Option<u32> {
+        let offset = self.get_row(self.tables.param, index)?;
+        self.read_string_index(offset + 4)
+    }
+
+    /// Returns the name of the enclosing type of a nested type.
+    fn parse_enclosing_types(&self, nested_idx: u32, depth: u32) -> Option<Vec<u8>> {
+        if depth > MAX_NAMESPACE_DEPTH {
+            return None;
+        }
+
+        for idx in 1..=self.tables.nestedclass.row_count {
+            let Some((nested, enclosing)) = self.read_nestedclass(idx) else {
+                continue;
+            };
+            if nested != nested_idx {
+                continue;
+            }
+
+            let row = self.read_typedef(enclosing)?;
+            let name = self.get_string(row.name);
+            // Skip the module pseudo class
+            if name == Some(b"<Module>") {
+                continue;
+            }
+            let namespace = self.get_string(row.namespace);
+
+            // The type might be further nested, but watch out for self references.
+            if is_nested(row.flags) && enclosing != nested {
+                let enclosing_namespace = self.parse_enclosing_types(enclosing, depth + 1);
+                let namespace = create_full_name(namespace, enclosing_namespace.as_deref());
+                return create_full_name(name, namespace.as_deref());
+            }
+            return create_full_name(name, namespace);
+        }
+
+        None
+    }
+
+    /// Returns the names of the generic parameters of a type or of a method.
+    fn parse_generic_params(&self, method: bool, owner_idx: u32) -> Vec<&[u8]> {
+        let mut names = Vec::new();
+
+        for idx in 1..=self.tables.genericparam.row_count {
+            let Some((owner, name)) = self.read_genericparam(idx) else {
+                continue;
+            };
+
+            // The owner is a TypeOrMethodDef coded index.
+            if (owner & 0x1 != 0) == method && (owner >> 1) == owner_idx {
+                match self.get_string(name) {
+                    Some(name) if !name.is_empty() => names.push(name),
+                    _ => break,
+                }
+            }
+        }
+
+        names
+    }
+
+    /// Returns the base type and the interfaces implemented by a type.
+    fn parse_type_parents(
+        &self,
+        extends: u32,
+        type_idx: u32,
+        class_gen_params: &[&[u8]],
+    ) -> Vec<Value> {
+        let mut base_types = Vec::new();
+
+        if let Some(parent) = self.get_type_def_or_ref_fullname(extends, class_gen_params, &[], 0) {
+            base_types.push(Value::Bytes(parent));
+        }
+
+        for idx in 1..=self.tables.interfaceimpl.row_count {
+            let Some((class, interface)) = self.read_interfaceimpl(idx) else {
+                continue;
+            };
+            if class == type_idx {
+                if let Some(interface) =
+                    self.get_type_def_or_ref_fullname(interface, class_gen_params, &[], 0)
+                {
+                    base_types.push(Value::Bytes(interface));
+                }
+            }
+        }
+
+        base_types
+    }
+
+    /// Parse the methods of a type.
+    ///
+    /// Returns None if the type has no method list.
+    fn parse_methods(
+        &self,
+        method_list: u32,
+        method_count: u32,
+        class_gen_params: &[&[u8]],
+    ) -> Option<Vec<Value>> {
+        if method_list == 0 {
+            return None;
+        }
+
+        let mut methods = Vec::new();
+        for idx in method_list..method_list.saturating_add(method_count) {
+            if let Some(method) = self.parse_method(idx, class_gen_params) {
+                methods.push(method);
+            }
+        }
+        Some(methods)
+    }
+
+    fn parse_method(&self, idx: u32, class_gen_params: &[&[u8]]) -> Option<Value> {
+        let row = self.read_methoddef(idx)?;
+
+        // Ignore invalid or empty names
+        let name = self.get_string(row.name).filter(|v| !v.is_empty())?;
+
+        let method_gen_params = self.parse_generic_params(true, idx);
+
+        // The signature contains at least the flags, the number of parameters and the
+        // return type.
+        let mut sig = self.get_signature(row.signature)?;
+        if sig.len() < 3 {
+            return None;
+        }
+        let flags = sig[0];
+        sig = &sig[1..];
+        if flags & SIG_FLAG_GENERIC != 0 {
+            // Skip the number of generic parameters
+            let _ = read_blob_unsigned(&mut sig);
+        }
+
+        let param_count = read_blob_unsigned(&mut sig);
+        // Sanity check for corrupted files
+        if param_count > MAX_PARAM_COUNT {
+            return None;
+        }
+
+        let gen_params = GenericParams {
+            class: class_gen_params,
+            method: &method_gen_params,
+        };
+        let return_type = self.parse_signature_type(&mut sig, &gen_params, 0)?;
+
+        // If any parameter cannot be parsed, the whole method is ignored.
+        let parameters = if row.param_list == 0 {
+            None
+        } else {
+            let mut parameters = Vec::new();
+            for i in 0..param_count {
+                // The Param table is only used to retrieve the name of the parameter. An index
+                // out of bounds is not an error, a default name is then generated.
+                let index = row.param_list.saturating_add(i);
+                let name = if (1..=self.tables.param.row_count).contains(&index) {
+                    let name = self.read_param(index)?;
+                    self.get_string(name).map(<[u8]>::to_vec)
+                } else {
+                    Some(format!("P_{i}").into_bytes())
+                };
+                let type_ = self.parse_signature_type(&mut sig, &gen_params, 0)?;
+
+                parameters.push(Value::object([
+                    ("name", name.into()),
+                    ("type", type_.into()),
+                ]));
+            }
+            Some(parameters)
+        };
+
+        let mut method: HashMap<_, _> = [
+            ("name", name.to_vec().into()),
+            ("visibility", method_visibility(row.flags).to_vec().into()),
+            ("static", flag_value(row.flags & METHOD_ATTR_STATIC != 0)),
+            ("final", flag_value(row.flags & METHOD_ATTR_FINAL != 0)),
+            ("virtual", flag_value(row.flags & METHOD_ATTR_VIRTUAL != 0)),
+            (
+                "abstract",
+                flag_value(row.flags & METHOD_ATTR_ABSTRACT != 0),
+            ),
+            ("return_type", return_type.into()),
+            (
+                "number_of_generic_parameters",
+                method_gen_params.len().into(),
+            ),
+            (
+                "generic_parameters",
+                Value::Array(
+                    method_gen_params
+                        .iter()
+                        .map(|v| Value::Bytes(v.to_vec()))
+                        .collect(),
+                ),
+            ),
+        ]
+        .into();
+        if let Some(parameters) = parameters {
+            let _r = method.insert("number_of_parameters", parameters.len().into());
+            let _r = method.insert("parameters", Value::Array(parameters));
+        }
+
+        Some(Value::Object(method))
+    }
+
+    /// Get a signature from the blob heap.
+    fn get_signature(&self, index: u32) -> Option<&[u8]> {
+        let offset = self.blob.offset.checked_add(index as usize)?;
+        let (length, size) = super::parse_blob_entry(self.mem(), offset)?;
+        let offset = offset + size;
+        // Do not fail if the signature is truncated: the parsing will only fail if it needs
+        // the missing bytes.
+        let end = std::cmp::min(offset.saturating_add(length), self.mem().len());
+        self.mem().get(offset..end)
+    }
+
+    /// Returns the full name of a type from a `TypeDefOrRef` coded index.
+    fn get_type_def_or_ref_fullname(
+        &self,
+        coded_index: u32,
+        class_gen_params: &[&[u8]],
+        method_gen_params: &[&[u8]],
+        depth: u32,
+    ) -> Option<Vec<u8>> {
+        let index = coded_index >> 2;
+        if index == 0 {
+            return None;
+        }
+
+        match coded_index & 0x3 {
+            // TypeDef
+            0 => {
+                let row = self.read_typedef(index)?;
+                create_full_name(self.get_string(row.name), self.get_string(row.namespace))
+            }
+            // TypeRef
+            1 => {
+                let (name, namespace) = self.read_typeref(index)?;
+                create_full_name(self.get_string(name), self.get_string(namespace))
+            }
+            // TypeSpec
+            2 => {
+                let mut sig = self.get_signature(self.read_typespec(index)?)?;
+                let gen_params = GenericParams {
+                    class: class_gen_params,
+                    method: method_gen_params,
+                };
+                self.parse_signature_type(&mut sig, &gen_params, depth)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse a type from a signature, returning its name.
+    ///
+    /// See ECMA-335 II.23.2.12.
+    fn parse_signature_type(
+        &self,
+        sig: &mut &[u8],
+        gen_params: &GenericParams,
+        depth: u32,
+    ) -> Option<Vec<u8>> {
+        if depth > MAX_TYPE_DEPTH {
+            return None;
+        }
+        let (type_, rest) = sig.split_first()?;
+        *sig = rest;
+
+        let name: &[u8] = match *type_ {
+            TYPE_VOID => b"void",
+            TYPE_BOOL => b"bool",
+            TYPE_CHAR => b"char",
+            TYPE_I1 => b"sbyte",
+            TYPE_U1 => b"byte",
+            TYPE_I2 => b"short",
+            TYPE_U2 => b"ushort",
+            TYPE_I4 => b"int",
+            TYPE_U4 => b"uint",
+            TYPE_I8 => b"long",
+            TYPE_U8 => b"ulong",
+            TYPE_R4 => b"float",
+            TYPE_R8 => b"double",
+            TYPE_STRING => b"string",
+            TYPE_TYPEDREF => b"TypedReference",
+            TYPE_I => b"IntPtr",
+            TYPE_U => b"UIntPtr",
+            TYPE_OBJECT => b"object",
+            TYPE_PTR => {
+                let inner = self.parse_signature_type(sig, gen_params, depth + 1)?;
+                return Some([b"Ptr<", &*inner, b">"].concat());
+            }
+            TYPE_BYREF => {
+                let inner = self.parse_signature_type(sig, gen_params, depth + 1)?;
+                return Some([b"ref ", &*inner].concat());
+            }
+            TYPE_VALUETYPE | TYPE_CLASS => {
+                let coded_index = read_blob_unsigned(sig);
+                return self.get_type_def_or_ref_fullname(
+                    coded_index,
+                    gen_params.class,
+                    gen_params.method,
+                    depth + 1,
+                );
+            }
+            TYPE_VAR => {
+                let index = read_blob_unsigned(sig);
+                gen_params.class.get(index as usize)?
+            }
+            TYPE_MVAR => {
+                let index = read_blob_unsigned(sig);
+                gen_params.method.get(index as usize)?
+            }
+            TYPE_ARRAY => return self.parse_array_type(sig, gen_params, depth),
+            TYPE_GENERICINST => {
+                let mut res = self.parse_signature_type(sig, gen_params, depth + 1)?;
+
+                let gen_count = read_blob_unsigned(sig);
+                // Sanity check for corrupted files
+                if gen_count > MAX_GEN_PARAM_COUNT {
+                    return None;
+                }
+
+                res.push(b'<');
+                let mut first = true;
+                for _ in 0..gen_count {
+                    if let Some(param) = self.parse_signature_type(sig, gen_params, depth + 1) {
+                        if !first {
+                            res.push(b',');
+                        }
+                        first = false;
+                        res.extend(param);
+                    }
+                }
+                res.push(b'>');
+                return Some(res);
+            }
+            TYPE_FNPTR => {
+                // Skip the flags
+                *sig = sig.get(1..)?;
+
+                let param_count = read_blob_unsigned(sig);
+                // Sanity check for corrupted files
+                if param_count > MAX_PARAM_COUNT {
+                    return None;
+                }
+
+                let return_type = self.parse_signature_type(sig, gen_params, depth + 1)?;
+                let mut res = [b"FnPtr<", &*return_type, b"("].concat();
+                let mut first = true;
+                for _ in 0..param_count {
+                    if let Some(param) = self.parse_signature_type(sig, gen_params, depth + 1) {
+                        if !first {
+                            res.extend(b", ");
+                        }
+                        first = false;
+                        res.extend(param);
+                    }
+                }
+                res.extend(b")>");
+                return Some(res);
+            }
+            TYPE_SZARRAY => {
+                let mut res = self.parse_signature_type(sig, gen_params, depth + 1)?;
+                res.extend(b"[]");
+                return Some(res);
+            }
+            TYPE_CMOD_REQD | TYPE_CMOD_OPT => {
+                // Custom modifiers are ignored
+                let _ = read_blob_unsigned(sig);
+                return self.parse_signature_type(sig, gen_params, depth + 1);
+            }
+            _ => return None,
+        };
+
+        Some(name.to_vec())
+    }
+
+    /// Parse a general array type, see ECMA-335 II.23.2.13.
+    fn parse_array_type(
+        &self,
+        sig: &mut &[u8],
+        gen_params: &GenericParams,
+        depth: u32,
+    ) -> Option<Vec<u8>> {
+        let mut res = self.parse_signature_type(sig, gen_params, depth + 1)?;
+
+        let rank = read_blob_unsigned(sig);
+        if rank == 0 || rank > MAX_ARRAY_RANK {
+            return None;
+        }
+
+        let num_sizes = read_blob_unsigned(sig);
+        if num_sizes > rank {
+            return None;
+        }
+        let mut sizes: Vec<i32> = (0..num_sizes)
+            .map(|_| i32::try_from(read_blob_unsigned(sig)).unwrap_or(0))
+            .collect();
+
+        let num_lo_bounds = read_blob_unsigned(sig);
+        if num_lo_bounds > rank {
+            return None;
+        }
+        let mut lo_bounds = Vec::new();
+        for i in 0..(num_lo_bounds as usize) {
+            let lo_bound = read_blob_signed(sig);
+            lo_bounds.push(lo_bound);
+
+            // Adjust the higher bound according to the lower bound
+            if lo_bound != 0 {
+                if let Some(size) = sizes.get_mut(i) {
+                    *size = size.wrapping_add(lo_bound - 1);
+                }
+            }
+        }
+
+        res.push(b'[');
+        for i in 0..(rank as usize) {
+            if let Some(lo_bound) = lo_bounds.get(i) {
+                if *lo_bound != 0 {
+                    res.extend(format!("{lo_bound}...").into_bytes());
+                }
+            }
+            if let Some(size) = sizes.get(i) {
+                res.extend(size.to_string().into_bytes());
+            }
+            if i + 1 != rank as usize {
+                res.push(b',');
+            }
+        }
+        res.push(b']');
+
+        Some(res)
+    }
+}
+
+/// Names of the generic parameters available when parsing a signature.
+struct GenericParams<'a, 'b> {
+    class: &'a [&'b [u8]],
+    method: &'a [&'b [u8]],
+}
+
+/// Read an unsigned compressed integer, see ECMA-335 II.23.2.
+///
+/// 0 is returned if the integer cannot be read.
+fn read_blob_unsigned(sig: &mut &[u8]) -> u32 {
+    let Some(first) = sig.first() else {
+        return 0;
+    };
+
+    if first & 0x80 == 0 {
+        *sig = &sig[1..];
+        u32::from(*first)
+    } else if first & 0xC0 == 0x80 {
+        if sig.len() < 2 {
+            return 0;
+        }
+        let v = u16::from_be_bytes([sig[0], sig[1]]);
+        *sig = &sig[2..];
+        u32::from(v & 0x3FFF)
+    } else if first & 0xE0 == 0xC0 {
+        if sig.len() < 4 {
+            return 0;
+        }
+        let v = u32::from_be_bytes([sig[0], sig[1], sig[2], sig[3]]);
+        *sig = &sig[4..];
+        v & 0x1FFF_FFFF
+    } else {
+        0
+    }
+}
+
+/// Read a signed compressed integer, see ECMA-335 II.23.2.
+///
+/// 0 is returned if the integer cannot be read.
+fn read_blob_signed(sig: &mut &[u8]) -> i32 {
+    let Some(first) = sig.first() else {
+        return 0;
+    };
+
+    // The value is rotated left by one bit, the sign bit being the lowest one.
+    if first & 0x80 == 0 {
+        let v = u32::from(*first);
+        *sig = &sig[1..];
+        sign_extend(v, 7)
+    } else if first & 0xC0 == 0x80 {
+        if sig.len() < 2 {
+            return 0;
+        }
+        let v = u32::from(u16::from_be_bytes([sig[0], sig[1]]) & 0x3FFF);
+        *sig = &sig[2..];
+        sign_extend(v, 14)
+    } else if first & 0xE0 == 0xC0 {
+        if sig.len() < 4 {
+            return 0;
+        }
+        let v = u32::from_be_bytes([sig[0], sig[1], sig[2], sig[3]]) & 0x1FFF_FFFF;
+        *sig = &sig[4..];
+        sign_extend(v, 29)
+    } else {
+        0
+    }
+}
+
+/// Undo the rotation of a signed compressed integer encoded on `nb_bits` bits.
+fn sign_extend(v: u32, nb_bits: u32) -> i32 {
+    // The value is at most 29 bits long, so this cannot fail.
+    let value = i32::try_from(v >> 1).unwrap_or(0);
+    if v & 1 == 0 {
+        value
+    } else {
+        value - (1 << (nb_bits - 1))
+    }
+}
+
+/// Build the full name of a type, `<namespace>.<name>`, without the generic suffix.
+fn create_full_name(name: Option<&[u8]>, namespace: Option<&[u8]>) -> Option<Vec<u8>> {
+    let name = match name {
+        Some(name) if !name.is_empty() => name,
+        _ => return namespace.map(<[u8]>::to_vec),
+    };
+
+    let mut full_name = match namespace {
+        Some(namespace) if !namespace.is_empty() => [namespace, b".", name].concat(),
+        _ => name.to_vec(),
+    };
+
+    // Remove the generic suffix, eg "List`1" becomes "List"
+    if let Some(pos) = memchr::memchr(b'`', &full_name) {
+        full_name.truncate(pos);
+    }
+
+    Some(full_name)
+}
+
+fn is_nested(flags: u32) -> bool {
+    // ECMA-335 II.22.37: a type is nested if its visibility is one of the nested ones.
+    !matches!(
+        flags & TYPE_ATTR_VISIBILITY_MASK,
+        TYPE_ATTR_NOT_PUBLIC | TYPE_ATTR_PUBLIC
+    )
+}
+
+fn type_visibility(flags: u32) -> &'static [u8] {
+    match flags & TYPE_ATTR_VISIBILITY_MASK {
+        TYPE_ATTR_PUBLIC | TYPE_ATTR_NESTED_PUBLIC => b"public",
+        TYPE_ATTR_NESTED_FAMILY => b"protected",
+        TYPE_ATTR_NOT_PUBLIC | TYPE_ATTR_NESTED_ASSEMBLY => b"internal",
+        TYPE_ATTR_NESTED_FAM_OR_ASSEM => b"protected internal",
+        TYPE_ATTR_NESTED_FAM_AND_ASSEM => b"private protected",
+        // Nested private
+        _ => b"private",
+    }
+}
+
+fn typedef_type(flags: u32) -> &'static [u8] {
+    if flags & TYPE_ATTR_INTERFACE == 0 {
+        b"class"
+    } else {
+        b"interface"
+    }
+}
+
+fn method_visibility(flags: u16) -> &'static [u8] {
+    match flags & METHOD_ATTR_ACCESS_MASK {
+        METHOD_ATTR_FAM_AND_ASSEM => b"private protected",
+        METHOD_ATTR_ASSEM => b"internal",
+        METHOD_ATTR_FAMILY => b"protected",
+        METHOD_ATTR_FAM_OR_ASSEM => b"protected internal",
+        METHOD_ATTR_PUBLIC => b"public",
+        _ => b"private",
+    }
+}
+
+fn flag_value(v: bool) -> Value {
+    Value::Integer(v.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_blob_integers() {
+        // Examples from ECMA-335 II.23.2
+        let check_unsigned = |mut bytes: &[u8], expected: u32| {
+            assert_eq!(read_blob_unsigned(&mut bytes), expected);
+            assert!(bytes.is_empty());
+        };
+        check_unsigned(b"\x03", 0x03);
+        check_unsigned(b"\x7F", 0x7F);
+        check_unsigned(b"\x80\x80", 0x80);
+        check_unsigned(b"\xAE\x57", 0x2E57);
+        check_unsigned(b"\xBF\xFF", 0x3FFF);
+        check_unsigned(b"\xC0\x00\x40\x00", 0x4000);
+        check_unsigned(b"\xDF\xFF\xFF\xFF", 0x1FFF_FFFF);
+
+        let check_signed = |mut bytes: &[u8], expected: i32| {
+            assert_eq!(read_blob_signed(&mut bytes), expected);
+            assert!(bytes.is_empty());
+        };
+        check_signed(b"\x06", 3);
+        check_signed(b"\x7B", -3);
+        check_signed(b"\x80\x80", 64);
+        check_signed(b"\x01", -64);
+        check_signed(b"\xC0\x00\x40\x00", 8192);
+        check_signed(b"\x80\x01", -8192);
+        check_signed(b"\xDF\xFF\xFF\xFE", 268_435_455);
+        check_signed(b"\xC0\x00\x00\x01", -268_435_456);
+
+        // Truncated values
+        let mut bytes: &[u8] = b"\x80";
+        assert_eq!(read_blob_unsigned(&mut bytes), 0);
+        assert_eq!(bytes.len(), 1);
+        let mut bytes: &[u8] = b"\xC0\x00";
+        assert_eq!(read_blob_signed(&mut bytes), 0);
+        assert_eq!(bytes.len(), 2);
+    }
+
+    #[test]
+    fn test_create_full_name() {
+        assert_eq!(
+            create_full_name(Some(b"List`1"), Some(b"System.Collections")),
+            Some(b"System.Collections.List".to_vec())
+        );
+        assert_eq!(create_full_name(Some(b"A"), Some(b"")), Some(b"A".to_vec()));
+        assert_eq!(create_full_name(Some(b"A`2"), None), Some(b"A".to_vec()));
+        assert_eq!(
+            create_full_name(Some(b""), Some(b"ns")),
+            Some(b"ns".to_vec())
+        );
+        assert_eq!(create_full_name(None, None), None);
+    }
+}
@@ -10,6 +10,9 @@ use super::{
     EvalContext, Module, ModuleData, ModuleDataMap, ScanContext, StaticValue, Type, Value,
 };
 
+mod linking;
+mod notes;
+
 const MAX_NB_SEGMENTS: usize = 32_768;
 const MAX_NB_SECTIONS: usize = 32_768;
 const MAX_NB_DYNAMIC: usize = 32_768;
@@ -151,6 +154,23 @@ impl Module for Elf {
             ("PF_X", StaticValue::Integer(elf::PF_X.into())),
             ("PF_W", StaticValue::Integer(elf::PF_W.into())),
             ("PF_R", StaticValue::Integer(elf::PF_R.into())),
+            // Symbols lookups
+            (
+                "has_import",
+                StaticValue::function(
+                    Self::has_import,
+                    vec![vec![Type::Bytes], vec![Type::Regex]],
+                    Type::Integer,
+                ),
+            ),
+            (
+                "has_export",
+                StaticValue::function(
+                    Self::has_export,
+                    vec![vec![Type::Bytes], vec![Type::Regex]],
+                    Type::Integer,
+                ),
+            ),
             // Hashes of import details
             #[cfg(feature = "hash")]
             (
@@ -239,6 +259,38 @@ impl Module for Elf {
                     ("shndx", Type::Integer),
                 ])),
             ),
+            // Dynamic linking
+            ("interpreter", Type::Bytes),
+            ("number_of_needed", Type::Integer),
+            ("needed", Type::array(Type::Bytes)),
+            ("soname", Type::Bytes),
+            ("rpath", Type::Bytes),
+            ("runpath", Type::Bytes),
+            ("number_of_version_requirements", Type::Integer),
+            (
+                "version_requirements",
+                Type::array(Type::object([
+                    ("file", Type::Bytes),
+                    ("number_of_versions", Type::Integer),
+                    ("versions", Type::array(Type::Bytes)),
+                ])),
+            ),
+            // Notes
+            ("number_of_notes", Type::Integer),
+            (
+                "notes",
+                Type::array(Type::object([
+                    ("name", Type::Bytes),
+                    ("type", Type::Integer),
+                    ("desc", Type::Bytes),
+                ])),
+            ),
+            ("build_id", Type::Bytes),
+            (
+                "abi_tag",
+                Type::object([("os", Type::Integer), ("version", Type::Bytes)]),
+            ),
+            ("go_build_id", Type::Bytes),
         ]
         .into()
     }
@@ -270,6 +322,9 @@ impl ModuleData for Elf {
 #[derive(Default)]
 pub struct Data {
     symbols: Vec<DataSymbol>,
+
+    /// Set if an ELF file was parsed.
+    found_elf: bool,
 }
 
 pub struct DataSymbol {
@@ -375,7 +430,9 @@ fn parse_file_inner<Elf: FileHeader<Endian = Endianness>>(
         entry_point(header, e, region.mem)
     };
 
-    let res = [
+    data.found_elf = true;
+
+    let mut res: HashMap<_, _> = [
         ("type", Value::from(header.e_type(e))),
         ("machine", header.e_machine(e).into()),
         ("entry_point", entrypoint.into()),
@@ -404,6 +461,10 @@ fn parse_file_inner<Elf: FileHeader<Endian = Endianness>>(
         ("dynamic_section_entries", dynamic_len.into()),
     ]
     .into();
+
+    linking::parse(header, e, region.mem, &mut res);
+    notes::parse(header, e, region.mem, &mut res);
+
     Some(res)
 }
 
@@ -567,6 +628,19 @@ fn get_symbols<Elf: FileHeader>(
 }
 
 impl Elf {
+    fn has_import(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_symbol(ctx, args, |sym| sym.shndx == elf::SHN_UNDEF)
+    }
+
+    fn has_export(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_symbol(ctx, args, |sym| {
+            sym.shndx != elf::SHN_UNDEF
+                && matches!(sym.bind, elf::STB_GLOBAL | elf::STB_WEAK)
+                && matches!(sym.visibility, elf::STV_DEFAULT | elf::STV_PROTECTED)
+                && !matches!(sym.type_, elf::STT_SECTION | elf::STT_FILE)
+        })
+    }
+
     #[cfg(feature = "hash")]
     fn import_md5(ctx: &mut EvalContext, _: Vec<Value>) -> Option<Value> {
         use md5::{Digest, Md5};
@@ -627,3 +701,28 @@ impl Elf {
             .map(Value::bytes)
     }
 }
+
+/// Check if a symbol matching the filter has the given name, or a name matching the given regex.
+fn has_symbol<F>(ctx: &mut EvalContext, args: Vec<Value>, filter: F) -> Option<Value>
+where
+    F: Fn(&DataSymbol) -> bool,
+{
+    let mut args = args.into_iter();
+    let name = args.next()?;
+
+    let data = ctx.module_data.get::<Elf>()?;
+    if !data.found_elf {
+        return None;
+    }
+
+    let mut symbols = data
+        .symbols
+        .iter()
+        .filter(|sym| !sym.name.is_empty() && filter(sym));
+    let res = match name {
+        Value::Bytes(name) => symbols.any(|sym| sym.name == name),
+        Value::Regex(regex) => symbols.any(|sym| regex.is_match(&sym.name)),
+        _ => return None,
+    };
+    Some(Value::Integer(res.into()))
+}
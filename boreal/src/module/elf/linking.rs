@@ -0,0 +1,186 @@
+//! Parsing of the information used by the dynamic linker: interpreter, needed libraries,
+//! search paths and version requirements.
+use std::collections::HashMap;
+
+use object::elf;
+use object::read::elf::{Dyn, FileHeader, ProgramHeader};
+
+use super::{Value, MAX_NB_DYNAMIC};
+
+const MAX_NB_NEEDED: usize = 1024;
+const MAX_NB_VERSION_REQUIREMENTS: usize = 1024;
+const MAX_NB_VERSIONS: usize = 1024;
+
+/// Parse the linking information, adding the values to the output.
+///
+/// Values are only added when present in the file.
+pub(super) fn parse<Elf: FileHeader>(
+    header: &Elf,
+    e: Elf::Endian,
+    mem: &[u8],
+    out: &mut HashMap<&'static str, Value>,
+) {
+    if let Ok(segments) = header.program_headers(e, mem) {
+        if let Some(interpreter) = interpreter::<Elf>(segments, e, mem) {
+            let _r = out.insert("interpreter", interpreter.into());
+        }
+        dynamic_strings::<Elf>(segments, e, mem, out);
+    }
+
+    if let Some(requirements) = version_requirements(header, e, mem) {
+        if !requirements.is_empty() {
+            let _r = out.insert("number_of_version_requirements", requirements.len().into());
+            let _r = out.insert("version_requirements", Value::Array(requirements));
+        }
+    }
+}
+
+fn interpreter<Elf: FileHeader>(
+    segments: &[Elf::ProgramHeader],
+    e: Elf::Endian,
+    mem: &[u8],
+) -> Option<Vec<u8>> {
+    let segment = segments
+        .iter()
+        .find(|segment| segment.p_type(e) == elf::PT_INTERP)?;
+    let path = segment.data(e, mem).ok()?;
+    Some(until_nul(path).to_vec())
+}
+
+/// Retrieve the strings referenced by the dynamic table: needed libraries, soname, rpath and
+/// runpath.
+///
+/// Only the segments are used to find the strings, so that the values are found even if the
+/// section headers are stripped.
+fn dynamic_strings<Elf: FileHeader>(
+    segments: &[Elf::ProgramHeader],
+    e: Elf::Endian,
+    mem: &[u8],
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let Some(dyn_table) = segments
+        .iter()
+        .find_map(|segment| segment.dynamic(e, mem).ok().flatten())
+    else {
+        return;
+    };
+
+    let mut strtab = None;
+    let mut strsz = None;
+    let mut entries = Vec::new();
+    for entry in dyn_table.iter().take(MAX_NB_DYNAMIC) {
+        let tag: u64 = entry.d_tag(e).into();
+        let val: u64 = entry.d_val(e).into();
+        match u32::try_from(tag) {
+            Ok(elf::DT_NULL) => break,
+            Ok(elf::DT_STRTAB) => strtab = Some(val),
+            Ok(elf::DT_STRSZ) => strsz = Some(val),
+            Ok(tag @ (elf::DT_NEEDED | elf::DT_SONAME | elf::DT_RPATH | elf::DT_RUNPATH)) => {
+                entries.push((tag, val));
+            }
+            _ => (),
+        }
+    }
+
+    // The string table is referenced by its virtual address.
+    let Some(strings) = strtab
+        .and_then(|va| va_to_file_offset::<Elf>(segments, e, va))
+        .and_then(|offset| usize::try_from(offset).ok())
+        .and_then(|offset| mem.get(offset..))
+    else {
+        return;
+    };
+    let strings = match strsz.and_then(|size| usize::try_from(size).ok()) {
+        Some(size) if size < strings.len() => &strings[..size],
+        _ => strings,
+    };
+    let get_string = |offset: u64| {
+        let offset = usize::try_from(offset).ok()?;
+        strings.get(offset..).map(|s| until_nul(s).to_vec())
+    };
+
+    let mut needed = Vec::new();
+    for (tag, offset) in entries {
+        let Some(s) = get_string(offset) else {
+            continue;
+        };
+        let name = match tag {
+            elf::DT_NEEDED => {
+                if needed.len() < MAX_NB_NEEDED {
+                    needed.push(Value::Bytes(s));
+                }
+                continue;
+            }
+            elf::DT_SONAME => "soname",
+            elf::DT_RPATH => "rpath",
+            _ => "runpath",
+        };
+        let _r = out.entry(name).or_insert_with(|| s.into());
+    }
+
+    if !needed.is_empty() {
+        let _r = out.insert("number_of_needed", needed.len().into());
+        let _r = out.insert("needed", Value::Array(needed));
+    }
+}
+
+/// List the versions needed from each library, from the `.gnu.version_r` section.
+fn version_requirements<Elf: FileHeader>(
+    header: &Elf,
+    e: Elf::Endian,
+    mem: &[u8],
+) -> Option<Vec<Value>> {
+    let sections = header.sections(e, mem).ok()?;
+    let (mut verneeds, link) = sections.gnu_verneed(e, mem).ok()??;
+    let strings = sections.strings(e, mem, link).ok()?;
+
+    let mut requirements = Vec::new();
+    while let Ok(Some((verneed, mut vernauxs))) = verneeds.next() {
+        if requirements.len() >= MAX_NB_VERSION_REQUIREMENTS {
+            break;
+        }
+
+        let mut versions = Vec::new();
+        while let Ok(Some(vernaux)) = vernauxs.next() {
+            if versions.len() >= MAX_NB_VERSIONS {
+                break;
+            }
+            if let Ok(name) = vernaux.name(e, strings) {
+                versions.push(Value::bytes(name));
+            }
+        }
+
+        requirements.push(Value::object([
+            (
+                "file",
+                verneed.file(e, strings).ok().map(<[u8]>::to_vec).into(),
+            ),
+            ("number_of_versions", versions.len().into()),
+            ("versions", Value::Array(versions)),
+        ]));
+    }
+    Some(requirements)
+}
+
+fn va_to_file_offset<Elf: FileHeader>(
+    segments: &[Elf::ProgramHeader],
+    e: Elf::Endian,
+    va: u64,
+) -> Option<u64> {
+    segments.iter().find_map(|segment| {
+        if segment.p_type(e) != elf::PT_LOAD {
+            return None;
+        }
+        let addr: u64 = segment.p_vaddr(e).into();
+        let size: u64 = segment.p_filesz(e).into();
+        if (addr..addr.saturating_add(size)).contains(&va) {
+            Some((va - addr).saturating_add(segment.p_offset(e).into()))
+        } else {
+            None
+        }
+    })
+}
+
+fn until_nul(s: &[u8]) -> &[u8] {
+    &s[..memchr::memchr(b'\0', s).unwrap_or(s.len())]
+}
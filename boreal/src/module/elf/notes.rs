@@ -0,0 +1,129 @@
+//! Parsing of the notes, such as the build id or the ABI tag.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use object::elf;
+use object::read::elf::{FileHeader, NoteIterator, ProgramHeader, SectionHeader};
+use object::Endian;
+
+use super::Value;
+
+const MAX_NB_NOTES: usize = 1024;
+/// Maximum size of a descriptor copied into the module values.
+///
+/// Bigger descriptors, which are not expected outside of core dumps, are left undefined.
+const MAX_DESC_SIZE: usize = 4096;
+
+/// Type of the note containing the build id of Go binaries.
+const NT_GO_BUILD_ID: u32 = 4;
+/// Name of the notes added by the Go toolchain.
+const ELF_NOTE_GO: &[u8] = b"Go";
+
+/// Parse the notes, adding the values to the output.
+///
+/// The notes are read from the note sections. If there are none, for example because the
+/// section headers are stripped, the note segments are used instead.
+pub(super) fn parse<Elf: FileHeader>(
+    header: &Elf,
+    e: Elf::Endian,
+    mem: &[u8],
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let mut notes = Vec::new();
+
+    let mut found_section = false;
+    if let Ok(sections) = header.sections(e, mem) {
+        for section in sections.iter() {
+            if let Ok(Some(iter)) = section.notes(e, mem) {
+                found_section = true;
+                parse_notes(iter, e, out, &mut notes);
+            }
+        }
+    }
+    if !found_section {
+        for segment in header.program_headers(e, mem).unwrap_or(&[]) {
+            if let Ok(Some(iter)) = segment.notes(e, mem) {
+                parse_notes(iter, e, out, &mut notes);
+            }
+        }
+    }
+
+    if !notes.is_empty() {
+        let _r = out.insert("number_of_notes", notes.len().into());
+        let _r = out.insert("notes", Value::Array(notes));
+    }
+}
+
+fn parse_notes<Elf: FileHeader>(
+    mut iter: NoteIterator<'_, Elf>,
+    e: Elf::Endian,
+    out: &mut HashMap<&'static str, Value>,
+    notes: &mut Vec<Value>,
+) {
+    while let Ok(Some(note)) = iter.next() {
+        if notes.len() >= MAX_NB_NOTES {
+            break;
+        }
+
+        let name = note.name();
+        let typ = note.n_type(e);
+        let desc = note.desc();
+
+        match (name, typ) {
+            (elf::ELF_NOTE_GNU, elf::NT_GNU_BUILD_ID) => {
+                let _r = out.insert("build_id", format_hex(desc).into());
+            }
+            (elf::ELF_NOTE_GNU, elf::NT_GNU_ABI_TAG) => {
+                if let Some(value) = abi_tag(desc, e) {
+                    let _r = out.insert("abi_tag", value);
+                }
+            }
+            (ELF_NOTE_GO, NT_GO_BUILD_ID) => {
+                let len = desc.iter().position(|c| *c == b'\0').unwrap_or(desc.len());
+                let _r = out.insert("go_build_id", desc[..len].to_vec().into());
+            }
+            _ => (),
+        }
+
+        notes.push(Value::object([
+            ("name", name.to_vec().into()),
+            ("type", typ.into()),
+            (
+                "desc",
+                if desc.len() <= MAX_DESC_SIZE {
+                    desc.to_vec().into()
+                } else {
+                    Value::Undefined
+                },
+            ),
+        ]));
+    }
+}
+
+/// Parse the ABI tag, made of the OS and of the earliest compatible kernel version.
+fn abi_tag<E: Endian>(desc: &[u8], e: E) -> Option<Value> {
+    let mut words = desc
+        .chunks_exact(4)
+        .map(|v| e.read_u32_bytes([v[0], v[1], v[2], v[3]]));
+
+    let os = words.next()?;
+    let major = words.next()?;
+    let minor = words.next()?;
+    let patch = words.next()?;
+
+    Some(Value::object([
+        ("os", os.into()),
+        (
+            "version",
+            format!("{major}.{minor}.{patch}").into_bytes().into(),
+        ),
+    ]))
+}
+
+fn format_hex(v: &[u8]) -> Vec<u8> {
+    let mut res = String::with_capacity(v.len() * 2);
+    for b in v {
+        let _r = write!(res, "{b:02x}");
+    }
+    res.into_bytes()
+}
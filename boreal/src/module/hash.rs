@@ -2,9 +2,12 @@ use std::collections::HashMap;
 use std::sync::RwLock;
 
 use super::{EvalContext, Module, ModuleData, ModuleDataMap, StaticValue, Type, Value};
+use fuzzyhash::FuzzyHash;
 use md5::{Digest, Md5};
 use sha1::Sha1;
-use sha2::Sha256;
+use sha2::{Sha256, Sha512};
+use sha3::Sha3_256;
+use tlsh2::{Tlsh128_1, TlshBuilder128_1};
 
 /// `hash` module. Exposes functions to compute hashes and checksums.
 #[derive(Debug)]
@@ -41,6 +44,62 @@ impl Module for Hash {
                     Type::Bytes,
                 ),
             ),
+            (
+                "sha512",
+                StaticValue::function(
+                    Self::sha512,
+                    vec![vec![Type::Integer, Type::Integer], vec![Type::Bytes]],
+                    Type::Bytes,
+                ),
+            ),
+            (
+                "sha3_256",
+                StaticValue::function(
+                    Self::sha3_256,
+                    vec![vec![Type::Integer, Type::Integer], vec![Type::Bytes]],
+                    Type::Bytes,
+                ),
+            ),
+            (
+                "blake3",
+                StaticValue::function(
+                    Self::blake3,
+                    vec![vec![Type::Integer, Type::Integer], vec![Type::Bytes]],
+                    Type::Bytes,
+                ),
+            ),
+            (
+                "ssdeep",
+                StaticValue::function(
+                    Self::ssdeep,
+                    vec![vec![Type::Integer, Type::Integer], vec![Type::Bytes]],
+                    Type::Bytes,
+                ),
+            ),
+            (
+                "ssdeep_compare",
+                StaticValue::function(
+                    Self::ssdeep_compare,
+                    vec![vec![Type::Bytes, Type::Bytes]],
+                    Type::Integer,
+                ),
+            ),
+            (
+                "tlsh",
+                StaticValue::function(
+                    Self::tlsh,
+                    vec![vec![Type::Integer, Type::Integer], vec![Type::Bytes]],
+                    Type::Bytes,
+                ),
+            ),
+            (
+                "tlsh_diff",
+                StaticValue::function(
+                    Self::tlsh_diff,
+                    vec![vec![Type::Bytes, Type::Bytes]],
+                    Type::Integer,
+                ),
+            ),
             (
                 "checksum32",
                 StaticValue::function(
@@ -68,14 +127,21 @@ impl Module for Hash {
 
 #[derive(Default)]
 pub struct Data {
-    cache: RwLock<Cache>,
+    /// Hashes already computed on ranges of the scanned memory.
+    cache: RwLock<HashMap<(Algorithm, usize, usize), Value>>,
 }
 
-#[derive(Default)]
-pub struct Cache {
-    md5: HashMap<(usize, usize), Value>,
-    sha1: HashMap<(usize, usize), Value>,
-    sha256: HashMap<(usize, usize), Value>,
+/// Hash algorithms whose results on ranges are cached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Sha3_256,
+    Blake3,
+    Ssdeep,
+    Tlsh,
 }
 
 impl ModuleData for Hash {
@@ -97,79 +163,119 @@ fn compute_hash_from_mem<D: Digest>(
     Some(Value::bytes(hex::encode(digest.finalize())))
 }
 
+/// Return the hash of a range from the cache, or compute it and store it in the cache.
+fn with_cache<F>(
+    ctx: &mut EvalContext,
+    algorithm: Algorithm,
+    offset: usize,
+    end: usize,
+    compute: F,
+) -> Option<Value>
+where
+    F: FnOnce(&mut EvalContext) -> Option<Value>,
+{
+    let data = ctx.module_data.get::<Hash>()?;
+    let key = (algorithm, offset, end);
+
+    {
+        if let Some(v) = data.cache.read().ok()?.get(&key) {
+            return Some(v.clone());
+        }
+    }
+
+    let hash = compute(ctx)?;
+    let _r = data.cache.write().ok()?.insert(key, hash.clone());
+    Some(hash)
+}
+
 impl Hash {
     fn md5(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
-        match get_args(args)? {
-            Args::Bytes(s) => Some(compute_hash_from_bytes::<Md5>(&s)),
-            Args::Range(offset, end) => {
-                let data = ctx.module_data.get::<Hash>()?;
+        Self::digest::<Md5>(ctx, args, Algorithm::Md5)
+    }
 
-                {
-                    if let Some(v) = data.cache.read().ok()?.md5.get(&(offset, end)) {
-                        return Some(v.clone());
-                    }
-                }
+    fn sha1(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        Self::digest::<Sha1>(ctx, args, Algorithm::Sha1)
+    }
 
-                let hash = compute_hash_from_mem::<Md5>(ctx, offset, end)?;
-                let _r = data
-                    .cache
-                    .write()
-                    .ok()?
-                    .md5
-                    .insert((offset, end), hash.clone());
-                Some(hash)
-            }
-        }
+    fn sha2(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        Self::digest::<Sha256>(ctx, args, Algorithm::Sha256)
     }
 
-    fn sha1(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
-        match get_args(args)? {
-            Args::Bytes(s) => Some(compute_hash_from_bytes::<Sha1>(&s)),
-            Args::Range(offset, end) => {
-                let data = ctx.module_data.get::<Hash>()?;
+    fn sha512(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        Self::digest::<Sha512>(ctx, args, Algorithm::Sha512)
+    }
 
-                {
-                    if let Some(v) = data.cache.read().ok()?.sha1.get(&(offset, end)) {
-                        return Some(v.clone());
-                    }
-                }
+    fn sha3_256(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        Self::digest::<Sha3_256>(ctx, args, Algorithm::Sha3_256)
+    }
 
-                let hash = compute_hash_from_mem::<Sha1>(ctx, offset, end)?;
-                let _r = data
-                    .cache
-                    .write()
-                    .ok()?
-                    .sha1
-                    .insert((offset, end), hash.clone());
-                Some(hash)
-            }
+    fn blake3(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        Self::digest::<blake3::Hasher>(ctx, args, Algorithm::Blake3)
+    }
+
+    fn digest<D: Digest>(
+        ctx: &mut EvalContext,
+        args: Vec<Value>,
+        algorithm: Algorithm,
+    ) -> Option<Value> {
+        match get_args(args)? {
+            Args::Bytes(s) => Some(compute_hash_from_bytes::<D>(&s)),
+            Args::Range(offset, end) => with_cache(ctx, algorithm, offset, end, |ctx| {
+                compute_hash_from_mem::<D>(ctx, offset, end)
+            }),
         }
     }
 
-    fn sha2(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+    fn ssdeep(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
         match get_args(args)? {
-            Args::Bytes(s) => Some(compute_hash_from_bytes::<Sha256>(&s)),
-            Args::Range(offset, end) => {
-                let data = ctx.module_data.get::<Hash>()?;
+            Args::Bytes(s) => Some(Value::bytes(FuzzyHash::new(s).to_string())),
+            Args::Range(offset, end) => with_cache(ctx, Algorithm::Ssdeep, offset, end, |ctx| {
+                let mut hash = FuzzyHash::default();
+                ctx.mem.on_range(offset, end, |data| hash.update(data))?;
+                hash.finalize();
+                Some(Value::bytes(hash.to_string()))
+            }),
+        }
+    }
 
-                {
-                    if let Some(v) = data.cache.read().ok()?.sha256.get(&(offset, end)) {
-                        return Some(v.clone());
-                    }
-                }
+    /// Compare two ssdeep hashes.
+    ///
+    /// Returns a similarity score between 0 and 100, to be compared against a threshold, e.g.
+    /// `hash.ssdeep_compare(hash.ssdeep(0, filesize), "...") >= 80`.
+    fn ssdeep_compare(_: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let a: Vec<u8> = args.next()?.try_into().ok()?;
+        let b: Vec<u8> = args.next()?.try_into().ok()?;
 
-                let hash = compute_hash_from_mem::<Sha256>(ctx, offset, end)?;
-                let _r = data
-                    .cache
-                    .write()
-                    .ok()?
-                    .sha256
-                    .insert((offset, end), hash.clone());
-                Some(hash)
-            }
+        let score =
+            FuzzyHash::compare(std::str::from_utf8(&a).ok()?, std::str::from_utf8(&b).ok()?)
+                .ok()?;
+        Some(Value::Integer(i64::from(score)))
+    }
+
+    fn tlsh(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        match get_args(args)? {
+            Args::Bytes(s) => TlshBuilder128_1::build_from(&s).map(|v| Value::bytes(v.hash())),
+            Args::Range(offset, end) => with_cache(ctx, Algorithm::Tlsh, offset, end, |ctx| {
+                let mut builder = TlshBuilder128_1::new();
+                ctx.mem.on_range(offset, end, |data| builder.update(data))?;
+                builder.build().map(|v| Value::bytes(v.hash()))
+            }),
         }
     }
 
+    /// Compute the distance between two TLSH hashes.
+    ///
+    /// 0 means the hashes are identical, and the lower the distance, the more similar the
+    /// inputs are, e.g. `hash.tlsh_diff(hash.tlsh(0, filesize), "...") < 50`.
+    fn tlsh_diff(_: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let a = parse_tlsh(args.next()?)?;
+        let b = parse_tlsh(args.next()?)?;
+
+        Some(Value::Integer(i64::from(a.diff(&b, true))))
+    }
+
     fn checksum32(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
         let mut checksum: u32 = 0;
         match get_args(args)? {
@@ -228,3 +334,8 @@ fn get_args(args: Vec<Value>) -> Option<Args> {
         _ => None,
     }
 }
+
+fn parse_tlsh(value: Value) -> Option<Tlsh128_1> {
+    let hash: Vec<u8> = value.try_into().ok()?;
+    Tlsh128_1::from_str(std::str::from_utf8(&hash).ok()?)
+}
@@ -0,0 +1,814 @@
+use std::collections::HashMap;
+
+use super::{Module, ModuleData, ModuleDataMap, ScanContext, StaticValue, Type, Value};
+
+const HEADER_SIZE: u32 = 0x4C;
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+// Link flags
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+
+// Link info flags
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+const COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX: u32 = 0x2;
+
+// Extra data block signatures
+const ENVIRONMENT_VARIABLE_DATA_BLOCK: u32 = 0xA000_0001;
+const CONSOLE_FE_DATA_BLOCK: u32 = 0xA000_0004;
+const TRACKER_DATA_BLOCK: u32 = 0xA000_0003;
+const SPECIAL_FOLDER_DATA_BLOCK: u32 = 0xA000_0005;
+const DARWIN_DATA_BLOCK: u32 = 0xA000_0006;
+const ICON_ENVIRONMENT_DATA_BLOCK: u32 = 0xA000_0007;
+const KNOWN_FOLDER_DATA_BLOCK: u32 = 0xA000_000B;
+
+/// Size of the ANSI and unicode paths in the environment, icon and darwin data blocks.
+const TARGET_ANSI_SIZE: usize = 260;
+const TARGET_UNICODE_SIZE: usize = 520;
+
+/// Number of seconds between the Windows epoch (1601-01-01) and the unix epoch.
+const WINDOWS_TO_UNIX_EPOCH: u64 = 11_644_473_600;
+
+/// `lnk` module. Parses Windows shortcut files.
+///
+/// The format is described in the `[MS-SHLLINK]` specification. Strings stored in UTF-16
+/// in the file are converted to UTF-8.
+#[derive(Debug)]
+pub struct Lnk;
+
+impl Module for Lnk {
+    fn get_name(&self) -> &'static str {
+        "lnk"
+    }
+
+    fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+        [
+            // Link flags
+            ("HasLinkTargetIDList", StaticValue::Integer(0x0000_0001)),
+            ("HasLinkInfo", StaticValue::Integer(0x0000_0002)),
+            ("HasName", StaticValue::Integer(0x0000_0004)),
+            ("HasRelativePath", StaticValue::Integer(0x0000_0008)),
+            ("HasWorkingDir", StaticValue::Integer(0x0000_0010)),
+            ("HasArguments", StaticValue::Integer(0x0000_0020)),
+            ("HasIconLocation", StaticValue::Integer(0x0000_0040)),
+            ("IsUnicode", StaticValue::Integer(0x0000_0080)),
+            ("ForceNoLinkInfo", StaticValue::Integer(0x0000_0100)),
+            ("HasExpString", StaticValue::Integer(0x0000_0200)),
+            ("RunInSeparateProcess", StaticValue::Integer(0x0000_0400)),
+            ("Unused1", StaticValue::Integer(0x0000_0800)),
+            ("HasDarwinID", StaticValue::Integer(0x0000_1000)),
+            ("RunAsUser", StaticValue::Integer(0x0000_2000)),
+            ("HasExpIcon", StaticValue::Integer(0x0000_4000)),
+            ("NoPidlAlias", StaticValue::Integer(0x0000_8000)),
+            ("Unused2", StaticValue::Integer(0x0001_0000)),
+            ("RunWithShimLayer", StaticValue::Integer(0x0002_0000)),
+            ("ForceNoLinkTrack", StaticValue::Integer(0x0004_0000)),
+            ("EnableTargetMetadata", StaticValue::Integer(0x0008_0000)),
+            ("DisableLinkPathTracking", StaticValue::Integer(0x0010_0000)),
+            (
+                "DisableKnownFolderTracking",
+                StaticValue::Integer(0x0020_0000),
+            ),
+            ("DisableKnownFolderAlias", StaticValue::Integer(0x0040_0000)),
+            ("AllowLinkToLink", StaticValue::Integer(0x0080_0000)),
+            ("UnaliasOnSave", StaticValue::Integer(0x0100_0000)),
+            ("PreferEnvironmentPath", StaticValue::Integer(0x0200_0000)),
+            (
+                "KeepLocalIDListForUNCTarget",
+                StaticValue::Integer(0x0400_0000),
+            ),
+            // File attributes flags
+            ("FILE_ATTRIBUTE_READONLY", StaticValue::Integer(0x0001)),
+            ("FILE_ATTRIBUTE_HIDDEN", StaticValue::Integer(0x0002)),
+            ("FILE_ATTRIBUTE_SYSTEM", StaticValue::Integer(0x0004)),
+            ("Reserved1", StaticValue::Integer(0x0008)),
+            ("FILE_ATTRIBUTE_DIRECTORY", StaticValue::Integer(0x0010)),
+            ("FILE_ATTRIBUTE_ARCHIVE", StaticValue::Integer(0x0020)),
+            ("Reserved2", StaticValue::Integer(0x0040)),
+            ("FILE_ATTRIBUTE_NORMAL", StaticValue::Integer(0x0080)),
+            ("FILE_ATTRIBUTE_TEMPORARY", StaticValue::Integer(0x0100)),
+            ("FILE_ATTRIBUTE_SPARSE_FILE", StaticValue::Integer(0x0200)),
+            ("FILE_ATTRIBUTE_REPARSE_POINT", StaticValue::Integer(0x0400)),
+            ("FILE_ATTRIBUTE_COMPRESSED", StaticValue::Integer(0x0800)),
+            ("FILE_ATTRIBUTE_OFFLINE", StaticValue::Integer(0x1000)),
+            (
+                "FILE_ATTRIBUTE_NOT_CONTENT_INDEXED",
+                StaticValue::Integer(0x2000),
+            ),
+            ("FILE_ATTRIBUTE_ENCRYPTED", StaticValue::Integer(0x4000)),
+            // Show command
+            ("SW_SHOWNORMAL", StaticValue::Integer(0x1)),
+            ("SW_SHOWMAXIMIZED", StaticValue::Integer(0x3)),
+            ("SW_SHOWMINNOACTIVE", StaticValue::Integer(0x7)),
+            // Hotkey modifier flags
+            ("HOTKEYF_SHIFT", StaticValue::Integer(0x1)),
+            ("HOTKEYF_CONTROL", StaticValue::Integer(0x2)),
+            ("HOTKEYF_ALT", StaticValue::Integer(0x4)),
+            // Link info flags
+            ("VolumeIDAndLocalBasePath", StaticValue::Integer(0x1)),
+            (
+                "CommonNetworkRelativeLinkAndPathSuffix",
+                StaticValue::Integer(0x2),
+            ),
+            // Drive types
+            ("DRIVE_UNKNOWN", StaticValue::Integer(0)),
+            ("DRIVE_NO_ROOT_DIR", StaticValue::Integer(1)),
+            ("DRIVE_REMOVABLE", StaticValue::Integer(2)),
+            ("DRIVE_FIXED", StaticValue::Integer(3)),
+            ("DRIVE_REMOTE", StaticValue::Integer(4)),
+            ("DRIVE_CDROM", StaticValue::Integer(5)),
+            ("DRIVE_RAMDISK", StaticValue::Integer(6)),
+            // Common network relative link flags
+            ("ValidDevice", StaticValue::Integer(0x1)),
+            ("ValidNetType", StaticValue::Integer(0x2)),
+            // Network provider types
+            ("WNNC_NET_AVID", StaticValue::Integer(0x001A_0000)),
+            ("WNNC_NET_DOCUSPACE", StaticValue::Integer(0x001B_0000)),
+            ("WNNC_NET_MANGOSOFT", StaticValue::Integer(0x001C_0000)),
+            ("WNNC_NET_SERNET", StaticValue::Integer(0x001D_0000)),
+            ("WNNC_NET_RIVERFRONT1", StaticValue::Integer(0x001E_0000)),
+            ("WNNC_NET_RIVERFRONT2", StaticValue::Integer(0x001F_0000)),
+            ("WNNC_NET_DECORB", StaticValue::Integer(0x0020_0000)),
+            ("WNNC_NET_PROTSTOR", StaticValue::Integer(0x0021_0000)),
+            ("WNNC_NET_FJ_REDIR", StaticValue::Integer(0x0022_0000)),
+            ("WNNC_NET_DISTINCT", StaticValue::Integer(0x0023_0000)),
+            ("WNNC_NET_TWINS", StaticValue::Integer(0x0024_0000)),
+            ("WNNC_NET_RDR2SAMPLE", StaticValue::Integer(0x0025_0000)),
+            ("WNNC_NET_CSC", StaticValue::Integer(0x0026_0000)),
+            ("WNNC_NET_3IN1", StaticValue::Integer(0x0027_0000)),
+            ("WNNC_NET_EXTENDNET", StaticValue::Integer(0x0029_0000)),
+            ("WNNC_NET_STAC", StaticValue::Integer(0x002A_0000)),
+            ("WNNC_NET_FOXBAT", StaticValue::Integer(0x002B_0000)),
+            ("WNNC_NET_YAHOO", StaticValue::Integer(0x002C_0000)),
+            ("WNNC_NET_EXIFS", StaticValue::Integer(0x002D_0000)),
+            ("WNNC_NET_DAV", StaticValue::Integer(0x002E_0000)),
+            ("WNNC_NET_KNOWARE", StaticValue::Integer(0x002F_0000)),
+            ("WNNC_NET_OBJECT_DIRE", StaticValue::Integer(0x0030_0000)),
+            ("WNNC_NET_MASFAX", StaticValue::Integer(0x0031_0000)),
+            ("WNNC_NET_HOB_NFS", StaticValue::Integer(0x0032_0000)),
+            ("WNNC_NET_SHIVA", StaticValue::Integer(0x0033_0000)),
+            ("WNNC_NET_IBMAL", StaticValue::Integer(0x0034_0000)),
+            ("WNNC_NET_LOCK", StaticValue::Integer(0x0035_0000)),
+            ("WNNC_NET_TERMSRV", StaticValue::Integer(0x0036_0000)),
+            ("WNNC_NET_SRT", StaticValue::Integer(0x0037_0000)),
+            ("WNNC_NET_QUINCY", StaticValue::Integer(0x0038_0000)),
+            ("WNNC_NET_OPENAFS", StaticValue::Integer(0x0039_0000)),
+            ("WNNC_NET_AVID1", StaticValue::Integer(0x003A_0000)),
+            ("WNNC_NET_DFS", StaticValue::Integer(0x003B_0000)),
+            ("WNNC_NET_KWNP", StaticValue::Integer(0x003C_0000)),
+            ("WNNC_NET_ZENWORKS", StaticValue::Integer(0x003D_0000)),
+            ("WNNC_NET_DRIVEONWEB", StaticValue::Integer(0x003E_0000)),
+            ("WNNC_NET_VMWARE", StaticValue::Integer(0x003F_0000)),
+            ("WNNC_NET_RSFX", StaticValue::Integer(0x0040_0000)),
+            ("WNNC_NET_MFILES", StaticValue::Integer(0x0041_0000)),
+            ("WNNC_NET_MS_NFS", StaticValue::Integer(0x0042_0000)),
+            ("WNNC_NET_GOOGLE", StaticValue::Integer(0x0043_0000)),
+        ]
+        .into()
+    }
+
+    fn get_dynamic_types(&self) -> HashMap<&'static str, Type> {
+        [
+            ("is_lnk", Type::Integer),
+            ("is_malformed", Type::Integer),
+            // Header
+            ("creation_time", Type::Integer),
+            ("access_time", Type::Integer),
+            ("write_time", Type::Integer),
+            ("file_size", Type::Integer),
+            ("link_flags", Type::Integer),
+            ("file_attributes_flags", Type::Integer),
+            ("icon_index", Type::Integer),
+            ("show_command", Type::Integer),
+            ("hotkey_flags", Type::Integer),
+            ("hotkey", Type::Bytes),
+            ("hotkey_modifier_flags", Type::Integer),
+            ("has_hotkey", Type::Integer),
+            // LinkTargetIDList
+            ("link_target_id_list_size", Type::Integer),
+            ("number_of_item_ids", Type::Integer),
+            (
+                "item_id_list",
+                Type::array(Type::object([
+                    ("size", Type::Integer),
+                    ("data", Type::Bytes),
+                ])),
+            ),
+            // LinkInfo
+            ("link_info_size", Type::Integer),
+            ("link_info_header_size", Type::Integer),
+            ("link_info_flags", Type::Integer),
+            ("volume_id_offset", Type::Integer),
+            ("local_base_path_offset", Type::Integer),
+            ("common_network_relative_link_offset", Type::Integer),
+            ("common_path_suffix_offset", Type::Integer),
+            ("local_base_path_offset_unicode", Type::Integer),
+            ("common_path_suffix_offset_unicode", Type::Integer),
+            (
+                "volume_id",
+                Type::object([
+                    ("size", Type::Integer),
+                    ("drive_type", Type::Integer),
+                    ("drive_serial_number", Type::Integer),
+                    ("volume_label_offset", Type::Integer),
+                    ("volume_label_offset_unicode", Type::Integer),
+                    ("data", Type::Bytes),
+                ]),
+            ),
+            ("local_base_path", Type::Bytes),
+            ("local_base_path_unicode", Type::Bytes),
+            (
+                "common_network_relative_link",
+                Type::object([
+                    ("size", Type::Integer),
+                    ("flags", Type::Integer),
+                    ("net_name_offset", Type::Integer),
+                    ("device_name_offset", Type::Integer),
+                    ("network_provider_type", Type::Integer),
+                    ("net_name_offset_unicode", Type::Integer),
+                    ("device_name_offset_unicode", Type::Integer),
+                    ("net_name", Type::Bytes),
+                    ("device_name", Type::Bytes),
+                    ("net_name_unicode", Type::Bytes),
+                    ("device_name_unicode", Type::Bytes),
+                ]),
+            ),
+            ("common_path_suffix", Type::Bytes),
+            ("common_path_suffix_unicode", Type::Bytes),
+            // StringData
+            ("name_string", Type::Bytes),
+            ("relative_path", Type::Bytes),
+            ("working_dir", Type::Bytes),
+            ("command_line_arguments", Type::Bytes),
+            ("icon_location", Type::Bytes),
+            // ExtraData
+            (
+                "environment_variables_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("target_ansi", Type::Bytes),
+                    ("target_unicode", Type::Bytes),
+                ]),
+            ),
+            (
+                "icon_environment_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("target_ansi", Type::Bytes),
+                    ("target_unicode", Type::Bytes),
+                ]),
+            ),
+            (
+                "darwin_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("darwin_data_ansi", Type::Bytes),
+                    ("darwin_data_unicode", Type::Bytes),
+                ]),
+            ),
+            (
+                "console_fe_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("code_page", Type::Integer),
+                ]),
+            ),
+            (
+                "known_folder_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("known_folder_id", Type::Bytes),
+                    ("offset", Type::Integer),
+                ]),
+            ),
+            (
+                "special_folder_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("special_folder_id", Type::Integer),
+                    ("offset", Type::Integer),
+                ]),
+            ),
+            (
+                "tracker_data",
+                Type::object([
+                    ("block_size", Type::Integer),
+                    ("block_signature", Type::Integer),
+                    ("version", Type::Integer),
+                    ("machine_id", Type::Bytes),
+                    ("droid_volume_identifier", Type::Bytes),
+                    ("droid_file_identifier", Type::Bytes),
+                    ("droid_birth_volume_identifier", Type::Bytes),
+                    ("droid_birth_file_identifier", Type::Bytes),
+                ]),
+            ),
+        ]
+        .into()
+    }
+
+    fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
+        data_map.insert::<Self>(Data::default());
+    }
+
+    fn get_dynamic_values(&self, ctx: &mut ScanContext, out: &mut HashMap<&'static str, Value>) {
+        let Some(data) = ctx.module_data.get_mut::<Self>() else {
+            return;
+        };
+        if data.found_lnk {
+            // We already found a lnk file in a scanned region, so ignore the others.
+            return;
+        }
+
+        let mem = ctx.region.mem;
+        if read_u32(mem, 0) != Some(HEADER_SIZE)
+            || mem.len() < HEADER_SIZE as usize
+            || mem[4..20] != LINK_CLSID
+        {
+            *out = [("is_lnk", 0.into())].into();
+            return;
+        }
+
+        data.found_lnk = true;
+        *out = Parser {
+            mem,
+            out: HashMap::new(),
+        }
+        .parse();
+    }
+}
+
+impl ModuleData for Lnk {
+    type Data = Data;
+}
+
+#[derive(Default)]
+pub struct Data {
+    found_lnk: bool,
+}
+
+struct Parser<'a> {
+    mem: &'a [u8],
+    out: HashMap<&'static str, Value>,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> HashMap<&'static str, Value> {
+        let link_flags = self.parse_header();
+        let is_malformed = self.parse_sections(link_flags).is_none();
+
+        let _r = self.out.insert("is_lnk", 1.into());
+        let _r = self
+            .out
+            .insert("is_malformed", Value::Integer(is_malformed.into()));
+        self.out
+    }
+
+    fn insert(&mut self, key: &'static str, value: Value) {
+        let _r = self.out.insert(key, value);
+    }
+
+    /// Parse the `ShellLinkHeader`, returning the link flags.
+    fn parse_header(&mut self) -> u32 {
+        let mem = self.mem;
+        let link_flags = read_u32(mem, 20).unwrap_or(0);
+        let hotkey = read_u16(mem, 64).unwrap_or(0);
+
+        self.insert("link_flags", link_flags.into());
+        self.insert("file_attributes_flags", read_u32(mem, 24).into());
+        self.insert("creation_time", filetime_to_unix(read_u64(mem, 28)).into());
+        self.insert("access_time", filetime_to_unix(read_u64(mem, 36)).into());
+        self.insert("write_time", filetime_to_unix(read_u64(mem, 44)).into());
+        self.insert("file_size", read_u32(mem, 52).into());
+        self.insert("icon_index", read_i32(mem, 56).into());
+        self.insert("show_command", read_u32(mem, 60).into());
+        self.insert("hotkey_flags", hotkey.into());
+        self.insert("has_hotkey", Value::Integer((hotkey != 0).into()));
+        if hotkey != 0 {
+            let [key, modifiers] = hotkey.to_le_bytes();
+            self.insert("hotkey", hotkey_name(key).map(String::into_bytes).into());
+            self.insert("hotkey_modifier_flags", modifiers.into());
+        }
+
+        link_flags
+    }
+
+    /// Parse the optional structures following the header.
+    ///
+    /// None is returned if the file is malformed.
+    fn parse_sections(&mut self, link_flags: u32) -> Option<()> {
+        let mut offset = HEADER_SIZE as usize;
+
+        if link_flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            offset = self.parse_link_target_id_list(offset)?;
+        }
+        if link_flags & HAS_LINK_INFO != 0 {
+            offset = self.parse_link_info(offset)?;
+        }
+
+        let is_unicode = link_flags & IS_UNICODE != 0;
+        for (flag, name) in [
+            (HAS_NAME, "name_string"),
+            (HAS_RELATIVE_PATH, "relative_path"),
+            (HAS_WORKING_DIR, "working_dir"),
+            (HAS_ARGUMENTS, "command_line_arguments"),
+            (HAS_ICON_LOCATION, "icon_location"),
+        ] {
+            if link_flags & flag != 0 {
+                let (value, size) = self.parse_string_data(offset, is_unicode)?;
+                self.insert(name, value.into());
+                offset += size;
+            }
+        }
+
+        self.parse_extra_data(offset)
+    }
+
+    /// Parse the `LinkTargetIDList` structure, returning the offset after it.
+    fn parse_link_target_id_list(&mut self, offset: usize) -> Option<usize> {
+        let list_size = usize::from(read_u16(self.mem, offset)?);
+        self.insert("link_target_id_list_size", list_size.into());
+
+        let list = self.mem.get((offset + 2)..(offset + 2 + list_size))?;
+        let mut items = Vec::new();
+        let mut item_offset = 0;
+        loop {
+            let item_size = usize::from(read_u16(list, item_offset)?);
+            // The list is terminated by an empty item.
+            if item_size == 0 {
+                break;
+            }
+            // The size includes the size field itself.
+            let data = list.get((item_offset + 2)..(item_offset + item_size))?;
+            items.push(Value::object([
+                ("size", item_size.into()),
+                ("data", data.to_vec().into()),
+            ]));
+            item_offset += item_size;
+        }
+
+        self.insert("number_of_item_ids", items.len().into());
+        self.insert("item_id_list", Value::Array(items));
+
+        Some(offset + 2 + list_size)
+    }
+
+    /// Parse the `LinkInfo` structure, returning the offset after it.
+    fn parse_link_info(&mut self, offset: usize) -> Option<usize> {
+        let mem = self.mem;
+        let size = read_u32(mem, offset)? as usize;
+        let info = mem.get(offset..offset.checked_add(size)?)?;
+
+        let header_size = read_u32(info, 4)?;
+        let flags = read_u32(info, 8)?;
+        let volume_id_offset = read_u32(info, 12)? as usize;
+        let local_base_path_offset = read_u32(info, 16)? as usize;
+        let common_network_relative_link_offset = read_u32(info, 20)? as usize;
+        let common_path_suffix_offset = read_u32(info, 24)? as usize;
+
+        self.insert("link_info_size", size.into());
+        self.insert("link_info_header_size", header_size.into());
+        self.insert("link_info_flags", flags.into());
+        self.insert("volume_id_offset", volume_id_offset.into());
+        self.insert("local_base_path_offset", local_base_path_offset.into());
+        self.insert(
+            "common_network_relative_link_offset",
+            common_network_relative_link_offset.into(),
+        );
+        self.insert(
+            "common_path_suffix_offset",
+            common_path_suffix_offset.into(),
+        );
+
+        // The unicode offsets are only present if the header is big enough.
+        let (local_base_path_offset_unicode, common_path_suffix_offset_unicode) =
+            if header_size >= 0x24 {
+                let local = read_u32(info, 28)? as usize;
+                let suffix = read_u32(info, 32)? as usize;
+                self.insert("local_base_path_offset_unicode", local.into());
+                self.insert("common_path_suffix_offset_unicode", suffix.into());
+                (Some(local), Some(suffix))
+            } else {
+                (None, None)
+            };
+
+        if flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+            let volume_id = parse_volume_id(info.get(volume_id_offset..)?)?;
+            self.insert("volume_id", volume_id);
+            self.insert(
+                "local_base_path",
+                read_cstring(info, local_base_path_offset).into(),
+            );
+            if let Some(offset) = local_base_path_offset_unicode {
+                self.insert(
+                    "local_base_path_unicode",
+                    read_cstring_unicode(info, offset).into(),
+                );
+            }
+        }
+        if flags & COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX != 0 {
+            let link = parse_common_network_relative_link(
+                info.get(common_network_relative_link_offset..)?,
+            )?;
+            self.insert("common_network_relative_link", link);
+        }
+        self.insert(
+            "common_path_suffix",
+            read_cstring(info, common_path_suffix_offset).into(),
+        );
+        if let Some(offset) = common_path_suffix_offset_unicode {
+            self.insert(
+                "common_path_suffix_unicode",
+                read_cstring_unicode(info, offset).into(),
+            );
+        }
+
+        Some(offset + size)
+    }
+
+    /// Parse a `StringData` structure, returning its value and its size.
+    fn parse_string_data(&self, offset: usize, is_unicode: bool) -> Option<(Vec<u8>, usize)> {
+        let count = usize::from(read_u16(self.mem, offset)?);
+        let start = offset + 2;
+
+        if is_unicode {
+            let data = self.mem.get(start..(start + count * 2))?;
+            Some((decode_utf16(data), 2 + count * 2))
+        } else {
+            let data = self.mem.get(start..(start + count))?;
+            Some((data.to_vec(), 2 + count))
+        }
+    }
+
+    /// Parse the `ExtraData` blocks.
+    fn parse_extra_data(&mut self, mut offset: usize) -> Option<()> {
+        loop {
+            let block_size = read_u32(self.mem, offset)? as usize;
+            // The list of blocks is terminated by a block smaller than 4 bytes.
+            if block_size < 4 {
+                return Some(());
+            }
+            let block = self.mem.get(offset..offset.checked_add(block_size)?)?;
+            let signature = read_u32(block, 4)?;
+
+            let value = match signature {
+                ENVIRONMENT_VARIABLE_DATA_BLOCK => Some((
+                    "environment_variables_data",
+                    parse_target_block(block, "target_ansi", "target_unicode")?,
+                )),
+                ICON_ENVIRONMENT_DATA_BLOCK => Some((
+                    "icon_environment_data",
+                    parse_target_block(block, "target_ansi", "target_unicode")?,
+                )),
+                DARWIN_DATA_BLOCK => Some((
+                    "darwin_data",
+                    parse_target_block(block, "darwin_data_ansi", "darwin_data_unicode")?,
+                )),
+                CONSOLE_FE_DATA_BLOCK => Some((
+                    "console_fe_data",
+                    Value::object([
+                        ("block_size", block_size.into()),
+                        ("block_signature", signature.into()),
+                        ("code_page", read_u32(block, 8)?.into()),
+                    ]),
+                )),
+                KNOWN_FOLDER_DATA_BLOCK => Some((
+                    "known_folder_data",
+                    Value::object([
+                        ("block_size", block_size.into()),
+                        ("block_signature", signature.into()),
+                        ("known_folder_id", format_guid(block.get(8..24)?).into()),
+                        ("offset", read_u32(block, 24)?.into()),
+                    ]),
+                )),
+                SPECIAL_FOLDER_DATA_BLOCK => Some((
+                    "special_folder_data",
+                    Value::object([
+                        ("block_size", block_size.into()),
+                        ("block_signature", signature.into()),
+                        ("special_folder_id", read_u32(block, 8)?.into()),
+                        ("offset", read_u32(block, 12)?.into()),
+                    ]),
+                )),
+                TRACKER_DATA_BLOCK => Some(("tracker_data", parse_tracker_block(block)?)),
+                // Other blocks are skipped.
+                _ => None,
+            };
+            if let Some((name, value)) = value {
+                self.insert(name, value);
+            }
+
+            offset += block_size;
+        }
+    }
+}
+
+fn parse_volume_id(volume_id: &[u8]) -> Option<Value> {
+    let size = read_u32(volume_id, 0)? as usize;
+    let volume_label_offset = read_u32(volume_id, 12)? as usize;
+
+    let mut values: HashMap<_, _> = [
+        ("size", size.into()),
+        ("drive_type", read_u32(volume_id, 4)?.into()),
+        ("drive_serial_number", read_u32(volume_id, 8)?.into()),
+        ("volume_label_offset", volume_label_offset.into()),
+    ]
+    .into();
+
+    // An offset of 0x14 indicates that the label is only present in unicode.
+    let data = if volume_label_offset == 0x14 {
+        let offset = read_u32(volume_id, 16)? as usize;
+        let _r = values.insert("volume_label_offset_unicode", offset.into());
+        read_cstring_unicode(volume_id.get(..size)?, offset)
+    } else {
+        read_cstring(volume_id.get(..size)?, volume_label_offset)
+    };
+    let _r = values.insert("data", data.into());
+
+    Some(Value::Object(values))
+}
+
+fn parse_common_network_relative_link(link: &[u8]) -> Option<Value> {
+    let size = read_u32(link, 0)? as usize;
+    let link = link.get(..size)?;
+    let net_name_offset = read_u32(link, 8)? as usize;
+    let device_name_offset = read_u32(link, 12)? as usize;
+
+    let mut values: HashMap<_, _> = [
+        ("size", size.into()),
+        ("flags", read_u32(link, 4)?.into()),
+        ("net_name_offset", net_name_offset.into()),
+        ("device_name_offset", device_name_offset.into()),
+        ("network_provider_type", read_u32(link, 16)?.into()),
+        ("net_name", read_cstring(link, net_name_offset).into()),
+    ]
+    .into();
+    if device_name_offset != 0 {
+        let _r = values.insert("device_name", read_cstring(link, device_name_offset).into());
+    }
+
+    // The unicode offsets are only present if the net name offset is after them.
+    if net_name_offset > 0x14 {
+        let net_name_offset_unicode = read_u32(link, 20)? as usize;
+        let device_name_offset_unicode = read_u32(link, 24)? as usize;
+        let _r = values.insert("net_name_offset_unicode", net_name_offset_unicode.into());
+        let _r = values.insert(
+            "device_name_offset_unicode",
+            device_name_offset_unicode.into(),
+        );
+        let _r = values.insert(
+            "net_name_unicode",
+            read_cstring_unicode(link, net_name_offset_unicode).into(),
+        );
+        if device_name_offset_unicode != 0 {
+            let _r = values.insert(
+                "device_name_unicode",
+                read_cstring_unicode(link, device_name_offset_unicode).into(),
+            );
+        }
+    }
+
+    Some(Value::Object(values))
+}
+
+/// Parse a block containing an ANSI and an unicode path, such as the
+/// `EnvironmentVariableDataBlock`.
+fn parse_target_block(
+    block: &[u8],
+    ansi_key: &'static str,
+    unicode_key: &'static str,
+) -> Option<Value> {
+    let ansi = block.get(8..(8 + TARGET_ANSI_SIZE))?;
+    let unicode =
+        block.get((8 + TARGET_ANSI_SIZE)..(8 + TARGET_ANSI_SIZE + TARGET_UNICODE_SIZE))?;
+
+    Some(Value::object([
+        ("block_size", block.len().into()),
+        ("block_signature", read_u32(block, 4)?.into()),
+        (ansi_key, read_cstring(ansi, 0).into()),
+        (unicode_key, read_cstring_unicode(unicode, 0).into()),
+    ]))
+}
+
+fn parse_tracker_block(block: &[u8]) -> Option<Value> {
+    let machine_id = read_cstring(block.get(16..32)?, 0);
+
+    Some(Value::object([
+        ("block_size", block.len().into()),
+        ("block_signature", read_u32(block, 4)?.into()),
+        ("version", read_u32(block, 12)?.into()),
+        ("machine_id", machine_id.into()),
+        (
+            "droid_volume_identifier",
+            format_guid(block.get(32..48)?).into(),
+        ),
+        (
+            "droid_file_identifier",
+            format_guid(block.get(48..64)?).into(),
+        ),
+        (
+            "droid_birth_volume_identifier",
+            format_guid(block.get(64..80)?).into(),
+        ),
+        (
+            "droid_birth_file_identifier",
+            format_guid(block.get(80..96)?).into(),
+        ),
+    ]))
+}
+
+/// Convert a FILETIME, in 100ns intervals since 1601-01-01, into a unix timestamp.
+fn filetime_to_unix(filetime: Option<u64>) -> Option<u64> {
+    filetime.map(|v| (v / 10_000_000).saturating_sub(WINDOWS_TO_UNIX_EPOCH))
+}
+
+/// Name of the key of a hotkey, from its virtual key code.
+fn hotkey_name(key: u8) -> Option<String> {
+    match key {
+        b'0'..=b'9' | b'A'..=b'Z' => Some(char::from(key).to_string()),
+        0x70..=0x87 => Some(format!("F{}", key - 0x6F)),
+        0x90 => Some("NUM LOCK".to_owned()),
+        0x91 => Some("SCROLL LOCK".to_owned()),
+        _ => None,
+    }
+}
+
+fn format_guid(guid: &[u8]) -> Vec<u8> {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8],
+        guid[9],
+        guid[10],
+        guid[11],
+        guid[12],
+        guid[13],
+        guid[14],
+        guid[15],
+    )
+    .into_bytes()
+}
+
+/// Read a NUL-terminated string.
+fn read_cstring(mem: &[u8], offset: usize) -> Option<Vec<u8>> {
+    let s = mem.get(offset..)?;
+    let len = s.iter().position(|c| *c == b'\0').unwrap_or(s.len());
+    Some(s[..len].to_vec())
+}
+
+/// Read a NUL-terminated UTF-16 string, and convert it to UTF-8.
+fn read_cstring_unicode(mem: &[u8], offset: usize) -> Option<Vec<u8>> {
+    let s = mem.get(offset..)?;
+    let len = s
+        .chunks_exact(2)
+        .position(|c| c == [0, 0])
+        .unwrap_or(s.len() / 2);
+    Some(decode_utf16(&s[..(len * 2)]))
+}
+
+fn decode_utf16(data: &[u8]) -> Vec<u8> {
+    let chars = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]));
+    char::decode_utf16(chars)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn read_u16(mem: &[u8], offset: usize) -> Option<u16> {
+    let v = mem.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([v[0], v[1]]))
+}
+
+fn read_u32(mem: &[u8], offset: usize) -> Option<u32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+fn read_i32(mem: &[u8], offset: usize) -> Option<i32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(i32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+fn read_u64(mem: &[u8], offset: usize) -> Option<u64> {
+    let v = mem.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes([
+        v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7],
+    ]))
+}
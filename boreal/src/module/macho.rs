@@ -15,13 +15,19 @@ use super::{
     EvalContext, Module, ModuleData, ModuleDataMap, ScanContext, StaticValue, Type, Value,
 };
 
+mod code_signature;
+mod load_commands;
+
 const MAX_NB_ARCHS: usize = 100;
 const MAX_NB_SEGMENTS: usize = 32_768;
 const MAX_NB_SECTIONS: usize = 32_768;
 
 /// `macho` module. Allows inspecting Mach-O inputs
-#[derive(Debug)]
-pub struct MachO;
+#[derive(Copy, Clone, Default, Debug)]
+pub struct MachO {
+    #[cfg(feature = "authenticode")]
+    token: Option<authenticode_parser::InitializationToken>,
+}
 
 impl Module for MachO {
     fn get_name(&self) -> &'static str {
@@ -587,6 +593,80 @@ impl Module for MachO {
                 "S_ATTR_LOC_RELOC",
                 StaticValue::Integer(macho::S_ATTR_LOC_RELOC.into()),
             ),
+            // Code signature flags and hash types
+            (
+                "CS_ADHOC",
+                StaticValue::Integer(code_signature::CS_ADHOC.into()),
+            ),
+            (
+                "CS_HARD",
+                StaticValue::Integer(code_signature::CS_HARD.into()),
+            ),
+            (
+                "CS_KILL",
+                StaticValue::Integer(code_signature::CS_KILL.into()),
+            ),
+            (
+                "CS_RESTRICT",
+                StaticValue::Integer(code_signature::CS_RESTRICT.into()),
+            ),
+            (
+                "CS_ENFORCEMENT",
+                StaticValue::Integer(code_signature::CS_ENFORCEMENT.into()),
+            ),
+            (
+                "CS_REQUIRE_LV",
+                StaticValue::Integer(code_signature::CS_REQUIRE_LV.into()),
+            ),
+            (
+                "CS_RUNTIME",
+                StaticValue::Integer(code_signature::CS_RUNTIME.into()),
+            ),
+            (
+                "CS_LINKER_SIGNED",
+                StaticValue::Integer(code_signature::CS_LINKER_SIGNED.into()),
+            ),
+            (
+                "CS_HASHTYPE_SHA1",
+                StaticValue::Integer(code_signature::CS_HASHTYPE_SHA1.into()),
+            ),
+            (
+                "CS_HASHTYPE_SHA256",
+                StaticValue::Integer(code_signature::CS_HASHTYPE_SHA256.into()),
+            ),
+            (
+                "CS_HASHTYPE_SHA256_TRUNCATED",
+                StaticValue::Integer(code_signature::CS_HASHTYPE_SHA256_TRUNCATED.into()),
+            ),
+            (
+                "CS_HASHTYPE_SHA384",
+                StaticValue::Integer(code_signature::CS_HASHTYPE_SHA384.into()),
+            ),
+            // Load commands helper functions
+            (
+                "has_dylib",
+                StaticValue::function(Self::has_dylib, vec![vec![Type::Bytes]], Type::Integer),
+            ),
+            (
+                "has_rpath",
+                StaticValue::function(Self::has_rpath, vec![vec![Type::Bytes]], Type::Integer),
+            ),
+            (
+                "has_entitlement",
+                StaticValue::function(
+                    Self::has_entitlement,
+                    vec![vec![Type::Bytes]],
+                    Type::Integer,
+                ),
+            ),
+            (
+                "has_import",
+                StaticValue::function(Self::has_import, vec![vec![Type::Bytes]], Type::Integer),
+            ),
+            (
+                "has_export",
+                StaticValue::function(Self::has_export, vec![vec![Type::Bytes]], Type::Integer),
+            ),
             // Mach-O fat binary helper functions
             (
                 "file_index_for_arch",
@@ -655,6 +735,104 @@ impl Module for MachO {
             // Entry point and stack size
             ("entry_point", Type::Integer),
             ("stack_size", Type::Integer),
+            // Dylibs
+            ("number_of_dylibs", Type::Integer),
+            (
+                "dylibs",
+                Type::array(Type::object([
+                    ("name", Type::Bytes),
+                    ("timestamp", Type::Integer),
+                    ("compatibility_version", Type::Bytes),
+                    ("current_version", Type::Bytes),
+                ])),
+            ),
+            ("dynamic_linker", Type::Bytes),
+            // Rpaths
+            ("number_of_rpaths", Type::Integer),
+            ("rpaths", Type::array(Type::Bytes)),
+            // Versions
+            ("uuid", Type::Bytes),
+            (
+                "build_version",
+                Type::object([
+                    ("platform", Type::Integer),
+                    ("minos", Type::Bytes),
+                    ("sdk", Type::Bytes),
+                    ("ntools", Type::Integer),
+                    (
+                        "tools",
+                        Type::array(Type::object([
+                            ("tool", Type::Integer),
+                            ("version", Type::Bytes),
+                        ])),
+                    ),
+                ]),
+            ),
+            (
+                "min_version",
+                Type::object([
+                    ("device", Type::Integer),
+                    ("version", Type::Bytes),
+                    ("sdk", Type::Bytes),
+                ]),
+            ),
+            ("source_version", Type::Bytes),
+            // Code signature
+            ("number_of_entitlements", Type::Integer),
+            ("entitlements", Type::array(Type::Bytes)),
+            (
+                "code_signature_data",
+                Type::object([("dataoff", Type::Integer), ("datasize", Type::Integer)]),
+            ),
+            ("number_of_signatures", Type::Integer),
+            (
+                "signatures",
+                Type::array(Type::object([
+                    ("version", Type::Integer),
+                    ("flags", Type::Integer),
+                    ("identifier", Type::Bytes),
+                    ("team_id", Type::Bytes),
+                    ("hash_type", Type::Integer),
+                    ("hash_size", Type::Integer),
+                    ("platform", Type::Integer),
+                    ("page_size", Type::Integer),
+                    ("number_of_special_slots", Type::Integer),
+                    ("number_of_code_slots", Type::Integer),
+                    ("code_limit", Type::Integer),
+                    #[cfg(feature = "hash")]
+                    ("cdhash", Type::Bytes),
+                ])),
+            ),
+            ("number_of_requirements", Type::Integer),
+            (
+                "requirements",
+                Type::array(Type::object([
+                    ("type", Type::Integer),
+                    ("data", Type::Bytes),
+                ])),
+            ),
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+            ("number_of_certificates", Type::Integer),
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+            (
+                "certificates",
+                Type::array(Type::object([
+                    ("thumbprint", Type::Bytes),
+                    ("issuer", Type::Bytes),
+                    ("subject", Type::Bytes),
+                    ("version", Type::Integer),
+                    ("algorithm", Type::Bytes),
+                    ("algorithm_oid", Type::Bytes),
+                    ("serial", Type::Bytes),
+                    ("not_before", Type::Integer),
+                    ("not_after", Type::Integer),
+                ])),
+            ),
+            // Symbols
+            ("number_of_exports", Type::Integer),
+            ("exports", Type::array(Type::Bytes)),
+            ("number_of_imports", Type::Integer),
+            ("imports", Type::array(Type::Bytes)),
         ];
 
         // Declare types only used for FAT files
@@ -685,7 +863,11 @@ impl Module for MachO {
     }
 
     fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
-        data_map.insert::<Self>(Data::default());
+        data_map.insert::<Self>(Data {
+            #[cfg(feature = "authenticode")]
+            token: self.token,
+            ..Data::default()
+        });
     }
 
     fn get_dynamic_values(&self, ctx: &mut ScanContext, out: &mut HashMap<&'static str, Value>) {
@@ -707,6 +889,20 @@ impl Module for MachO {
 #[derive(Default)]
 pub struct Data {
     files: Vec<FileData>,
+
+    /// Set if a Mach-O file was parsed, either directly or in a fat file.
+    found_macho: bool,
+
+    // Names listed in the load commands of all the parsed files.
+    dylibs: Vec<Vec<u8>>,
+    rpaths: Vec<Vec<u8>>,
+    entitlements: Vec<Vec<u8>>,
+    imports: Vec<Vec<u8>>,
+    exports: Vec<Vec<u8>>,
+
+    /// Token used to parse the certificates of the code signatures, if enabled.
+    #[cfg(feature = "authenticode")]
+    token: Option<authenticode_parser::InitializationToken>,
 }
 
 struct FileData {
@@ -721,6 +917,24 @@ impl ModuleData for MachO {
 }
 
 impl MachO {
+    /// Create a Mach-O module with the parsing of the code signature certificates enabled.
+    ///
+    /// # Safety
+    ///
+    /// The certificates parsing requires creating OpenSSL objects, which is not thread-safe and
+    /// should be done while no other calls into OpenSSL can race with this call. Therefore,
+    /// this function should for example be called before setting up any multithreaded environment.
+    #[must_use]
+    #[cfg(feature = "authenticode")]
+    pub unsafe fn new_with_signatures() -> Self {
+        Self {
+            token: Some(
+                // Safety: guaranteed by the safety contract of this function
+                unsafe { authenticode_parser::InitializationToken::new() },
+            ),
+        }
+    }
+
     fn file_index_for_arch(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
         let mut args = args.into_iter();
         let v1: i64 = args.next()?.try_into().ok()?;
@@ -763,6 +977,45 @@ impl MachO {
         }
         None
     }
+
+    fn has_dylib(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_name(ctx, args, |data| &data.dylibs)
+    }
+
+    fn has_rpath(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_name(ctx, args, |data| &data.rpaths)
+    }
+
+    fn has_entitlement(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_name(ctx, args, |data| &data.entitlements)
+    }
+
+    fn has_import(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_name(ctx, args, |data| &data.imports)
+    }
+
+    fn has_export(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        has_name(ctx, args, |data| &data.exports)
+    }
+}
+
+/// Check if a name is in a list of the module data.
+///
+/// For fat files, the names of every architecture are checked.
+fn has_name<F>(ctx: &mut EvalContext, args: Vec<Value>, get_list: F) -> Option<Value>
+where
+    F: FnOnce(&Data) -> &[Vec<u8>],
+{
+    let mut args = args.into_iter();
+    let name: Vec<u8> = args.next()?.try_into().ok()?;
+
+    let data = ctx.module_data.get::<MachO>()?;
+    if !data.found_macho {
+        return None;
+    }
+
+    let res = get_list(data).contains(&name);
+    Some(Value::Integer(res.into()))
 }
 
 fn parse_file(
@@ -782,7 +1035,8 @@ fn parse_file(
                 region,
                 process_memory,
                 None,
-                add_file_to_data.then_some(data),
+                data,
+                add_file_to_data,
                 arch_offset,
             ))
         }
@@ -795,7 +1049,8 @@ fn parse_file(
                 region,
                 process_memory,
                 Some(header.reserved.get(e)),
-                add_file_to_data.then_some(data),
+                data,
+                add_file_to_data,
                 arch_offset,
             ))
         }
@@ -806,13 +1061,15 @@ fn parse_file(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn parse_header<Mach: MachHeader<Endian = Endianness>>(
     header: &Mach,
     e: Endianness,
     region: &Region,
     process_memory: bool,
     reserved: Option<u32>,
-    data: Option<&mut Data>,
+    data: &mut Data,
+    add_file_to_data: bool,
     arch_offset: u64,
 ) -> HashMap<&'static str, Value> {
     let cputype = header.cputype(e);
@@ -823,7 +1080,8 @@ fn parse_header<Mach: MachHeader<Endian = Endianness>>(
 
     let (entry_point, stack_size) = entry_point_data(header, e, region, process_memory, cputype);
 
-    if let Some(data) = data {
+    data.found_macho = true;
+    if add_file_to_data {
         data.files.push(FileData {
             cputype,
             cpusubtype,
@@ -832,7 +1090,7 @@ fn parse_header<Mach: MachHeader<Endian = Endianness>>(
         });
     }
 
-    [
+    let mut out: HashMap<_, _> = [
         ("magic", header.magic().into()),
         ("cputype", cputype.into()),
         ("cpusubtype", cpusubtype.into()),
@@ -846,7 +1104,11 @@ fn parse_header<Mach: MachHeader<Endian = Endianness>>(
         ("entry_point", entry_point.into()),
         ("stack_size", stack_size.into()),
     ]
-    .into()
+    .into();
+
+    load_commands::parse(header, e, region.mem, process_memory, &mut out, data);
+
+    out
 }
 
 fn segments<Mach: MachHeader<Endian = Endianness>>(
@@ -0,0 +1,351 @@
+//! Parsing of the code signature, referenced by the `LC_CODE_SIGNATURE` load command.
+//!
+//! The code signature is a super blob, made of an index of blobs: code directories,
+//! requirements, entitlements and a CMS signature. All of it is stored in big endian.
+use std::collections::HashMap;
+
+use super::Value;
+#[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+use crate::module::pe::signatures::{parse_certificates, Backend};
+
+const MAX_NB_BLOBS: usize = 1024;
+const MAX_NB_ENTITLEMENTS: usize = 1024;
+const MAX_NB_REQUIREMENTS: usize = 64;
+
+/// Magic of the code signature super blob.
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade_0cc0;
+/// Magic of a code directory.
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade_0c02;
+/// Magic of the super blob containing the requirements.
+const CSMAGIC_REQUIREMENTS: u32 = 0xfade_0c01;
+/// Magic of a single requirement.
+const CSMAGIC_REQUIREMENT: u32 = 0xfade_0c00;
+/// Magic of the blob containing the entitlements, as a XML plist.
+const CSMAGIC_EMBEDDED_ENTITLEMENTS: u32 = 0xfade_7171;
+/// Magic of the blob wrapping the CMS signature.
+#[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+const CSMAGIC_BLOBWRAPPER: u32 = 0xfade_0b01;
+
+// Versions of the code directory adding new fields.
+const CS_SUPPORTSTEAMID: u32 = 0x20200;
+
+// Flags of a code directory.
+pub(super) const CS_ADHOC: u32 = 0x0000_0002;
+pub(super) const CS_HARD: u32 = 0x0000_0100;
+pub(super) const CS_KILL: u32 = 0x0000_0200;
+pub(super) const CS_RESTRICT: u32 = 0x0000_0800;
+pub(super) const CS_ENFORCEMENT: u32 = 0x0000_1000;
+pub(super) const CS_REQUIRE_LV: u32 = 0x0000_2000;
+pub(super) const CS_RUNTIME: u32 = 0x0001_0000;
+pub(super) const CS_LINKER_SIGNED: u32 = 0x0002_0000;
+
+// Hash types of a code directory.
+pub(super) const CS_HASHTYPE_SHA1: u8 = 1;
+pub(super) const CS_HASHTYPE_SHA256: u8 = 2;
+pub(super) const CS_HASHTYPE_SHA256_TRUNCATED: u8 = 3;
+pub(super) const CS_HASHTYPE_SHA384: u8 = 4;
+
+/// Length of a cdhash, as used by the OS: longer hashes are truncated.
+#[cfg(feature = "hash")]
+const CS_CDHASH_LEN: usize = 20;
+
+/// Parse the code signature, adding the values to the output.
+///
+/// Certificates are only parsed if a signatures backend is available, see
+/// [`crate::module::MachO::new_with_signatures`].
+pub(super) fn parse(
+    code_signature: &[u8],
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))] backend: Option<Backend>,
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let mut signatures = Vec::new();
+    let mut requirements = Vec::new();
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+    let mut certificates = Vec::new();
+
+    for (_, blob) in blobs(code_signature) {
+        match read_be_u32(blob, 0) {
+            Some(CSMAGIC_CODEDIRECTORY) => {
+                if let Some(value) = parse_code_directory(blob) {
+                    signatures.push(value);
+                }
+            }
+            Some(CSMAGIC_REQUIREMENTS) => {
+                requirements.extend(parse_requirements(blob));
+            }
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+            Some(CSMAGIC_BLOBWRAPPER) => {
+                if let Some(backend) = backend {
+                    certificates.extend(parse_certificates(&blob[8..], backend));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let _r = out.insert("number_of_signatures", signatures.len().into());
+    let _r = out.insert("signatures", Value::Array(signatures));
+    let _r = out.insert("number_of_requirements", requirements.len().into());
+    let _r = out.insert("requirements", Value::Array(requirements));
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+    if backend.is_some() {
+        let _r = out.insert("number_of_certificates", certificates.len().into());
+        let _r = out.insert("certificates", Value::Array(certificates));
+    }
+}
+
+/// Retrieve the keys of the entitlements plist stored in the code signature.
+pub(super) fn parse_entitlements(code_signature: &[u8]) -> Vec<Vec<u8>> {
+    let Some((_, blob)) = blobs(code_signature)
+        .find(|(_, blob)| read_be_u32(blob, 0) == Some(CSMAGIC_EMBEDDED_ENTITLEMENTS))
+    else {
+        return Vec::new();
+    };
+
+    let mut entitlements = Vec::new();
+    let mut plist = &blob[8..];
+    while entitlements.len() < MAX_NB_ENTITLEMENTS {
+        let Some(start) = memchr::memmem::find(plist, b"<key>") else {
+            break;
+        };
+        plist = &plist[(start + 5)..];
+        let Some(end) = memchr::memmem::find(plist, b"</key>") else {
+            break;
+        };
+        entitlements.push(plist[..end].to_vec());
+        plist = &plist[(end + 6)..];
+    }
+    entitlements
+}
+
+/// Iterate over the blobs of a super blob, with the type listed in the index.
+///
+/// Each blob starts with its magic and its length, which includes those two fields. Blobs
+/// whose length is invalid are skipped.
+fn blobs(super_blob: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    let count = match read_be_u32(super_blob, 0) {
+        Some(CSMAGIC_EMBEDDED_SIGNATURE | CSMAGIC_REQUIREMENTS) => {
+            read_be_u32(super_blob, 8).unwrap_or(0)
+        }
+        _ => 0,
+    };
+
+    (0..std::cmp::min(count as usize, MAX_NB_BLOBS)).filter_map(move |i| {
+        // Each index entry is a type and an offset, after the magic, length and count.
+        let typ = read_be_u32(super_blob, 12 + i * 8)?;
+        let offset = read_be_u32(super_blob, 12 + i * 8 + 4)? as usize;
+        let length = read_be_u32(super_blob, offset.checked_add(4)?)? as usize;
+        if length < 8 {
+            return None;
+        }
+        let blob = super_blob.get(offset..offset.checked_add(length)?)?;
+        Some((typ, blob))
+    })
+}
+
+fn parse_code_directory(blob: &[u8]) -> Option<Value> {
+    let version = read_be_u32(blob, 8)?;
+    let flags = read_be_u32(blob, 12)?;
+    let ident_offset = read_be_u32(blob, 20)?;
+    let nb_special_slots = read_be_u32(blob, 24)?;
+    let nb_code_slots = read_be_u32(blob, 28)?;
+    let code_limit = read_be_u32(blob, 32)?;
+    let [hash_size, hash_type, platform, page_size] = *blob.get(36..40)? else {
+        return None;
+    };
+
+    let team_id = if version >= CS_SUPPORTSTEAMID {
+        read_be_u32(blob, 48)
+            .filter(|offset| *offset != 0)
+            .and_then(|offset| read_cstr(blob, offset))
+    } else {
+        None
+    };
+
+    Some(Value::object([
+        ("version", version.into()),
+        ("flags", flags.into()),
+        ("identifier", read_cstr(blob, ident_offset).into()),
+        ("team_id", team_id.into()),
+        ("hash_type", hash_type.into()),
+        ("hash_size", hash_size.into()),
+        ("platform", platform.into()),
+        (
+            "page_size",
+            if page_size == 0 {
+                Value::Integer(0)
+            } else {
+                1_u64.checked_shl(page_size.into()).into()
+            },
+        ),
+        ("number_of_special_slots", nb_special_slots.into()),
+        ("number_of_code_slots", nb_code_slots.into()),
+        ("code_limit", code_limit.into()),
+        #[cfg(feature = "hash")]
+        ("cdhash", cdhash(blob, hash_type).into()),
+    ]))
+}
+
+/// Compute the cdhash of a code directory: the hash of the whole blob, using the hash type
+/// of the code directory.
+#[cfg(feature = "hash")]
+fn cdhash(blob: &[u8], hash_type: u8) -> Option<Vec<u8>> {
+    use sha1::Digest;
+
+    let mut hash = match hash_type {
+        CS_HASHTYPE_SHA1 => sha1::Sha1::digest(blob).to_vec(),
+        CS_HASHTYPE_SHA256 | CS_HASHTYPE_SHA256_TRUNCATED => sha2::Sha256::digest(blob).to_vec(),
+        CS_HASHTYPE_SHA384 => sha2::Sha384::digest(blob).to_vec(),
+        _ => return None,
+    };
+    hash.truncate(CS_CDHASH_LEN);
+    Some(hex::encode(hash).into_bytes())
+}
+
+/// List the requirements, with their type and the raw compiled expression.
+fn parse_requirements(blob: &[u8]) -> Vec<Value> {
+    blobs(blob)
+        .filter(|(_, requirement)| read_be_u32(requirement, 0) == Some(CSMAGIC_REQUIREMENT))
+        .take(MAX_NB_REQUIREMENTS)
+        .map(|(typ, requirement)| {
+            Value::object([
+                ("type", typ.into()),
+                ("data", requirement[8..].to_vec().into()),
+            ])
+        })
+        .collect()
+}
+
+/// Read a nul-terminated string at the given offset.
+fn read_cstr(mem: &[u8], offset: u32) -> Option<Vec<u8>> {
+    let s = mem.get((offset as usize)..)?;
+    let len = memchr::memchr(b'\0', s)?;
+    Some(s[..len].to_vec())
+}
+
+fn read_be_u32(mem: &[u8], offset: usize) -> Option<u32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(magic: u32, content: &[u8]) -> Vec<u8> {
+        let mut res = magic.to_be_bytes().to_vec();
+        res.extend((u32::try_from(content.len()).unwrap() + 8).to_be_bytes());
+        res.extend(content);
+        res
+    }
+
+    fn super_blob(magic: u32, blobs: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut index = Vec::new();
+        let mut data = Vec::new();
+        let mut offset = 12 + 8 * blobs.len();
+        for (typ, blob) in blobs {
+            index.extend(typ.to_be_bytes());
+            index.extend(u32::try_from(offset).unwrap().to_be_bytes());
+            data.extend_from_slice(blob);
+            offset += blob.len();
+        }
+        let mut content = u32::try_from(blobs.len()).unwrap().to_be_bytes().to_vec();
+        content.extend(index);
+        content.extend(data);
+        blob(magic, &content)
+    }
+
+    fn code_directory() -> Vec<u8> {
+        let mut content = Vec::new();
+        content.extend(0x20400_u32.to_be_bytes()); // version
+        content.extend(0x2_u32.to_be_bytes()); // flags
+        content.extend(0_u32.to_be_bytes()); // hash offset
+        content.extend(88_u32.to_be_bytes()); // ident offset
+        content.extend(2_u32.to_be_bytes()); // nb special slots
+        content.extend(3_u32.to_be_bytes()); // nb code slots
+        content.extend(0x3000_u32.to_be_bytes()); // code limit
+        content.extend([32, CS_HASHTYPE_SHA256, 0, 12]);
+        content.extend(0_u32.to_be_bytes()); // spare2
+        content.extend(0_u32.to_be_bytes()); // scatter offset
+        content.extend(92_u32.to_be_bytes()); // team offset
+        content.resize(80, 0);
+        content.extend(b"foo\0TEAM\0");
+        blob(CSMAGIC_CODEDIRECTORY, &content)
+    }
+
+    #[test]
+    fn test_parse() {
+        let requirements = super_blob(
+            CSMAGIC_REQUIREMENTS,
+            &[(3, blob(CSMAGIC_REQUIREMENT, b"\x00\x00\x00\x01abcd"))],
+        );
+        let code_signature = super_blob(
+            CSMAGIC_EMBEDDED_SIGNATURE,
+            &[
+                (0, code_directory()),
+                (2, requirements),
+                (
+                    5,
+                    blob(
+                        CSMAGIC_EMBEDDED_ENTITLEMENTS,
+                        b"<plist><dict><key>a</key><true/><key>b.c</key></dict></plist>",
+                    ),
+                ),
+            ],
+        );
+
+        let mut out = HashMap::new();
+        parse(
+            &code_signature,
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+            None,
+            &mut out,
+        );
+        assert!(matches!(out["number_of_signatures"], Value::Integer(1)));
+        let Value::Array(signatures) = &out["signatures"] else {
+            panic!("invalid signatures: {out:?}");
+        };
+        let Value::Object(cd) = &signatures[0] else {
+            panic!("invalid code directory: {signatures:?}");
+        };
+        assert!(matches!(&cd["identifier"], Value::Bytes(v) if v == b"foo"));
+        assert!(matches!(&cd["team_id"], Value::Bytes(v) if v == b"TEAM"));
+        assert!(matches!(cd["flags"], Value::Integer(2)));
+        assert!(matches!(cd["hash_type"], Value::Integer(2)));
+        assert!(matches!(cd["page_size"], Value::Integer(4096)));
+        assert!(matches!(cd["number_of_code_slots"], Value::Integer(3)));
+
+        assert!(matches!(out["number_of_requirements"], Value::Integer(1)));
+        let Value::Array(requirements) = &out["requirements"] else {
+            panic!("invalid requirements: {out:?}");
+        };
+        let Value::Object(requirement) = &requirements[0] else {
+            panic!("invalid requirement: {requirements:?}");
+        };
+        assert!(matches!(requirement["type"], Value::Integer(3)));
+        assert!(matches!(&requirement["data"], Value::Bytes(v) if v == b"\x00\x00\x00\x01abcd"));
+
+        assert_eq!(
+            parse_entitlements(&code_signature),
+            [b"a".to_vec(), b"b.c".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let mut out = HashMap::new();
+        let mut code_signature = super_blob(CSMAGIC_EMBEDDED_SIGNATURE, &[(0, code_directory())]);
+        // Invalid length for the blob
+        code_signature[24..28].copy_from_slice(&0xFFFF_u32.to_be_bytes());
+        parse(
+            &code_signature,
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+            None,
+            &mut out,
+        );
+        assert!(matches!(out["number_of_signatures"], Value::Integer(0)));
+
+        assert!(parse_entitlements(b"").is_empty());
+        assert_eq!(blobs(&blob(CSMAGIC_CODEDIRECTORY, b"")).count(), 0);
+    }
+}
@@ -0,0 +1,548 @@
+//! Parsing of the load commands describing the linking and the build of a Mach-O file.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use object::macho::{
+    self, BuildToolVersion, BuildVersionCommand, DyldInfoCommand, DylibCommand, DylinkerCommand,
+    LinkeditDataCommand, RpathCommand, SourceVersionCommand, UuidCommand, VersionMinCommand,
+};
+use object::read::macho::{LoadCommandData, MachHeader};
+use object::{Endianness, U32};
+
+use super::{code_signature, Data, Value};
+
+const MAX_NB_DYLIBS: usize = 1024;
+const MAX_NB_RPATHS: usize = 1024;
+const MAX_NB_TOOLS: usize = 128;
+const MAX_NB_EXPORTS: usize = 65_536;
+const MAX_NB_IMPORTS: usize = 65_536;
+
+// Bind opcodes
+const BIND_OPCODE_MASK: u8 = 0xF0;
+const BIND_IMMEDIATE_MASK: u8 = 0x0F;
+const BIND_OPCODE_DONE: u8 = 0x00;
+const BIND_OPCODE_SET_DYLIB_ORDINAL_IMM: u8 = 0x10;
+const BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB: u8 = 0x20;
+const BIND_OPCODE_SET_DYLIB_SPECIAL_IMM: u8 = 0x30;
+const BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM: u8 = 0x40;
+const BIND_OPCODE_SET_TYPE_IMM: u8 = 0x50;
+const BIND_OPCODE_SET_ADDEND_SLEB: u8 = 0x60;
+const BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB: u8 = 0x70;
+const BIND_OPCODE_ADD_ADDR_ULEB: u8 = 0x80;
+const BIND_OPCODE_DO_BIND: u8 = 0x90;
+const BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB: u8 = 0xA0;
+const BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED: u8 = 0xB0;
+const BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB: u8 = 0xC0;
+const BIND_OPCODE_THREADED: u8 = 0xD0;
+const BIND_SUBOPCODE_THREADED_SET_BIND_ORDINAL_TABLE_SIZE_ULEB: u8 = 0x00;
+
+/// Parse the load commands of a file, adding the values to the output.
+///
+/// The names of the dylibs, rpaths, entitlements, imports and exports are also saved in the
+/// module data, to be used by the module functions.
+///
+/// The code signature, imports and exports are stored in the `__LINKEDIT` segment, and are
+/// referenced through file offsets, so they are not retrieved when scanning process memory.
+pub(super) fn parse<Mach: MachHeader<Endian = Endianness>>(
+    header: &Mach,
+    e: Endianness,
+    mem: &[u8],
+    process_memory: bool,
+    out: &mut HashMap<&'static str, Value>,
+    data: &mut Data,
+) {
+    let mut dylibs = Vec::new();
+    let mut rpaths = Vec::new();
+    let mut entitlements = Vec::new();
+    let mut exports = Vec::new();
+    let mut imports = Vec::new();
+
+    let Ok(mut cmds) = header.load_commands(e, mem, 0) else {
+        return;
+    };
+    while let Ok(Some(cmd)) = cmds.next() {
+        match cmd.cmd() {
+            macho::LC_ID_DYLIB
+            | macho::LC_LOAD_DYLIB
+            | macho::LC_LOAD_WEAK_DYLIB
+            | macho::LC_REEXPORT_DYLIB
+            | macho::LC_LAZY_LOAD_DYLIB
+            | macho::LC_LOAD_UPWARD_DYLIB => {
+                if dylibs.len() < MAX_NB_DYLIBS {
+                    if let Some((name, value)) = parse_dylib(cmd, e) {
+                        data.dylibs.push(name);
+                        dylibs.push(value);
+                    }
+                }
+            }
+            macho::LC_RPATH => {
+                if rpaths.len() < MAX_NB_RPATHS {
+                    if let Some(path) = parse_lc_str(cmd, e, |c: &RpathCommand<_>| c.path) {
+                        data.rpaths.push(path.clone());
+                        rpaths.push(path.into());
+                    }
+                }
+            }
+            macho::LC_LOAD_DYLINKER => {
+                if let Some(name) = parse_lc_str(cmd, e, |c: &DylinkerCommand<_>| c.name) {
+                    let _r = out.insert("dynamic_linker", name.into());
+                }
+            }
+            macho::LC_UUID => {
+                if let Ok(cmd) = cmd.data::<UuidCommand<Endianness>>() {
+                    let _r = out.insert("uuid", format_uuid(&cmd.uuid).into());
+                }
+            }
+            macho::LC_BUILD_VERSION => {
+                if let Some(value) = parse_build_version(cmd, e) {
+                    let _r = out.insert("build_version", value);
+                }
+            }
+            macho::LC_VERSION_MIN_MACOSX
+            | macho::LC_VERSION_MIN_IPHONEOS
+            | macho::LC_VERSION_MIN_TVOS
+            | macho::LC_VERSION_MIN_WATCHOS => {
+                if let Ok(v) = cmd.data::<VersionMinCommand<Endianness>>() {
+                    let _r = out.insert(
+                        "min_version",
+                        Value::object([
+                            ("device", v.cmd.get(e).into()),
+                            ("version", format_version(v.version.get(e)).into()),
+                            ("sdk", format_version(v.sdk.get(e)).into()),
+                        ]),
+                    );
+                }
+            }
+            macho::LC_SOURCE_VERSION => {
+                if let Ok(v) = cmd.data::<SourceVersionCommand<Endianness>>() {
+                    let _r = out.insert(
+                        "source_version",
+                        format_source_version(v.version.get(e)).into(),
+                    );
+                }
+            }
+            macho::LC_CODE_SIGNATURE => {
+                if let Ok(c) = cmd.data::<LinkeditDataCommand<Endianness>>() {
+                    let _r = out.insert(
+                        "code_signature_data",
+                        Value::object([
+                            ("dataoff", c.dataoff.get(e).into()),
+                            ("datasize", c.datasize.get(e).into()),
+                        ]),
+                    );
+                }
+                if !process_memory {
+                    if let Some(blob) = linkedit_data(cmd, e, mem) {
+                        entitlements = code_signature::parse_entitlements(blob);
+                        code_signature::parse(
+                            blob,
+                            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+                            crate::module::pe::signatures::Backend::new(
+                                #[cfg(feature = "authenticode")]
+                                data.token,
+                            ),
+                            out,
+                        );
+                    }
+                }
+            }
+            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY if !process_memory => {
+                if let Ok(info) = cmd.data::<DyldInfoCommand<Endianness>>() {
+                    let get = |off: &U32<_>, size: &U32<_>| {
+                        get_range(mem, off.get(e), size.get(e)).unwrap_or(&[])
+                    };
+                    exports.extend(parse_export_trie(get(&info.export_off, &info.export_size)));
+                    for (off, size) in [
+                        (&info.bind_off, &info.bind_size),
+                        (&info.weak_bind_off, &info.weak_bind_size),
+                        (&info.lazy_bind_off, &info.lazy_bind_size),
+                    ] {
+                        parse_bind_opcodes(get(off, size), &mut imports);
+                    }
+                }
+            }
+            macho::LC_DYLD_EXPORTS_TRIE if !process_memory => {
+                if let Some(data) = linkedit_data(cmd, e, mem) {
+                    exports.extend(parse_export_trie(data));
+                }
+            }
+            macho::LC_DYLD_CHAINED_FIXUPS if !process_memory => {
+                if let Some(data) = linkedit_data(cmd, e, mem) {
+                    parse_chained_fixups_imports(data, &mut imports);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    exports.truncate(MAX_NB_EXPORTS);
+    imports.truncate(MAX_NB_IMPORTS);
+
+    let _r = out.insert("number_of_dylibs", dylibs.len().into());
+    let _r = out.insert("dylibs", Value::Array(dylibs));
+    let _r = out.insert("number_of_rpaths", rpaths.len().into());
+    let _r = out.insert("rpaths", Value::Array(rpaths));
+
+    if !process_memory {
+        data.entitlements.extend(entitlements.iter().cloned());
+        data.exports.extend(exports.iter().cloned());
+        data.imports.extend(imports.iter().cloned());
+
+        let mut add_list = |count_name, list_name, list: Vec<Vec<u8>>| {
+            let _r = out.insert(count_name, list.len().into());
+            let _r = out.insert(
+                list_name,
+                Value::Array(list.into_iter().map(Value::Bytes).collect()),
+            );
+        };
+        add_list("number_of_entitlements", "entitlements", entitlements);
+        add_list("number_of_exports", "exports", exports);
+        add_list("number_of_imports", "imports", imports);
+    }
+}
+
+fn parse_dylib(cmd: LoadCommandData<Endianness>, e: Endianness) -> Option<(Vec<u8>, Value)> {
+    let dylib = &cmd.data::<DylibCommand<Endianness>>().ok()?.dylib;
+    let name = cmd.string(e, dylib.name).ok()?.to_vec();
+
+    let value = Value::object([
+        ("name", name.clone().into()),
+        ("timestamp", dylib.timestamp.get(e).into()),
+        (
+            "compatibility_version",
+            format_version(dylib.compatibility_version.get(e)).into(),
+        ),
+        (
+            "current_version",
+            format_version(dylib.current_version.get(e)).into(),
+        ),
+    ]);
+    Some((name, value))
+}
+
+/// Retrieve the string referenced by a load command.
+fn parse_lc_str<T, F>(cmd: LoadCommandData<Endianness>, e: Endianness, f: F) -> Option<Vec<u8>>
+where
+    T: object::Pod,
+    F: FnOnce(&T) -> macho::LcStr<Endianness>,
+{
+    let s = f(cmd.data::<T>().ok()?);
+    cmd.string(e, s).ok().map(<[u8]>::to_vec)
+}
+
+fn parse_build_version(cmd: LoadCommandData<Endianness>, e: Endianness) -> Option<Value> {
+    let v = cmd.data::<BuildVersionCommand<Endianness>>().ok()?;
+    let ntools = v.ntools.get(e);
+
+    // The tools are stored right after the command.
+    let tools_data = cmd
+        .raw_data()
+        .get(std::mem::size_of::<BuildVersionCommand<Endianness>>()..)
+        .unwrap_or(&[]);
+    let tools = tools_data
+        .chunks_exact(std::mem::size_of::<BuildToolVersion<Endianness>>())
+        .take(std::cmp::min(ntools as usize, MAX_NB_TOOLS))
+        .filter_map(|tool| object::pod::from_bytes::<BuildToolVersion<Endianness>>(tool).ok())
+        .map(|(tool, _)| {
+            Value::object([
+                ("tool", tool.tool.get(e).into()),
+                ("version", format_version(tool.version.get(e)).into()),
+            ])
+        })
+        .collect();
+
+    Some(Value::object([
+        ("platform", v.platform.get(e).into()),
+        ("minos", format_version(v.minos.get(e)).into()),
+        ("sdk", format_version(v.sdk.get(e)).into()),
+        ("ntools", ntools.into()),
+        ("tools", Value::Array(tools)),
+    ]))
+}
+
+/// Retrieve the data referenced by a `linkedit_data_command`.
+fn linkedit_data<'a>(
+    cmd: LoadCommandData<Endianness>,
+    e: Endianness,
+    mem: &'a [u8],
+) -> Option<&'a [u8]> {
+    let cmd = cmd.data::<LinkeditDataCommand<Endianness>>().ok()?;
+    get_range(mem, cmd.dataoff.get(e), cmd.datasize.get(e))
+}
+
+fn get_range(mem: &[u8], offset: u32, size: u32) -> Option<&[u8]> {
+    let offset = offset as usize;
+    mem.get(offset..offset.checked_add(size as usize)?)
+}
+
+/// Format a version encoded as `xxxx.yy.zz`.
+fn format_version(version: u32) -> Vec<u8> {
+    format!(
+        "{}.{}.{}",
+        version >> 16,
+        (version >> 8) & 0xFF,
+        version & 0xFF
+    )
+    .into_bytes()
+}
+
+/// Format a source version, encoded as `a24.b10.c10.d10.e10`.
+fn format_source_version(version: u64) -> Vec<u8> {
+    format!(
+        "{}.{}.{}.{}.{}",
+        version >> 40,
+        (version >> 30) & 0x3FF,
+        (version >> 20) & 0x3FF,
+        (version >> 10) & 0x3FF,
+        version & 0x3FF
+    )
+    .into_bytes()
+}
+
+fn format_uuid(uuid: &[u8; 16]) -> Vec<u8> {
+    let mut res = String::with_capacity(36);
+    for (i, b) in uuid.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            res.push('-');
+        }
+        let _r = write!(res, "{b:02X}");
+    }
+    res.into_bytes()
+}
+
+/// List the symbols exported in an export trie.
+fn parse_export_trie(trie: &[u8]) -> Vec<Vec<u8>> {
+    let mut exports = Vec::new();
+    // Nodes to visit, with the prefix of their symbols. Offsets already visited are tracked to
+    // prevent infinite loops on malformed tries.
+    let mut stack = vec![(0_usize, Vec::new())];
+    let mut visited = std::collections::HashSet::new();
+
+    while let Some((offset, prefix)) = stack.pop() {
+        if exports.len() >= MAX_NB_EXPORTS || !visited.insert(offset) {
+            continue;
+        }
+        let Some(mut node) = trie.get(offset..) else {
+            continue;
+        };
+
+        let Some(terminal_size) = read_uleb128(&mut node) else {
+            continue;
+        };
+        // A node with terminal information is an exported symbol.
+        if terminal_size != 0 {
+            exports.push(prefix.clone());
+        }
+        let Some(children) = usize::try_from(terminal_size)
+            .ok()
+            .and_then(|size| node.get(size..))
+        else {
+            continue;
+        };
+        let Some((&nb_children, mut children)) = children.split_first() else {
+            continue;
+        };
+
+        let mut nodes = Vec::new();
+        for _ in 0..nb_children {
+            let Some(label_len) = children.iter().position(|c| *c == b'\0') else {
+                break;
+            };
+            let mut name = prefix.clone();
+            name.extend_from_slice(&children[..label_len]);
+            children = &children[(label_len + 1)..];
+            let Some(child_offset) = read_uleb128(&mut children) else {
+                break;
+            };
+            let Ok(child_offset) = usize::try_from(child_offset) else {
+                break;
+            };
+            nodes.push((child_offset, name));
+        }
+        // Push in reverse order, so that the children are visited in order.
+        stack.extend(nodes.into_iter().rev());
+    }
+
+    exports
+}
+
+/// Add the symbols bound by bind opcodes to the list of imports.
+fn parse_bind_opcodes(mut opcodes: &[u8], imports: &mut Vec<Vec<u8>>) {
+    while let Some((&byte, rest)) = opcodes.split_first() {
+        opcodes = rest;
+        let immediate = byte & BIND_IMMEDIATE_MASK;
+
+        let res = match byte & BIND_OPCODE_MASK {
+            BIND_OPCODE_DONE
+            | BIND_OPCODE_SET_DYLIB_ORDINAL_IMM
+            | BIND_OPCODE_SET_DYLIB_SPECIAL_IMM
+            | BIND_OPCODE_SET_TYPE_IMM
+            | BIND_OPCODE_DO_BIND
+            | BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => Some(()),
+            BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                match opcodes.iter().position(|c| *c == b'\0') {
+                    Some(len) => {
+                        let name = &opcodes[..len];
+                        if !imports.iter().any(|v| v == name) {
+                            imports.push(name.to_vec());
+                        }
+                        opcodes = &opcodes[(len + 1)..];
+                        Some(())
+                    }
+                    None => None,
+                }
+            }
+            BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB
+            | BIND_OPCODE_SET_ADDEND_SLEB
+            | BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB
+            | BIND_OPCODE_ADD_ADDR_ULEB
+            | BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => read_uleb128(&mut opcodes).map(|_| ()),
+            BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => read_uleb128(&mut opcodes)
+                .and_then(|_| read_uleb128(&mut opcodes))
+                .map(|_| ()),
+            BIND_OPCODE_THREADED => {
+                if immediate == BIND_SUBOPCODE_THREADED_SET_BIND_ORDINAL_TABLE_SIZE_ULEB {
+                    read_uleb128(&mut opcodes).map(|_| ())
+                } else {
+                    Some(())
+                }
+            }
+            _ => None,
+        };
+        if res.is_none() || imports.len() >= MAX_NB_IMPORTS {
+            break;
+        }
+    }
+}
+
+/// Add the symbols imported through chained fixups to the list of imports.
+fn parse_chained_fixups_imports(fixups: &[u8], imports: &mut Vec<Vec<u8>>) {
+    let read_u32 = |offset: usize| {
+        let v = fixups.get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+    };
+    // dyld_chained_fixups_header
+    let (Some(imports_offset), Some(symbols_offset), Some(count), Some(format), Some(0)) = (
+        read_u32(8),
+        read_u32(12),
+        read_u32(16),
+        read_u32(20),
+        read_u32(24),
+    ) else {
+        // Only uncompressed symbols are handled.
+        return;
+    };
+    let (import_size, name_offset): (usize, fn(&[u8]) -> usize) = match format {
+        // DYLD_CHAINED_IMPORT
+        1 => (4, |v| {
+            (u32::from_le_bytes([v[0], v[1], v[2], v[3]]) >> 9) as usize
+        }),
+        // DYLD_CHAINED_IMPORT_ADDEND
+        2 => (8, |v| {
+            (u32::from_le_bytes([v[0], v[1], v[2], v[3]]) >> 9) as usize
+        }),
+        // DYLD_CHAINED_IMPORT_ADDEND64
+        3 => (16, |v| {
+            u32::from_le_bytes([v[4], v[5], v[6], v[7]]) as usize
+        }),
+        _ => return,
+    };
+
+    let Some(imports_table) = fixups.get((imports_offset as usize)..) else {
+        return;
+    };
+    let Some(symbols) = fixups.get((symbols_offset as usize)..) else {
+        return;
+    };
+    for import in imports_table
+        .chunks_exact(import_size)
+        .take(std::cmp::min(count as usize, MAX_NB_IMPORTS))
+    {
+        let Some(name) = symbols.get(name_offset(import)..) else {
+            continue;
+        };
+        let name = &name[..name.iter().position(|c| *c == b'\0').unwrap_or(name.len())];
+        if !imports.iter().any(|v| v == name) {
+            imports.push(name.to_vec());
+        }
+    }
+}
+
+fn read_uleb128(data: &mut &[u8]) -> Option<u64> {
+    let mut value: u64 = 0;
+    let mut shift: u32 = 0;
+
+    loop {
+        let (byte, rest) = data.split_first()?;
+        *data = rest;
+        if shift < 64 {
+            value |= u64::from(byte & 0x7F) << shift;
+        }
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift = shift.saturating_add(7);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_versions() {
+        assert_eq!(format_version(0x000A_0F02), b"10.15.2");
+        assert_eq!(format_version(0x04B1_0000), b"1201.0.0");
+        assert_eq!(
+            format_source_version((1_300 << 40) | (20 << 30) | (3 << 20) | (4 << 10) | 5),
+            b"1300.20.3.4.5"
+        );
+        assert_eq!(
+            format_uuid(&[
+                0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+                0xcd, 0xef
+            ]),
+            b"12345678-9ABC-DEF0-0123-456789ABCDEF"
+        );
+    }
+
+    #[test]
+    fn test_parse_export_trie() {
+        // Root node with two children: "_f" (non terminal) and "_main".
+        // "_f" has two children: "oo" and "bar".
+        let trie = [
+            // Root: no terminal, 2 children
+            0x00, 0x02, b'_', b'f', 0x00, 0x0E, b'_', b'm', b'a', b'i', b'n', 0x00, 0x24,
+            // padding
+            0x00, // Node "_f" at 0x0E: no terminal, 2 children
+            0x00, 0x02, b'o', b'o', 0x00, 0x1C, b'b', b'a', b'r', 0x00, 0x20, 0x00, 0x00, 0x00,
+            // Terminal node at 0x1C: flags 0, address 0x10
+            0x02, 0x00, 0x10, 0x00, // Terminal node at 0x20: flags 0, address 0x20
+            0x02, 0x00, 0x20, 0x00, // Terminal node at 0x24: flags 0, address 0x30
+            0x02, 0x00, 0x30, 0x00,
+        ];
+        assert_eq!(
+            parse_export_trie(&trie),
+            [b"_foo".to_vec(), b"_fbar".to_vec(), b"_main".to_vec()]
+        );
+
+        // Loops are ignored
+        let trie = [0x00, 0x01, b'a', 0x00, 0x00];
+        assert!(parse_export_trie(&trie).is_empty());
+    }
+
+    #[test]
+    fn test_parse_bind_opcodes() {
+        let mut imports = Vec::new();
+        let opcodes = [
+            // ordinal 1, symbol "_printf", type pointer, segment 2 offset 0x10, bind
+            0x11, 0x40, b'_', b'p', b'r', b'i', b'n', b't', b'f', 0x00, 0x51, 0x72, 0x10, 0x90,
+            // ordinal uleb 300, symbol "_exit", bind uleb times skipping
+            0x20, 0xAC, 0x02, 0x40, b'_', b'e', b'x', b'i', b't', 0x00, 0xC0, 0x02, 0x08,
+            // same symbol again
+            0x40, b'_', b'e', b'x', b'i', b't', 0x00, 0x90, 0x00,
+        ];
+        parse_bind_opcodes(&opcodes, &mut imports);
+        assert_eq!(imports, [b"_printf".to_vec(), b"_exit".to_vec()]);
+    }
+}
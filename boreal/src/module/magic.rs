@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::{EvalContext, Module, ModuleData, ModuleDataMap, StaticValue, Type, Value};
+
+mod database;
+
+/// Number of bytes at the start of the scanned memory that are inspected.
+const HEADER_SIZE: usize = 4096;
+
+/// `magic` module. Identifies the type of the scanned input.
+///
+/// Unlike libyara, this module does not depend on libmagic. Instead, it embeds its own
+/// database of signatures, which covers the most common file formats: executables, documents,
+/// archives, images and scripts. The descriptions and mime types are made to look like
+/// the ones returned by libmagic, but may differ for some formats, or be less detailed.
+///
+/// The type is only detected on the first call to one of the module functions, so rules
+/// that do not use them do not pay for the detection.
+#[derive(Debug)]
+pub struct Magic;
+
+impl Magic {
+    /// Version of the embedded database of signatures.
+    ///
+    /// This is bumped every time the database is modified in a way that can change the results
+    /// of the module functions.
+    pub const DATABASE_VERSION: u32 = database::VERSION;
+}
+
+impl Module for Magic {
+    fn get_name(&self) -> &'static str {
+        "magic"
+    }
+
+    fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+        [
+            (
+                "type",
+                StaticValue::function(Self::type_, vec![], Type::Bytes),
+            ),
+            (
+                "mime_type",
+                StaticValue::function(Self::mime_type, vec![], Type::Bytes),
+            ),
+        ]
+        .into()
+    }
+
+    fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
+        data_map.insert::<Self>(Data::default());
+    }
+}
+
+impl ModuleData for Magic {
+    type Data = Data;
+}
+
+#[derive(Default)]
+pub struct Data {
+    /// Type of the scanned input, computed on the first call to one of the functions.
+    file_type: RwLock<Detection>,
+}
+
+/// State of the detection of the type of the scanned input.
+#[derive(Default)]
+enum Detection {
+    /// The detection has not been done yet.
+    #[default]
+    Pending,
+    /// The scanned input could not be read.
+    Unavailable,
+    /// The detected type.
+    Done(database::FileType),
+}
+
+impl Magic {
+    fn type_(ctx: &mut EvalContext, _: Vec<Value>) -> Option<Value> {
+        Self::with_file_type(ctx, |file_type| {
+            Value::Bytes(file_type.description.clone().into_bytes())
+        })
+    }
+
+    fn mime_type(ctx: &mut EvalContext, _: Vec<Value>) -> Option<Value> {
+        Self::with_file_type(ctx, |file_type| {
+            Value::Bytes(file_type.mime_type.as_bytes().to_vec())
+        })
+    }
+
+    fn with_file_type<F>(ctx: &mut EvalContext, f: F) -> Option<Value>
+    where
+        F: FnOnce(&database::FileType) -> Value,
+    {
+        let data = ctx.module_data.get::<Self>()?;
+
+        {
+            match &*data.file_type.read().ok()? {
+                Detection::Pending => (),
+                Detection::Unavailable => return None,
+                Detection::Done(file_type) => return Some(f(file_type)),
+            }
+        }
+
+        let mut header = Vec::new();
+        let file_type = if ctx.mem.filesize() == Some(0) {
+            Some(database::detect(&header))
+        } else {
+            ctx.mem
+                .on_range(0, HEADER_SIZE, |data| header.extend_from_slice(data))
+                .map(|()| database::detect(&header))
+        };
+        let (res, detection) = match file_type {
+            Some(file_type) => (Some(f(&file_type)), Detection::Done(file_type)),
+            None => (None, Detection::Unavailable),
+        };
+        *data.file_type.write().ok()? = detection;
+        res
+    }
+}
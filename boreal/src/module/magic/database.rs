@@ -0,0 +1,775 @@
+//! Database of signatures used to detect the type of a file.
+//!
+//! Signatures are checked in order, and the first one that matches is used. More specific
+//! signatures must thus be placed before the more generic ones.
+//!
+//! The descriptions and mime types are taken from the ones returned by libmagic.
+
+use std::fmt::Write;
+
+/// Version of the database, see [`super::Magic::DATABASE_VERSION`].
+pub(super) const VERSION: u32 = 1;
+
+/// Type of a file.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct FileType {
+    /// Human readable description of the type, as returned by `magic.type()`.
+    pub(super) description: String,
+
+    /// Mime type, as returned by `magic.mime_type()`.
+    pub(super) mime_type: &'static str,
+}
+
+impl FileType {
+    fn new(description: impl Into<String>, mime_type: &'static str) -> Self {
+        Self {
+            description: description.into(),
+            mime_type,
+        }
+    }
+}
+
+struct Signature {
+    /// Offset of the magic bytes.
+    offset: usize,
+
+    /// Bytes that must be found at the offset.
+    magic: &'static [u8],
+
+    /// Type of the file if the magic bytes are found.
+    kind: Kind,
+}
+
+enum Kind {
+    /// The description and mime type are always the same.
+    Simple {
+        description: &'static str,
+        mime_type: &'static str,
+    },
+
+    /// The file must be inspected further to get its type.
+    ///
+    /// If None is returned, the next signatures are checked.
+    Custom(fn(&[u8]) -> Option<FileType>),
+}
+
+const fn simple(
+    offset: usize,
+    magic: &'static [u8],
+    description: &'static str,
+    mime_type: &'static str,
+) -> Signature {
+    Signature {
+        offset,
+        magic,
+        kind: Kind::Simple {
+            description,
+            mime_type,
+        },
+    }
+}
+
+const fn custom(
+    offset: usize,
+    magic: &'static [u8],
+    detect: fn(&[u8]) -> Option<FileType>,
+) -> Signature {
+    Signature {
+        offset,
+        magic,
+        kind: Kind::Custom(detect),
+    }
+}
+
+const SIGNATURES: &[Signature] = &[
+    // Executables
+    custom(0, b"MZ", detect_mz),
+    custom(0, b"\x7FELF", detect_elf),
+    custom(0, b"\xFE\xED\xFA\xCE", detect_macho),
+    custom(0, b"\xFE\xED\xFA\xCF", detect_macho),
+    custom(0, b"\xCE\xFA\xED\xFE", detect_macho),
+    custom(0, b"\xCF\xFA\xED\xFE", detect_macho),
+    custom(0, b"\xCA\xFE\xBA\xBE", detect_cafebabe),
+    custom(0, b"dex\n", detect_dex),
+    // Documents
+    custom(0, b"%PDF-", detect_pdf),
+    simple(
+        0,
+        b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1",
+        "Composite Document File V2 Document",
+        "application/CDFV2",
+    ),
+    simple(0, b"{\\rtf", "Rich Text Format data", "text/rtf"),
+    simple(
+        0,
+        b"L\x00\x00\x00\x01\x14\x02\x00\x00\x00\x00\x00\xC0\x00\x00\x00\x00\x00\x00\x46",
+        "MS Windows shortcut",
+        "application/x-ms-shortcut",
+    ),
+    // Archives
+    custom(0, b"PK\x03\x04", detect_zip),
+    simple(
+        0,
+        b"PK\x05\x06",
+        "Zip archive data (empty)",
+        "application/zip",
+    ),
+    simple(0, b"\x1F\x8B", "gzip compressed data", "application/gzip"),
+    custom(0, b"BZh", detect_bzip2),
+    simple(0, b"\xFD7zXZ\x00", "XZ compressed data", "application/x-xz"),
+    simple(
+        0,
+        b"7z\xBC\xAF\x27\x1C",
+        "7-zip archive data",
+        "application/x-7z-compressed",
+    ),
+    simple(
+        0,
+        b"Rar!\x1A\x07\x01\x00",
+        "RAR archive data, v5",
+        "application/x-rar",
+    ),
+    simple(
+        0,
+        b"Rar!\x1A\x07\x00",
+        "RAR archive data, v4",
+        "application/x-rar",
+    ),
+    simple(
+        0,
+        b"\x28\xB5\x2F\xFD",
+        "Zstandard compressed data",
+        "application/zstd",
+    ),
+    simple(
+        0,
+        b"MSCF\x00\x00\x00\x00",
+        "Microsoft Cabinet archive data",
+        "application/vnd.ms-cab-compressed",
+    ),
+    simple(
+        257,
+        b"ustar  \x00",
+        "POSIX tar archive (GNU)",
+        "application/x-tar",
+    ),
+    simple(257, b"ustar", "POSIX tar archive", "application/x-tar"),
+    // Images
+    custom(0, b"\x89PNG\r\n\x1A\n", detect_png),
+    custom(0, b"\xFF\xD8\xFF", detect_jpeg),
+    custom(0, b"GIF8", detect_gif),
+    custom(0, b"BM", detect_bmp),
+    custom(0, b"\x00\x00\x01\x00", detect_ico),
+    simple(
+        0,
+        b"II*\x00",
+        "TIFF image data, little-endian",
+        "image/tiff",
+    ),
+    simple(0, b"MM\x00*", "TIFF image data, big-endian", "image/tiff"),
+    custom(0, b"RIFF", detect_riff),
+];
+
+/// Detect the type of a file from its first bytes.
+pub(super) fn detect(mem: &[u8]) -> FileType {
+    if mem.is_empty() {
+        return FileType::new("empty", "application/x-empty");
+    }
+
+    for sig in SIGNATURES {
+        let Some(magic) = mem.get(sig.offset..(sig.offset + sig.magic.len())) else {
+            continue;
+        };
+        if magic != sig.magic {
+            continue;
+        }
+
+        match sig.kind {
+            Kind::Simple {
+                description,
+                mime_type,
+            } => return FileType::new(description, mime_type),
+            Kind::Custom(detect) => {
+                if let Some(file_type) = detect(mem) {
+                    return file_type;
+                }
+            }
+        }
+    }
+
+    match text_encoding(mem) {
+        Some(encoding) => detect_text(mem, encoding),
+        None => FileType::new("data", "application/octet-stream"),
+    }
+}
+
+fn read_u16_le(mem: &[u8], offset: usize) -> Option<u16> {
+    let v = mem.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([v[0], v[1]]))
+}
+
+fn read_u16_be(mem: &[u8], offset: usize) -> Option<u16> {
+    let v = mem.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([v[0], v[1]]))
+}
+
+fn read_u32_le(mem: &[u8], offset: usize) -> Option<u32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+fn read_u32_be(mem: &[u8], offset: usize) -> Option<u32> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+fn detect_mz(mem: &[u8]) -> Option<FileType> {
+    const MIME_TYPE: &str = "application/x-dosexec";
+
+    let pe_offset = read_u32_le(mem, 0x3C).and_then(|v| usize::try_from(v).ok());
+    let Some(pe_offset) = pe_offset.filter(|v| mem.get(*v..(v + 4)) == Some(&b"PE\0\0"[..])) else {
+        return Some(FileType::new("MS-DOS executable", MIME_TYPE));
+    };
+
+    let machine = read_u16_le(mem, pe_offset + 4)?;
+    let characteristics = read_u16_le(mem, pe_offset + 22)?;
+    let opt_header = pe_offset + 24;
+    let (format, subsystem_offset, clr_dir_offset) = match read_u16_le(mem, opt_header)? {
+        0x10B => ("PE32", opt_header + 68, opt_header + 208),
+        0x20B => ("PE32+", opt_header + 68, opt_header + 224),
+        _ => return Some(FileType::new("MS-DOS executable", MIME_TYPE)),
+    };
+
+    let mut description = format!("{format} executable");
+    if characteristics & 0x2000 != 0 {
+        description.push_str(" (DLL)");
+    }
+    match read_u16_le(mem, subsystem_offset) {
+        Some(1) => description.push_str(" (native)"),
+        Some(2) => description.push_str(" (GUI)"),
+        Some(3) => description.push_str(" (console)"),
+        Some(10..=13) => description.push_str(" (EFI application)"),
+        _ => (),
+    }
+    match machine {
+        0x014C => description.push_str(" Intel 80386"),
+        0x8664 => description.push_str(" x86-64"),
+        0x01C4 => description.push_str(" ARMv7 Thumb"),
+        0xAA64 => description.push_str(" Aarch64"),
+        0x0200 => description.push_str(" Intel Itanium"),
+        _ => (),
+    }
+    if read_u32_le(mem, clr_dir_offset).map_or(false, |v| v != 0) {
+        description.push_str(" Mono/.Net assembly");
+    }
+    description.push_str(", for MS Windows");
+
+    Some(FileType::new(description, MIME_TYPE))
+}
+
+fn detect_elf(mem: &[u8]) -> Option<FileType> {
+    let class = match mem.get(4)? {
+        1 => "32-bit",
+        2 => "64-bit",
+        _ => return None,
+    };
+    let (endianness, big_endian) = match mem.get(5)? {
+        1 => ("LSB", false),
+        2 => ("MSB", true),
+        _ => return None,
+    };
+    let read_u16 = |offset| {
+        let v = mem.get(offset..(offset + 2))?;
+        Some(if big_endian {
+            u16::from_be_bytes([v[0], v[1]])
+        } else {
+            u16::from_le_bytes([v[0], v[1]])
+        })
+    };
+
+    let (kind, mime_type) = match read_u16(16)? {
+        1 => ("relocatable", "application/x-object"),
+        2 => ("executable", "application/x-executable"),
+        3 => ("shared object", "application/x-sharedlib"),
+        4 => ("core file", "application/x-coredump"),
+        _ => ("unknown type", "application/octet-stream"),
+    };
+    let mut description = format!("ELF {class} {endianness} {kind}");
+    let machine = match read_u16(18)? {
+        0x02 => Some("SPARC"),
+        0x03 => Some("Intel 80386"),
+        0x08 => Some("MIPS"),
+        0x14 => Some("PowerPC"),
+        0x15 => Some("64-bit PowerPC"),
+        0x28 => Some("ARM"),
+        0x32 => Some("IA-64"),
+        0x3E => Some("x86-64"),
+        0xB7 => Some("ARM aarch64"),
+        0xF3 => Some("UCB RISC-V"),
+        _ => None,
+    };
+    if let Some(machine) = machine {
+        description.push_str(", ");
+        description.push_str(machine);
+    }
+    let _r = write!(description, ", version {}", mem.get(6)?);
+    match mem.get(7)? {
+        0 => description.push_str(" (SYSV)"),
+        3 => description.push_str(" (GNU/Linux)"),
+        9 => description.push_str(" (FreeBSD)"),
+        _ => (),
+    }
+
+    Some(FileType::new(description, mime_type))
+}
+
+fn detect_macho(mem: &[u8]) -> Option<FileType> {
+    let (is_64, big_endian) = match mem.get(0..4)? {
+        b"\xFE\xED\xFA\xCE" => (false, true),
+        b"\xFE\xED\xFA\xCF" => (true, true),
+        b"\xCE\xFA\xED\xFE" => (false, false),
+        _ => (true, false),
+    };
+    let read_u32 = |offset| {
+        if big_endian {
+            read_u32_be(mem, offset)
+        } else {
+            read_u32_le(mem, offset)
+        }
+    };
+
+    let mut description = String::from(if is_64 { "Mach-O 64-bit" } else { "Mach-O" });
+    let cpu = match read_u32(4)? {
+        7 => Some("i386"),
+        0x0100_0007 => Some("x86_64"),
+        12 => Some("arm"),
+        0x0100_000C => Some("arm64"),
+        18 => Some("ppc"),
+        0x0100_0012 => Some("ppc64"),
+        _ => None,
+    };
+    if let Some(cpu) = cpu {
+        description.push(' ');
+        description.push_str(cpu);
+    }
+    let kind = match read_u32(12)? {
+        1 => "object",
+        2 => "executable",
+        4 => "fixed virtual memory shared library",
+        5 => "core",
+        6 => "dynamically linked shared library",
+        7 => "dynamic linker",
+        8 => "bundle",
+        10 => "dSYM companion file",
+        11 => "kext bundle",
+        _ => "unknown file type",
+    };
+    description.push(' ');
+    description.push_str(kind);
+
+    Some(FileType::new(description, "application/x-mach-binary"))
+}
+
+fn detect_cafebabe(mem: &[u8]) -> Option<FileType> {
+    // This magic is shared between Mach-O universal binaries and Java classes. The number of
+    // architectures of the former is small, while the version of the latter is big.
+    let value = read_u32_be(mem, 4)?;
+    if value == 0 {
+        None
+    } else if value < 20 {
+        Some(FileType::new(
+            format!("Mach-O universal binary with {value} architectures"),
+            "application/x-mach-binary",
+        ))
+    } else {
+        let minor = read_u16_be(mem, 4)?;
+        let major = read_u16_be(mem, 6)?;
+        Some(FileType::new(
+            format!("compiled Java class data, version {major}.{minor}"),
+            "application/x-java-applet",
+        ))
+    }
+}
+
+fn detect_dex(mem: &[u8]) -> Option<FileType> {
+    let version = mem.get(4..7)?;
+    if !version.iter().all(u8::is_ascii_digit) || mem.get(7) != Some(&0) {
+        return None;
+    }
+
+    Some(FileType::new(
+        format!(
+            "Dalvik dex file version {}",
+            String::from_utf8_lossy(version)
+        ),
+        "application/vnd.android.dex",
+    ))
+}
+
+fn detect_pdf(mem: &[u8]) -> Option<FileType> {
+    let version: Vec<u8> = mem
+        .get(5..)?
+        .iter()
+        .take_while(|c| c.is_ascii_digit() || **c == b'.')
+        .copied()
+        .collect();
+
+    let description = if version.is_empty() {
+        String::from("PDF document")
+    } else {
+        format!(
+            "PDF document, version {}",
+            String::from_utf8_lossy(&version)
+        )
+    };
+    Some(FileType::new(description, "application/pdf"))
+}
+
+fn detect_zip(mem: &[u8]) -> Option<FileType> {
+    // Inspect the name of the first file of the archive to detect documents using the zip
+    // format.
+    let name_len = usize::from(read_u16_le(mem, 26)?);
+    let extra_len = usize::from(read_u16_le(mem, 28)?);
+    let name = mem.get(30..(30 + name_len));
+
+    match name {
+        Some(b"[Content_Types].xml" | b"_rels/.rels") => {
+            // Office Open XML document. The type of the document is given by the name of the
+            // directory containing its contents.
+            if let Some(file_type) = detect_ooxml(mem) {
+                return Some(file_type);
+            }
+        }
+        Some(b"mimetype") => {
+            // OpenDocument and EPUB files start with a stored file containing the mime type.
+            let content = mem.get((30 + name_len + extra_len)..)?;
+            if content.starts_with(b"application/epub+zip") {
+                return Some(FileType::new("EPUB document", "application/epub+zip"));
+            }
+            for (mime_type, description) in [
+                (
+                    "application/vnd.oasis.opendocument.text",
+                    "OpenDocument Text",
+                ),
+                (
+                    "application/vnd.oasis.opendocument.spreadsheet",
+                    "OpenDocument Spreadsheet",
+                ),
+                (
+                    "application/vnd.oasis.opendocument.presentation",
+                    "OpenDocument Presentation",
+                ),
+            ] {
+                if content.starts_with(mime_type.as_bytes()) {
+                    return Some(FileType::new(description, mime_type));
+                }
+            }
+        }
+        Some(b"META-INF/MANIFEST.MF" | b"META-INF/") => {
+            return Some(FileType::new(
+                "Java archive data (JAR)",
+                "application/java-archive",
+            ));
+        }
+        Some(b"AndroidManifest.xml" | b"classes.dex") => {
+            return Some(FileType::new(
+                "Android package (APK)",
+                "application/vnd.android.package-archive",
+            ));
+        }
+        _ => (),
+    }
+
+    let version = read_u16_le(mem, 4)?;
+    Some(FileType::new(
+        format!(
+            "Zip archive data, at least v{}.{} to extract",
+            version / 10,
+            version % 10
+        ),
+        "application/zip",
+    ))
+}
+
+fn detect_ooxml(mem: &[u8]) -> Option<FileType> {
+    for (dir, description, mime_type) in [
+        (
+            &b"word/"[..],
+            "Microsoft Word 2007+",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        ),
+        (
+            &b"xl/"[..],
+            "Microsoft Excel 2007+",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        ),
+        (
+            &b"ppt/"[..],
+            "Microsoft PowerPoint 2007+",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        ),
+    ] {
+        if memchr::memmem::find(mem, dir).is_some() {
+            return Some(FileType::new(description, mime_type));
+        }
+    }
+    None
+}
+
+fn detect_bzip2(mem: &[u8]) -> Option<FileType> {
+    let block_size = *mem.get(3)?;
+    if !(b'1'..=b'9').contains(&block_size) {
+        return None;
+    }
+    Some(FileType::new(
+        format!(
+            "bzip2 compressed data, block size = {}00k",
+            char::from(block_size)
+        ),
+        "application/x-bzip2",
+    ))
+}
+
+// The signature of the function must match the one of custom detections.
+#[allow(clippy::unnecessary_wraps)]
+fn detect_png(mem: &[u8]) -> Option<FileType> {
+    let description = match (read_u32_be(mem, 16), read_u32_be(mem, 20)) {
+        (Some(width), Some(height)) => format!("PNG image data, {width} x {height}"),
+        _ => String::from("PNG image data"),
+    };
+    Some(FileType::new(description, "image/png"))
+}
+
+// The signature of the function must match the one of custom detections.
+#[allow(clippy::unnecessary_wraps)]
+fn detect_jpeg(mem: &[u8]) -> Option<FileType> {
+    let description = if mem.get(6..11) == Some(&b"JFIF\0"[..]) {
+        match mem.get(11..13) {
+            Some([major, minor]) => format!("JPEG image data, JFIF standard {major}.{minor:02}"),
+            _ => String::from("JPEG image data, JFIF standard"),
+        }
+    } else if mem.get(6..11) == Some(&b"Exif\0"[..]) {
+        String::from("JPEG image data, Exif standard")
+    } else {
+        String::from("JPEG image data")
+    };
+    Some(FileType::new(description, "image/jpeg"))
+}
+
+fn detect_gif(mem: &[u8]) -> Option<FileType> {
+    let version = match mem.get(4..6)? {
+        b"7a" => "87a",
+        b"9a" => "89a",
+        _ => return None,
+    };
+    let description = match (read_u16_le(mem, 6), read_u16_le(mem, 8)) {
+        (Some(width), Some(height)) => {
+            format!("GIF image data, version {version}, {width} x {height}")
+        }
+        _ => format!("GIF image data, version {version}"),
+    };
+    Some(FileType::new(description, "image/gif"))
+}
+
+fn detect_bmp(mem: &[u8]) -> Option<FileType> {
+    // The two bytes magic is very weak, so check the size of the info header, which depends
+    // on the version of the format.
+    let format = match read_u32_le(mem, 14)? {
+        12 => "OS/2 1.x format",
+        40 => "Windows 3.x format",
+        108 => "Windows 95/NT4 and newer format",
+        124 => "Windows 98/2000 and newer format",
+        _ => return None,
+    };
+    Some(FileType::new(format!("PC bitmap, {format}"), "image/bmp"))
+}
+
+fn detect_ico(mem: &[u8]) -> Option<FileType> {
+    // The magic is very weak, so check the number of icons and the reserved byte of the first
+    // entry.
+    let count = read_u16_le(mem, 4)?;
+    if count == 0 || mem.get(9) != Some(&0) {
+        return None;
+    }
+    let description = if count == 1 {
+        String::from("MS Windows icon resource - 1 icon")
+    } else {
+        format!("MS Windows icon resource - {count} icons")
+    };
+    Some(FileType::new(description, "image/vnd.microsoft.icon"))
+}
+
+fn detect_riff(mem: &[u8]) -> Option<FileType> {
+    let (kind, mime_type) = match mem.get(8..12)? {
+        b"WEBP" => ("Web/P image", "image/webp"),
+        b"WAVE" => ("WAVE audio", "audio/x-wav"),
+        b"AVI " => ("AVI", "video/x-msvideo"),
+        _ => ("", "application/octet-stream"),
+    };
+    let description = if kind.is_empty() {
+        String::from("RIFF (little-endian) data")
+    } else {
+        format!("RIFF (little-endian) data, {kind}")
+    };
+    Some(FileType::new(description, mime_type))
+}
+
+/// Detect the encoding of a text file, None if the file is not text.
+fn text_encoding(mem: &[u8]) -> Option<&'static str> {
+    let is_text_byte = |c: &u8| matches!(c, 0x20..=0x7E | b'\t' | b'\n' | b'\r' | 0x0C | 0x1B);
+
+    if mem.iter().all(is_text_byte) {
+        return Some("ASCII");
+    }
+
+    // The header might end in the middle of a character, which must not prevent the detection.
+    let valid_len = match std::str::from_utf8(mem) {
+        Ok(_) => mem.len(),
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(_) => return None,
+    };
+    if mem[..valid_len]
+        .iter()
+        .all(|c| *c >= 0x80 || is_text_byte(c))
+    {
+        Some("UTF-8 Unicode")
+    } else {
+        None
+    }
+}
+
+fn detect_text(mem: &[u8], encoding: &str) -> FileType {
+    if let Some(interpreter) = mem.strip_prefix(b"#!") {
+        return detect_script(interpreter, encoding);
+    }
+
+    let start = mem
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(0);
+    let trimmed = &mem[start..];
+    let starts_with = |prefix: &[u8]| {
+        trimmed
+            .get(..prefix.len())
+            .map_or(false, |v| v.eq_ignore_ascii_case(prefix))
+    };
+
+    let (kind, mime_type) = if starts_with(b"<?php") {
+        ("PHP script", "text/x-php")
+    } else if starts_with(b"<?xml") {
+        ("XML 1.0 document", "text/xml")
+    } else if starts_with(b"<!doctype html") || starts_with(b"<html") {
+        ("HTML document", "text/html")
+    } else if starts_with(b"@echo off") {
+        ("DOS batch file", "text/x-msdos-batch")
+    } else {
+        return FileType::new(format!("{encoding} text"), "text/plain");
+    };
+
+    FileType::new(format!("{kind}, {encoding} text"), mime_type)
+}
+
+fn detect_script(interpreter: &[u8], encoding: &str) -> FileType {
+    let line_end = memchr::memchr(b'\n', interpreter).unwrap_or(interpreter.len());
+    let mut words = interpreter[..line_end]
+        .split(u8::is_ascii_whitespace)
+        .filter(|v| !v.is_empty());
+
+    // Resolve the interpreter name, using the first argument when using env.
+    let mut name = words.next().unwrap_or_default();
+    name = name.rsplit(|c| *c == b'/').next().unwrap_or_default();
+    if name == b"env" {
+        name = words.next().unwrap_or_default();
+    }
+
+    let (kind, mime_type) = match name {
+        b"sh" | b"bash" | b"dash" | b"zsh" | b"ksh" => ("POSIX shell script", "text/x-shellscript"),
+        b"python" | b"python2" | b"python3" => ("Python script", "text/x-script.python"),
+        b"perl" => ("Perl script", "text/x-perl"),
+        b"ruby" => ("Ruby script", "text/x-ruby"),
+        b"node" | b"nodejs" => ("Node.js script", "application/javascript"),
+        b"php" => ("PHP script", "text/x-php"),
+        b"pwsh" | b"powershell" => ("PowerShell script", "text/plain"),
+        name => {
+            return FileType::new(
+                format!(
+                    "a {} script, {encoding} text executable",
+                    String::from_utf8_lossy(name)
+                ),
+                "text/plain",
+            )
+        }
+    };
+
+    FileType::new(format!("{kind}, {encoding} text executable"), mime_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test(mem: &[u8], description: &str, mime_type: &str) {
+        let file_type = detect(mem);
+        assert_eq!(file_type.description, description);
+        assert_eq!(file_type.mime_type, mime_type);
+    }
+
+    #[test]
+    fn test_detect_text() {
+        test(b"", "empty", "application/x-empty");
+        test(b"hello world\n", "ASCII text", "text/plain");
+        test("h\u{e9}llo".as_bytes(), "UTF-8 Unicode text", "text/plain");
+        // Truncated character at the end of the header
+        test(b"h\xC3\xA9llo \xC3", "UTF-8 Unicode text", "text/plain");
+        test(b"\x00\x01\x02", "data", "application/octet-stream");
+
+        test(
+            b"#!/bin/sh\necho a\n",
+            "POSIX shell script, ASCII text executable",
+            "text/x-shellscript",
+        );
+        test(
+            b"#!/usr/bin/env python3\nprint(1)\n",
+            "Python script, ASCII text executable",
+            "text/x-script.python",
+        );
+        test(
+            b"#!/usr/bin/foo",
+            "a foo script, ASCII text executable",
+            "text/plain",
+        );
+        test(
+            b"  <!DOCTYPE html><html></html>",
+            "HTML document, ASCII text",
+            "text/html",
+        );
+        test(
+            b"<?xml version=\"1.0\"?>",
+            "XML 1.0 document, ASCII text",
+            "text/xml",
+        );
+    }
+
+    #[test]
+    fn test_detect_weak_magics() {
+        // Those magics are only used if the rest of the header is valid.
+        test(b"BMxx", "ASCII text", "text/plain");
+        test(
+            b"\x00\x00\x01\x00\x00\x00",
+            "data",
+            "application/octet-stream",
+        );
+        test(b"BZh0", "ASCII text", "text/plain");
+        test(
+            b"\xCA\xFE\xBA\xBE\x00\x00\x00\x02",
+            "Mach-O universal binary with 2 architectures",
+            "application/x-mach-binary",
+        );
+        test(
+            b"\xCA\xFE\xBA\xBE\x00\x00\x00\x34",
+            "compiled Java class data, version 52.0",
+            "application/x-java-applet",
+        );
+    }
+}
@@ -65,6 +65,37 @@ impl Module for Math {
                     Type::Float,
                 ),
             ),
+            (
+                "chi_square",
+                StaticValue::function(
+                    Self::chi_square,
+                    vec![vec![Type::Integer, Type::Integer], vec![Type::Bytes]],
+                    Type::Float,
+                ),
+            ),
+            (
+                "distribution_distance",
+                StaticValue::function(
+                    Self::distribution_distance,
+                    vec![
+                        vec![Type::Integer, Type::Integer, Type::Integer, Type::Integer],
+                        vec![Type::Integer, Type::Integer, Type::Bytes],
+                        vec![Type::Bytes, Type::Bytes],
+                    ],
+                    Type::Float,
+                ),
+            ),
+            (
+                "max_entropy",
+                StaticValue::function(
+                    Self::max_entropy,
+                    vec![
+                        vec![Type::Integer, Type::Integer, Type::Integer],
+                        vec![Type::Bytes, Type::Integer],
+                    ],
+                    Type::Float,
+                ),
+            ),
             (
                 "min",
                 StaticValue::function(
@@ -225,6 +256,50 @@ impl Math {
         Some(Value::Float(compute_entropy(distribution)))
     }
 
+    /// Chi-square statistic of the byte distribution, compared to a uniform distribution.
+    ///
+    /// Random data gives a value close to 255, while compressed data, although having a high
+    /// entropy, usually gives much higher values.
+    fn chi_square(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let distribution = distribution_from_args(ctx, &mut args)?;
+
+        compute_chi_square(distribution).map(Value::Float)
+    }
+
+    /// Distance between the byte distributions of two inputs.
+    ///
+    /// This is the total variation distance: 0.0 if both distributions are identical, 1.0 if
+    /// they do not have any byte value in common.
+    fn distribution_distance(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let a = distribution_from_args(ctx, &mut args)?;
+        let b = distribution_from_args(ctx, &mut args)?;
+
+        compute_distribution_distance(&a, &b).map(Value::Float)
+    }
+
+    /// Maximum entropy of all the windows of the given size in the input.
+    fn max_entropy(ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
+        let mut args = args.into_iter();
+        let entropy = match args.next()? {
+            Value::Bytes(bytes) => {
+                let window: i64 = args.next()?.try_into().ok()?;
+
+                compute_from_bytes(&bytes, MaxEntropy::new(window)?)?
+            }
+            Value::Integer(offset) => {
+                let length: i64 = args.next()?.try_into().ok()?;
+                let window: i64 = args.next()?.try_into().ok()?;
+
+                compute_from_mem(ctx, offset, length, MaxEntropy::new(window)?)?
+            }
+            _ => return None,
+        };
+
+        Some(Value::Float(entropy))
+    }
+
     fn min(_ctx: &mut EvalContext, args: Vec<Value>) -> Option<Value> {
         let mut args = args.into_iter();
         let a: i64 = args.next()?.try_into().ok()?;
@@ -528,6 +603,106 @@ impl MathDigest for MonteCarloPi {
     }
 }
 
+/// Entropy of sliding windows over the input, keeping the maximum value.
+///
+/// The distribution of the window is updated with the byte entering and the byte leaving the
+/// window on each step, so that computing the entropy of a window does not require going over
+/// the whole window.
+struct MaxEntropy {
+    window: usize,
+    /// Bytes of the current window, used as a ring buffer once full.
+    buffer: Vec<u8>,
+    /// Index in the buffer of the oldest byte of the window.
+    oldest: usize,
+    counters: Vec<u64>,
+    /// Sum of `n * log2(n)` for every counter `n`.
+    sum: f64,
+    max: Option<f64>,
+}
+
+impl MaxEntropy {
+    fn new(window: i64) -> Option<Self> {
+        let window: usize = window.try_into().ok()?;
+        if window == 0 {
+            return None;
+        }
+
+        Some(Self {
+            window,
+            buffer: Vec::new(),
+            oldest: 0,
+            counters: vec![0u64; 256],
+            sum: 0.0,
+            max: None,
+        })
+    }
+
+    fn add(&mut self, b: u8) {
+        let counter = &mut self.counters[usize::from(b)];
+        self.sum -= n_log2_n(*counter);
+        *counter += 1;
+        self.sum += n_log2_n(*counter);
+    }
+
+    fn remove(&mut self, b: u8) {
+        let counter = &mut self.counters[usize::from(b)];
+        self.sum -= n_log2_n(*counter);
+        *counter -= 1;
+        self.sum += n_log2_n(*counter);
+    }
+
+    /// Entropy of the bytes currently in the buffer.
+    fn entropy(&self) -> f64 {
+        // entropy = -sum((n / N) * log2(n / N)) = log2(N) - sum(n * log2(n)) / N
+        let nb_values = self.buffer.len() as f64;
+        // Rounding errors can lead to slightly negative values.
+        (nb_values.log2() - self.sum / nb_values).max(0.0)
+    }
+
+    fn update_max(&mut self) {
+        let entropy = self.entropy();
+        self.max = Some(self.max.map_or(entropy, |max| max.max(entropy)));
+    }
+}
+
+impl MathDigest for MaxEntropy {
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            if self.buffer.len() < self.window {
+                self.buffer.push(*b);
+                self.add(*b);
+                if self.buffer.len() == self.window {
+                    self.update_max();
+                }
+            } else {
+                let old = std::mem::replace(&mut self.buffer[self.oldest], *b);
+                self.oldest = (self.oldest + 1) % self.window;
+                self.remove(old);
+                self.add(*b);
+                self.update_max();
+            }
+        }
+    }
+
+    fn finalize(self) -> Option<f64> {
+        match self.max {
+            Some(max) => Some(max),
+            // The input is smaller than the window, use the whole input.
+            None if !self.buffer.is_empty() => Some(self.entropy()),
+            None => None,
+        }
+    }
+}
+
+fn n_log2_n(n: u64) -> f64 {
+    if n == 0 {
+        0.0
+    } else {
+        let n = n as f64;
+        n * n.log2()
+    }
+}
+
 fn compute_from_bytes<T: MathDigest>(data: &[u8], mut digest: T) -> Option<f64> {
     digest.update(data);
     digest.finalize()
@@ -576,6 +751,44 @@ fn compute_entropy(distribution: Distribution) -> f64 {
         .sum()
 }
 
+fn compute_chi_square(distribution: Distribution) -> Option<f64> {
+    let Distribution {
+        counters,
+        nb_values,
+    } = distribution;
+    if nb_values == 0 {
+        return None;
+    }
+
+    let expected = (nb_values as f64) / 256.0;
+    Some(
+        counters
+            .into_iter()
+            .map(|n| {
+                let diff = (n as f64) - expected;
+                diff * diff / expected
+            })
+            .sum(),
+    )
+}
+
+fn compute_distribution_distance(a: &Distribution, b: &Distribution) -> Option<f64> {
+    if a.nb_values == 0 || b.nb_values == 0 {
+        return None;
+    }
+
+    let nb_a = a.nb_values as f64;
+    let nb_b = b.nb_values as f64;
+    let sum: f64 = a
+        .counters
+        .iter()
+        .zip(b.counters.iter())
+        .map(|(na, nb)| ((*na as f64) / nb_a - (*nb as f64) / nb_b).abs())
+        .sum();
+
+    Some(sum / 2.0)
+}
+
 struct Distribution {
     counters: Vec<u64>,
     nb_values: usize,
@@ -612,6 +825,25 @@ fn distribution_from_bytes(bytes: &[u8]) -> Distribution {
     distrib
 }
 
+/// Compute the distribution of either a bytes argument, or of a range given as two integer
+/// arguments.
+fn distribution_from_args(
+    ctx: &mut EvalContext,
+    args: &mut std::vec::IntoIter<Value>,
+) -> Option<Distribution> {
+    match args.next()? {
+        Value::Bytes(bytes) => Some(distribution_from_bytes(&bytes)),
+        Value::Integer(offset) => {
+            let length: i64 = args.next()?.try_into().ok()?;
+
+            let start: usize = offset.try_into().ok()?;
+            let length: usize = length.try_into().ok()?;
+            distribution(ctx, start, length)
+        }
+        _ => None,
+    }
+}
+
 fn offset_length_to_start_end(offset: i64, length: i64) -> Option<(usize, usize)> {
     let start: usize = offset.try_into().ok()?;
     let length: usize = length.try_into().ok()?;
@@ -702,6 +934,68 @@ mod tests {
         assert!(Math::entropy(&mut ctx, vec![0.into(), 0.5.into()]).is_none());
     }
 
+    #[test]
+    fn test_chi_square_invalid_args() {
+        let mut ctx = ctx!();
+
+        assert!(Math::chi_square(&mut ctx, vec![]).is_none());
+        assert!(Math::chi_square(&mut ctx, vec![0.5.into()]).is_none());
+        assert!(Math::chi_square(&mut ctx, vec![0.into()]).is_none());
+        assert!(Math::chi_square(&mut ctx, vec![0.into(), 0.5.into()]).is_none());
+    }
+
+    #[test]
+    fn test_distribution_distance_invalid_args() {
+        let mut ctx = ctx!();
+
+        assert!(Math::distribution_distance(&mut ctx, vec![]).is_none());
+        assert!(Math::distribution_distance(&mut ctx, vec![b"a".to_vec().into()]).is_none());
+        assert!(
+            Math::distribution_distance(&mut ctx, vec![b"a".to_vec().into(), 0.5.into()]).is_none()
+        );
+        assert!(
+            Math::distribution_distance(&mut ctx, vec![b"a".to_vec().into(), 0.into()]).is_none()
+        );
+    }
+
+    #[test]
+    fn test_max_entropy_invalid_args() {
+        let mut ctx = ctx!();
+
+        assert!(Math::max_entropy(&mut ctx, vec![]).is_none());
+        assert!(Math::max_entropy(&mut ctx, vec![0.5.into()]).is_none());
+        assert!(Math::max_entropy(&mut ctx, vec![b"a".to_vec().into()]).is_none());
+        assert!(Math::max_entropy(&mut ctx, vec![b"a".to_vec().into(), 0.5.into()]).is_none());
+        assert!(Math::max_entropy(&mut ctx, vec![0.into(), 0.into()]).is_none());
+    }
+
+    #[test]
+    fn test_max_entropy() {
+        fn max_entropy(data: &[u8], window: i64) -> Option<f64> {
+            compute_from_bytes(data, MaxEntropy::new(window)?)
+        }
+
+        fn entropy(data: &[u8]) -> f64 {
+            compute_entropy(distribution_from_bytes(data))
+        }
+
+        let data: Vec<u8> = (0..=255).chain([0; 300]).chain(0..=20).collect();
+        for window in [1, 2, 10, 64, 256, 300, 577] {
+            let expected = data
+                .windows(usize::try_from(window).unwrap())
+                .map(entropy)
+                .fold(0.0, f64::max);
+            let res = max_entropy(&data, window).unwrap();
+            assert!((res - expected).abs() < 1e-9, "{res} != {expected}");
+        }
+
+        // Window bigger than the input
+        assert_eq!(max_entropy(b"AC", 5), Some(1.0));
+        assert_eq!(max_entropy(b"", 5), None);
+        assert_eq!(max_entropy(b"AC", 0), None);
+        assert_eq!(max_entropy(b"AC", -1), None);
+    }
+
     #[test]
     fn test_min_invalid_args() {
         let mut ctx = ctx!();
@@ -50,6 +50,18 @@ pub use console::{Console, LogCallback};
 mod time;
 pub use time::Time;
 
+mod cuckoo;
+pub use cuckoo::Cuckoo;
+
+mod dex;
+pub use dex::Dex;
+
+mod lnk;
+pub use lnk::Lnk;
+
+mod magic;
+pub use magic::Magic;
+
 #[allow(clippy::cast_precision_loss)]
 mod math;
 pub use math::Math;
@@ -57,6 +69,11 @@ pub use math::Math;
 mod string;
 pub use string::String_;
 
+#[cfg(feature = "object")]
+mod dotnet;
+#[cfg(feature = "object")]
+pub use dotnet::Dotnet;
+
 #[cfg(feature = "hash")]
 mod hash;
 #[cfg(feature = "hash")]
@@ -154,6 +171,9 @@ pub trait Module: Send + Sync {
     /// - The [`Module::get_dynamic_values`] method can be called multiple times
     ///   during a single scan, for example when scanning the memory of a process.
     /// - Some module use data without having any dynamic values
+    ///
+    /// Data provided by the user for this scan, if any, is already available in the map,
+    /// see [`ModuleDataMap::get_user_data`].
     fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
         let _ = data_map;
     }
@@ -226,11 +246,17 @@ impl std::fmt::Debug for EvalContext<'_, '_> {
 
 /// Object holding the data of each module. See [`ModuleData`].
 #[derive(Default)]
-pub struct ModuleDataMap(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+pub struct ModuleDataMap {
+    /// Private data of each module, indexed by the type of the module.
+    private_data: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+
+    /// Data provided by the user for the scan, indexed by the name of the module.
+    user_data: HashMap<&'static str, Box<dyn Any + Send + Sync>>,
+}
 
 impl std::fmt::Debug for ModuleDataMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("ModuleDataMap").finish()
+        f.debug_struct("ModuleDataMap").finish()
     }
 }
 
@@ -298,13 +324,13 @@ pub trait ModuleData: Module {
 impl ModuleDataMap {
     /// Insert the data of a module in the map.
     pub fn insert<T: Module + ModuleData + 'static>(&mut self, data: T::Data) {
-        let _r = self.0.insert(TypeId::of::<T>(), Box::new(data));
+        let _r = self.private_data.insert(TypeId::of::<T>(), Box::new(data));
     }
 
     /// Retrieve the data of a module.
     #[must_use]
     pub fn get<T: Module + ModuleData + 'static>(&self) -> Option<&T::Data> {
-        self.0
+        self.private_data
             .get(&TypeId::of::<T>())
             .and_then(|v| v.downcast_ref())
     }
@@ -312,10 +338,30 @@ impl ModuleDataMap {
     /// Retrieve a mutable borrow on the data of a module.
     #[must_use]
     pub fn get_mut<T: Module + ModuleData + 'static>(&mut self) -> Option<&mut T::Data> {
-        self.0
+        self.private_data
             .get_mut(&TypeId::of::<T>())
             .and_then(|v| v.downcast_mut())
     }
+
+    /// Retrieve the data provided by the user for the module with the given name.
+    ///
+    /// This data is provided for a single scan, see
+    /// [`crate::Scanner::scan_mem_with_module_data`]. None is returned if no data was
+    /// provided for this module, or if the data is not of type `D`.
+    #[must_use]
+    pub fn get_user_data<D: Any>(&self, module_name: &str) -> Option<&D> {
+        self.user_data
+            .get(module_name)
+            .and_then(|v| v.downcast_ref())
+    }
+
+    pub(crate) fn insert_user_data(
+        &mut self,
+        module_name: &'static str,
+        data: Box<dyn Any + Send + Sync>,
+    ) {
+        let _r = self.user_data.insert(module_name, data);
+    }
 }
 
 /// A value bound to an identifier.
@@ -414,6 +460,82 @@ impl std::fmt::Debug for Value {
     }
 }
 
+/// A module value owning all of its data.
+///
+/// This is the same as [`Value`], but with owned keys for objects. It is used in
+/// [`OwnedScanResult`](crate::scanner::OwnedScanResult), and can be deserialized when the
+/// _serde_ feature is enabled.
+#[derive(Clone)]
+pub enum OwnedValue {
+    /// An integer
+    Integer(i64),
+    /// A floating-point value.
+    Float(f64),
+    /// A byte string.
+    Bytes(Vec<u8>),
+    /// A regex.
+    Regex(Regex),
+    /// A boolean.
+    Boolean(bool),
+    /// An object, mapping to other values.
+    Object(HashMap<String, OwnedValue>),
+    /// An array.
+    Array(Vec<OwnedValue>),
+    /// A dictionary, indexed by bytes.
+    Dictionary(HashMap<Vec<u8>, OwnedValue>),
+    /// A function, see [`Value::Function`].
+    #[allow(clippy::type_complexity)]
+    Function(Arc<Box<dyn Fn(&mut EvalContext, Vec<Value>) -> Option<Value> + Send + Sync>>),
+    /// An undefined value.
+    Undefined,
+}
+
+impl std::fmt::Debug for OwnedValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(arg0) => f.debug_tuple("Integer").field(arg0).finish(),
+            Self::Float(arg0) => f.debug_tuple("Float").field(arg0).finish(),
+            Self::Bytes(arg0) => {
+                let mut t = f.debug_tuple("Bytes");
+                match std::str::from_utf8(arg0) {
+                    Ok(v) => t.field(&v).finish(),
+                    Err(_) => t.field(arg0).finish(),
+                }
+            }
+            Self::Regex(arg0) => f.debug_tuple("Regex").field(arg0).finish(),
+            Self::Boolean(arg0) => f.debug_tuple("Boolean").field(arg0).finish(),
+            Self::Object(arg0) => f.debug_tuple("Object").field(arg0).finish(),
+            Self::Array(arg0) => f.debug_tuple("Array").field(arg0).finish(),
+            Self::Dictionary(arg0) => f.debug_tuple("Dictionary").field(arg0).finish(),
+            Self::Function(_) => f.debug_struct("Function").finish(),
+            Self::Undefined => write!(f, "Undefined"),
+        }
+    }
+}
+
+impl From<Value> for OwnedValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Integer(v) => Self::Integer(v),
+            Value::Float(v) => Self::Float(v),
+            Value::Bytes(v) => Self::Bytes(v),
+            Value::Regex(v) => Self::Regex(v),
+            Value::Boolean(v) => Self::Boolean(v),
+            Value::Object(v) => Self::Object(
+                v.into_iter()
+                    .map(|(k, v)| (k.to_owned(), v.into()))
+                    .collect(),
+            ),
+            Value::Array(v) => Self::Array(v.into_iter().map(Into::into).collect()),
+            Value::Dictionary(v) => {
+                Self::Dictionary(v.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+            Value::Function(v) => Self::Function(v),
+            Value::Undefined => Self::Undefined,
+        }
+    }
+}
+
 /// A static value provided by a module at compilation time.
 ///
 /// This is similar to [`Value`], but without some compounds values that require evaluation
@@ -728,6 +850,150 @@ where
     }
 }
 
+/// Serialization of module values.
+///
+/// Values are encoded as an enum with one variant per kind of value. Bytes are encoded as byte
+/// strings, objects as maps sorted by key, and dictionaries as sequences of `(key, value)` pairs
+/// sorted by key, as their keys are not valid strings in most formats.
+///
+/// Functions cannot be serialized: they are skipped in objects, and encoded as undefined values
+/// everywhere else.
+///
+/// [`Value`] and [`OwnedValue`] have the same representation, but only the latter can be
+/// deserialized, as object keys of a [`Value`] are static strings.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde_bytes::{ByteBuf, Bytes};
+
+    use super::{OwnedValue, Value};
+    use crate::regex::Regex;
+
+    // Both representations must have the same variants in the same order, as some formats
+    // encode the variant index.
+    #[derive(Serialize)]
+    #[serde(
+        rename = "Value",
+        bound = "K: Ord + Serialize, V: Serialize + MaybeFunction"
+    )]
+    enum ValueRef<'a, K, V> {
+        Integer(i64),
+        Float(f64),
+        Bytes(&'a Bytes),
+        Regex(&'a Regex),
+        Boolean(bool),
+        Object(ObjectRef<'a, K, V>),
+        Array(&'a [V]),
+        Dictionary(DictionaryRef<'a, V>),
+        Undefined,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Value")]
+    enum ValueOwned {
+        Integer(i64),
+        Float(f64),
+        Bytes(ByteBuf),
+        Regex(Regex),
+        Boolean(bool),
+        Object(HashMap<String, OwnedValue>),
+        Array(Vec<OwnedValue>),
+        Dictionary(Vec<(ByteBuf, OwnedValue)>),
+        Undefined,
+    }
+
+    /// Values that can be a function, which are not serialized in objects.
+    trait MaybeFunction {
+        fn is_function(&self) -> bool;
+    }
+
+    impl MaybeFunction for Value {
+        fn is_function(&self) -> bool {
+            matches!(self, Self::Function(_))
+        }
+    }
+
+    impl MaybeFunction for OwnedValue {
+        fn is_function(&self) -> bool {
+            matches!(self, Self::Function(_))
+        }
+    }
+
+    struct ObjectRef<'a, K, V>(&'a HashMap<K, V>);
+
+    impl<K: Ord + Serialize, V: Serialize + MaybeFunction> Serialize for ObjectRef<'_, K, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut entries: Vec<_> = self.0.iter().filter(|(_, v)| !v.is_function()).collect();
+            entries.sort_unstable_by_key(|(k, _)| *k);
+            serializer.collect_map(entries)
+        }
+    }
+
+    struct DictionaryRef<'a, V>(&'a HashMap<Vec<u8>, V>);
+
+    impl<V: Serialize> Serialize for DictionaryRef<'_, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut entries: Vec<_> = self.0.iter().collect();
+            entries.sort_unstable_by_key(|(k, _)| *k);
+            serializer.collect_seq(entries.into_iter().map(|(k, v)| (Bytes::new(k), v)))
+        }
+    }
+
+    impl Serialize for Value {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let value = match self {
+                Self::Integer(v) => ValueRef::Integer(*v),
+                Self::Float(v) => ValueRef::Float(*v),
+                Self::Bytes(v) => ValueRef::Bytes(Bytes::new(v)),
+                Self::Regex(v) => ValueRef::Regex(v),
+                Self::Boolean(v) => ValueRef::Boolean(*v),
+                Self::Object(v) => ValueRef::Object(ObjectRef(v)),
+                Self::Array(v) => ValueRef::Array(v),
+                Self::Dictionary(v) => ValueRef::Dictionary(DictionaryRef(v)),
+                Self::Function(_) | Self::Undefined => ValueRef::Undefined,
+            };
+            value.serialize(serializer)
+        }
+    }
+
+    impl Serialize for OwnedValue {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let value = match self {
+                Self::Integer(v) => ValueRef::Integer(*v),
+                Self::Float(v) => ValueRef::Float(*v),
+                Self::Bytes(v) => ValueRef::Bytes(Bytes::new(v)),
+                Self::Regex(v) => ValueRef::Regex(v),
+                Self::Boolean(v) => ValueRef::Boolean(*v),
+                Self::Object(v) => ValueRef::Object(ObjectRef(v)),
+                Self::Array(v) => ValueRef::Array(v),
+                Self::Dictionary(v) => ValueRef::Dictionary(DictionaryRef(v)),
+                Self::Function(_) | Self::Undefined => ValueRef::Undefined,
+            };
+            value.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for OwnedValue {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Ok(match ValueOwned::deserialize(deserializer)? {
+                ValueOwned::Integer(v) => Self::Integer(v),
+                ValueOwned::Float(v) => Self::Float(v),
+                ValueOwned::Bytes(v) => Self::Bytes(v.into_vec()),
+                ValueOwned::Regex(v) => Self::Regex(v),
+                ValueOwned::Boolean(v) => Self::Boolean(v),
+                ValueOwned::Object(v) => Self::Object(v),
+                ValueOwned::Array(v) => Self::Array(v),
+                ValueOwned::Dictionary(v) => {
+                    Self::Dictionary(v.into_iter().map(|(k, v)| (k.into_vec(), v)).collect())
+                }
+                ValueOwned::Undefined => Self::Undefined,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -743,28 +1009,34 @@ mod tests {
     fn test_types_traits() {
         test_type_traits_non_clonable(ScanContext {
             region: &Region { start: 0, mem: b"" },
-            module_data: &mut ModuleDataMap(HashMap::new()),
+            module_data: &mut ModuleDataMap::default(),
             process_memory: false,
         });
         test_type_traits_non_clonable(EvalContext {
             mem: &mut Memory::Direct(b""),
-            module_data: &ModuleDataMap(HashMap::new()),
+            module_data: &ModuleDataMap::default(),
             process_memory: false,
         });
 
         test_type_traits(Value::Integer(0));
+        test_type_traits(OwnedValue::Integer(0));
         test_type_traits(StaticValue::Integer(0));
         test_type_traits(Type::Integer);
 
         test_type_traits_non_clonable(Time);
+        test_type_traits_non_clonable(Cuckoo);
+        test_type_traits_non_clonable(Dex);
+        test_type_traits_non_clonable(Lnk);
+        test_type_traits_non_clonable(Magic);
         test_type_traits_non_clonable(Math);
         test_type_traits_non_clonable(String_);
         #[cfg(feature = "hash")]
         test_type_traits_non_clonable(Hash);
         #[cfg(feature = "object")]
         {
+            test_type_traits_non_clonable(Dotnet);
             test_type_traits_non_clonable(Elf);
-            test_type_traits_non_clonable(MachO);
+            test_type_traits(MachO::default());
             test_type_traits(Pe::default());
         }
 
@@ -785,6 +1057,13 @@ mod tests {
             "Dictionary({})"
         );
         assert!(format!("{:?}", Value::function(test_fun)).starts_with("Function"));
+        assert_eq!(
+            format!("{:?}", OwnedValue::from(Value::bytes("a"))),
+            "Bytes(\"a\")"
+        );
+        assert!(
+            format!("{:?}", OwnedValue::from(Value::function(test_fun))).starts_with("Function")
+        );
 
         assert_eq!(format!("{:?}", StaticValue::Integer(0)), "Integer(0)");
         assert_eq!(format!("{:?}", StaticValue::Float(0.0)), "Float(0.0)");
@@ -805,4 +1084,82 @@ mod tests {
         )
         .starts_with("Function"));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_value_serde() {
+        let value = Value::Object(
+            [
+                ("int", Value::Integer(-3)),
+                ("float", Value::Float(1.5)),
+                ("bytes", Value::bytes(b"a\xFF")),
+                (
+                    "regex",
+                    Value::Regex(Regex::from_string("a.b".to_owned(), true, false).unwrap()),
+                ),
+                ("bool", Value::Boolean(true)),
+                (
+                    "array",
+                    Value::Array(vec![Value::Integer(1), Value::Undefined]),
+                ),
+                (
+                    "dict",
+                    Value::Dictionary(
+                        [
+                            (b"z".to_vec(), Value::Integer(1)),
+                            (b"a".to_vec(), Value::Integer(2)),
+                        ]
+                        .into(),
+                    ),
+                ),
+            ]
+            .into(),
+        );
+
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Object":{"array":{"Array":[{"Integer":1},"Undefined"]},"bool":{"Boolean":true},"#
+                .to_owned()
+                + r#""bytes":{"Bytes":[97,255]},"dict":{"Dictionary":[[[97],{"Integer":2}],"#
+                + r#"[[122],{"Integer":1}]]},"float":{"Float":1.5},"int":{"Integer":-3},"#
+                + r#""regex":{"Regex":{"expr":"a.b","case_insensitive":true,"dot_all":false}}}}"#
+        );
+
+        // The owned value has the same representation, and can be deserialized.
+        assert_eq!(
+            serde_json::to_string(&OwnedValue::from(value)).unwrap(),
+            json
+        );
+        let value2: OwnedValue = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&value2).unwrap(), json);
+        let OwnedValue::Object(obj) = value2 else {
+            panic!()
+        };
+        let Some(OwnedValue::Regex(regex)) = obj.get("regex") else {
+            panic!()
+        };
+        assert!(regex.is_match(b"AxB"));
+
+        // Functions are skipped in objects, and encoded as undefined values elsewhere.
+        let value = Value::object([
+            ("fun", Value::function(test_fun)),
+            ("arr", Value::Array(vec![Value::function(test_fun)])),
+        ]);
+        let json = r#"{"Object":{"arr":{"Array":["Undefined"]}}}"#;
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(
+            serde_json::to_string(&OwnedValue::from(value)).unwrap(),
+            json
+        );
+        assert_eq!(
+            serde_json::to_string(&Value::function(test_fun)).unwrap(),
+            r#""Undefined""#
+        );
+        assert!(serde_json::from_str::<OwnedValue>(r#"{"Function":null}"#).is_err());
+        assert!(serde_json::from_str::<OwnedValue>(
+            r#"{"Regex":{"expr":"(","case_insensitive":true,"dot_all":false}}"#
+        )
+        .is_err());
+    }
 }
@@ -17,9 +17,10 @@ use super::{
 };
 
 mod debug;
+mod directories;
 mod ord;
-#[cfg(feature = "authenticode")]
-mod signatures;
+#[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+pub(super) mod signatures;
 mod version_info;
 
 const MAX_PE_SECTIONS: usize = 96;
@@ -398,6 +399,54 @@ impl Module for Pe {
                 "IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR",
                 StaticValue::Integer(pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR as i64),
             ),
+            (
+                "IMAGE_GUARD_CF_INSTRUMENTED",
+                StaticValue::Integer(pe::IMAGE_GUARD_CF_INSTRUMENTED.into()),
+            ),
+            (
+                "IMAGE_GUARD_CFW_INSTRUMENTED",
+                StaticValue::Integer(pe::IMAGE_GUARD_CFW_INSTRUMENTED.into()),
+            ),
+            (
+                "IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT",
+                StaticValue::Integer(pe::IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT.into()),
+            ),
+            (
+                "IMAGE_GUARD_SECURITY_COOKIE_UNUSED",
+                StaticValue::Integer(pe::IMAGE_GUARD_SECURITY_COOKIE_UNUSED.into()),
+            ),
+            (
+                "IMAGE_GUARD_PROTECT_DELAYLOAD_IAT",
+                StaticValue::Integer(pe::IMAGE_GUARD_PROTECT_DELAYLOAD_IAT.into()),
+            ),
+            (
+                "IMAGE_GUARD_DELAYLOAD_IAT_IN_ITS_OWN_SECTION",
+                StaticValue::Integer(pe::IMAGE_GUARD_DELAYLOAD_IAT_IN_ITS_OWN_SECTION.into()),
+            ),
+            (
+                "IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT",
+                StaticValue::Integer(pe::IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT.into()),
+            ),
+            (
+                "IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION",
+                StaticValue::Integer(pe::IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION.into()),
+            ),
+            (
+                "IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT",
+                StaticValue::Integer(pe::IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT.into()),
+            ),
+            (
+                "IMAGE_GUARD_RF_INSTRUMENTED",
+                StaticValue::Integer(pe::IMAGE_GUARD_RF_INSTRUMENTED.into()),
+            ),
+            (
+                "IMAGE_GUARD_RF_ENABLE",
+                StaticValue::Integer(pe::IMAGE_GUARD_RF_ENABLE.into()),
+            ),
+            (
+                "IMAGE_GUARD_RF_STRICT",
+                StaticValue::Integer(pe::IMAGE_GUARD_RF_STRICT.into()),
+            ),
             (
                 "IMAGE_NT_OPTIONAL_HDR32_MAGIC",
                 StaticValue::Integer(pe::IMAGE_NT_OPTIONAL_HDR32_MAGIC.into()),
@@ -1009,11 +1058,49 @@ impl Module for Pe {
             ),
             ("number_of_resources", Type::Integer),
             ("pdb_path", Type::Bytes),
-            #[cfg(feature = "authenticode")]
+            ("number_of_tls_callbacks", Type::Integer),
+            ("tls_callbacks", Type::array(Type::Integer)),
+            (
+                "load_config",
+                Type::object([
+                    ("size", Type::Integer),
+                    ("time_date_stamp", Type::Integer),
+                    ("major_version", Type::Integer),
+                    ("minor_version", Type::Integer),
+                    ("global_flags_clear", Type::Integer),
+                    ("global_flags_set", Type::Integer),
+                    ("security_cookie", Type::Integer),
+                    ("se_handler_table", Type::Integer),
+                    ("se_handler_count", Type::Integer),
+                    ("guard_cf_check_function_pointer", Type::Integer),
+                    ("guard_cf_dispatch_function_pointer", Type::Integer),
+                    ("guard_cf_function_table", Type::Integer),
+                    ("guard_cf_function_count", Type::Integer),
+                    ("guard_flags", Type::Integer),
+                ]),
+            ),
+            ("number_of_runtime_functions", Type::Integer),
+            ("number_of_base_relocation_blocks", Type::Integer),
+            (
+                "base_relocation_blocks",
+                Type::array(Type::object([
+                    ("virtual_address", Type::Integer),
+                    ("number_of_entries", Type::Integer),
+                ])),
+            ),
+            ("number_of_bound_imports", Type::Integer),
+            (
+                "bound_imports",
+                Type::array(Type::object([
+                    ("library_name", Type::Bytes),
+                    ("timestamp", Type::Integer),
+                ])),
+            ),
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
             ("number_of_signatures", Type::Integer),
-            #[cfg(feature = "authenticode")]
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
             ("is_signed", Type::Integer),
-            #[cfg(feature = "authenticode")]
+            #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
             (
                 "signatures",
                 Type::array(Type::object([
@@ -1335,12 +1422,29 @@ impl Pe {
             add_delay_load_imports::<HEADERS>(&data_dirs, region.mem, sections, data, &mut map);
             add_exports(&data_dirs, region.mem, sections, data, &mut map);
             add_resources(&data_dirs, region.mem, sections, data, &mut map);
+
+            let is_64 = nt_headers.is_type_64();
+            directories::add_tls_callbacks(
+                &data_dirs,
+                region.mem,
+                sections,
+                is_64,
+                opt_hdr.image_base(),
+                &mut map,
+            );
+            directories::add_load_config(&data_dirs, region.mem, sections, is_64, &mut map);
+            directories::add_base_relocations(&data_dirs, region.mem, sections, &mut map);
         }
+        directories::add_runtime_functions(&data_dirs, hdr.machine.get(LE), &mut map);
+        directories::add_bound_imports(&data_dirs, region.mem, &mut map);
 
-        #[cfg(feature = "authenticode")]
-        if let Some(token) = self.token {
+        #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
+        if let Some(backend) = signatures::Backend::new(
+            #[cfg(feature = "authenticode")]
+            self.token,
+        ) {
             if let Some((signatures, is_signed)) =
-                signatures::get_signatures(&data_dirs, region.mem, token)
+                signatures::get_signatures(&data_dirs, region.mem, backend)
             {
                 let _r = map.insert("number_of_signatures", signatures.len().into());
                 let _r = map.insert("is_signed", Value::Integer(is_signed.into()));
@@ -1968,7 +2072,7 @@ pub fn add_version_infos(mem: &[u8], offset: u32, out: &mut HashMap<&'static str
     ]);
 }
 
-fn va_to_file_offset(mem: &[u8], sections: &SectionTable, va: u32) -> Option<u32> {
+pub(super) fn va_to_file_offset(mem: &[u8], sections: &SectionTable, va: u32) -> Option<u32> {
     va_to_file_offset_inner(sections, va).and_then(|v| {
         let len: u32 = mem.len().try_into().ok()?;
         if v < len {
@@ -0,0 +1,281 @@
+//! Parsing of data directories that libyara does not expose: TLS callbacks, load config,
+//! exception directory, base relocations and bound imports.
+use std::collections::HashMap;
+
+use object::{coff::SectionTable, pe, read::pe::DataDirectories, LittleEndian as LE};
+
+use super::{va_to_file_offset, Value};
+
+const MAX_TLS_CALLBACKS: usize = 1024;
+const MAX_BASE_RELOCATION_BLOCKS: usize = 16384;
+const MAX_BOUND_IMPORTS: usize = 1024;
+const MAX_BOUND_IMPORT_NAME_LENGTH: usize = 256;
+
+/// Add the callbacks listed in the TLS directory.
+///
+/// The callbacks are virtual addresses, as stored in the file.
+pub fn add_tls_callbacks(
+    data_dirs: &DataDirectories,
+    mem: &[u8],
+    sections: &SectionTable,
+    is_64: bool,
+    image_base: u64,
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let Some(dir) = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_TLS) else {
+        return;
+    };
+    let Some(tls) = directory_data(mem, sections, dir.virtual_address.get(LE)) else {
+        return;
+    };
+
+    // Offset of the AddressOfCallBacks field in IMAGE_TLS_DIRECTORY.
+    let ptr_size = if is_64 { 8 } else { 4 };
+    let Some(callbacks_va) = read_uint(tls, ptr_size * 3, ptr_size) else {
+        return;
+    };
+    let Some(callbacks) = callbacks_va
+        .checked_sub(image_base)
+        .and_then(|rva| u32::try_from(rva).ok())
+        .and_then(|rva| directory_data(mem, sections, rva))
+    else {
+        return;
+    };
+
+    let callbacks: Vec<Value> = callbacks
+        .chunks_exact(ptr_size)
+        .map(|v| read_uint(v, 0, ptr_size).unwrap_or(0))
+        .take_while(|va| *va != 0)
+        .take(MAX_TLS_CALLBACKS)
+        .map(Value::from)
+        .collect();
+
+    if !callbacks.is_empty() {
+        let _r = out.insert("number_of_tls_callbacks", callbacks.len().into());
+        let _r = out.insert("tls_callbacks", Value::Array(callbacks));
+    }
+}
+
+#[derive(Clone, Copy)]
+enum FieldSize {
+    Word,
+    Dword,
+    Pointer,
+}
+
+/// Fields of `IMAGE_LOAD_CONFIG_DIRECTORY`, with their offsets in the 32 and 64 bits versions.
+const LOAD_CONFIG_FIELDS: [(&str, usize, usize, FieldSize); 14] = [
+    ("size", 0, 0, FieldSize::Dword),
+    ("time_date_stamp", 4, 4, FieldSize::Dword),
+    ("major_version", 8, 8, FieldSize::Word),
+    ("minor_version", 10, 10, FieldSize::Word),
+    ("global_flags_clear", 12, 12, FieldSize::Dword),
+    ("global_flags_set", 16, 16, FieldSize::Dword),
+    ("security_cookie", 60, 88, FieldSize::Pointer),
+    ("se_handler_table", 64, 96, FieldSize::Pointer),
+    ("se_handler_count", 68, 104, FieldSize::Pointer),
+    (
+        "guard_cf_check_function_pointer",
+        72,
+        112,
+        FieldSize::Pointer,
+    ),
+    (
+        "guard_cf_dispatch_function_pointer",
+        76,
+        120,
+        FieldSize::Pointer,
+    ),
+    ("guard_cf_function_table", 80, 128, FieldSize::Pointer),
+    ("guard_cf_function_count", 84, 136, FieldSize::Pointer),
+    ("guard_flags", 88, 144, FieldSize::Dword),
+];
+
+/// Add the load config directory.
+///
+/// The structure grew with each Windows version, only the fields covered by the size stored in
+/// the structure itself are set.
+pub fn add_load_config(
+    data_dirs: &DataDirectories,
+    mem: &[u8],
+    sections: &SectionTable,
+    is_64: bool,
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let Some(dir) = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG) else {
+        return;
+    };
+    let Some(config) = directory_data(mem, sections, dir.virtual_address.get(LE)) else {
+        return;
+    };
+    let Some(size) = read_uint(config, 0, 4).and_then(|v| usize::try_from(v).ok()) else {
+        return;
+    };
+    let config = &config[..size.min(config.len())];
+
+    let fields = LOAD_CONFIG_FIELDS
+        .iter()
+        .filter_map(|(name, offset32, offset64, size)| {
+            let offset = if is_64 { *offset64 } else { *offset32 };
+            let size = match size {
+                FieldSize::Word => 2,
+                FieldSize::Dword => 4,
+                FieldSize::Pointer if is_64 => 8,
+                FieldSize::Pointer => 4,
+            };
+            read_uint(config, offset, size).map(|v| (*name, Value::from(v)))
+        })
+        .collect();
+
+    let _r = out.insert("load_config", Value::Object(fields));
+}
+
+/// Add the number of functions described in the exception directory.
+///
+/// Only architectures using table-based exception handling are handled.
+pub fn add_runtime_functions(
+    data_dirs: &DataDirectories,
+    machine: u16,
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let Some(dir) = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_EXCEPTION) else {
+        return;
+    };
+
+    let entry_size = match machine {
+        pe::IMAGE_FILE_MACHINE_AMD64 | pe::IMAGE_FILE_MACHINE_IA64 => 12,
+        pe::IMAGE_FILE_MACHINE_ARM64 | pe::IMAGE_FILE_MACHINE_ARMNT => 8,
+        _ => return,
+    };
+
+    let _r = out.insert(
+        "number_of_runtime_functions",
+        (dir.size.get(LE) / entry_size).into(),
+    );
+}
+
+/// Add the blocks of the base relocation directory.
+pub fn add_base_relocations(
+    data_dirs: &DataDirectories,
+    mem: &[u8],
+    sections: &SectionTable,
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let Some(dir) = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_BASERELOC) else {
+        return;
+    };
+    let Some(mut relocs) = directory_data(mem, sections, dir.virtual_address.get(LE)) else {
+        return;
+    };
+    if let Ok(size) = usize::try_from(dir.size.get(LE)) {
+        relocs = &relocs[..size.min(relocs.len())];
+    }
+
+    let mut blocks = Vec::new();
+    while blocks.len() < MAX_BASE_RELOCATION_BLOCKS {
+        let (Some(virtual_address), Some(block_size)) =
+            (read_uint(relocs, 0, 4), read_uint(relocs, 4, 4))
+        else {
+            break;
+        };
+        let Some(block_size) = usize::try_from(block_size)
+            .ok()
+            .filter(|size| *size >= 8 && *size <= relocs.len())
+        else {
+            break;
+        };
+
+        blocks.push(Value::object([
+            ("virtual_address", virtual_address.into()),
+            ("number_of_entries", ((block_size - 8) / 2).into()),
+        ]));
+        relocs = &relocs[block_size..];
+    }
+
+    if !blocks.is_empty() {
+        let _r = out.insert("number_of_base_relocation_blocks", blocks.len().into());
+        let _r = out.insert("base_relocation_blocks", Value::Array(blocks));
+    }
+}
+
+/// Add the bound imports.
+///
+/// Unlike other directories, the address of this directory is a file offset, as it is usually
+/// stored in the headers. The forwarder references of each descriptor are skipped.
+pub fn add_bound_imports(
+    data_dirs: &DataDirectories,
+    mem: &[u8],
+    out: &mut HashMap<&'static str, Value>,
+) {
+    let Some(dir) = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_BOUND_IMPORT) else {
+        return;
+    };
+    let Some(bound_imports) = usize::try_from(dir.virtual_address.get(LE))
+        .ok()
+        .and_then(|offset| mem.get(offset..))
+    else {
+        return;
+    };
+    let descriptors = match usize::try_from(dir.size.get(LE)) {
+        Ok(size) if size < bound_imports.len() => &bound_imports[..size],
+        _ => bound_imports,
+    };
+
+    let mut imports = Vec::new();
+    let mut descriptors = descriptors.chunks_exact(8);
+    while imports.len() < MAX_BOUND_IMPORTS {
+        let Some(descriptor) = descriptors.next() else {
+            break;
+        };
+        let (Some(timestamp), Some(name_offset), Some(nb_forwarders)) = (
+            read_uint(descriptor, 0, 4),
+            read_uint(descriptor, 4, 2),
+            read_uint(descriptor, 6, 2),
+        ) else {
+            break;
+        };
+        if timestamp == 0 && name_offset == 0 {
+            break;
+        }
+
+        // The name offset is relative to the start of the directory.
+        let name = usize::try_from(name_offset)
+            .ok()
+            .and_then(|offset| bound_imports.get(offset..))
+            .map(|name| {
+                let len = memchr::memchr(b'\0', name)
+                    .unwrap_or(name.len())
+                    .min(MAX_BOUND_IMPORT_NAME_LENGTH);
+                name[..len].to_vec()
+            });
+        imports.push(Value::object([
+            ("library_name", name.into()),
+            ("timestamp", timestamp.into()),
+        ]));
+
+        for _ in 0..nb_forwarders {
+            let _r = descriptors.next();
+        }
+    }
+
+    if !imports.is_empty() {
+        let _r = out.insert("number_of_bound_imports", imports.len().into());
+        let _r = out.insert("bound_imports", Value::Array(imports));
+    }
+}
+
+fn directory_data<'a>(mem: &'a [u8], sections: &SectionTable, va: u32) -> Option<&'a [u8]> {
+    let offset = va_to_file_offset(mem, sections, va)?;
+    mem.get(usize::try_from(offset).ok()?..)
+}
+
+/// Read a little-endian unsigned integer of `size` bytes at the given offset.
+fn read_uint(mem: &[u8], offset: usize, size: usize) -> Option<u64> {
+    let bytes = mem.get(offset..offset.checked_add(size)?)?;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |acc, b| (acc << 8) | u64::from(*b)),
+    )
+}
@@ -1,17 +1,119 @@
 use std::collections::HashMap;
 
-use authenticode_parser::{
-    Authenticode, AuthenticodeArray, AuthenticodeVerify, Certificate, CounterSignatureVerify,
-    Countersignature, InitializationToken, Signer,
-};
 use object::{pe, read::pe::DataDirectories};
 
 use super::Value;
 
+#[cfg(feature = "authenticode")]
+mod openssl;
+#[cfg(feature = "authenticode-pure")]
+mod pkcs7;
+
+/// Parser used to extract the authenticode signatures.
+#[derive(Copy, Clone, Debug)]
+pub(in crate::module) enum Backend {
+    /// `authenticode-parser`, which relies on OpenSSL.
+    #[cfg(feature = "authenticode")]
+    OpenSsl(authenticode_parser::InitializationToken),
+
+    /// Parser written in pure rust, which only checks the consistency of the digests.
+    #[cfg(feature = "authenticode-pure")]
+    Pure,
+}
+
+impl Backend {
+    /// Backend used when the OpenSSL one has not been initialized.
+    #[cfg(feature = "authenticode-pure")]
+    const FALLBACK: Option<Self> = Some(Self::Pure);
+    #[cfg(not(feature = "authenticode-pure"))]
+    const FALLBACK: Option<Self> = None;
+
+    /// Select the backend to use.
+    ///
+    /// The OpenSSL backend is used if it has been initialized, otherwise the pure rust one is
+    /// used if available.
+    pub(in crate::module) fn new(
+        #[cfg(feature = "authenticode")] token: Option<authenticode_parser::InitializationToken>,
+    ) -> Option<Self> {
+        #[cfg(feature = "authenticode")]
+        if let Some(token) = token {
+            return Some(Self::OpenSsl(token));
+        }
+        Self::FALLBACK
+    }
+
+    /// Parse the signatures of a PE file.
+    fn parse_pe(self, mem: &[u8]) -> Option<Vec<Signature>> {
+        match self {
+            #[cfg(feature = "authenticode")]
+            Self::OpenSsl(token) => {
+                authenticode_parser::parse_pe(&token, mem).map(|auth| openssl::convert(&auth))
+            }
+            #[cfg(feature = "authenticode-pure")]
+            Self::Pure => pkcs7::parse_pe(mem),
+        }
+    }
+
+    /// Parse a PKCS#7 signature.
+    fn parse(self, cms: &[u8]) -> Option<Vec<Signature>> {
+        match self {
+            #[cfg(feature = "authenticode")]
+            Self::OpenSsl(token) => {
+                authenticode_parser::parse(&token, cms).map(|auth| openssl::convert(&auth))
+            }
+            #[cfg(feature = "authenticode-pure")]
+            Self::Pure => pkcs7::parse(cms),
+        }
+    }
+}
+
+/// An authenticode signature, as extracted by one of the backends.
+#[derive(Debug, Default)]
+struct Signature {
+    verified: bool,
+    digest_alg: Option<Vec<u8>>,
+    digest: Option<Vec<u8>>,
+    file_digest: Option<Vec<u8>>,
+    certs: Vec<Certificate>,
+    signer: Option<Signer>,
+    countersigs: Vec<Countersignature>,
+}
+
+#[derive(Debug, Default)]
+struct Signer {
+    program_name: Option<Vec<u8>>,
+    digest: Option<Vec<u8>>,
+    digest_alg: Option<Vec<u8>>,
+    chain: Vec<Certificate>,
+}
+
+#[derive(Debug, Default)]
+struct Countersignature {
+    verified: bool,
+    sign_time: i64,
+    digest: Option<Vec<u8>>,
+    digest_alg: Option<Vec<u8>>,
+    chain: Vec<Certificate>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Certificate {
+    sha1: Option<Vec<u8>>,
+    issuer: Option<Vec<u8>>,
+    subject: Option<Vec<u8>>,
+    /// Version as encoded in the certificate, starting at 0.
+    version: i64,
+    sig_alg: Option<Vec<u8>>,
+    sig_alg_oid: Option<Vec<u8>>,
+    serial: Option<Vec<u8>>,
+    not_before: i64,
+    not_after: i64,
+}
+
 pub fn get_signatures(
     data_dirs: &DataDirectories,
     mem: &[u8],
-    token: InitializationToken,
+    backend: Backend,
 ) -> Option<(Vec<Value>, bool)> {
     let dir = data_dirs.get(pe::IMAGE_DIRECTORY_ENTRY_SECURITY)?;
     let (va, size) = dir.address_range();
@@ -24,36 +126,48 @@ pub fn get_signatures(
     }
 
     // TODO: use parse instead of parse_pe as we have the payload already?
-    let auth = authenticode_parser::parse_pe(&token, mem)?;
-    Some(process_authenticode(&auth))
+    let signatures = backend.parse_pe(mem)?;
+    Some(process_signatures(&signatures))
+}
+
+/// Parse the certificates contained in a CMS signature.
+pub(in crate::module) fn parse_certificates(cms: &[u8], backend: Backend) -> Vec<Value> {
+    backend.parse(cms).map_or_else(Vec::new, |signatures| {
+        signatures
+            .iter()
+            .flat_map(|sig| process_certs(&sig.certs))
+            .collect()
+    })
 }
 
-fn process_authenticode(auth: &AuthenticodeArray) -> (Vec<Value>, bool) {
-    let mut signatures = Vec::new();
+fn process_signatures(signatures: &[Signature]) -> (Vec<Value>, bool) {
+    let mut values = Vec::new();
     // Whole pe is signed if at least one signature is signed.
     let mut is_signed = false;
 
-    for sig in auth.signatures() {
-        let verified = sig.verify_flags() == Some(AuthenticodeVerify::Valid);
-        is_signed = is_signed || verified;
+    for sig in signatures {
+        is_signed = is_signed || sig.verified;
 
-        let digest = sig.digest().map(hex::encode).map(Value::bytes);
-        let digest_alg = sig.digest_alg().map(Value::bytes);
-        let file_digest = sig.file_digest().map(hex::encode).map(Value::bytes);
+        let digest = sig.digest.as_deref().map(hex::encode).map(Value::bytes);
+        let file_digest = sig
+            .file_digest
+            .as_deref()
+            .map(hex::encode)
+            .map(Value::bytes);
 
         // TODO on length_of_chain or other lengths, behavior is not aligned:
         // yara does not save the length if the pointer is 0.
-        let certs = process_certs(sig.certs());
+        let certs = process_certs(&sig.certs);
         let signer_info = sig
-            .signer()
+            .signer
             .as_ref()
             .map_or(Value::Undefined, signer_to_value);
-        let countersigs: Vec<_> = sig.countersigs().iter().map(countersig_to_value).collect();
+        let countersigs: Vec<_> = sig.countersigs.iter().map(countersig_to_value).collect();
 
         let mut map = get_legacy_signer_data(sig);
         map.extend([
-            ("verified", Value::Integer(verified.into())),
-            ("digest_alg", digest_alg.unwrap_or(Value::Undefined)),
+            ("verified", Value::Integer(sig.verified.into())),
+            ("digest_alg", sig.digest_alg.clone().into()),
             ("digest", digest.unwrap_or(Value::Undefined)),
             ("file_digest", file_digest.unwrap_or(Value::Undefined)),
             ("number_of_certificates", certs.len().into()),
@@ -63,10 +177,10 @@ fn process_authenticode(auth: &AuthenticodeArray) -> (Vec<Value>, bool) {
             ("countersignatures", Value::Array(countersigs)),
         ]);
 
-        signatures.push(Value::Object(map));
+        values.push(Value::Object(map));
     }
 
-    (signatures, is_signed)
+    (values, is_signed)
 }
 
 fn process_certs(certs: &[Certificate]) -> Vec<Value> {
@@ -78,62 +192,57 @@ fn process_certs(certs: &[Certificate]) -> Vec<Value> {
 }
 
 fn signer_to_value(signer: &Signer) -> Value {
-    let program_name = signer.program_name().map(Value::bytes);
-    let digest = signer.digest().map(hex::encode).map(Value::bytes);
-    let digest_alg = signer.digest_alg().map(Value::bytes);
-    let chain = process_certs(signer.certificate_chain());
+    let digest = signer.digest.as_deref().map(hex::encode).map(Value::bytes);
+    let chain = process_certs(&signer.chain);
 
     Value::object([
-        ("program_name", program_name.unwrap_or(Value::Undefined)),
+        ("program_name", signer.program_name.clone().into()),
         ("digest", digest.unwrap_or(Value::Undefined)),
-        ("digest_alg", digest_alg.unwrap_or(Value::Undefined)),
+        ("digest_alg", signer.digest_alg.clone().into()),
         ("length_of_chain", chain.len().into()),
         ("chain", Value::Array(chain)),
     ])
 }
 
 fn countersig_to_value(countersig: &Countersignature) -> Value {
-    let verified =
-        Value::Integer((countersig.verify_flags() == Some(CounterSignatureVerify::Valid)).into());
-    let sign_time = countersig.sign_time().into();
-    let digest = countersig.digest().map(hex::encode).map(Value::bytes);
-    let digest_alg = countersig.digest_alg().map(Value::bytes);
-    let chain = process_certs(countersig.certificate_chain());
+    let digest = countersig
+        .digest
+        .as_deref()
+        .map(hex::encode)
+        .map(Value::bytes);
+    let chain = process_certs(&countersig.chain);
 
     Value::object([
-        ("verified", verified),
-        ("sign_time", sign_time),
+        ("verified", Value::Integer(countersig.verified.into())),
+        ("sign_time", countersig.sign_time.into()),
         ("digest", digest.unwrap_or(Value::Undefined)),
-        ("digest_alg", digest_alg.unwrap_or(Value::Undefined)),
+        ("digest_alg", countersig.digest_alg.clone().into()),
         ("length_of_chain", chain.len().into()),
         ("chain", Value::Array(chain)),
     ])
 }
 
-fn get_legacy_signer_data(sig: &Authenticode) -> HashMap<&'static str, Value> {
-    sig.signer()
+fn get_legacy_signer_data(sig: &Signature) -> HashMap<&'static str, Value> {
+    sig.signer
         .as_ref()
-        .and_then(|signer| signer.certificate_chain().first())
+        .and_then(|signer| signer.chain.first())
         .map(|v| cert_to_map(v, true))
         .unwrap_or_default()
 }
 
 fn cert_to_map(cert: &Certificate, with_valid_on: bool) -> HashMap<&'static str, Value> {
-    let thumbprint_ascii = cert.sha1().map(hex::encode).map(Value::bytes);
-    let not_before = cert.not_before();
-    let not_after = cert.not_after();
+    let thumbprint_ascii = cert.sha1.as_deref().map(hex::encode).map(Value::bytes);
+    let not_before = cert.not_before;
+    let not_after = cert.not_after;
 
     [
         ("thumbprint", thumbprint_ascii.unwrap_or(Value::Undefined)),
-        ("issuer", cert.issuer().map(ToOwned::to_owned).into()),
-        ("subject", cert.subject().map(ToOwned::to_owned).into()),
-        ("version", (cert.version() + 1).into()),
-        ("algorithm", cert.sig_alg().map(ToOwned::to_owned).into()),
-        (
-            "algorithm_oid",
-            cert.sig_alg_oid().map(ToOwned::to_owned).into(),
-        ),
-        ("serial", cert.serial().map(ToOwned::to_owned).into()),
+        ("issuer", cert.issuer.clone().into()),
+        ("subject", cert.subject.clone().into()),
+        ("version", (cert.version + 1).into()),
+        ("algorithm", cert.sig_alg.clone().into()),
+        ("algorithm_oid", cert.sig_alg_oid.clone().into()),
+        ("serial", cert.serial.clone().into()),
         ("not_before", not_before.into()),
         ("not_after", not_after.into()),
         (
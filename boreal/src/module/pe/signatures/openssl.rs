@@ -0,0 +1,51 @@
+//! Conversion of the signatures parsed by `authenticode-parser`.
+use authenticode_parser::{AuthenticodeArray, AuthenticodeVerify, CounterSignatureVerify};
+
+use super::{Certificate, Countersignature, Signature, Signer};
+
+pub(super) fn convert(auth: &AuthenticodeArray) -> Vec<Signature> {
+    auth.signatures()
+        .iter()
+        .map(|sig| Signature {
+            verified: sig.verify_flags() == Some(AuthenticodeVerify::Valid),
+            digest_alg: sig.digest_alg().map(Into::into),
+            digest: sig.digest().map(Into::into),
+            file_digest: sig.file_digest().map(Into::into),
+            certs: convert_certs(sig.certs()),
+            signer: sig.signer().as_ref().map(|signer| Signer {
+                program_name: signer.program_name().map(Into::into),
+                digest: signer.digest().map(Into::into),
+                digest_alg: signer.digest_alg().map(Into::into),
+                chain: convert_certs(signer.certificate_chain()),
+            }),
+            countersigs: sig
+                .countersigs()
+                .iter()
+                .map(|countersig| Countersignature {
+                    verified: countersig.verify_flags() == Some(CounterSignatureVerify::Valid),
+                    sign_time: countersig.sign_time(),
+                    digest: countersig.digest().map(Into::into),
+                    digest_alg: countersig.digest_alg().map(Into::into),
+                    chain: convert_certs(countersig.certificate_chain()),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn convert_certs(certs: &[authenticode_parser::Certificate]) -> Vec<Certificate> {
+    certs
+        .iter()
+        .map(|cert| Certificate {
+            sha1: cert.sha1().map(Into::into),
+            issuer: cert.issuer().map(Into::into),
+            subject: cert.subject().map(Into::into),
+            version: cert.version(),
+            sig_alg: cert.sig_alg().map(Into::into),
+            sig_alg_oid: cert.sig_alg_oid().map(Into::into),
+            serial: cert.serial().map(Into::into),
+            not_before: cert.not_before(),
+            not_after: cert.not_after(),
+        })
+        .collect()
+}
@@ -0,0 +1,661 @@
+//! Parsing of authenticode signatures written in pure rust.
+//!
+//! An authenticode signature is a PKCS#7 `SignedData`, whose content holds the digest of the
+//! signed file. The values are extracted the same way `authenticode-parser` does, so that both
+//! backends are interchangeable.
+//!
+//! The signatures are however not cryptographically verified: a signature is considered
+//! verified if the digests it contains are consistent with the signed content and the file.
+//! Neither the signer signatures nor the certificate chains are checked, so anyone can forge
+//! a signature that is reported as verified.
+use sha1::Digest;
+
+use self::der::{
+    oid_to_string, parse_time, Reader, Tlv, TAG_CONTEXT_0, TAG_CONTEXT_1, TAG_CONTEXT_CONS_0,
+    TAG_CONTEXT_CONS_1, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE, TAG_SET,
+};
+use self::x509::{algorithm_oid, X509};
+use super::{Certificate, Countersignature, Signature, Signer};
+
+mod der;
+mod x509;
+
+const MAX_NB_CERTIFICATES: usize = 256;
+const MAX_CHAIN_LENGTH: usize = 16;
+const MAX_NB_NESTED_SIGNATURES: usize = 16;
+const MAX_NESTING_DEPTH: u32 = 8;
+
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+const OID_COUNTERSIGNATURE: &str = "1.2.840.113549.1.9.6";
+const OID_TST_INFO: &str = "1.2.840.113549.1.9.16.1.4";
+const OID_SPC_INDIRECT_DATA: &str = "1.3.6.1.4.1.311.2.1.4";
+const OID_SPC_SP_OPUS_INFO: &str = "1.3.6.1.4.1.311.2.1.12";
+const OID_SPC_NESTED_SIGNATURE: &str = "1.3.6.1.4.1.311.2.4.1";
+const OID_MS_COUNTERSIGNATURE: &str = "1.3.6.1.4.1.311.3.3.1";
+
+/// Parse the signatures of a PE file, and compute the digest of the file for each of them.
+///
+/// Only the first entry of the certificate table is parsed.
+pub(super) fn parse_pe(mem: &[u8]) -> Option<Vec<Signature>> {
+    if mem.len() < 0x40 || &mem[..2] != b"MZ" {
+        return None;
+    }
+    let pe_offset = read_u32(mem, 0x3C)?;
+    let is_64 = read_u16(mem, pe_offset.checked_add(0x18)?)? == 0x20B;
+    // Offset of the security entry in the data directories.
+    let dir_offset = pe_offset.checked_add(if is_64 { 0xA8 } else { 0x98 })?;
+
+    let cert_addr = read_u32(mem, dir_offset)?;
+    let cert_len = read_u32(mem, dir_offset.checked_add(4)?)?;
+    if cert_len < 8 {
+        return None;
+    }
+    // The table starts with a WIN_CERTIFICATE header, whose length includes the header.
+    let length = read_u32(mem, cert_addr)?;
+    let win_certificate = mem.get(cert_addr..cert_addr.checked_add(length)?)?;
+    let mut signatures = parse(win_certificate.get(8..)?)?;
+
+    let parts = digested_parts(mem, pe_offset, dir_offset, cert_addr);
+
+    for sig in &mut signatures {
+        let alg = sig
+            .digest_alg
+            .as_deref()
+            .and_then(DigestAlgorithm::from_name);
+        match (alg, &parts) {
+            (Some(alg), Some(parts)) if sig.digest.is_some() => {
+                let file_digest = alg.digest(parts);
+                sig.verified = sig.verified && sig.digest.as_ref() == Some(&file_digest);
+                sig.file_digest = Some(file_digest);
+            }
+            _ => sig.verified = false,
+        }
+    }
+
+    Some(signatures)
+}
+
+/// List the parts of a PE file covered by its authenticode digest.
+///
+/// This is the whole file up to the certificate table, except for the checksum and the
+/// security entry of the data directories.
+fn digested_parts(
+    mem: &[u8],
+    pe_offset: usize,
+    dir_offset: usize,
+    cert_addr: usize,
+) -> Option<[&[u8]; 3]> {
+    let checksum_offset = pe_offset.checked_add(0x58)?;
+    Some([
+        mem.get(..checksum_offset)?,
+        mem.get(checksum_offset.checked_add(4)?..dir_offset)?,
+        mem.get(dir_offset.checked_add(8)?..cert_addr)?,
+    ])
+}
+
+/// Parse a PKCS#7 signature, and the signatures nested in it.
+pub(super) fn parse(cms: &[u8]) -> Option<Vec<Signature>> {
+    if cms.is_empty() {
+        return None;
+    }
+
+    let mut signatures = Vec::new();
+    parse_signatures(cms, 0, &mut signatures);
+    Some(signatures)
+}
+
+/// Parse a signature, and add it to the list followed by the signatures nested in it.
+fn parse_signatures(cms: &[u8], depth: u32, out: &mut Vec<Signature>) {
+    let mut nested = Vec::new();
+    let signature = match SignedData::parse(cms) {
+        Some(signed_data) => parse_signature(&signed_data, depth, &mut nested),
+        // Signatures that cannot be parsed are still listed, but with no details.
+        None => Signature::default(),
+    };
+    out.push(signature);
+    out.append(&mut nested);
+}
+
+fn parse_signature(signed_data: &SignedData, depth: u32, nested: &mut Vec<Signature>) -> Signature {
+    let mut sig = Signature {
+        certs: signed_data.certs.iter().map(|v| v.cert.clone()).collect(),
+        ..Signature::default()
+    };
+
+    let Some((digest_alg, digest)) = signed_data.indirect_data_digest() else {
+        return sig;
+    };
+    sig.digest_alg = Some(digest_name(digest_alg.as_deref()));
+    sig.digest = Some(digest.to_vec());
+
+    let Some(si) = &signed_data.signer_info else {
+        sig.signer = Some(Signer::default());
+        return sig;
+    };
+
+    if depth < MAX_NESTING_DEPTH {
+        for value in si
+            .unauthenticated_attribute(OID_SPC_NESTED_SIGNATURE)
+            .into_iter()
+            .take(MAX_NB_NESTED_SIGNATURES)
+        {
+            parse_signatures(value.raw, depth + 1, nested);
+        }
+    }
+
+    for value in si.unauthenticated_attribute(OID_COUNTERSIGNATURE) {
+        sig.countersigs.push(pkcs9_countersignature(
+            value.raw,
+            &signed_data.certs,
+            si.encrypted_digest,
+        ));
+    }
+    for value in si.unauthenticated_attribute(OID_MS_COUNTERSIGNATURE) {
+        let Some(timestamp) = SignedData::parse(value.raw) else {
+            sig.countersigs.push(Countersignature::default());
+            continue;
+        };
+        sig.countersigs
+            .push(ms_countersignature(&timestamp, si.encrypted_digest));
+        // The timestamp has its own set of certificates. Add them to the signature, for
+        // consistency with PKCS#9 countersignatures.
+        sig.certs
+            .extend(timestamp.certs.iter().map(|v| v.cert.clone()));
+    }
+
+    let mut signer = Signer {
+        program_name: program_name(si),
+        digest: si.message_digest().map(<[u8]>::to_vec),
+        digest_alg: Some(digest_name(si.digest_alg.as_deref())),
+        chain: Vec::new(),
+    };
+
+    if let Some(cert) = find_certificate(&signed_data.certs, si) {
+        signer.chain = build_chain(cert, &signed_data.certs);
+
+        // The digest of the content is stored in the signer info. For version 1 signatures,
+        // only the contents of the content sequence are hashed.
+        let content_digest = signed_data.content.and_then(|content| {
+            let content = if signed_data.version == [1] {
+                content.value
+            } else {
+                content.raw
+            };
+            DigestAlgorithm::from_oid(si.digest_alg.as_deref()?).map(|alg| alg.digest(&[content]))
+        });
+        sig.verified = content_digest.is_some() && content_digest == signer.digest;
+    }
+    sig.signer = Some(signer);
+
+    sig
+}
+
+/// Parse a PKCS#9 countersignature, usually a timestamp of the signature.
+///
+/// The certificates of the countersignature are stored in the signature it countersigns.
+fn pkcs9_countersignature(raw: &[u8], certs: &[X509], encrypted_digest: &[u8]) -> Countersignature {
+    let mut countersig = Countersignature::default();
+
+    let Some(si) = SignerInfo::parse(raw) else {
+        return countersig;
+    };
+    let Some(digest) = si.message_digest() else {
+        return countersig;
+    };
+    countersig.digest = Some(digest.to_vec());
+    let Some(sign_time) = si
+        .authenticated_attribute(OID_SIGNING_TIME)
+        .and_then(|v| parse_time(&v))
+    else {
+        return countersig;
+    };
+    countersig.sign_time = sign_time;
+    let Some(cert) = find_certificate(certs, &si) else {
+        return countersig;
+    };
+    countersig.chain = build_chain(cert, certs);
+    let Some(alg) = si.digest_alg.as_deref().and_then(DigestAlgorithm::from_oid) else {
+        return countersig;
+    };
+    countersig.digest_alg = Some(alg.name().as_bytes().to_vec());
+
+    // The countersignature signs the digest of the encrypted digest of the signature.
+    countersig.verified = alg.digest(&[encrypted_digest]) == digest;
+    countersig
+}
+
+/// Parse a Microsoft countersignature: a RFC 3161 timestamp of the signature.
+fn ms_countersignature(timestamp: &SignedData, encrypted_digest: &[u8]) -> Countersignature {
+    let mut countersig = Countersignature::default();
+
+    let Some(tst_info) = timestamp.tst_info() else {
+        return countersig;
+    };
+    countersig.sign_time = tst_info.sign_time;
+    let Some(cert) = timestamp
+        .signer_info
+        .as_ref()
+        .and_then(|si| find_certificate(&timestamp.certs, si))
+    else {
+        return countersig;
+    };
+    countersig.chain = build_chain(cert, &timestamp.certs);
+    countersig.digest_alg = Some(digest_name(tst_info.digest_alg.as_deref()));
+    countersig.digest = Some(tst_info.digest.to_vec());
+
+    countersig.verified = tst_info
+        .digest_alg
+        .as_deref()
+        .and_then(DigestAlgorithm::from_oid)
+        .map_or(false, |alg| {
+            alg.digest(&[encrypted_digest]) == tst_info.digest
+        });
+    countersig
+}
+
+/// Retrieve the name of the signed program, stored in the signer info.
+fn program_name(si: &SignerInfo) -> Option<Vec<u8>> {
+    let opus_info = si
+        .authenticated_attribute(OID_SPC_SP_OPUS_INFO)
+        .filter(|v| v.tag == TAG_SEQUENCE)?;
+    let name = Reader::new(opus_info.value).read_optional(TAG_CONTEXT_CONS_0)?;
+    let name = Reader::new(name.value).read()?;
+
+    match name.tag {
+        // BMPString, converted to utf-8.
+        TAG_CONTEXT_0 => {
+            let name: Vec<u16> = name
+                .value
+                .chunks_exact(2)
+                .map(|v| u16::from_be_bytes([v[0], v[1]]))
+                .collect();
+            String::from_utf16(&name).ok().map(String::into_bytes)
+        }
+        // IA5String
+        TAG_CONTEXT_1 => Some(name.value.to_vec()),
+        _ => None,
+    }
+}
+
+fn find_certificate<'a, 'b>(certs: &'a [X509<'b>], si: &SignerInfo) -> Option<&'a X509<'b>> {
+    certs.iter().find(|v| v.is_issued_by(si.issuer, si.serial))
+}
+
+/// Build the certificate chain of a signer, by following the issuers of the certificates.
+///
+/// The chain stops on a self-signed certificate, or if the issuer cannot be found.
+fn build_chain<'a, 'b>(cert: &'a X509<'b>, certs: &'a [X509<'b>]) -> Vec<Certificate> {
+    let mut chain = vec![cert];
+    while chain.len() < MAX_CHAIN_LENGTH {
+        let last = chain[chain.len() - 1];
+        if last.is_self_signed() {
+            break;
+        }
+        let issuer = certs.iter().find(|v| {
+            v.subject == last.issuer && !chain.iter().any(|prev| std::ptr::eq(*prev, *v))
+        });
+        match issuer {
+            Some(issuer) => chain.push(issuer),
+            None => break,
+        }
+    }
+
+    chain.into_iter().map(|v| v.cert.clone()).collect()
+}
+
+/// Name of a digest algorithm, as returned by OpenSSL.
+fn digest_name(oid: Option<&str>) -> Vec<u8> {
+    oid.and_then(DigestAlgorithm::from_oid)
+        .map_or("undefined", DigestAlgorithm::name)
+        .as_bytes()
+        .to_vec()
+}
+
+/// A PKCS#7 `SignedData`.
+struct SignedData<'a> {
+    /// Contents of the version integer.
+    version: &'a [u8],
+    content_type: Option<String>,
+    /// Element stored in the content.
+    content: Option<Tlv<'a>>,
+    certs: Vec<X509<'a>>,
+    /// First signer info. Authenticode signatures only have one.
+    signer_info: Option<SignerInfo<'a>>,
+}
+
+impl<'a> SignedData<'a> {
+    /// Parse a `ContentInfo` containing a `SignedData`.
+    fn parse(content_info: &'a [u8]) -> Option<Self> {
+        let mut content_info = Reader::new(Reader::new(content_info).expect(TAG_SEQUENCE)?);
+        if oid_to_string(content_info.expect(TAG_OID)?)? != OID_SIGNED_DATA {
+            return None;
+        }
+        let signed_data =
+            Reader::new(content_info.expect(TAG_CONTEXT_CONS_0)?).expect(TAG_SEQUENCE)?;
+        let mut signed_data = Reader::new(signed_data);
+
+        let version = signed_data.expect(TAG_INTEGER)?;
+        let _digest_algorithms = signed_data.expect(TAG_SET)?;
+
+        let mut encap_content_info = Reader::new(signed_data.expect(TAG_SEQUENCE)?);
+        let content_type = oid_to_string(encap_content_info.expect(TAG_OID)?);
+        let content = encap_content_info
+            .read_optional(TAG_CONTEXT_CONS_0)
+            .and_then(|v| Reader::new(v.value).read());
+
+        let mut certs = Vec::new();
+        if let Some(certificates) = signed_data.read_optional(TAG_CONTEXT_CONS_0) {
+            let mut certificates = Reader::new(certificates.value);
+            while let Some(cert) = certificates.read() {
+                if certs.len() >= MAX_NB_CERTIFICATES {
+                    break;
+                }
+                // Other kinds of certificates, such as attribute certificates, are ignored.
+                if cert.tag == TAG_SEQUENCE {
+                    certs.extend(X509::parse(cert.raw));
+                }
+            }
+        }
+        let _crls = signed_data.read_optional(TAG_CONTEXT_CONS_1);
+
+        let signer_info = match Reader::new(signed_data.expect(TAG_SET)?).read() {
+            Some(si) => Some(SignerInfo::parse(si.raw)?),
+            None => None,
+        };
+
+        Some(Self {
+            version,
+            content_type,
+            content,
+            certs,
+            signer_info,
+        })
+    }
+
+    /// Retrieve the digest algorithm and the digest of the file, from a
+    /// `SpcIndirectDataContent` content.
+    fn indirect_data_digest(&self) -> Option<(Option<String>, &'a [u8])> {
+        if self.content_type.as_deref() != Some(OID_SPC_INDIRECT_DATA) {
+            return None;
+        }
+        let content = self.content.filter(|v| v.tag == TAG_SEQUENCE)?;
+        let mut content = Reader::new(content.value);
+        let _data = content.expect(TAG_SEQUENCE)?;
+        let mut digest_info = Reader::new(content.expect(TAG_SEQUENCE)?);
+        let digest_alg = algorithm_oid(digest_info.expect(TAG_SEQUENCE)?);
+        let digest = digest_info.expect(TAG_OCTET_STRING)?;
+
+        Some((digest_alg, digest))
+    }
+
+    /// Retrieve the `TSTInfo` content of a timestamp.
+    fn tst_info(&self) -> Option<TstInfo<'a>> {
+        if self.content_type.as_deref() != Some(OID_TST_INFO) {
+            return None;
+        }
+        let content = self.content.filter(|v| v.tag == TAG_OCTET_STRING)?;
+        let mut tst_info = Reader::new(Reader::new(content.value).expect(TAG_SEQUENCE)?);
+
+        let _version = tst_info.expect(TAG_INTEGER)?;
+        let _policy = tst_info.expect(TAG_OID)?;
+        let mut message_imprint = Reader::new(tst_info.expect(TAG_SEQUENCE)?);
+        let digest_alg = algorithm_oid(message_imprint.expect(TAG_SEQUENCE)?);
+        let digest = message_imprint.expect(TAG_OCTET_STRING)?;
+        let _serial = tst_info.expect(TAG_INTEGER)?;
+        let sign_time = parse_time(&tst_info.read()?)?;
+
+        Some(TstInfo {
+            digest_alg,
+            digest,
+            sign_time,
+        })
+    }
+}
+
+/// The information of a timestamp.
+struct TstInfo<'a> {
+    digest_alg: Option<String>,
+    /// Digest of the timestamped data.
+    digest: &'a [u8],
+    sign_time: i64,
+}
+
+struct SignerInfo<'a> {
+    /// Encoded name of the issuer of the signer certificate.
+    issuer: &'a [u8],
+    /// Serial number of the signer certificate.
+    serial: &'a [u8],
+    digest_alg: Option<String>,
+    authenticated_attributes: &'a [u8],
+    encrypted_digest: &'a [u8],
+    unauthenticated_attributes: &'a [u8],
+}
+
+impl<'a> SignerInfo<'a> {
+    fn parse(raw: &'a [u8]) -> Option<Self> {
+        let mut si = Reader::new(Reader::new(raw).expect(TAG_SEQUENCE)?);
+
+        let _version = si.expect(TAG_INTEGER)?;
+        let mut issuer_and_serial = Reader::new(si.expect(TAG_SEQUENCE)?);
+        let issuer = issuer_and_serial.read().filter(|v| v.tag == TAG_SEQUENCE)?;
+        let serial = issuer_and_serial.expect(TAG_INTEGER)?;
+        let digest_alg = algorithm_oid(si.expect(TAG_SEQUENCE)?);
+        let authenticated_attributes = si
+            .read_optional(TAG_CONTEXT_CONS_0)
+            .map_or(&[][..], |v| v.value);
+        let _encryption_alg = si.expect(TAG_SEQUENCE)?;
+        let encrypted_digest = si.expect(TAG_OCTET_STRING)?;
+        let unauthenticated_attributes = si
+            .read_optional(TAG_CONTEXT_CONS_1)
+            .map_or(&[][..], |v| v.value);
+
+        Some(Self {
+            issuer: issuer.raw,
+            serial,
+            digest_alg,
+            authenticated_attributes,
+            encrypted_digest,
+            unauthenticated_attributes,
+        })
+    }
+
+    /// Retrieve the first value of an authenticated attribute.
+    fn authenticated_attribute(&self, oid: &str) -> Option<Tlv<'a>> {
+        attribute_values(self.authenticated_attributes, oid)
+            .into_iter()
+            .next()
+    }
+
+    /// Retrieve the values of an unauthenticated attribute.
+    fn unauthenticated_attribute(&self, oid: &str) -> Vec<Tlv<'a>> {
+        attribute_values(self.unauthenticated_attributes, oid)
+    }
+
+    /// Retrieve the digest of the signed content.
+    fn message_digest(&self) -> Option<&'a [u8]> {
+        self.authenticated_attribute(OID_MESSAGE_DIGEST)
+            .filter(|v| v.tag == TAG_OCTET_STRING)
+            .map(|v| v.value)
+    }
+}
+
+/// List the values of the first attribute with the given type.
+fn attribute_values<'a>(attributes: &'a [u8], oid: &str) -> Vec<Tlv<'a>> {
+    let mut attributes = Reader::new(attributes);
+    while let Some(attribute) = attributes.expect(TAG_SEQUENCE) {
+        let mut attribute = Reader::new(attribute);
+        if attribute.expect(TAG_OID).and_then(oid_to_string).as_deref() != Some(oid) {
+            continue;
+        }
+
+        let mut values = Reader::new(attribute.expect(TAG_SET).unwrap_or_default());
+        let mut res = Vec::new();
+        while let Some(value) = values.read() {
+            res.push(value);
+        }
+        return res;
+    }
+    Vec::new()
+}
+
+#[derive(Copy, Clone, Debug)]
+enum DigestAlgorithm {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    const ALL: [Self; 6] = [
+        Self::Md5,
+        Self::Sha1,
+        Self::Sha224,
+        Self::Sha256,
+        Self::Sha384,
+        Self::Sha512,
+    ];
+
+    fn from_oid(oid: &str) -> Option<Self> {
+        Some(match oid {
+            "1.2.840.113549.2.5" => Self::Md5,
+            "1.3.14.3.2.26" => Self::Sha1,
+            "2.16.840.1.101.3.4.2.4" => Self::Sha224,
+            "2.16.840.1.101.3.4.2.1" => Self::Sha256,
+            "2.16.840.1.101.3.4.2.2" => Self::Sha384,
+            "2.16.840.1.101.3.4.2.3" => Self::Sha512,
+            _ => return None,
+        })
+    }
+
+    fn from_name(name: &[u8]) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|alg| alg.name().as_bytes() == name)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha1",
+            Self::Sha224 => "sha224",
+            Self::Sha256 => "sha256",
+            Self::Sha384 => "sha384",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    /// Compute the digest of the concatenation of the given parts.
+    fn digest(self, parts: &[&[u8]]) -> Vec<u8> {
+        match self {
+            Self::Md5 => digest::<md5::Md5>(parts),
+            Self::Sha1 => digest::<sha1::Sha1>(parts),
+            Self::Sha224 => digest::<sha2::Sha224>(parts),
+            Self::Sha256 => digest::<sha2::Sha256>(parts),
+            Self::Sha384 => digest::<sha2::Sha384>(parts),
+            Self::Sha512 => digest::<sha2::Sha512>(parts),
+        }
+    }
+}
+
+fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = D::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn read_u16(mem: &[u8], offset: usize) -> Option<u16> {
+    let v = mem.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([v[0], v[1]]))
+}
+
+fn read_u32(mem: &[u8], offset: usize) -> Option<usize> {
+    let v = mem.get(offset..offset.checked_add(4)?)?;
+    usize::try_from(u32::from_le_bytes([v[0], v[1], v[2], v[3]])).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_values() {
+        // Two attributes: messageDigest with one value, and one with two values.
+        let attributes =
+            b"\x30\x11\x06\x09\x2a\x86\x48\x86\xf7\x0d\x01\x09\x04\x31\x04\x04\x02\xab\xcd\
+                           \x30\x0d\x06\x03\x2a\x03\x04\x31\x06\x02\x01\x01\x02\x01\x02";
+
+        let values = attribute_values(attributes, OID_MESSAGE_DIGEST);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].tag, TAG_OCTET_STRING);
+        assert_eq!(values[0].value, b"\xab\xcd");
+
+        let values = attribute_values(attributes, "1.2.3.4");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1].raw, b"\x02\x01\x02");
+
+        assert!(attribute_values(attributes, OID_SIGNING_TIME).is_empty());
+        assert!(attribute_values(b"", OID_SIGNING_TIME).is_empty());
+    }
+
+    #[test]
+    fn test_digest_algorithm() {
+        for alg in DigestAlgorithm::ALL {
+            assert!(matches!(
+                DigestAlgorithm::from_name(alg.name().as_bytes()),
+                Some(v) if v.name() == alg.name()
+            ));
+        }
+        assert!(DigestAlgorithm::from_name(b"undefined").is_none());
+        assert_eq!(digest_name(Some("1.3.14.3.2.26")), b"sha1");
+        assert_eq!(digest_name(Some("1.2.3")), b"undefined");
+        assert_eq!(digest_name(None), b"undefined");
+
+        assert_eq!(
+            hex::encode(DigestAlgorithm::Sha1.digest(&[b"a", b"bc"])),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse(b"").is_none());
+
+        // Signatures that cannot be parsed are still listed.
+        let signatures = parse(b"\x30\x03\x02\x01\x00").unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert!(!signatures[0].verified);
+        assert!(signatures[0].signer.is_none());
+
+        assert!(parse_pe(b"MZ").is_none());
+    }
+
+    #[test]
+    fn test_parse_pe_digests() {
+        let mut mem = std::fs::read(
+            "tests/assets/libyara/data/\
+             079a472d22290a94ebb212aa8015cdc8dd28a968c6b4d3b88acdd58ce2d3b885",
+        )
+        .unwrap();
+
+        let signatures = parse_pe(&mem).unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert!(signatures[0].verified);
+        assert_eq!(signatures[0].file_digest, signatures[0].digest);
+        assert_eq!(signatures[0].countersigs.len(), 1);
+        assert!(signatures[0].countersigs[0].verified);
+
+        // Modifying the signed file invalidates the signature, but not its countersignature.
+        mem[0x400] ^= 0xFF;
+        let signatures = parse_pe(&mem).unwrap();
+        assert!(!signatures[0].verified);
+        assert_ne!(signatures[0].file_digest, signatures[0].digest);
+        assert!(signatures[0].countersigs[0].verified);
+    }
+}
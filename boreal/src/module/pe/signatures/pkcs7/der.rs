@@ -0,0 +1,268 @@
+//! Minimal DER reader, handling only what is needed to parse authenticode signatures.
+
+use std::fmt::Write;
+
+pub(super) const TAG_INTEGER: u8 = 0x02;
+pub(super) const TAG_OCTET_STRING: u8 = 0x04;
+pub(super) const TAG_OID: u8 = 0x06;
+pub(super) const TAG_UTC_TIME: u8 = 0x17;
+pub(super) const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub(super) const TAG_SEQUENCE: u8 = 0x30;
+pub(super) const TAG_SET: u8 = 0x31;
+/// `[0]` tag, with primitive contents.
+pub(super) const TAG_CONTEXT_0: u8 = 0x80;
+/// `[1]` tag, with primitive contents.
+pub(super) const TAG_CONTEXT_1: u8 = 0x81;
+/// `[0]` tag, with constructed contents.
+pub(super) const TAG_CONTEXT_CONS_0: u8 = 0xa0;
+/// `[1]` tag, with constructed contents.
+pub(super) const TAG_CONTEXT_CONS_1: u8 = 0xa1;
+
+/// A DER element.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct Tlv<'a> {
+    pub(super) tag: u8,
+    /// Contents of the element.
+    pub(super) value: &'a [u8],
+    /// Whole encoding of the element, including the tag and the length.
+    pub(super) raw: &'a [u8],
+}
+
+/// Reader over a list of DER elements.
+#[derive(Clone, Debug)]
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Read the next element.
+    ///
+    /// Only tags on a single byte and definite lengths are handled.
+    pub(super) fn read(&mut self) -> Option<Tlv<'a>> {
+        let (tlv, rest) = parse_tlv(self.data)?;
+        self.data = rest;
+        Some(tlv)
+    }
+
+    /// Read the next element if it has the given tag, and return its contents.
+    pub(super) fn expect(&mut self, tag: u8) -> Option<&'a [u8]> {
+        let (tlv, rest) = parse_tlv(self.data)?;
+        if tlv.tag != tag {
+            return None;
+        }
+        self.data = rest;
+        Some(tlv.value)
+    }
+
+    /// Read the next element only if it has the given tag.
+    ///
+    /// This is used for optional elements: if the tag does not match, nothing is consumed.
+    pub(super) fn read_optional(&mut self, tag: u8) -> Option<Tlv<'a>> {
+        match parse_tlv(self.data) {
+            Some((tlv, rest)) if tlv.tag == tag => {
+                self.data = rest;
+                Some(tlv)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parse_tlv(data: &[u8]) -> Option<(Tlv<'_>, &[u8])> {
+    let tag = *data.first()?;
+    // High tag numbers are not used in the structures we parse.
+    if tag & 0x1F == 0x1F {
+        return None;
+    }
+
+    let first = *data.get(1)?;
+    let (len, header_len) = if first & 0x80 == 0 {
+        (usize::from(first), 2)
+    } else {
+        // Long form, indefinite lengths (0x80) are rejected as they are not valid DER.
+        let nb_bytes = usize::from(first & 0x7F);
+        if nb_bytes == 0 || nb_bytes > 4 {
+            return None;
+        }
+        let len = data
+            .get(2..2 + nb_bytes)?
+            .iter()
+            .fold(0, |acc, b| (acc << 8) | usize::from(*b));
+        (len, 2 + nb_bytes)
+    };
+
+    let end = header_len.checked_add(len)?;
+    let raw = data.get(..end)?;
+    Some((
+        Tlv {
+            tag,
+            value: &raw[header_len..],
+            raw,
+        },
+        &data[end..],
+    ))
+}
+
+/// Format an encoded object identifier into its dotted representation.
+pub(super) fn oid_to_string(oid: &[u8]) -> Option<String> {
+    let mut arcs = Vec::new();
+    let mut value: u64 = 0;
+    for b in oid {
+        value = value.checked_mul(128)? | u64::from(b & 0x7F);
+        if b & 0x80 == 0 {
+            arcs.push(value);
+            value = 0;
+        }
+    }
+    // The encoding must not end in the middle of an arc.
+    if oid.last().map_or(true, |b| b & 0x80 != 0) {
+        return None;
+    }
+
+    // The first two arcs are merged in the first encoded value.
+    let first = arcs[0];
+    let (a, b) = match first {
+        0..=39 => (0, first),
+        40..=79 => (1, first - 40),
+        _ => (2, first - 80),
+    };
+    let mut res = format!("{a}.{b}");
+    for arc in &arcs[1..] {
+        let _r = write!(res, ".{arc}");
+    }
+    Some(res)
+}
+
+/// Convert a `UTCTime` or `GeneralizedTime` element into a unix timestamp.
+pub(super) fn parse_time(tlv: &Tlv<'_>) -> Option<i64> {
+    let (year, rest) = match tlv.tag {
+        TAG_UTC_TIME => {
+            let year = parse_digits(tlv.value.get(..2)?)?;
+            // As specified in RFC 5280, two digits years are in the range 1950-2049.
+            let year = if year < 50 { 2000 + year } else { 1900 + year };
+            (year, &tlv.value[2..])
+        }
+        TAG_GENERALIZED_TIME => (parse_digits(tlv.value.get(..4)?)?, &tlv.value[4..]),
+        _ => return None,
+    };
+
+    let month = parse_digits(rest.get(0..2)?)?;
+    let day = parse_digits(rest.get(2..4)?)?;
+    let hour = parse_digits(rest.get(4..6)?)?;
+    let minute = parse_digits(rest.get(6..8)?)?;
+    // Seconds are optional in UTCTime.
+    let second = rest.get(8..10).and_then(parse_digits).unwrap_or(0);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+fn parse_digits(digits: &[u8]) -> Option<i64> {
+    digits.iter().try_fold(0, |acc, c| {
+        c.is_ascii_digit().then(|| acc * 10 + i64::from(c - b'0'))
+    })
+}
+
+/// Number of days since the unix epoch for a date of the proleptic gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Years start in march, so that the leap day is the last day of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = (month + 9) % 12;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader() {
+        let data = b"\x30\x03\x02\x01\x05\x04\x81\x02ab\xa0\x00";
+        let mut reader = Reader::new(data);
+
+        let seq = reader.read().unwrap();
+        assert_eq!(seq.tag, TAG_SEQUENCE);
+        assert_eq!(seq.raw, b"\x30\x03\x02\x01\x05");
+        assert_eq!(
+            Reader::new(seq.value).expect(TAG_INTEGER),
+            Some(&b"\x05"[..])
+        );
+
+        assert!(reader.read_optional(TAG_SET).is_none());
+        assert!(reader.expect(TAG_INTEGER).is_none());
+        assert_eq!(reader.expect(TAG_OCTET_STRING), Some(&b"ab"[..]));
+        assert!(reader.read_optional(TAG_CONTEXT_CONS_0).is_some());
+        assert!(reader.is_empty());
+        assert!(reader.read().is_none());
+
+        // Truncated contents
+        assert!(Reader::new(b"\x30\x05\x02\x01").read().is_none());
+        // Indefinite length
+        assert!(Reader::new(b"\x30\x80\x00\x00").read().is_none());
+        // High tag number
+        assert!(Reader::new(b"\x1f\x01\x00").read().is_none());
+        // Truncated length
+        assert!(Reader::new(b"\x04\x82\x01").read().is_none());
+    }
+
+    #[test]
+    fn test_oid_to_string() {
+        assert_eq!(
+            oid_to_string(b"\x2a\x86\x48\x86\xf7\x0d\x01\x07\x02").as_deref(),
+            Some("1.2.840.113549.1.7.2")
+        );
+        assert_eq!(
+            oid_to_string(b"\x2b\x0e\x03\x02\x1a").as_deref(),
+            Some("1.3.14.3.2.26")
+        );
+        assert_eq!(
+            oid_to_string(b"\x60\x86\x48\x01\x65\x03\x04\x02\x01").as_deref(),
+            Some("2.16.840.1.101.3.4.2.1")
+        );
+        assert_eq!(oid_to_string(b"\x2a\x86").as_deref(), None);
+        assert_eq!(oid_to_string(b"").as_deref(), None);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        fn time(tag: u8, value: &[u8]) -> Option<i64> {
+            parse_time(&Tlv {
+                tag,
+                value,
+                raw: value,
+            })
+        }
+
+        assert_eq!(time(TAG_UTC_TIME, b"700101000000Z"), Some(0));
+        assert_eq!(time(TAG_UTC_TIME, b"180605163551Z"), Some(1_528_216_551));
+        assert_eq!(time(TAG_UTC_TIME, b"491231235959Z"), Some(2_524_607_999));
+        assert_eq!(time(TAG_UTC_TIME, b"0001010000Z"), Some(946_684_800));
+        assert_eq!(
+            time(TAG_GENERALIZED_TIME, b"20180605163551.123Z"),
+            Some(1_528_216_551)
+        );
+        assert_eq!(
+            time(TAG_GENERALIZED_TIME, b"19600229000000Z"),
+            Some(-310_521_600)
+        );
+
+        assert_eq!(time(TAG_UTC_TIME, b"181305163551Z"), None);
+        assert_eq!(time(TAG_UTC_TIME, b"18060516"), None);
+        assert_eq!(time(TAG_UTC_TIME, b"1a0605163551Z"), None);
+        assert_eq!(time(TAG_OCTET_STRING, b"180605163551Z"), None);
+    }
+}
@@ -0,0 +1,229 @@
+//! Parsing of X.509 certificates, formatted as OpenSSL does.
+use sha1::Digest;
+
+use super::der::{
+    oid_to_string, parse_time, Reader, TAG_CONTEXT_CONS_0, TAG_INTEGER, TAG_OID, TAG_SEQUENCE,
+    TAG_SET,
+};
+use crate::module::pe::signatures::Certificate;
+
+/// A certificate found in a signature.
+#[derive(Debug)]
+pub(super) struct X509<'a> {
+    /// Encoded name of the issuer.
+    pub(super) issuer: &'a [u8],
+    /// Encoded name of the subject.
+    pub(super) subject: &'a [u8],
+    /// Contents of the serial number integer.
+    pub(super) serial: &'a [u8],
+    pub(super) cert: Certificate,
+}
+
+impl<'a> X509<'a> {
+    /// Parse an encoded certificate.
+    pub(super) fn parse(raw: &'a [u8]) -> Option<Self> {
+        let mut cert = Reader::new(Reader::new(raw).expect(TAG_SEQUENCE)?);
+        let mut tbs = Reader::new(cert.expect(TAG_SEQUENCE)?);
+        let sig_alg = algorithm_oid(cert.expect(TAG_SEQUENCE)?);
+
+        let version = match tbs.read_optional(TAG_CONTEXT_CONS_0) {
+            Some(version) => parse_integer(Reader::new(version.value).expect(TAG_INTEGER)?)?,
+            None => 0,
+        };
+        let serial = tbs.expect(TAG_INTEGER)?;
+        let _signature_algorithm = tbs.expect(TAG_SEQUENCE)?;
+        let issuer = tbs.read().filter(|v| v.tag == TAG_SEQUENCE)?;
+        let mut validity = Reader::new(tbs.expect(TAG_SEQUENCE)?);
+        let not_before = validity.read().and_then(|v| parse_time(&v));
+        let not_after = validity.read().and_then(|v| parse_time(&v));
+        let subject = tbs.read().filter(|v| v.tag == TAG_SEQUENCE)?;
+
+        Some(Self {
+            issuer: issuer.raw,
+            subject: subject.raw,
+            serial,
+            cert: Certificate {
+                sha1: Some(sha1::Sha1::digest(raw).to_vec()),
+                issuer: format_name(issuer.value),
+                subject: format_name(subject.value),
+                version,
+                sig_alg: sig_alg
+                    .as_deref()
+                    .map(|oid| signature_algorithm_name(oid).as_bytes().to_vec()),
+                sig_alg_oid: sig_alg.map(String::into_bytes),
+                serial: Some(format_serial(serial)),
+                not_before: not_before.unwrap_or(0),
+                not_after: not_after.unwrap_or(0),
+            },
+        })
+    }
+
+    /// Is the certificate self-signed.
+    pub(super) fn is_self_signed(&self) -> bool {
+        self.issuer == self.subject
+    }
+
+    /// Is the certificate the one with the given issuer and serial number.
+    pub(super) fn is_issued_by(&self, issuer: &[u8], serial: &[u8]) -> bool {
+        self.issuer == issuer && strip_integer_padding(self.serial) == strip_integer_padding(serial)
+    }
+}
+
+/// Retrieve the object identifier of an `AlgorithmIdentifier`.
+pub(super) fn algorithm_oid(algorithm: &[u8]) -> Option<String> {
+    oid_to_string(Reader::new(algorithm).expect(TAG_OID)?)
+}
+
+/// Long name of a signature algorithm, as returned by OpenSSL.
+fn signature_algorithm_name(oid: &str) -> &'static str {
+    match oid {
+        "1.2.840.113549.1.1.1" => "rsaEncryption",
+        "1.2.840.113549.1.1.2" => "md2WithRSAEncryption",
+        "1.2.840.113549.1.1.4" => "md5WithRSAEncryption",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "rsassaPss",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.113549.1.1.14" => "sha224WithRSAEncryption",
+        "1.3.14.3.2.29" => "sha1WithRSA",
+        "1.2.840.10040.4.3" => "dsaWithSHA1",
+        "2.16.840.1.101.3.4.3.2" => "dsa_with_SHA256",
+        "1.2.840.10045.4.1" => "ecdsa-with-SHA1",
+        "1.2.840.10045.4.3.1" => "ecdsa-with-SHA224",
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        "1.2.840.10045.4.3.4" => "ecdsa-with-SHA512",
+        _ => "undefined",
+    }
+}
+
+/// Short name of an attribute of a distinguished name, as used by OpenSSL.
+fn attribute_short_name(oid: &str) -> Option<&'static str> {
+    Some(match oid {
+        "2.5.4.3" => "CN",
+        "2.5.4.4" => "SN",
+        "2.5.4.5" => "serialNumber",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.9" => "street",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "2.5.4.12" => "title",
+        "2.5.4.13" => "description",
+        "2.5.4.15" => "businessCategory",
+        "2.5.4.17" => "postalCode",
+        "2.5.4.41" => "name",
+        "2.5.4.42" => "GN",
+        "2.5.4.43" => "initials",
+        "2.5.4.46" => "dnQualifier",
+        "2.5.4.65" => "pseudonym",
+        "2.5.4.97" => "organizationIdentifier",
+        "1.2.840.113549.1.9.1" => "emailAddress",
+        "1.2.840.113549.1.9.2" => "unstructuredName",
+        "0.9.2342.19200300.100.1.1" => "UID",
+        "0.9.2342.19200300.100.1.25" => "DC",
+        "1.3.6.1.4.1.311.60.2.1.1" => "jurisdictionL",
+        "1.3.6.1.4.1.311.60.2.1.2" => "jurisdictionST",
+        "1.3.6.1.4.1.311.60.2.1.3" => "jurisdictionC",
+        _ => return None,
+    })
+}
+
+/// Format a distinguished name in the OpenSSL "oneline" format, e.g. `/C=US/O=Org/CN=Name`.
+///
+/// Bytes that are not printable ascii characters are escaped as `\xHH`.
+fn format_name(name: &[u8]) -> Option<Vec<u8>> {
+    let mut res = Vec::new();
+
+    let mut rdns = Reader::new(name);
+    while !rdns.is_empty() {
+        let mut attributes = Reader::new(rdns.expect(TAG_SET)?);
+        while !attributes.is_empty() {
+            let mut attribute = Reader::new(attributes.expect(TAG_SEQUENCE)?);
+            let oid = oid_to_string(attribute.expect(TAG_OID)?)?;
+            let value = attribute.read()?;
+
+            res.push(b'/');
+            res.extend(
+                attribute_short_name(&oid)
+                    .unwrap_or(oid.as_str())
+                    .as_bytes(),
+            );
+            res.push(b'=');
+            for b in value.value {
+                if (b' '..=b'~').contains(b) {
+                    res.push(*b);
+                } else {
+                    res.extend(format!("\\x{b:02X}").as_bytes());
+                }
+            }
+        }
+    }
+
+    Some(res)
+}
+
+/// Format a serial number as colon-separated hex bytes, e.g. `0e:cf:f4`.
+fn format_serial(serial: &[u8]) -> Vec<u8> {
+    strip_integer_padding(serial)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+        .into_bytes()
+}
+
+/// Remove the leading zero byte that is added to positive integers with the high bit set.
+fn strip_integer_padding(v: &[u8]) -> &[u8] {
+    match v {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => v,
+    }
+}
+
+fn parse_integer(v: &[u8]) -> Option<i64> {
+    if v.is_empty() || v.len() > 8 {
+        return None;
+    }
+    // Sign extend from the first byte.
+    let init = if v[0] & 0x80 == 0 { 0 } else { -1 };
+    Some(v.iter().fold(init, |acc, b| (acc << 8) | i64::from(*b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_name() {
+        // C=US, CN=a\nb
+        let name = b"\x31\x0b\x30\x09\x06\x03\x55\x04\x06\x13\x02US\
+                     \x31\x0c\x30\x0a\x06\x03\x55\x04\x03\x0c\x03a\nb";
+        assert_eq!(format_name(name).unwrap(), b"/C=US/CN=a\\x0Ab");
+
+        // Unknown attribute, with an utf-8 value
+        let name = b"\x31\x0a\x30\x08\x06\x02\x2a\x03\x0c\x02\xc3\xa9";
+        assert_eq!(format_name(name).unwrap(), b"/1.2.3=\\xC3\\xA9");
+
+        assert_eq!(format_name(b"").unwrap(), b"");
+        assert!(format_name(b"\x30\x00").is_none());
+    }
+
+    #[test]
+    fn test_format_serial() {
+        assert_eq!(format_serial(b"\x00\x8e\xcf\x01"), b"8e:cf:01");
+        assert_eq!(format_serial(b"\x7e\x93"), b"7e:93");
+        assert_eq!(format_serial(b"\x00"), b"00");
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer(b"\x02"), Some(2));
+        assert_eq!(parse_integer(b"\x01\x00"), Some(256));
+        assert_eq!(parse_integer(b"\xff"), Some(-1));
+        assert_eq!(parse_integer(b""), None);
+        assert_eq!(parse_integer(&[1; 9]), None);
+    }
+}
@@ -388,6 +388,288 @@ pub enum RegexAstError {
     },
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use boreal_parser::regex::{
+        AssertionKind, BracketedClass, BracketedClassItem, ClassKind, Literal, PerlClass,
+        PerlClassKind, RepetitionKind, RepetitionRange,
+    };
+
+    use crate::bitmaps::Bitmap;
+    use crate::wire::{invalid_data, Deserialize, DeserializeContext, Serialize};
+
+    use super::{Class, Hir};
+
+    impl Serialize for Hir {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Alternation(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Assertion(v) => {
+                    writer.push(1);
+                    v.serialize(writer);
+                }
+                Self::Class(v) => {
+                    writer.push(2);
+                    v.serialize(writer);
+                }
+                Self::Mask {
+                    value,
+                    mask,
+                    negated,
+                } => {
+                    writer.push(3);
+                    value.serialize(writer);
+                    mask.serialize(writer);
+                    negated.serialize(writer);
+                }
+                Self::Concat(v) => {
+                    writer.push(4);
+                    v.serialize(writer);
+                }
+                Self::Dot => writer.push(5),
+                Self::Empty => writer.push(6),
+                Self::Literal(v) => {
+                    writer.push(7);
+                    v.serialize(writer);
+                }
+                Self::Group(v) => {
+                    writer.push(8);
+                    v.serialize(writer);
+                }
+                Self::Repetition { hir, kind, greedy } => {
+                    writer.push(9);
+                    hir.serialize(writer);
+                    kind.serialize(writer);
+                    greedy.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for Hir {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Alternation(Vec::deserialize_reader(ctx, reader)?)),
+                1 => Ok(Self::Assertion(AssertionKind::deserialize_reader(
+                    ctx, reader,
+                )?)),
+                2 => Ok(Self::Class(Class::deserialize_reader(ctx, reader)?)),
+                3 => Ok(Self::Mask {
+                    value: u8::deserialize_reader(ctx, reader)?,
+                    mask: u8::deserialize_reader(ctx, reader)?,
+                    negated: bool::deserialize_reader(ctx, reader)?,
+                }),
+                4 => Ok(Self::Concat(Vec::deserialize_reader(ctx, reader)?)),
+                5 => Ok(Self::Dot),
+                6 => Ok(Self::Empty),
+                7 => Ok(Self::Literal(u8::deserialize_reader(ctx, reader)?)),
+                8 => Ok(Self::Group(Box::deserialize_reader(ctx, reader)?)),
+                9 => Ok(Self::Repetition {
+                    hir: Box::deserialize_reader(ctx, reader)?,
+                    kind: RepetitionKind::deserialize_reader(ctx, reader)?,
+                    greedy: bool::deserialize_reader(ctx, reader)?,
+                }),
+                _ => invalid_data("invalid hir discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for Class {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.definition.serialize(writer);
+            self.bitmap.serialize(writer);
+        }
+    }
+
+    impl Deserialize for Class {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self {
+                definition: ClassKind::deserialize_reader(ctx, reader)?,
+                bitmap: Bitmap::deserialize_reader(ctx, reader)?,
+            })
+        }
+    }
+
+    impl Serialize for ClassKind {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Perl(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Bracketed(BracketedClass { items, negated }) => {
+                    writer.push(1);
+                    items.serialize(writer);
+                    negated.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for ClassKind {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Perl(PerlClass::deserialize_reader(ctx, reader)?)),
+                1 => Ok(Self::Bracketed(BracketedClass {
+                    items: Vec::deserialize_reader(ctx, reader)?,
+                    negated: bool::deserialize_reader(ctx, reader)?,
+                })),
+                _ => invalid_data("invalid class kind discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for BracketedClassItem {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::Perl(v) => {
+                    writer.push(0);
+                    v.serialize(writer);
+                }
+                Self::Literal(v) => {
+                    writer.push(1);
+                    v.serialize(writer);
+                }
+                Self::Range(a, b) => {
+                    writer.push(2);
+                    a.serialize(writer);
+                    b.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for BracketedClassItem {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::Perl(PerlClass::deserialize_reader(ctx, reader)?)),
+                1 => Ok(Self::Literal(Literal::deserialize_reader(ctx, reader)?)),
+                2 => Ok(Self::Range(
+                    Literal::deserialize_reader(ctx, reader)?,
+                    Literal::deserialize_reader(ctx, reader)?,
+                )),
+                _ => invalid_data("invalid class item discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for PerlClass {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            writer.push(match self.kind {
+                PerlClassKind::Word => 0,
+                PerlClassKind::Space => 1,
+                PerlClassKind::Digit => 2,
+            });
+            self.negated.serialize(writer);
+        }
+    }
+
+    impl Deserialize for PerlClass {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            let kind = match u8::deserialize_reader(ctx, reader)? {
+                0 => PerlClassKind::Word,
+                1 => PerlClassKind::Space,
+                2 => PerlClassKind::Digit,
+                _ => return invalid_data("invalid perl class discriminant"),
+            };
+            Ok(Self {
+                kind,
+                negated: bool::deserialize_reader(ctx, reader)?,
+            })
+        }
+    }
+
+    impl Serialize for Literal {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.byte.serialize(writer);
+            self.span.serialize(writer);
+            self.escaped.serialize(writer);
+        }
+    }
+
+    impl Deserialize for Literal {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self {
+                byte: u8::deserialize_reader(ctx, reader)?,
+                span: std::ops::Range::deserialize_reader(ctx, reader)?,
+                escaped: bool::deserialize_reader(ctx, reader)?,
+            })
+        }
+    }
+
+    impl Serialize for AssertionKind {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            writer.push(match self {
+                Self::StartLine => 0,
+                Self::EndLine => 1,
+                Self::WordBoundary => 2,
+                Self::NonWordBoundary => 3,
+            });
+        }
+    }
+
+    impl Deserialize for AssertionKind {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::StartLine),
+                1 => Ok(Self::EndLine),
+                2 => Ok(Self::WordBoundary),
+                3 => Ok(Self::NonWordBoundary),
+                _ => invalid_data("invalid assertion discriminant"),
+            }
+        }
+    }
+
+    impl Serialize for RepetitionKind {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            match self {
+                Self::ZeroOrOne => writer.push(0),
+                Self::ZeroOrMore => writer.push(1),
+                Self::OneOrMore => writer.push(2),
+                Self::Range(RepetitionRange::Exactly(v)) => {
+                    writer.push(3);
+                    v.serialize(writer);
+                }
+                Self::Range(RepetitionRange::AtLeast(v)) => {
+                    writer.push(4);
+                    v.serialize(writer);
+                }
+                Self::Range(RepetitionRange::Bounded(a, b)) => {
+                    writer.push(5);
+                    a.serialize(writer);
+                    b.serialize(writer);
+                }
+            }
+        }
+    }
+
+    impl Deserialize for RepetitionKind {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            match u8::deserialize_reader(ctx, reader)? {
+                0 => Ok(Self::ZeroOrOne),
+                1 => Ok(Self::ZeroOrMore),
+                2 => Ok(Self::OneOrMore),
+                3 => Ok(Self::Range(RepetitionRange::Exactly(
+                    u32::deserialize_reader(ctx, reader)?,
+                ))),
+                4 => Ok(Self::Range(RepetitionRange::AtLeast(
+                    u32::deserialize_reader(ctx, reader)?,
+                ))),
+                5 => Ok(Self::Range(RepetitionRange::Bounded(
+                    u32::deserialize_reader(ctx, reader)?,
+                    u32::deserialize_reader(ctx, reader)?,
+                ))),
+                _ => invalid_data("invalid repetition discriminant"),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test_helpers::test_type_traits;
@@ -23,6 +23,10 @@ pub(crate) use visitor::{visit, VisitAction, Visitor};
 pub struct Regex {
     meta: meta::Regex,
     expr: String,
+
+    /// Flags used to build the regex, kept so that it can be rebuilt.
+    #[cfg(any(feature = "serialize", feature = "serde"))]
+    flags: (bool, bool),
 }
 
 impl Regex {
@@ -43,7 +47,12 @@ impl Regex {
             .build(&expr)
             .map_err(Error::from)?;
 
-        Ok(Regex { meta, expr })
+        Ok(Regex {
+            meta,
+            expr,
+            #[cfg(any(feature = "serialize", feature = "serde"))]
+            flags: (case_insensitive, dot_all),
+        })
     }
 
     pub(crate) fn builder(case_insensitive: bool, dot_all: bool) -> meta::Builder {
@@ -306,6 +315,67 @@ impl std::fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::wire::{Deserialize, DeserializeContext, Serialize};
+
+    use super::Regex;
+
+    impl Serialize for Regex {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.expr.serialize(writer);
+            self.flags.serialize(writer);
+        }
+    }
+
+    impl Deserialize for Regex {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            let expr = String::deserialize_reader(ctx, reader)?;
+            let (case_insensitive, dot_all) = <(bool, bool)>::deserialize_reader(ctx, reader)?;
+            Regex::from_string(expr, case_insensitive, dot_all)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Regex;
+
+    /// Representation of a regex, from which it can be rebuilt.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Regex")]
+    struct RegexRepr<E> {
+        expr: E,
+        case_insensitive: bool,
+        dot_all: bool,
+    }
+
+    impl Serialize for Regex {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            RegexRepr {
+                expr: &*self.expr,
+                case_insensitive: self.flags.0,
+                dot_all: self.flags.1,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Regex {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = RegexRepr::<String>::deserialize(deserializer)?;
+            Regex::from_string(repr.expr, repr.case_insensitive, repr.dot_all)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
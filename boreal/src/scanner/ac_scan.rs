@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder, AhoCorasickKind};
 
-use super::{ScanError, ScanParams, StringMatch};
+use super::{RulesFilter, ScanError, ScanParams, StringMatch};
 use crate::atoms::pick_atom_in_literal;
 use crate::compiler::variable::Variable;
 use crate::matcher::{AcMatchStatus, Matcher};
@@ -66,15 +66,25 @@ pub struct ScanData<'a> {
     /// [`AcScan`] object.
     pub variables: &'a [Variable],
 
+    /// Rules enabled on the scanner.
+    ///
+    /// Variables of disabled rules are not scanned.
+    pub rules_filter: Option<&'a RulesFilter>,
+
     /// Max number of matches for a given string.
     pub params: &'a ScanParams,
 }
 
 impl ScanData<'_> {
-    fn check_timeout(&mut self) -> bool {
+    fn check_timeout(&mut self) -> Option<timeout::Interruption> {
         self.timeout_checker
             .as_mut()
-            .map_or(false, |checker| checker.check_timeout())
+            .and_then(|checker| checker.check_timeout())
+    }
+
+    fn is_variable_enabled(&self, index: usize) -> bool {
+        self.rules_filter
+            .map_or(true, |filter| filter.is_variable_enabled(index))
     }
 }
 
@@ -124,20 +134,8 @@ impl AcScan {
             }
         }
 
-        // TODO: Should this AC be case insensitive or not? Redo some benches once other
-        // optimizations are done.
-
-        let mut builder = AhoCorasickBuilder::new();
-        let builder = builder
-            .ascii_case_insensitive(true)
-            .kind(Some(AhoCorasickKind::DFA));
-
-        // First try with a smaller size to reduce memory use and improve performances, otherwise
-        // use the default version.
-        let aho = builder.build(&lits).unwrap();
-
         Self {
-            aho,
+            aho: build_aho(&lits),
             aho_index_to_literal_info,
             non_handled_var_indexes,
         }
@@ -157,8 +155,8 @@ impl AcScan {
 
         // Iterate over aho-corasick matches, validating those matches
         for mat in self.aho.find_overlapping_iter(region.mem) {
-            if scan_data.check_timeout() {
-                return Err(ScanError::Timeout);
+            if let Some(interruption) = scan_data.check_timeout() {
+                return Err(interruption.into());
             }
             self.handle_possible_match(region, &mat, scan_data, matches);
         }
@@ -169,6 +167,9 @@ impl AcScan {
 
             // For every "raw" variable, scan the memory for this variable.
             for variable_index in &self.non_handled_var_indexes {
+                if !scan_data.is_variable_enabled(*variable_index) {
+                    continue;
+                }
                 let var = &scan_data.variables[*variable_index].matcher;
 
                 scan_single_variable(region, var, scan_data, &mut matches[*variable_index]);
@@ -196,6 +197,9 @@ impl AcScan {
                 literal_index,
                 slice_offset: (start_offset, end_offset),
             } = *literal_info;
+            if !scan_data.is_variable_enabled(variable_index) {
+                continue;
+            }
             let var = &scan_data.variables[variable_index].matcher;
 
             #[cfg(feature = "profiling")]
@@ -250,18 +254,20 @@ impl AcScan {
                 }
             }
 
+            let details = var.literal_details(literal_index, match_type);
             match res {
                 AcMatchStatus::Multiple(v) if v.is_empty() => (),
-                AcMatchStatus::Multiple(found_matches) => var_matches.extend(
-                    found_matches
-                        .into_iter()
-                        .map(|m| StringMatch::new(region, m, scan_data.params.match_max_length)),
-                ),
+                AcMatchStatus::Multiple(found_matches) => {
+                    var_matches.extend(found_matches.into_iter().map(|m| {
+                        StringMatch::new(region, m, scan_data.params.match_max_length, details)
+                    }));
+                }
                 AcMatchStatus::Single(m) => {
                     var_matches.push(StringMatch::new(
                         region,
                         m,
                         scan_data.params.match_max_length,
+                        details,
                     ));
                 }
                 AcMatchStatus::None => (),
@@ -286,12 +292,13 @@ fn scan_single_variable(
 
         match mat {
             None => break,
-            Some(mat) => {
+            Some((mat, match_type)) => {
                 offset = mat.start + 1;
                 string_matches.push(StringMatch::new(
                     region,
                     mat,
                     scan_data.params.match_max_length,
+                    (match_type.encoding(), None),
                 ));
 
                 // This is safe to allow because this is called on every iterator of self.matches, so once
@@ -305,6 +312,108 @@ fn scan_single_variable(
     }
 }
 
+fn build_aho(lits: &[Vec<u8>]) -> AhoCorasick {
+    // TODO: Should this AC be case insensitive or not? Redo some benches once other
+    // optimizations are done.
+
+    let mut builder = AhoCorasickBuilder::new();
+    let builder = builder
+        .ascii_case_insensitive(true)
+        .kind(Some(AhoCorasickKind::DFA));
+
+    // First try with a smaller size to reduce memory use and improve performances, otherwise
+    // use the default version.
+    builder.build(lits).unwrap()
+}
+
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::io;
+
+    use crate::compiler::variable::Variable;
+    use crate::wire::{invalid_data, Deserialize, DeserializeContext, Serialize};
+
+    use super::{build_aho, AcScan, LiteralInfo};
+
+    impl Serialize for AcScan {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            // The automaton itself cannot be serialized. The literals it is built from can
+            // however be retrieved from the variables, so only the mapping is saved.
+            self.aho_index_to_literal_info.serialize(writer);
+            self.non_handled_var_indexes.serialize(writer);
+        }
+    }
+
+    impl AcScan {
+        /// Deserialize the object, rebuilding the automaton from the given variables.
+        ///
+        /// The variables must be the ones used when building the serialized object.
+        pub(crate) fn deserialize_reader(
+            ctx: &DeserializeContext,
+            reader: &mut &[u8],
+            variables: &[Variable],
+        ) -> io::Result<Self> {
+            let aho_index_to_literal_info: Vec<Vec<LiteralInfo>> =
+                Vec::deserialize_reader(ctx, reader)?;
+            let non_handled_var_indexes: Vec<usize> = Vec::deserialize_reader(ctx, reader)?;
+
+            let mut lits = Vec::with_capacity(aho_index_to_literal_info.len());
+            for infos in &aho_index_to_literal_info {
+                let mut atom = None;
+                for info in infos {
+                    let Some(lit) = variables
+                        .get(info.variable_index)
+                        .and_then(|var| var.matcher.literals.get(info.literal_index))
+                    else {
+                        return invalid_data("invalid literal index");
+                    };
+                    let (start, end) = info.slice_offset;
+                    if start.checked_add(end).map_or(true, |v| v > lit.len()) {
+                        return invalid_data("invalid literal slice");
+                    }
+                    if atom.is_none() {
+                        atom = Some(lit[start..(lit.len() - end)].to_ascii_lowercase());
+                    }
+                }
+                match atom {
+                    Some(atom) => lits.push(atom),
+                    None => return invalid_data("missing literal for atom"),
+                }
+            }
+            if non_handled_var_indexes
+                .iter()
+                .any(|index| *index >= variables.len())
+            {
+                return invalid_data("invalid variable index");
+            }
+
+            Ok(Self {
+                aho: build_aho(&lits),
+                aho_index_to_literal_info,
+                non_handled_var_indexes,
+            })
+        }
+    }
+
+    impl Serialize for LiteralInfo {
+        fn serialize(&self, writer: &mut Vec<u8>) {
+            self.variable_index.serialize(writer);
+            self.literal_index.serialize(writer);
+            self.slice_offset.serialize(writer);
+        }
+    }
+
+    impl Deserialize for LiteralInfo {
+        fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+            Ok(Self {
+                variable_index: usize::deserialize_reader(ctx, reader)?,
+                literal_index: usize::deserialize_reader(ctx, reader)?,
+                slice_offset: <(usize, usize)>::deserialize_reader(ctx, reader)?,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,6 +429,7 @@ mod tests {
         });
         test_type_traits_non_clonable(ScanData {
             variables: &[],
+            rules_filter: None,
             statistics: None,
             timeout_checker: None,
             params: &ScanParams::default(),
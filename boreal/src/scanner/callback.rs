@@ -0,0 +1,224 @@
+//! Types related to the [`crate::Scanner::scan_mem_with_callback`] API.
+use crate::compiler::rule::Rule;
+use crate::compiler::variable::Variable;
+use crate::module::{Console, Value as ModuleValue};
+
+use super::{MatchedRule, ScanData, ScanError, StringMatch};
+
+/// Event emitted during a scan.
+///
+/// See [`crate::Scanner::scan_mem_with_callback`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ScanEvent<'scanner, 'a> {
+    /// A rule matched.
+    ///
+    /// Private rules are never reported.
+    RuleMatch(MatchedRule<'scanner>),
+
+    /// A rule did not match.
+    ///
+    /// Private rules are never reported.
+    RuleNoMatch {
+        /// Name of the rule.
+        name: &'scanner str,
+
+        /// Namespace containing the rule. None if in the default namespace.
+        namespace: Option<&'scanner str>,
+
+        /// Tags associated with the rule.
+        tags: &'scanner [String],
+
+        /// Metadata associated with the rule.
+        metadatas: &'scanner [boreal_parser::rule::Metadata],
+    },
+
+    /// A module imported by the rules has been evaluated.
+    ///
+    /// This is emitted for each module, before the evaluation of the rules.
+    ModuleImport {
+        /// Name of the module.
+        name: &'static str,
+
+        /// Dynamic values produced by the module.
+        dynamic_values: &'a ModuleValue,
+    },
+
+    /// A string has reached the maximum number of matches.
+    ///
+    /// Matches above this limit are not reported, see
+    /// [`crate::scanner::ScanParams::string_max_nb_matches`].
+    StringReachedMatchLimit {
+        /// Name of the rule containing the string.
+        rule_name: &'scanner str,
+
+        /// Namespace containing the rule. None if in the default namespace.
+        rule_namespace: Option<&'scanner str>,
+
+        /// Name of the string, without the `$`.
+        string_name: &'scanner str,
+    },
+
+    /// A message was logged with the `console` module.
+    ConsoleLog(String),
+}
+
+/// Value returned by the callback given to [`crate::Scanner::scan_mem_with_callback`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScanCallbackResult {
+    /// Continue the scan.
+    Continue,
+
+    /// Abort the scan.
+    ///
+    /// The scan then returns [`ScanError::CallbackAbort`].
+    Abort,
+}
+
+/// Callback called on every event of a scan.
+pub(super) type Callback<'scanner, 'cb> =
+    dyn for<'a> FnMut(ScanEvent<'scanner, 'a>) -> ScanCallbackResult + 'cb;
+
+/// Reports events of a scan to the callback, if any.
+///
+/// When no callback is set, no events are generated, and results are accumulated
+/// in the scan data.
+#[derive(Default)]
+pub(super) struct Reporter<'scanner, 'cb> {
+    callback: Option<&'cb mut Callback<'scanner, 'cb>>,
+
+    /// Has the `ModuleImport` events already been reported.
+    modules_reported: bool,
+}
+
+impl<'scanner, 'cb> Reporter<'scanner, 'cb> {
+    pub(super) fn new(callback: &'cb mut Callback<'scanner, 'cb>) -> Self {
+        Self {
+            callback: Some(callback),
+            modules_reported: false,
+        }
+    }
+
+    pub(super) fn is_enabled(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    fn report(&mut self, event: ScanEvent<'scanner, '_>) -> Result<(), ScanError> {
+        match &mut self.callback {
+            Some(callback) => match callback(event) {
+                ScanCallbackResult::Continue => Ok(()),
+                ScanCallbackResult::Abort => Err(ScanError::CallbackAbort),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Report the result of the evaluation of a rule.
+    ///
+    /// If no callback is set, the rule is added to the matched rules of the scan data if it
+    /// matched.
+    pub(super) fn report_rule(
+        &mut self,
+        scan_data: &mut ScanData<'scanner, '_>,
+        rule: &'scanner Rule,
+        matched: bool,
+        variables: &'scanner [Variable],
+        var_matches: Vec<Vec<StringMatch>>,
+    ) -> Result<(), ScanError> {
+        if rule.is_private {
+            return Ok(());
+        }
+
+        if self.is_enabled() {
+            if matched {
+                self.report(ScanEvent::RuleMatch(super::build_matched_rule(
+                    rule,
+                    variables,
+                    var_matches,
+                )))
+            } else {
+                self.report(ScanEvent::RuleNoMatch {
+                    name: &rule.name,
+                    namespace: rule.namespace.as_deref(),
+                    tags: &rule.tags,
+                    metadatas: &rule.metadatas,
+                })
+            }
+        } else {
+            if matched {
+                scan_data.matched_rules.push(super::build_matched_rule(
+                    rule,
+                    variables,
+                    var_matches,
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    /// Report rules that matched and were accumulated in the scan data.
+    pub(super) fn report_accumulated_matches(
+        &mut self,
+        scan_data: &mut ScanData<'scanner, '_>,
+    ) -> Result<(), ScanError> {
+        if self.is_enabled() {
+            for rule in scan_data.matched_rules.drain(..) {
+                self.report(ScanEvent::RuleMatch(rule))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Report the dynamic values of all modules, if not already done.
+    pub(super) fn report_modules(&mut self, scan_data: &ScanData) -> Result<(), ScanError> {
+        if !self.is_enabled() || self.modules_reported {
+            return Ok(());
+        }
+        self.modules_reported = true;
+
+        for (name, dynamic_values) in &scan_data.module_values.values {
+            self.report(ScanEvent::ModuleImport {
+                name,
+                dynamic_values,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Report the messages logged by the console module since the last call.
+    pub(super) fn report_logs(&mut self, scan_data: &ScanData) -> Result<(), ScanError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        for log in Console::take_captured_logs(&scan_data.module_values.data_map) {
+            self.report(ScanEvent::ConsoleLog(log))?;
+        }
+        Ok(())
+    }
+
+    /// Report the strings of a rule that reached the maximum number of matches.
+    pub(super) fn report_match_limits(
+        &mut self,
+        scan_data: &ScanData,
+        rule: &'scanner Rule,
+        variables: &'scanner [Variable],
+        var_matches: &[Vec<StringMatch>],
+    ) -> Result<(), ScanError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let max_nb_matches = scan_data.params.string_max_nb_matches as usize;
+        for (var, matches) in variables.iter().zip(var_matches) {
+            if matches.len() >= max_nb_matches {
+                self.report(ScanEvent::StringReachedMatchLimit {
+                    rule_name: &rule.name,
+                    rule_namespace: rule.namespace.as_deref(),
+                    string_name: &var.name,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::timeout::Interruption;
+
 /// Scanning error
 #[derive(Debug)]
 pub enum ScanError {
@@ -17,6 +19,17 @@ pub enum ScanError {
 
     /// Error when listing regions of a process before a scan.
     CannotListProcessRegions(std::io::Error),
+
+    /// Error when reading from the stream to scan.
+    CannotReadStream(std::io::Error),
+
+    /// The scan was aborted by the callback.
+    CallbackAbort,
+
+    /// The scan was cancelled.
+    ///
+    /// See [`crate::scanner::ScanParams::cancellation_token`] for more details.
+    Cancelled,
 }
 
 impl std::fmt::Display for ScanError {
@@ -33,12 +46,60 @@ impl std::fmt::Display for ScanError {
             Self::CannotListProcessRegions(error) => {
                 write!(f, "error listing memory regions of process: {error}")
             }
+            Self::CannotReadStream(err) => write!(f, "cannot read stream to scan: {err}"),
+            Self::CallbackAbort => write!(f, "scan aborted by callback"),
+            Self::Cancelled => write!(f, "scan cancelled"),
         }
     }
 }
 
 impl std::error::Error for ScanError {}
 
+impl From<Interruption> for ScanError {
+    fn from(interruption: Interruption) -> Self {
+        match interruption {
+            Interruption::Timeout => Self::Timeout,
+            Interruption::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+/// Error when deserializing a [`crate::Scanner`].
+#[cfg(feature = "serialize")]
+#[derive(Debug)]
+pub enum DeserializeError {
+    /// The data does not start with the expected header.
+    ///
+    /// This most likely means the data was not generated with [`crate::Scanner::serialize`].
+    InvalidHeader,
+
+    /// The data was serialized with an unsupported version of the format.
+    UnsupportedVersion(u32),
+
+    /// A module used by the rules is not available in the provided compiler.
+    MissingModule(String),
+
+    /// The data is malformed.
+    Malformed(std::io::Error),
+}
+
+#[cfg(feature = "serialize")]
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid header, not serialized rules"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            Self::MissingModule(name) => write!(f, "module {name} is not available"),
+            Self::Malformed(err) => write!(f, "malformed data: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl std::error::Error for DeserializeError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,5 +108,7 @@ mod tests {
     #[test]
     fn test_types_traits() {
         test_type_traits_non_clonable(ScanError::Timeout);
+        #[cfg(feature = "serialize")]
+        test_type_traits_non_clonable(DeserializeError::InvalidHeader);
     }
 }
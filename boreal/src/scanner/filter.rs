@@ -0,0 +1,119 @@
+//! Filtering of the rules evaluated by a scanner.
+use crate::compiler::rule::Rule;
+
+/// Selection of rules, used to enable or disable them on a scanner.
+///
+/// See [`crate::Scanner::set_rules_enabled`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RuleSelector<'a> {
+    /// Select all rules.
+    All,
+
+    /// Select a single rule.
+    Rule {
+        /// Namespace of the rule, None for the default namespace.
+        namespace: Option<&'a str>,
+
+        /// Name of the rule.
+        name: &'a str,
+    },
+
+    /// Select all rules of a namespace, None for the default namespace.
+    Namespace(Option<&'a str>),
+
+    /// Select all rules with the given tag.
+    Tag(&'a str),
+}
+
+impl RuleSelector<'_> {
+    fn matches(&self, rule: &Rule) -> bool {
+        match self {
+            Self::All => true,
+            Self::Rule { namespace, name } => {
+                rule.namespace.as_deref() == *namespace && rule.name == *name
+            }
+            Self::Namespace(namespace) => rule.namespace.as_deref() == *namespace,
+            Self::Tag(tag) => rule.tags.iter().any(|v| v == tag),
+        }
+    }
+}
+
+/// Rules enabled on a scanner.
+#[derive(Clone, Debug)]
+pub(crate) struct RulesFilter {
+    /// Is each rule enabled.
+    ///
+    /// Global rules come first, followed by the other rules.
+    rules: Vec<bool>,
+
+    /// Is each variable enabled.
+    ///
+    /// A variable is enabled if the rule it belongs to is enabled. This uses the same order as
+    /// the variables of the scanner.
+    variables: Vec<bool>,
+}
+
+impl RulesFilter {
+    /// Build a filter where all the given rules are enabled.
+    pub(super) fn new<'a, I>(rules: I) -> Self
+    where
+        I: Iterator<Item = &'a Rule>,
+    {
+        let mut nb_rules = 0;
+        let mut nb_variables = 0;
+        for rule in rules {
+            nb_rules += 1;
+            nb_variables += rule.nb_variables;
+        }
+
+        Self {
+            rules: vec![true; nb_rules],
+            variables: vec![true; nb_variables],
+        }
+    }
+
+    /// Enable or disable the selected rules, returning the number of selected rules.
+    ///
+    /// The given rules must be the ones used to build the filter.
+    pub(super) fn set_enabled<'a, I>(
+        &mut self,
+        rules: I,
+        selector: RuleSelector,
+        enabled: bool,
+    ) -> usize
+    where
+        I: Iterator<Item = &'a Rule>,
+    {
+        let mut nb_selected = 0;
+        let mut var_index = 0;
+        for (rule, rule_enabled) in rules.zip(&mut self.rules) {
+            if selector.matches(rule) {
+                *rule_enabled = enabled;
+                nb_selected += 1;
+            }
+            let end = var_index + rule.nb_variables;
+            for var_enabled in &mut self.variables[var_index..end] {
+                *var_enabled = *rule_enabled;
+            }
+            var_index = end;
+        }
+        nb_selected
+    }
+
+    /// Returns true if all rules are enabled.
+    pub(super) fn all_enabled(&self) -> bool {
+        self.rules.iter().all(|v| *v)
+    }
+
+    /// Is the rule at the given index enabled.
+    ///
+    /// The index is the one of the rule in the global rules, followed by the other rules.
+    pub(super) fn is_rule_enabled(&self, index: usize) -> bool {
+        self.rules.get(index).copied().unwrap_or(true)
+    }
+
+    /// Is the variable at the given index enabled.
+    pub(super) fn is_variable_enabled(&self, index: usize) -> bool {
+        self.variables.get(index).copied().unwrap_or(true)
+    }
+}
@@ -1,23 +1,36 @@
 //! Provides the [`Scanner`] object used to scan bytes against a set of compiled rules.
+use std::any::Any;
 use std::collections::HashMap;
+use std::io::Read;
 use std::sync::Arc;
 
 use crate::compiler::external_symbol::{ExternalSymbol, ExternalValue};
 use crate::compiler::rule::Rule;
 use crate::compiler::variable::Variable;
+use crate::evaluator::module::ModuleUserData;
 use crate::evaluator::{self, entrypoint, evaluate_rule, EvalError};
-use crate::memory::{FragmentedMemory, Memory, Region};
-use crate::module::Module;
+use crate::memory::{
+    Fragmented, FragmentedMemory, Memory, MemoryParams, Region, RegionDescription,
+};
+use crate::module::{Console, Module};
 use crate::statistics;
-use crate::timeout::TimeoutChecker;
+use crate::timeout::{Interruption, TimeoutChecker};
 
-pub use crate::evaluator::variable::StringMatch;
+pub use crate::evaluator::variable::{MatchEncoding, StringMatch};
 
 mod ac_scan;
+mod callback;
+use callback::Reporter;
+pub use callback::{ScanCallbackResult, ScanEvent};
 mod error;
+mod filter;
+#[cfg(feature = "serialize")]
+pub use error::DeserializeError;
 pub use error::ScanError;
+pub use filter::RuleSelector;
+use filter::RulesFilter;
 mod params;
-pub use params::{FragmentedScanMode, ScanParams};
+pub use params::{CancellationToken, FragmentedScanMode, ScanParams};
 
 #[cfg(feature = "process")]
 mod process;
@@ -91,6 +104,11 @@ pub struct Scanner {
     ///
     /// Compiled rules uses indexing into this vec to retrieve the symbols values.
     external_symbols_values: Vec<ExternalValue>,
+
+    /// Rules enabled on this scanner.
+    ///
+    /// None if all rules are enabled.
+    rules_filter: Option<RulesFilter>,
 }
 
 impl Scanner {
@@ -125,6 +143,7 @@ impl Scanner {
             }),
             scan_params: ScanParams::default(),
             external_symbols_values,
+            rules_filter: None,
         }
     }
 
@@ -144,6 +163,156 @@ impl Scanner {
             Memory::Direct(mem),
             &self.scan_params,
             &self.external_symbols_values,
+            self.rules_filter.as_ref(),
+            HashMap::new(),
+        )
+    }
+
+    /// Scan a byte slice, providing data to some modules for this scan.
+    ///
+    /// The data is indexed by the name of the module it is provided to. Modules can retrieve
+    /// it with [`crate::module::ModuleDataMap::get_user_data`], for example when
+    /// [`crate::module::Module::setup_new_scan`] is called. Data for modules that are not
+    /// used by the scanner is ignored.
+    ///
+    /// ```
+    /// use std::any::Any;
+    /// use std::collections::HashMap;
+    /// use boreal::module::{Module, ModuleDataMap, ScanContext, StaticValue, Type, Value};
+    ///
+    /// struct Sandbox;
+    ///
+    /// impl Module for Sandbox {
+    ///     fn get_name(&self) -> &'static str {
+    ///         "sandbox"
+    ///     }
+    ///
+    ///     fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+    ///         HashMap::new()
+    ///     }
+    ///
+    ///     fn get_dynamic_types(&self) -> HashMap<&'static str, Type> {
+    ///         [("verdict", Type::Bytes)].into()
+    ///     }
+    ///
+    ///     fn get_dynamic_values(
+    ///         &self,
+    ///         ctx: &mut ScanContext,
+    ///         out: &mut HashMap<&'static str, Value>,
+    ///     ) {
+    ///         if let Some(verdict) = ctx.module_data.get_user_data::<String>(self.get_name()) {
+    ///             out.insert("verdict", Value::bytes(verdict.as_bytes()));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut compiler = boreal::Compiler::new();
+    /// compiler.add_module(Sandbox);
+    /// compiler.add_rules_str(r#"
+    /// import "sandbox"
+    ///
+    /// rule a {
+    ///     condition:
+    ///         sandbox.verdict == "malicious"
+    /// }"#)?;
+    /// let scanner = compiler.into_scanner();
+    ///
+    /// let mut module_data: HashMap<&str, Box<dyn Any + Send + Sync>> = HashMap::new();
+    /// module_data.insert("sandbox", Box::new("malicious".to_owned()));
+    /// let scan_result = scanner.scan_mem_with_module_data(b"", module_data).unwrap();
+    /// assert_eq!(scan_result.matched_rules.len(), 1);
+    ///
+    /// // The data is only provided for a single scan.
+    /// let scan_result = scanner.scan_mem(b"").unwrap();
+    /// assert_eq!(scan_result.matched_rules.len(), 0);
+    /// # Ok::<(), boreal::compiler::AddRuleError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can fail if a timeout has been configured and is reached during the scan. Since results
+    /// can still have been partially computed, results are returned with the error.
+    pub fn scan_mem_with_module_data<'scanner>(
+        &'scanner self,
+        mem: &[u8],
+        module_data: HashMap<&str, Box<dyn Any + Send + Sync>>,
+    ) -> Result<ScanResult<'scanner>, (ScanError, ScanResult<'scanner>)> {
+        self.inner.scan(
+            Memory::Direct(mem),
+            &self.scan_params,
+            &self.external_symbols_values,
+            self.rules_filter.as_ref(),
+            module_data,
+        )
+    }
+
+    /// Scan a byte slice, calling a callback on every event of the scan.
+    ///
+    /// Instead of returning the results once the scan is done, events are sent to the
+    /// callback as soon as they occur:
+    ///
+    /// - [`ScanEvent::ModuleImport`] for each module imported by the rules, once the module
+    ///   has been evaluated.
+    /// - [`ScanEvent::RuleMatch`] and [`ScanEvent::RuleNoMatch`] when a rule is evaluated.
+    ///   Private rules are not reported.
+    /// - [`ScanEvent::StringReachedMatchLimit`] when a string has more matches than
+    ///   [`ScanParams::string_max_nb_matches`].
+    /// - [`ScanEvent::ConsoleLog`] when a message is logged by the `console` module. When
+    ///   using this method, the callback set with
+    ///   [`crate::module::Console::with_callback`] is not called.
+    ///
+    /// The scan can be aborted by returning [`ScanCallbackResult::Abort`] from the callback.
+    ///
+    /// ```
+    /// use boreal::scanner::{ScanCallbackResult, ScanEvent};
+    ///
+    /// let mut compiler = boreal::Compiler::new();
+    /// compiler.add_rules_str(r#"
+    /// rule a {
+    ///     strings:
+    ///         $a = "abc"
+    ///     condition:
+    ///         $a
+    /// }
+    /// rule b {
+    ///     condition:
+    ///         filesize > 100
+    /// }"#)?;
+    /// let scanner = compiler.into_scanner();
+    ///
+    /// let mut matched = Vec::new();
+    /// let mut not_matched = Vec::new();
+    /// scanner.scan_mem_with_callback(b"abc", |event| {
+    ///     match event {
+    ///         ScanEvent::RuleMatch(rule) => matched.push(rule.name),
+    ///         ScanEvent::RuleNoMatch { name, .. } => not_matched.push(name),
+    ///         _ => (),
+    ///     }
+    ///     ScanCallbackResult::Continue
+    /// }).unwrap();
+    /// assert_eq!(matched, ["a"]);
+    /// assert_eq!(not_matched, ["b"]);
+    /// # Ok::<(), boreal::compiler::AddRuleError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the callback aborts the scan, or if a timeout has been configured and is
+    /// reached during the scan.
+    pub fn scan_mem_with_callback<'scanner, F>(
+        &'scanner self,
+        mem: &[u8],
+        mut callback: F,
+    ) -> Result<(), ScanError>
+    where
+        F: for<'a> FnMut(ScanEvent<'scanner, 'a>) -> ScanCallbackResult,
+    {
+        self.inner.scan_with_callback(
+            Memory::Direct(mem),
+            &self.scan_params,
+            &self.external_symbols_values,
+            self.rules_filter.as_ref(),
+            &mut callback,
         )
     }
 
@@ -166,6 +335,50 @@ impl Scanner {
         }
     }
 
+    /// Scan the bytes read from a stream.
+    ///
+    /// This can be used to scan data that cannot be seeked nor memory mapped, such as a pipe
+    /// or a network socket, without holding the whole data in memory.
+    ///
+    /// The stream is read in chunks of [`ScanParams::memory_chunk_size`] bytes, or 1MB if this
+    /// parameter is unset. Consecutive chunks overlap, so that a match that crosses the
+    /// boundary between two chunks is still found, as long as it is shorter than 4KB.
+    ///
+    /// The stream is scanned as fragmented memory in single-pass mode, regardless of the value
+    /// of [`ScanParams::fragmented_scan_mode`]: see [`FragmentedScanMode::single_pass`] for
+    /// details on those semantics. The only difference is that the `filesize` condition is
+    /// defined, as the whole stream has been read once rules are evaluated.
+    ///
+    /// ```
+    /// let mut compiler = boreal::Compiler::new();
+    /// compiler.add_rules_str(r#"
+    /// rule a {
+    ///     strings:
+    ///         $a = "abc"
+    ///     condition:
+    ///         $a and filesize == 7
+    /// }"#)?;
+    /// let scanner = compiler.into_scanner();
+    ///
+    /// let scan_result = scanner.scan_reader(&b"123abc4"[..]).unwrap();
+    /// assert_eq!(scan_result.matched_rules.len(), 1);
+    /// # Ok::<(), boreal::compiler::AddRuleError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading from the stream fails, or if a timeout has been configured and is
+    /// reached during the scan. Since results can still have been partially computed, results
+    /// are returned with the error.
+    pub fn scan_reader<R: Read>(&self, reader: R) -> Result<ScanResult, (ScanError, ScanResult)> {
+        self.inner.scan_reader(
+            reader,
+            &self.scan_params,
+            &self.external_symbols_values,
+            self.rules_filter.as_ref(),
+        )
+    }
+
     /// Scan a file using memmap to read from it.
     ///
     /// Returns a list of rules that matched the given file.
@@ -227,6 +440,8 @@ impl Scanner {
                 Memory::new_fragmented(memory, self.scan_params.to_memory_params()),
                 &self.scan_params,
                 &self.external_symbols_values,
+                self.rules_filter.as_ref(),
+                HashMap::new(),
             ),
             Err(err) => Err((err, ScanResult::default())),
         }
@@ -261,6 +476,8 @@ impl Scanner {
             Memory::new_fragmented(Box::new(obj), self.scan_params.to_memory_params()),
             &self.scan_params,
             &self.external_symbols_values,
+            self.rules_filter.as_ref(),
+            HashMap::new(),
         )
     }
 
@@ -305,6 +522,50 @@ impl Scanner {
         Ok(())
     }
 
+    /// Enable or disable rules on this scanner.
+    ///
+    /// Disabled rules are not evaluated during scans, and their strings are not searched for,
+    /// which can make scans faster. They are never reported as matching, and rules that depend
+    /// on them consider them as not matching. A disabled global rule no longer prevents other
+    /// rules from matching.
+    ///
+    /// Like external symbols, this only impacts this scanner and its future clones, so that a
+    /// single compiled set of rules can be used with different subsets of rules.
+    ///
+    /// Returns the number of rules that were selected.
+    ///
+    /// ```
+    /// use boreal::scanner::RuleSelector;
+    ///
+    /// let mut compiler = boreal::Compiler::new();
+    /// compiler.add_rules_str(r#"
+    /// rule a: foo { condition: true }
+    /// rule b: bar { condition: true }
+    /// rule c: foo bar { condition: true }
+    /// "#)?;
+    /// let mut scanner = compiler.into_scanner();
+    ///
+    /// assert_eq!(scanner.set_rules_enabled(RuleSelector::All, false), 3);
+    /// assert_eq!(scanner.set_rules_enabled(RuleSelector::Tag("foo"), true), 2);
+    /// let scan_result = scanner.scan_mem(b"").unwrap();
+    /// let names: Vec<_> = scan_result.matched_rules.iter().map(|r| r.name).collect();
+    /// assert_eq!(names, ["a", "c"]);
+    /// # Ok::<(), boreal::compiler::AddRuleError>(())
+    /// ```
+    pub fn set_rules_enabled(&mut self, selector: RuleSelector, enabled: bool) -> usize {
+        let inner = &*self.inner;
+        let rules = || inner.global_rules.iter().chain(&inner.rules);
+
+        let filter = self
+            .rules_filter
+            .get_or_insert_with(|| RulesFilter::new(rules()));
+        let nb_selected = filter.set_enabled(rules(), selector, enabled);
+        if filter.all_enabled() {
+            self.rules_filter = None;
+        }
+        nb_selected
+    }
+
     /// Set scan parameters on this scanner.
     pub fn set_scan_params(&mut self, params: ScanParams) {
         self.scan_params = params;
@@ -315,6 +576,51 @@ impl Scanner {
     pub fn scan_params(&self) -> &ScanParams {
         &self.scan_params
     }
+
+    /// Serialize the scanner into bytes.
+    ///
+    /// This allows compiling rules once, and loading the result with [`Scanner::deserialize`]
+    /// in other processes or on other machines, avoiding the cost of the compilation.
+    ///
+    /// The values currently defined for the external symbols are saved, and will be the default
+    /// values of the deserialized scanner. Scan parameters are not saved.
+    ///
+    /// ```
+    /// let mut compiler = boreal::Compiler::new();
+    /// compiler.add_rules_str("rule a { strings: $a = \"abc\" condition: $a }")?;
+    /// let bytes = compiler.into_scanner().serialize();
+    ///
+    /// // Modules are taken from the compiler given on deserialization.
+    /// let scanner = boreal::Scanner::deserialize(&bytes, boreal::Compiler::new()).unwrap();
+    /// let scan_result = scanner.scan_mem(b"abc").unwrap();
+    /// assert_eq!(scan_result.matched_rules.len(), 1);
+    /// # Ok::<(), boreal::compiler::AddRuleError>(())
+    /// ```
+    #[cfg(feature = "serialize")]
+    #[must_use]
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut res = Vec::new();
+        wire::serialize_scanner(self, &mut res);
+        res
+    }
+
+    /// Deserialize a scanner from bytes generated by [`Scanner::serialize`].
+    ///
+    /// Modules are not part of the serialized data: the modules used by the rules are instead
+    /// taken by name from the provided compiler. Any rules or symbols added to this compiler
+    /// are ignored.
+    ///
+    /// The data should only come from a trusted source. It is checked for consistency, but
+    /// data that was crafted or corrupted could still lead to panics when scanning.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the data was not produced by a compatible version of [`Scanner::serialize`],
+    /// is malformed, or if a module used by the rules is missing from the compiler.
+    #[cfg(feature = "serialize")]
+    pub fn deserialize(bytes: &[u8], compiler: crate::Compiler) -> Result<Self, DeserializeError> {
+        wire::deserialize_scanner(bytes, compiler)
+    }
 }
 
 #[derive(Debug)]
@@ -356,23 +662,109 @@ impl Inner {
         mem: Memory,
         params: &'scanner ScanParams,
         external_symbols_values: &'scanner [ExternalValue],
+        rules_filter: Option<&'scanner RulesFilter>,
+        module_user_data: ModuleUserData,
     ) -> Result<ScanResult<'scanner>, (ScanError, ScanResult<'scanner>)> {
+        self.scan_with(
+            mem,
+            params,
+            external_symbols_values,
+            rules_filter,
+            module_user_data,
+            |scan_data| self.do_scan(scan_data, &mut Reporter::default()),
+        )
+    }
+
+    fn scan_with_callback<'scanner>(
+        &'scanner self,
+        mem: Memory,
+        params: &'scanner ScanParams,
+        external_symbols_values: &'scanner [ExternalValue],
+        rules_filter: Option<&'scanner RulesFilter>,
+        callback: &mut callback::Callback<'scanner, '_>,
+    ) -> Result<(), ScanError> {
+        let mut reporter = Reporter::new(callback);
+
+        self.scan_with(
+            mem,
+            params,
+            external_symbols_values,
+            rules_filter,
+            HashMap::new(),
+            |scan_data| {
+                // Logs of the console module are reported as events instead.
+                Console::capture_logs(&mut scan_data.module_values.data_map);
+                self.do_scan(scan_data, &mut reporter)
+            },
+        )
+        .map(|_| ())
+        .map_err(|(err, _)| err)
+    }
+
+    fn scan_reader<'scanner, R: Read>(
+        &'scanner self,
+        mut reader: R,
+        params: &'scanner ScanParams,
+        external_symbols_values: &'scanner [ExternalValue],
+        rules_filter: Option<&'scanner RulesFilter>,
+    ) -> Result<ScanResult<'scanner>, (ScanError, ScanResult<'scanner>)> {
+        // The stream is consumed during the string scan. Once done, only its size is
+        // available to the evaluation of the rules.
+        let mem = Memory::Fragmented(Fragmented {
+            obj: Box::new(ConsumedStream),
+            params: MemoryParams {
+                max_fetched_region_size: params.max_fetched_region_size,
+                memory_chunk_size: params.memory_chunk_size,
+                can_refetch_regions: false,
+            },
+            filesize: None,
+        });
+
+        self.scan_with(
+            mem,
+            params,
+            external_symbols_values,
+            rules_filter,
+            HashMap::new(),
+            |scan_data| {
+                let ac_matches = self.do_stream_scan(scan_data, &mut reader)?;
+                self.evaluate_with_matches(scan_data, ac_matches, &mut Reporter::default())
+            },
+        )
+    }
+
+    fn scan_with<'scanner, 'mem, F>(
+        &'scanner self,
+        mem: Memory<'mem>,
+        params: &'scanner ScanParams,
+        external_symbols_values: &'scanner [ExternalValue],
+        rules_filter: Option<&'scanner RulesFilter>,
+        module_user_data: ModuleUserData,
+        do_scan: F,
+    ) -> Result<ScanResult<'scanner>, (ScanError, ScanResult<'scanner>)>
+    where
+        F: FnOnce(&mut ScanData<'scanner, 'mem>) -> Result<(), ScanError>,
+    {
         let mut scan_data = ScanData {
             mem,
             external_symbols_values,
+            rules_filter,
             matched_rules: Vec::new(),
-            module_values: evaluator::module::EvalData::new(&self.modules),
+            module_values: evaluator::module::EvalData::new(&self.modules, module_user_data),
             statistics: if params.compute_statistics {
                 Some(statistics::Evaluation::default())
             } else {
                 None
             },
-            timeout_checker: params.timeout_duration.map(TimeoutChecker::new),
+            timeout_checker: TimeoutChecker::new(
+                params.timeout_duration,
+                params.cancellation_token.as_ref(),
+            ),
             params,
             entrypoint: None,
         };
 
-        let res = self.do_scan(&mut scan_data);
+        let res = do_scan(&mut scan_data);
         let results = ScanResult {
             matched_rules: scan_data.matched_rules,
             module_values: scan_data.module_values.values,
@@ -388,6 +780,7 @@ impl Inner {
     fn do_scan<'scanner>(
         &'scanner self,
         scan_data: &mut ScanData<'scanner, '_>,
+        reporter: &mut Reporter<'scanner, '_>,
     ) -> Result<(), ScanError> {
         if let Some(mem) = scan_data.mem.get_direct() {
             // We can evaluate module values and then try to evaluate rules without matches.
@@ -399,6 +792,8 @@ impl Inner {
         }
 
         if can_use_no_scan_optimization(scan_data) {
+            reporter.report_modules(scan_data)?;
+
             #[cfg(feature = "profiling")]
             let start = std::time::Instant::now();
 
@@ -409,19 +804,50 @@ impl Inner {
                 stats.no_scan_eval_duration = start.elapsed();
             }
 
+            reporter.report_logs(scan_data)?;
             match res {
-                Ok(()) => return Ok(()),
-                Err(EvalError::Timeout) => return Err(ScanError::Timeout),
-                Err(EvalError::Undecidable) => {
-                    // Reset the rules that might have matched already.
-                    scan_data.matched_rules.clear();
+                Ok(results) => {
+                    // If a global rule did not match, no rules can match.
+                    let globals_matched = results
+                        .iter()
+                        .take(self.global_rules.len())
+                        .all(|matched| *matched);
+                    for (index, (rule, matched)) in self
+                        .global_rules
+                        .iter()
+                        .chain(&self.rules)
+                        .zip(results.into_iter().chain(std::iter::repeat(false)))
+                        .enumerate()
+                    {
+                        if !scan_data.is_rule_enabled(index) {
+                            continue;
+                        }
+                        let matched = matched && globals_matched;
+                        reporter.report_rule(scan_data, rule, matched, &[], Vec::new())?;
+                    }
+                    return Ok(());
                 }
+                Err(EvalError::Timeout) => return Err(ScanError::Timeout),
+                Err(EvalError::Cancelled) => return Err(ScanError::Cancelled),
+                Err(EvalError::Undecidable) => (),
             }
         }
 
         // First, run the regex set on the memory. This does a single pass on it, finding out
         // which variables have no miss at all.
         let ac_matches = self.do_memory_scan(scan_data)?;
+        self.evaluate_with_matches(scan_data, ac_matches, reporter)
+    }
+
+    /// Evaluate all rules, using the matches of the variables computed during the string scan.
+    fn evaluate_with_matches<'scanner>(
+        &'scanner self,
+        scan_data: &mut ScanData<'scanner, '_>,
+        ac_matches: Vec<Vec<StringMatch>>,
+        reporter: &mut Reporter<'scanner, '_>,
+    ) -> Result<(), ScanError> {
+        reporter.report_modules(scan_data)?;
+
         let mut ac_matches_iter = ac_matches.into_iter();
 
         let mut previous_results = Vec::with_capacity(self.rules.len());
@@ -430,13 +856,27 @@ impl Inner {
         let start = std::time::Instant::now();
 
         let mut var_index = 0;
-        for (rule, is_global) in self
+        for (index, (rule, is_global)) in self
             .global_rules
             .iter()
             .map(|v| (v, true))
             .chain(self.rules.iter().map(|v| (v, false)))
+            .enumerate()
         {
             let var_matches = collect_nb_elems(&mut ac_matches_iter, rule.nb_variables);
+            let variables = &self.variables[var_index..(var_index + rule.nb_variables)];
+            var_index += rule.nb_variables;
+
+            // A disabled global rule is ignored, while a disabled rule is considered as not
+            // matching for the rules depending on it.
+            if !scan_data.is_rule_enabled(index) {
+                if !is_global {
+                    previous_results.push(false);
+                }
+                continue;
+            }
+
+            reporter.report_match_limits(scan_data, rule, variables, &var_matches)?;
 
             let res = match evaluate_rule(
                 rule,
@@ -447,31 +887,46 @@ impl Inner {
                 Ok(res) => res,
                 Err(EvalError::Undecidable) => unreachable!(),
                 Err(EvalError::Timeout) => return Err(ScanError::Timeout),
+                Err(EvalError::Cancelled) => return Err(ScanError::Cancelled),
             };
+            reporter.report_logs(scan_data)?;
 
-            if is_global && !res {
-                scan_data.matched_rules.clear();
-
-                #[cfg(feature = "profiling")]
-                if let Some(stats) = scan_data.statistics.as_mut() {
-                    stats.rules_eval_duration = start.elapsed();
-                }
+            if is_global {
+                if !res {
+                    scan_data.matched_rules.clear();
 
-                return Ok(());
-            }
-            if res && !rule.is_private {
-                scan_data.matched_rules.push(build_matched_rule(
-                    rule,
-                    &self.variables[var_index..(var_index + rule.nb_variables)],
-                    var_matches,
-                ));
-            }
+                    #[cfg(feature = "profiling")]
+                    if let Some(stats) = scan_data.statistics.as_mut() {
+                        stats.rules_eval_duration = start.elapsed();
+                    }
 
-            if !is_global {
+                    // No rules can match anymore.
+                    if reporter.is_enabled() {
+                        for (index, rule) in self.global_rules.iter().chain(&self.rules).enumerate()
+                        {
+                            if scan_data.is_rule_enabled(index) {
+                                reporter.report_rule(scan_data, rule, false, &[], Vec::new())?;
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                // The matches on global rules can only be reported once all of them have
+                // been evaluated, so accumulate them until then.
+                if !rule.is_private {
+                    scan_data
+                        .matched_rules
+                        .push(build_matched_rule(rule, variables, var_matches));
+                }
+            } else {
+                if previous_results.is_empty() {
+                    reporter.report_accumulated_matches(scan_data)?;
+                }
+                reporter.report_rule(scan_data, rule, res, variables, var_matches)?;
                 previous_results.push(res);
             }
-            var_index += rule.nb_variables;
         }
+        reporter.report_accumulated_matches(scan_data)?;
 
         #[cfg(feature = "profiling")]
         if let Some(stats) = scan_data.statistics.as_mut() {
@@ -482,30 +937,40 @@ impl Inner {
 
     /// Evaluate all rules without availability of the variables' matches.
     ///
-    /// This returns None if variables' matches must be computed, otherwise it returns the
-    /// final result of the scan.
+    /// This returns an error if variables' matches must be computed, otherwise it returns the
+    /// results of the global rules, followed by the results of the rules. If a global rule
+    /// does not match, the evaluation stops and the rules that were not evaluated are missing
+    /// from the results.
+    ///
+    /// Disabled rules are not evaluated: a disabled global rule is considered as matching, and
+    /// other disabled rules as not matching.
     fn evaluate_without_matches<'scanner>(
         &'scanner self,
         scan_data: &mut ScanData<'scanner, '_>,
-    ) -> Result<(), EvalError> {
-        let mut previous_results = Vec::with_capacity(self.rules.len());
+    ) -> Result<Vec<bool>, EvalError> {
+        let mut results = Vec::with_capacity(self.global_rules.len() + self.rules.len());
 
         // First, check global rules
         let mut has_unknown_globals = false;
-        for rule in &self.global_rules {
-            match evaluate_rule(rule, None, &previous_results, scan_data) {
-                Ok(true) => {
-                    if !rule.is_private {
-                        scan_data
-                            .matched_rules
-                            .push(build_matched_rule(rule, &[], Vec::new()));
+        for (index, rule) in self.global_rules.iter().enumerate() {
+            if !scan_data.is_rule_enabled(index) {
+                results.push(true);
+                continue;
+            }
+            match evaluate_rule(rule, None, &[], scan_data) {
+                Ok(matched) => {
+                    results.push(matched);
+                    if !matched {
+                        return Ok(results);
                     }
                 }
-                Ok(false) => return Ok(()),
                 // Do not rethrow immediately, so that if one of the globals is false, it is
                 // detected.
-                Err(EvalError::Undecidable) => has_unknown_globals = true,
-                Err(EvalError::Timeout) => return Err(EvalError::Timeout),
+                Err(EvalError::Undecidable) => {
+                    has_unknown_globals = true;
+                    results.push(false);
+                }
+                Err(err @ (EvalError::Timeout | EvalError::Cancelled)) => return Err(err),
             }
         }
         if has_unknown_globals {
@@ -513,18 +978,17 @@ impl Inner {
         }
 
         // Then, if all global rules matched, the normal rules
-        for rule in &self.rules {
-            let matched = evaluate_rule(rule, None, &previous_results, scan_data)?;
-
-            if matched && !rule.is_private {
-                scan_data
-                    .matched_rules
-                    .push(build_matched_rule(rule, &[], Vec::new()));
-            }
-            previous_results.push(matched);
+        let nb_globals = results.len();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let matched = if scan_data.is_rule_enabled(nb_globals + index) {
+                evaluate_rule(rule, None, &results[nb_globals..], scan_data)?
+            } else {
+                false
+            };
+            results.push(matched);
         }
 
-        Ok(())
+        Ok(results)
     }
 
     fn do_memory_scan(&self, scan_data: &mut ScanData) -> Result<Vec<Vec<StringMatch>>, ScanError> {
@@ -537,6 +1001,7 @@ impl Inner {
             timeout_checker: scan_data.timeout_checker.as_mut(),
             statistics: scan_data.statistics.as_mut(),
             variables: &self.variables,
+            rules_filter: scan_data.rules_filter,
             params: scan_data.params,
         };
         match &mut scan_data.mem {
@@ -600,6 +1065,161 @@ impl Inner {
 
         Ok(matches)
     }
+
+    /// Scan a stream for all variables occurences, reading it chunk by chunk.
+    ///
+    /// Consecutive chunks overlap, so that matches that cross the boundary between two chunks
+    /// are still found. To avoid reporting the same match twice, each chunk only reports the
+    /// matches that start in the part of the stream it "owns": every part of the stream is
+    /// owned by exactly one chunk.
+    fn do_stream_scan<R: Read>(
+        &self,
+        scan_data: &mut ScanData,
+        reader: &mut R,
+    ) -> Result<Vec<Vec<StringMatch>>, ScanError> {
+        let mut matches = vec![Vec::new(); self.variables.len()];
+        let mut chunk_matches = vec![Vec::new(); self.variables.len()];
+        let chunk_size = std::cmp::max(
+            scan_data
+                .params
+                .memory_chunk_size
+                .unwrap_or(DEFAULT_STREAM_CHUNK_SIZE),
+            STREAM_CHUNK_OVERLAP,
+        );
+        let max_nb_matches = scan_data.params.string_max_nb_matches as usize;
+
+        #[cfg(feature = "profiling")]
+        let start = std::time::Instant::now();
+
+        let mut ac_scan_data = ac_scan::ScanData {
+            timeout_checker: scan_data.timeout_checker.as_mut(),
+            statistics: scan_data.statistics.as_mut(),
+            variables: &self.variables,
+            rules_filter: scan_data.rules_filter,
+            params: scan_data.params,
+        };
+
+        let mut buffer = Vec::new();
+        // Offset in the stream of the first byte of the buffer.
+        let mut buffer_start = 0;
+        // Offset in the stream of the first byte owned by the current chunk.
+        let mut owned_start = 0;
+        let filesize = loop {
+            #[cfg(feature = "profiling")]
+            let start_fetch = std::time::Instant::now();
+
+            let nb_read = reader
+                .by_ref()
+                .take(chunk_size as u64)
+                .read_to_end(&mut buffer)
+                .map_err(ScanError::CannotReadStream)?;
+            let is_eof = nb_read < chunk_size;
+
+            #[cfg(feature = "profiling")]
+            if let Some(stats) = ac_scan_data.statistics.as_mut() {
+                stats.fetch_memory_duration += start_fetch.elapsed();
+            }
+
+            let buffer_end = buffer_start + buffer.len();
+            // Matches starting at the end of the buffer could be truncated. Leave them to the
+            // next chunk, which will contain the bytes following them.
+            let owned_end = if is_eof {
+                buffer_end
+            } else {
+                buffer_end - STREAM_CHUNK_OVERLAP
+            };
+
+            if owned_end > owned_start {
+                let region = Region {
+                    start: buffer_start,
+                    mem: &buffer,
+                };
+                self.ac_scan
+                    .scan_region(&region, &mut ac_scan_data, &mut chunk_matches)?;
+
+                for (var_matches, new_matches) in matches.iter_mut().zip(&mut chunk_matches) {
+                    for mut mat in new_matches.drain(..) {
+                        // Express the match relative to the start of the stream, as
+                        // if it was a single contiguous memory.
+                        let offset = mat.base + mat.offset;
+                        if offset < owned_start || offset >= owned_end {
+                            continue;
+                        }
+                        if var_matches.len() >= max_nb_matches {
+                            break;
+                        }
+                        mat.base = 0;
+                        mat.offset = offset;
+                        var_matches.push(mat);
+                    }
+                }
+
+                #[cfg(feature = "object")]
+                if buffer_start == 0 {
+                    scan_data.entrypoint = entrypoint::get_pe_or_elf_entry_point(
+                        region.mem,
+                        scan_data.params.process_memory,
+                    );
+                }
+
+                scan_data.module_values.scan_region(
+                    &region,
+                    &self.modules,
+                    scan_data.params.process_memory,
+                );
+            }
+
+            if is_eof {
+                break buffer_end;
+            }
+
+            // Keep the bytes that are not owned yet, as well as some bytes preceding them,
+            // so that the bytes surrounding the next matches are available.
+            let keep_start =
+                std::cmp::max(owned_end.saturating_sub(STREAM_CHUNK_OVERLAP), buffer_start);
+            drop(buffer.drain(..(keep_start - buffer_start)));
+            buffer_start = keep_start;
+            owned_start = owned_end;
+        };
+
+        if let Memory::Fragmented(fragmented) = &mut scan_data.mem {
+            fragmented.filesize = Some(filesize);
+        }
+
+        #[cfg(feature = "profiling")]
+        if let Some(stats) = scan_data.statistics.as_mut() {
+            stats.ac_duration = start.elapsed();
+        }
+
+        Ok(matches)
+    }
+}
+
+/// Default size of the chunks read from a stream, see [`Scanner::scan_reader`].
+const DEFAULT_STREAM_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Size of the overlap between two consecutive chunks read from a stream.
+///
+/// Matches that cross the boundary between two chunks are guaranteed to be found only if
+/// they are shorter than this size.
+const STREAM_CHUNK_OVERLAP: usize = 4096;
+
+/// Fragmented memory used when evaluating rules on a stream.
+///
+/// The stream has been consumed when evaluating rules, so no regions can be listed.
+#[derive(Debug)]
+struct ConsumedStream;
+
+impl FragmentedMemory for ConsumedStream {
+    fn next(&mut self, _params: &MemoryParams) -> Option<RegionDescription> {
+        None
+    }
+
+    fn fetch(&mut self, _params: &MemoryParams) -> Option<Region> {
+        None
+    }
+
+    fn reset(&mut self) {}
 }
 
 fn can_use_no_scan_optimization(scan_data: &ScanData) -> bool {
@@ -620,6 +1240,9 @@ pub(crate) struct ScanData<'scanner, 'mem> {
     /// Values of external symbols.
     pub(crate) external_symbols_values: &'scanner [ExternalValue],
 
+    /// Rules enabled on the scanner, None if all rules are enabled.
+    pub(crate) rules_filter: Option<&'scanner RulesFilter>,
+
     /// List of rules that matched.
     pub(crate) matched_rules: Vec<MatchedRule<'scanner>>,
 
@@ -650,10 +1273,18 @@ pub(crate) struct ScanData<'scanner, 'mem> {
 }
 
 impl ScanData<'_, '_> {
-    pub(crate) fn check_timeout(&mut self) -> bool {
+    pub(crate) fn check_timeout(&mut self) -> Option<Interruption> {
         self.timeout_checker
             .as_mut()
-            .map_or(false, TimeoutChecker::check_timeout)
+            .and_then(TimeoutChecker::check_timeout)
+    }
+
+    /// Is the rule at the given index enabled.
+    ///
+    /// The index is the one of the rule in the global rules, followed by the other rules.
+    fn is_rule_enabled(&self, index: usize) -> bool {
+        self.rules_filter
+            .map_or(true, |filter| filter.is_rule_enabled(index))
     }
 }
 
@@ -692,7 +1323,11 @@ fn build_matched_rule<'a>(
 }
 
 /// Result of a scan
+///
+/// This borrows from the [`Scanner`] used for the scan, see [`ScanResult::into_owned`] to
+/// get a result that does not.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ScanResult<'scanner> {
     /// List of rules that matched.
     pub matched_rules: Vec<MatchedRule<'scanner>>,
@@ -706,8 +1341,29 @@ pub struct ScanResult<'scanner> {
     pub statistics: Option<statistics::Evaluation>,
 }
 
+impl ScanResult<'_> {
+    /// Convert into a result that does not borrow from the scanner.
+    #[must_use]
+    pub fn into_owned(self) -> OwnedScanResult {
+        OwnedScanResult {
+            matched_rules: self
+                .matched_rules
+                .into_iter()
+                .map(MatchedRule::into_owned)
+                .collect(),
+            module_values: self
+                .module_values
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value.into()))
+                .collect(),
+            statistics: self.statistics,
+        }
+    }
+}
+
 /// Description of a rule that matched during a scan.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MatchedRule<'scanner> {
     /// Name of the rule.
     pub name: &'scanner str,
@@ -725,8 +1381,27 @@ pub struct MatchedRule<'scanner> {
     pub matches: Vec<StringMatches<'scanner>>,
 }
 
+impl MatchedRule<'_> {
+    /// Convert into a description that does not borrow from the scanner.
+    #[must_use]
+    pub fn into_owned(self) -> OwnedMatchedRule {
+        OwnedMatchedRule {
+            name: self.name.to_owned(),
+            namespace: self.namespace.map(ToOwned::to_owned),
+            tags: self.tags.to_vec(),
+            metadatas: self.metadatas.to_vec(),
+            matches: self
+                .matches
+                .into_iter()
+                .map(StringMatches::into_owned)
+                .collect(),
+        }
+    }
+}
+
 /// Details on matches for a string.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct StringMatches<'scanner> {
     /// Name of the string
     pub name: &'scanner str,
@@ -740,6 +1415,69 @@ pub struct StringMatches<'scanner> {
     pub matches: Vec<StringMatch>,
 }
 
+impl StringMatches<'_> {
+    /// Convert into details that do not borrow from the scanner.
+    #[must_use]
+    pub fn into_owned(self) -> OwnedStringMatches {
+        OwnedStringMatches {
+            name: self.name.to_owned(),
+            matches: self.matches,
+        }
+    }
+}
+
+/// Result of a scan, owning all of its data.
+///
+/// This is built with [`ScanResult::into_owned`]. Both types use the same serde
+/// representation, so a serialized [`ScanResult`] can be deserialized into this type.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedScanResult {
+    /// List of rules that matched.
+    pub matched_rules: Vec<OwnedMatchedRule>,
+
+    /// On-scan values of all modules used in the scanner.
+    ///
+    /// First element is the module name, second one is the dynamic values produced by the module.
+    pub module_values: Vec<(String, crate::module::OwnedValue)>,
+
+    /// Statistics related to the scan.
+    pub statistics: Option<statistics::Evaluation>,
+}
+
+/// Description of a rule that matched during a scan, owning all of its data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedMatchedRule {
+    /// Name of the rule.
+    pub name: String,
+
+    /// Namespace containing the rule. None if in the default namespace.
+    pub namespace: Option<String>,
+
+    /// Tags associated with the rule.
+    pub tags: Vec<String>,
+
+    /// Metadata associated with the rule.
+    pub metadatas: Vec<boreal_parser::rule::Metadata>,
+
+    /// List of matched strings, with details on their matches.
+    pub matches: Vec<OwnedStringMatches>,
+}
+
+/// Details on matches for a string, owning all of its data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedStringMatches {
+    /// Name of the string
+    pub name: String,
+
+    /// List of matches found for this string.
+    ///
+    /// See [`StringMatches::matches`].
+    pub matches: Vec<StringMatch>,
+}
+
 /// Error when defining a symbol's value in a [`Scanner`].
 #[derive(Debug)]
 pub enum DefineSymbolError {
@@ -760,6 +1498,146 @@ impl std::fmt::Display for DefineSymbolError {
     }
 }
 
+#[cfg(feature = "serialize")]
+mod wire {
+    use std::collections::HashMap;
+    use std::io;
+    use std::sync::Arc;
+
+    use crate::compiler::external_symbol::ExternalValue;
+    use crate::compiler::rule::Rule;
+    use crate::compiler::variable::Variable;
+    use crate::wire::{invalid_data, Deserialize, DeserializeContext, Serialize};
+    use crate::Compiler;
+
+    use super::{ac_scan, DeserializeError, Inner, ScanParams, Scanner};
+
+    /// Header of the serialized data.
+    const MAGIC: &[u8] = b"BOREALRU";
+
+    /// Version of the serialization format.
+    ///
+    /// This must be bumped on any change to the format.
+    const FORMAT_VERSION: u32 = 2;
+
+    pub(super) fn serialize_scanner(scanner: &Scanner, writer: &mut Vec<u8>) {
+        writer.extend_from_slice(MAGIC);
+        FORMAT_VERSION.serialize(writer);
+
+        let inner = &*scanner.inner;
+        let names: Vec<String> = inner
+            .modules
+            .iter()
+            .map(|module| module.get_name().to_owned())
+            .collect();
+        names.serialize(writer);
+
+        inner.rules.serialize(writer);
+        inner.global_rules.serialize(writer);
+        inner.variables.serialize(writer);
+        inner.ac_scan.serialize(writer);
+
+        let mut symbols: Vec<_> = inner
+            .external_symbols_map
+            .iter()
+            .map(|(name, index)| (name.clone(), *index))
+            .collect();
+        symbols.sort_unstable_by_key(|(_, index)| *index);
+        symbols.serialize(writer);
+        scanner.external_symbols_values.serialize(writer);
+    }
+
+    pub(super) fn deserialize_scanner(
+        bytes: &[u8],
+        compiler: Compiler,
+    ) -> Result<Scanner, DeserializeError> {
+        let Some(mut reader) = bytes.strip_prefix(MAGIC) else {
+            return Err(DeserializeError::InvalidHeader);
+        };
+        let reader = &mut reader;
+
+        let mut ctx = DeserializeContext::default();
+        let version = u32::deserialize_reader(&ctx, reader).map_err(DeserializeError::Malformed)?;
+        if version != FORMAT_VERSION {
+            return Err(DeserializeError::UnsupportedVersion(version));
+        }
+
+        let names =
+            Vec::<String>::deserialize_reader(&ctx, reader).map_err(DeserializeError::Malformed)?;
+        let mut available_modules = compiler.into_modules();
+        let mut modules = Vec::with_capacity(names.len());
+        for name in names {
+            match available_modules.remove(&*name) {
+                Some((compiled_module, module)) => {
+                    let _r = ctx.modules.insert(compiled_module.name, compiled_module);
+                    modules.push(module);
+                }
+                None => return Err(DeserializeError::MissingModule(name)),
+            }
+        }
+
+        let (inner, external_symbols_values) =
+            deserialize_inner(&ctx, reader, modules).map_err(DeserializeError::Malformed)?;
+        if !reader.is_empty() {
+            return Err(DeserializeError::Malformed(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "trailing data",
+            )));
+        }
+
+        Ok(Scanner {
+            inner: Arc::new(inner),
+            scan_params: ScanParams::default(),
+            external_symbols_values,
+            rules_filter: None,
+        })
+    }
+
+    fn deserialize_inner(
+        ctx: &DeserializeContext,
+        reader: &mut &[u8],
+        modules: Vec<Box<dyn crate::module::Module>>,
+    ) -> io::Result<(Inner, Vec<ExternalValue>)> {
+        let rules: Vec<Rule> = Vec::deserialize_reader(ctx, reader)?;
+        let global_rules: Vec<Rule> = Vec::deserialize_reader(ctx, reader)?;
+        let variables: Vec<Variable> = Vec::deserialize_reader(ctx, reader)?;
+
+        // The variables are split between the rules during scanning, check this is
+        // consistent.
+        let nb_variables = rules
+            .iter()
+            .chain(global_rules.iter())
+            .try_fold(0_usize, |acc, rule| acc.checked_add(rule.nb_variables));
+        if nb_variables != Some(variables.len()) {
+            return invalid_data("number of variables is inconsistent with the rules");
+        }
+
+        let ac_scan = ac_scan::AcScan::deserialize_reader(ctx, reader, &variables)?;
+
+        let symbols: Vec<(String, usize)> = Vec::deserialize_reader(ctx, reader)?;
+        let external_symbols_values: Vec<ExternalValue> = Vec::deserialize_reader(ctx, reader)?;
+        if symbols
+            .iter()
+            .any(|(_, index)| *index >= external_symbols_values.len())
+        {
+            return invalid_data("invalid external symbol index");
+        }
+        let external_symbols_map: HashMap<String, usize> = symbols.into_iter().collect();
+
+        Ok((
+            Inner {
+                rules,
+                global_rules,
+                variables,
+                ac_scan,
+                modules,
+                external_symbols_map,
+            },
+            external_symbols_values,
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::module::{EvalContext, ScanContext, StaticValue, Type, Value as ModuleValue};
@@ -854,12 +1732,14 @@ mod tests {
         let _r = compiler.add_rules_str(rule_str).unwrap();
         let scanner = compiler.into_scanner();
 
-        let mut module_values = evaluator::module::EvalData::new(&scanner.inner.modules);
+        let mut module_values =
+            evaluator::module::EvalData::new(&scanner.inner.modules, HashMap::new());
         module_values.scan_region(&Region { start: 0, mem }, &scanner.inner.modules, false);
 
         let mut scan_data = ScanData {
             mem: Memory::Direct(mem),
             external_symbols_values: &[],
+            rules_filter: None,
             matched_rules: Vec::new(),
             module_values,
             statistics: None,
@@ -1373,6 +2253,663 @@ mod tests {
         test_eval_with_poison(&single_var_rule("test.dict[test.to_bytes(#a)]"), b"", None);
     }
 
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn test_serialize() {
+        fn summary(scanner: &Scanner, mem: &[u8]) -> Vec<String> {
+            let res = scanner.scan_mem(mem).unwrap();
+            res.matched_rules
+                .into_iter()
+                .map(|rule| {
+                    let matches: Vec<_> = rule
+                        .matches
+                        .iter()
+                        .map(|m| {
+                            let offsets: Vec<_> = m.matches.iter().map(|m| m.offset).collect();
+                            format!("{}{:?}", m.name, offsets)
+                        })
+                        .collect();
+                    format!("{:?}:{}:{:?}", rule.namespace, rule.name, matches)
+                })
+                .collect()
+        }
+
+        let new_compiler = || {
+            let mut compiler = Compiler::new();
+            _ = compiler.add_module(Test);
+            compiler
+        };
+
+        let mut compiler = new_compiler();
+        _ = compiler.define_symbol("ext", "exe");
+        _ = compiler.define_symbol("limit", 3);
+        let _r = compiler
+            .add_rules_str(
+                r#"
+import "test"
+import "math"
+
+global rule g { condition: filesize > 2 }
+
+rule strings: tag1 tag2 {
+    meta:
+        a = "b"
+        c = -3
+        d = true
+    strings:
+        $a = "abc" wide ascii nocase
+        $b = { 61 ?? [1-2] 63 }
+        $c = /a.c+d?/s
+        $d = "xyz" xor(1-3) fullword
+        $e = "base64" base64
+        $f = /[a-f]{2,5}\x01/ private
+    condition:
+        any of them
+}
+
+rule modules {
+    condition:
+        test.to_bytes(test.array[0]) == "3" and test.dict["a"] == 3
+        and math.entropy(0, filesize) > 1.0
+}
+
+rule symbols {
+    strings:
+        $a = "abc"
+    condition:
+        ext == "exe" and limit < #a and for any i in (1..#a): (@a[i] < 100)
+}
+"#,
+            )
+            .unwrap();
+        let _r = compiler
+            .add_rules_str_in_namespace("rule ns { condition: strings and not modules }", "other");
+        let scanner = compiler.into_scanner();
+        let bytes = scanner.serialize();
+
+        let scanner2 = Scanner::deserialize(&bytes, new_compiler()).unwrap();
+        // Serializing again gives the same result
+        assert_eq!(scanner2.serialize(), bytes);
+
+        for mem in [
+            &b"abc a\0b\0c\0 aXbc ABC"[..],
+            b"xz a__c a.ccc\x01 {z{ YmFzZTY0",
+            b"ab",
+        ] {
+            assert_eq!(summary(&scanner, mem), summary(&scanner2, mem));
+        }
+        assert!(!summary(&scanner2, b"abc abc aBc abc").is_empty());
+
+        // Symbols can still be redefined
+        let mut scanner2 = scanner2;
+        scanner2.define_symbol("ext", "pdf").unwrap();
+        assert!(scanner2.define_symbol("limit", "a").is_err());
+
+        // Missing module
+        assert!(matches!(
+            Scanner::deserialize(&bytes, Compiler::default()),
+            Err(DeserializeError::MissingModule(_))
+        ));
+
+        // Invalid header or version
+        assert!(matches!(
+            Scanner::deserialize(b"BOREAL", Compiler::new()),
+            Err(DeserializeError::InvalidHeader)
+        ));
+        let mut invalid = bytes.clone();
+        invalid[8] = 0xFF;
+        assert!(matches!(
+            Scanner::deserialize(&invalid, Compiler::new()),
+            Err(DeserializeError::UnsupportedVersion(_))
+        ));
+
+        // Truncated or trailing data
+        for len in [12, bytes.len() / 2, bytes.len() - 1] {
+            assert!(matches!(
+                Scanner::deserialize(&bytes[..len], new_compiler()),
+                Err(DeserializeError::Malformed(_))
+            ));
+        }
+        let mut invalid = bytes;
+        invalid.push(0);
+        assert!(matches!(
+            Scanner::deserialize(&invalid, new_compiler()),
+            Err(DeserializeError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_scan_callback() {
+        fn events(scanner: &Scanner, mem: &[u8], abort_on: Option<&str>) -> Vec<String> {
+            let mut events = Vec::new();
+            let res = scanner.scan_mem_with_callback(mem, |event| {
+                let desc = match event {
+                    ScanEvent::RuleMatch(rule) => format!("match {}", rule.name),
+                    ScanEvent::RuleNoMatch { name, .. } => format!("nomatch {name}"),
+                    ScanEvent::ModuleImport { name, .. } => format!("module {name}"),
+                    ScanEvent::StringReachedMatchLimit {
+                        rule_name,
+                        string_name,
+                        ..
+                    } => format!("limit {rule_name}:{string_name}"),
+                    ScanEvent::ConsoleLog(log) => format!("log {log}"),
+                };
+                let abort = abort_on == Some(&*desc);
+                events.push(desc);
+                if abort {
+                    ScanCallbackResult::Abort
+                } else {
+                    ScanCallbackResult::Continue
+                }
+            });
+            if abort_on.is_some() {
+                assert!(matches!(res, Err(ScanError::CallbackAbort)));
+            } else {
+                res.unwrap();
+            }
+            events
+        }
+
+        let build = |rules: &str| {
+            let mut compiler = Compiler::new();
+            // Logs must be reported as events, and not sent to this callback.
+            let _r = compiler.add_module(Console::with_callback(Box::new(|log| {
+                panic!("unexpected log {log}")
+            })));
+            let _r = compiler.add_rules_str(rules).unwrap();
+            let mut scanner = compiler.into_scanner();
+            scanner.set_scan_params(ScanParams::default().string_max_nb_matches(2));
+            scanner
+        };
+
+        let scanner = build(
+            r#"
+import "console"
+
+private rule private { condition: true }
+rule a {
+    strings:
+        $a = "a"
+    condition:
+        $a and console.log("a: ", #a)
+}
+rule b { condition: private and filesize > 10 }
+rule c { strings: $c = "c" condition: $c }
+"#,
+        );
+        assert_eq!(
+            events(&scanner, b"aaa c", None),
+            [
+                "module console",
+                "limit a:a",
+                "log a: 2",
+                "match a",
+                "nomatch b",
+                "match c",
+            ]
+        );
+        assert_eq!(
+            events(&scanner, b"aaa c", Some("match a")),
+            ["module console", "limit a:a", "log a: 2", "match a"]
+        );
+
+        // Without string scan
+        let scanner = build(
+            r#"
+import "console"
+
+rule a { condition: console.log("a") and filesize == 3 }
+rule b { condition: a and filesize < 3 }
+"#,
+        );
+        assert_eq!(
+            events(&scanner, b"aaa", None),
+            ["module console", "log a", "match a", "nomatch b"]
+        );
+
+        // Global rules
+        let rules = r#"
+global rule g1 { strings: $a = "a" condition: $a }
+global rule g2 { condition: filesize > 2 }
+rule a { condition: true }
+"#;
+        let scanner = build(rules);
+        assert_eq!(
+            events(&scanner, b"abc", None),
+            ["match g1", "match g2", "match a"]
+        );
+        assert_eq!(
+            events(&scanner, b"ab", None),
+            ["nomatch g1", "nomatch g2", "nomatch a"]
+        );
+        assert_eq!(
+            events(&scanner, b"bcd", None),
+            ["nomatch g1", "nomatch g2", "nomatch a"]
+        );
+        assert_eq!(events(&scanner, b"abc", Some("match g1")), ["match g1"]);
+
+        // Without string scan, the globals results are consistent with the other rules.
+        let scanner = build(
+            r"
+global rule g1 { condition: filesize > 1 }
+global rule g2 { condition: filesize > 2 }
+rule a { condition: true }
+",
+        );
+        assert_eq!(
+            events(&scanner, b"ab", None),
+            ["nomatch g1", "nomatch g2", "nomatch a"]
+        );
+        let res = scanner.scan_mem(b"ab").unwrap();
+        assert!(res.matched_rules.is_empty());
+    }
+
+    #[test]
+    fn test_rules_filter() {
+        fn matched(scanner: &Scanner, mem: &[u8]) -> Vec<String> {
+            let res = scanner.scan_mem(mem).unwrap();
+            res.matched_rules
+                .iter()
+                .map(|rule| match rule.namespace {
+                    Some(ns) => format!("{ns}:{}", rule.name),
+                    None => rule.name.to_owned(),
+                })
+                .collect()
+        }
+
+        let mut compiler = Compiler::new();
+        let _r = compiler
+            .add_rules_str(
+                r#"
+private global rule g { condition: filesize > 2 }
+rule a: foo { strings: $a = "a" condition: $a }
+rule b: bar { condition: a or filesize > 5 }
+rule c: foo bar { strings: $c = "c" condition: #c == 1 }
+"#,
+            )
+            .unwrap();
+        let _r = compiler
+            .add_rules_str_in_namespace("rule a: foo { condition: true }", "ns")
+            .unwrap();
+        let mut scanner = compiler.into_scanner();
+        assert_eq!(matched(&scanner, b"abc"), ["a", "b", "c", "ns:a"]);
+
+        let selector = RuleSelector::Rule {
+            namespace: None,
+            name: "a",
+        };
+        assert_eq!(scanner.set_rules_enabled(selector, false), 1);
+        // b depends on a, which is now considered false.
+        assert_eq!(matched(&scanner, b"abc"), ["c", "ns:a"]);
+        assert_eq!(matched(&scanner, b"abcdef"), ["b", "c", "ns:a"]);
+
+        // Filters are not shared between clones.
+        let mut scanner2 = scanner.clone();
+        assert_eq!(
+            scanner2.set_rules_enabled(RuleSelector::Tag("bar"), false),
+            2
+        );
+        assert_eq!(matched(&scanner2, b"abc"), ["ns:a"]);
+        assert_eq!(matched(&scanner, b"abc"), ["c", "ns:a"]);
+
+        assert_eq!(
+            scanner2.set_rules_enabled(RuleSelector::Namespace(Some("ns")), false),
+            1
+        );
+        assert!(matched(&scanner2, b"abc").is_empty());
+        assert_eq!(
+            scanner2.set_rules_enabled(RuleSelector::Namespace(None), true),
+            4
+        );
+        assert_eq!(matched(&scanner2, b"abc"), ["a", "b", "c"]);
+
+        // Disabling a global rule removes its constraint.
+        assert!(matched(&scanner2, b"a").is_empty());
+        let selector = RuleSelector::Rule {
+            namespace: None,
+            name: "g",
+        };
+        assert_eq!(scanner2.set_rules_enabled(selector, false), 1);
+        assert_eq!(matched(&scanner2, b"a"), ["a", "b"]);
+
+        // Disabled rules are not reported in callbacks.
+        let mut names = Vec::new();
+        scanner2
+            .scan_mem_with_callback(b"a", |event| {
+                match event {
+                    ScanEvent::RuleMatch(rule) => names.push(rule.name),
+                    ScanEvent::RuleNoMatch { name, .. } => names.push(name),
+                    _ => (),
+                }
+                ScanCallbackResult::Continue
+            })
+            .unwrap();
+        assert_eq!(names, ["a", "b", "c"]);
+
+        assert_eq!(scanner2.set_rules_enabled(RuleSelector::All, true), 5);
+        assert!(scanner2.rules_filter.is_none());
+        assert_eq!(matched(&scanner2, b"abc"), ["a", "b", "c", "ns:a"]);
+
+        assert_eq!(
+            scanner.set_rules_enabled(RuleSelector::Tag("none"), false),
+            0
+        );
+    }
+
+    #[test]
+    fn test_scan_result_into_owned() {
+        let mut compiler = Compiler::new();
+        let _r = compiler.add_module(Test);
+        let _r = compiler
+            .add_rules_str_in_namespace(
+                r#"
+import "test"
+
+rule a: foo bar {
+    meta:
+        s = "a\xFFb"
+        i = 3
+    strings:
+        $a = "abc"
+    condition:
+        $a and test.array[0] == 3
+}"#,
+                "ns",
+            )
+            .unwrap();
+        let mut scanner = compiler.into_scanner();
+        scanner.set_scan_params(ScanParams::default().compute_full_matches(true));
+
+        let result = scanner.scan_mem(b"xabc").unwrap();
+        #[cfg(feature = "serde")]
+        let json = serde_json::to_string(&result).unwrap();
+
+        let result = result.into_owned();
+        // Check the result can outlive the scanner.
+        drop(scanner);
+        assert_eq!(
+            result.matched_rules,
+            [OwnedMatchedRule {
+                name: "a".to_owned(),
+                namespace: Some("ns".to_owned()),
+                tags: vec!["foo".to_owned(), "bar".to_owned()],
+                metadatas: vec![
+                    crate::Metadata {
+                        name: "s".to_owned(),
+                        value: crate::MetadataValue::Bytes(b"a\xFFb".to_vec()),
+                    },
+                    crate::Metadata {
+                        name: "i".to_owned(),
+                        value: crate::MetadataValue::Integer(3),
+                    },
+                ],
+                matches: vec![OwnedStringMatches {
+                    name: "a".to_owned(),
+                    matches: vec![StringMatch {
+                        base: 0,
+                        offset: 1,
+                        length: 3,
+                        data: b"abc".to_vec(),
+                        encoding: MatchEncoding::Ascii,
+                        xor_key: None,
+                    }],
+                }],
+            }]
+        );
+        assert_eq!(result.module_values.len(), 1);
+        assert_eq!(result.module_values[0].0, "test");
+
+        // The borrowed and owned results have the same serialization.
+        #[cfg(feature = "serde")]
+        {
+            assert_eq!(serde_json::to_string(&result).unwrap(), json);
+            let result2: OwnedScanResult = serde_json::from_str(&json).unwrap();
+            assert_eq!(result2.matched_rules, result.matched_rules);
+            assert_eq!(serde_json::to_string(&result2).unwrap(), json);
+        }
+    }
+
+    #[cfg(all(feature = "serde", feature = "object"))]
+    #[test]
+    fn test_scan_result_serde_pe() {
+        // The pe module exposes functions in its objects, which must not prevent serialization.
+        let mut compiler = Compiler::new();
+        let _r = compiler
+            .add_rules_str("import \"pe\" rule a { condition: pe.is_pe }")
+            .unwrap();
+        let scanner = compiler.into_scanner();
+
+        let mem = std::fs::read(
+            "tests/assets/libyara/data/\
+             079a472d22290a94ebb212aa8015cdc8dd28a968c6b4d3b88acdd58ce2d3b885",
+        )
+        .unwrap();
+        let result = scanner.scan_mem(&mem).unwrap();
+        assert_eq!(result.matched_rules.len(), 1);
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"rich_signature\""));
+        assert!(!json.contains("\"toolid\""));
+        assert!(json.contains("\"signatures\""));
+        assert!(!json.contains("Function"));
+
+        let result = result.into_owned();
+        assert_eq!(serde_json::to_string(&result).unwrap(), json);
+        let result2: OwnedScanResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&result2).unwrap(), json);
+    }
+
+    #[test]
+    fn test_match_encoding() {
+        let mut compiler = Compiler::new();
+        let _r = compiler
+            .add_rules_str(
+                r#"
+rule a {
+    strings:
+        $xor = "abc" xor(1-3) ascii wide
+        $wide = "wide" wide
+        $b64 = "boreal" base64 base64wide
+        $regex = /re+gex/ ascii wide
+    condition:
+        all of them
+}"#,
+            )
+            .unwrap();
+        let scanner = compiler.into_scanner();
+
+        let mut mem = Vec::new();
+        for part in [
+            &b"c`a"[..],
+            b"b\x03a\x03`\x03",
+            b"w\0i\0d\0e\0",
+            b"Ym9yZWFs",
+            b"Y\0m\09\0y\0Z\0W\0F\0s\0",
+            b"reegex",
+            b"r\0e\0g\0e\0x\0",
+        ] {
+            mem.extend_from_slice(part);
+            mem.extend_from_slice(b"----");
+        }
+        let result = scanner.scan_mem(&mem).unwrap();
+        assert_eq!(result.matched_rules.len(), 1);
+        let details: Vec<(&str, Vec<_>)> = result.matched_rules[0]
+            .matches
+            .iter()
+            .map(|s| {
+                (
+                    s.name,
+                    s.matches.iter().map(|m| (m.encoding, m.xor_key)).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            details,
+            [
+                (
+                    "xor",
+                    vec![
+                        (MatchEncoding::Ascii, Some(2)),
+                        (MatchEncoding::Wide, Some(3))
+                    ]
+                ),
+                ("wide", vec![(MatchEncoding::Wide, None)]),
+                (
+                    "b64",
+                    vec![
+                        (MatchEncoding::Base64, None),
+                        (MatchEncoding::Base64Wide, None)
+                    ]
+                ),
+                (
+                    "regex",
+                    vec![(MatchEncoding::Ascii, None), (MatchEncoding::Wide, None)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut compiler = Compiler::new();
+        let _r = compiler
+            .add_rules_str(
+                r#"
+rule a { condition: true }
+rule b {
+    condition: for all i in (0..9223372036854775807) : ( i >= 0 )
+}
+rule c {
+    strings:
+        $a = "abc"
+    condition:
+        for all i in (#a..9223372036854775807) : ( i >= 0 )
+}"#,
+            )
+            .unwrap();
+        let mut scanner = compiler.into_scanner();
+        let token = CancellationToken::new();
+        scanner.set_scan_params(ScanParams::default().cancellation_token(Some(token.clone())));
+
+        // Token not cancelled, the scan is not interrupted.
+        let _r = scanner.set_rules_enabled(RuleSelector::All, false);
+        let _r = scanner.set_rules_enabled(
+            RuleSelector::Rule {
+                namespace: None,
+                name: "a",
+            },
+            true,
+        );
+        let res = scanner.scan_mem(b"abc").unwrap();
+        assert_eq!(res.matched_rules.len(), 1);
+
+        token.cancel();
+        let _r = scanner.set_rules_enabled(RuleSelector::All, true);
+
+        // Evaluation without scan
+        let (err, res) = scanner.scan_mem(b"").unwrap_err();
+        assert!(matches!(err, ScanError::Cancelled));
+        assert!(res.matched_rules.is_empty());
+
+        // Evaluation after the string scan
+        let _r = scanner.set_rules_enabled(RuleSelector::All, false);
+        let _r = scanner.set_rules_enabled(
+            RuleSelector::Rule {
+                namespace: None,
+                name: "a",
+            },
+            true,
+        );
+        let _r = scanner.set_rules_enabled(
+            RuleSelector::Rule {
+                namespace: None,
+                name: "c",
+            },
+            true,
+        );
+        let (err, res) = scanner.scan_mem(b"").unwrap_err();
+        assert!(matches!(err, ScanError::Cancelled));
+        let names: Vec<_> = res.matched_rules.iter().map(|r| r.name).collect();
+        assert_eq!(names, ["a"]);
+    }
+
+    #[test]
+    fn test_scan_mem_with_module_data() {
+        use crate::module::{EvalContext, ModuleData, ModuleDataMap, StaticValue, Type, Value};
+
+        struct Foo;
+
+        impl ModuleData for Foo {
+            type Data = u32;
+        }
+
+        impl Module for Foo {
+            fn get_name(&self) -> &'static str {
+                "foo"
+            }
+
+            fn get_static_values(&self) -> HashMap<&'static str, StaticValue> {
+                [
+                    (
+                        "private",
+                        StaticValue::function(Self::private, vec![], Type::Integer),
+                    ),
+                    (
+                        "user",
+                        StaticValue::function(Self::user, vec![], Type::Integer),
+                    ),
+                ]
+                .into()
+            }
+
+            fn setup_new_scan(&self, data_map: &mut ModuleDataMap) {
+                if let Some(v) = data_map.get_user_data::<u32>("foo") {
+                    let v = *v;
+                    data_map.insert::<Self>(v * 2);
+                }
+            }
+        }
+
+        impl Foo {
+            fn private(ctx: &mut EvalContext, _: Vec<Value>) -> Option<Value> {
+                ctx.module_data
+                    .get::<Self>()
+                    .map(|v| Value::Integer((*v).into()))
+            }
+
+            fn user(ctx: &mut EvalContext, _: Vec<Value>) -> Option<Value> {
+                ctx.module_data
+                    .get_user_data::<u32>("foo")
+                    .map(|v| Value::Integer((*v).into()))
+            }
+        }
+
+        let mut compiler = Compiler::new();
+        let _r = compiler.add_module(Foo);
+        let _r = compiler
+            .add_rules_str(
+                r#"
+import "foo"
+rule a { condition: foo.user() == 3 and foo.private() == 6 }
+rule b { condition: not defined foo.user() }
+"#,
+            )
+            .unwrap();
+        let scanner = compiler.into_scanner();
+
+        let scan = |data: Option<(&str, Box<dyn Any + Send + Sync>)>| {
+            let res = scanner
+                .scan_mem_with_module_data(b"", data.into_iter().collect())
+                .unwrap();
+            res.matched_rules.iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(scan(None), ["b"]);
+        assert_eq!(scan(Some(("foo", Box::new(3_u32)))), ["a"]);
+        // Data of the wrong type, or for another module, is not visible.
+        assert_eq!(scan(Some(("foo", Box::new(3_u64)))), ["b"]);
+        assert_eq!(scan(Some(("bar", Box::new(3_u32)))), ["b"]);
+    }
+
     #[test]
     fn test_types_traits() {
         test_type_traits(Scanner::new(
@@ -1399,9 +2936,25 @@ mod tests {
             matches: Vec::new(),
         });
         test_type_traits_non_clonable(DefineSymbolError::UnknownName);
+        test_type_traits(ScanCallbackResult::Continue);
+        test_type_traits(RuleSelector::All);
+        test_type_traits_non_clonable(OwnedScanResult::default());
+        test_type_traits(OwnedMatchedRule {
+            name: String::new(),
+            namespace: None,
+            tags: Vec::new(),
+            metadatas: Vec::new(),
+            matches: Vec::new(),
+        });
+        test_type_traits(OwnedStringMatches {
+            name: String::new(),
+            matches: Vec::new(),
+        });
+        test_type_traits_non_clonable(ScanEvent::ConsoleLog(String::new()));
         test_type_traits_non_clonable(ScanData {
             mem: Memory::Direct(b""),
             external_symbols_values: &[],
+            rules_filter: None,
             matched_rules: Vec::new(),
             module_values: evaluator::module::EvalData {
                 values: Vec::new(),
@@ -1,5 +1,7 @@
 //! Parameters applicable to a scan.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::memory::MemoryParams;
@@ -19,6 +21,9 @@ pub struct ScanParams {
     /// Max duration for a scan before it is aborted.
     pub(crate) timeout_duration: Option<Duration>,
 
+    /// Token used to cancel the scan.
+    pub(crate) cancellation_token: Option<CancellationToken>,
+
     /// Compute statistics on scanning.
     ///
     /// This requires the `profiling` feature.
@@ -168,6 +173,50 @@ impl FragmentedScanMode {
     }
 }
 
+/// Token used to cancel scans.
+///
+/// This token can be cloned and shared between threads. Once cancelled, all the scans using
+/// this token through [`ScanParams::cancellation_token`] are stopped.
+///
+/// ```
+/// use boreal::scanner::{CancellationToken, ScanError, ScanParams};
+///
+/// let mut compiler = boreal::Compiler::new();
+/// compiler.add_rules_str("rule a { strings: $a = \"a\" condition: $a }")?;
+/// let mut scanner = compiler.into_scanner();
+///
+/// let token = CancellationToken::new();
+/// scanner.set_scan_params(ScanParams::default().cancellation_token(Some(token.clone())));
+///
+/// // This would typically be done in another thread.
+/// token.cancel();
+///
+/// let (err, _partial_result) = scanner.scan_mem(&[b'a'; 100_000]).unwrap_err();
+/// assert!(matches!(err, ScanError::Cancelled));
+/// # Ok::<(), boreal::compiler::AddRuleError>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new token, that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token, stopping all scans using it.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
 impl Default for ScanParams {
     fn default() -> Self {
         Self {
@@ -175,6 +224,7 @@ impl Default for ScanParams {
             match_max_length: 512,
             string_max_nb_matches: 1_000,
             timeout_duration: None,
+            cancellation_token: None,
             compute_statistics: false,
             process_memory: false,
             max_fetched_region_size: 1024 * 1024 * 1024,
@@ -239,6 +289,20 @@ impl ScanParams {
         self
     }
 
+    /// Token used to cancel the scan.
+    ///
+    /// When the token is cancelled, scans using those parameters are stopped, and only
+    /// results computed before the cancellation are returned, along with a
+    /// [`crate::scanner::ScanError::Cancelled`] error. The token is checked at the same
+    /// points as the timeout, see [`ScanParams::timeout_duration`].
+    ///
+    /// By default, no token is set.
+    #[must_use]
+    pub fn cancellation_token(mut self, cancellation_token: Option<CancellationToken>) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
     /// Compute statistics during scanning.
     ///
     /// This option allows retrieve statistics related to the scanning of bytes.
@@ -329,6 +393,10 @@ impl ScanParams {
     /// Please note that, if set, this value may be adjusted to ensure it
     /// is a multiple of the page size.
     ///
+    /// This parameter is also used by [`crate::Scanner::scan_reader`], as the
+    /// size of the chunks read from the stream. In this case, strings split between
+    /// two chunks are still matched, see the documentation of this function for details.
+    ///
     /// By default, this parameter is unset.
     #[must_use]
     pub fn memory_chunk_size(mut self, memory_chunk_size: Option<usize>) -> Self {
@@ -375,6 +443,12 @@ impl ScanParams {
         self.timeout_duration.as_ref()
     }
 
+    /// Returns the token used to cancel the scan.
+    #[must_use]
+    pub fn get_cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
     /// Returns whether statistics are computed during scanning.
     #[must_use]
     pub fn get_compute_statistics(&self) -> bool {
@@ -422,6 +496,7 @@ mod tests {
     #[test]
     fn test_types_traits() {
         test_type_traits(ScanParams::default());
+        test_type_traits(CancellationToken::new());
     }
 
     #[test]
@@ -440,6 +515,13 @@ mod tests {
         let params = params.timeout_duration(Some(Duration::from_secs(4)));
         assert_eq!(params.get_timeout_duration(), Some(&Duration::from_secs(4)));
 
+        let token = CancellationToken::new();
+        let params = params.cancellation_token(Some(token.clone()));
+        assert!(Arc::ptr_eq(
+            &params.get_cancellation_token().unwrap().0,
+            &token.0
+        ));
+
         let params = params.compute_statistics(true);
         assert!(params.get_compute_statistics());
 
@@ -51,6 +51,7 @@ pub struct CompiledString {
 ///
 /// This is only filled if the `profiling` feature is enabled.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Evaluation {
     /// Time spent evaluating rules before any scanning.
     ///
@@ -1,37 +1,97 @@
 use std::time::{Duration, Instant};
 
+use crate::scanner::CancellationToken;
+
+/// Reason for the interruption of a scan.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interruption {
+    /// The scan timed out.
+    Timeout,
+
+    /// The scan was cancelled through its cancellation token.
+    Cancelled,
+}
+
 #[derive(Debug)]
 pub struct TimeoutChecker {
-    duration: Duration,
+    timeout: Option<(Instant, Duration)>,
 
-    start: Instant,
+    cancellation_token: Option<CancellationToken>,
 
     counter: u32,
 }
 
 impl TimeoutChecker {
-    pub fn new(duration: Duration) -> Self {
-        Self {
-            duration,
-            start: Instant::now(),
-            counter: 0,
+    /// Build a checker, or None if the scan can neither time out nor be cancelled.
+    pub fn new(
+        duration: Option<Duration>,
+        cancellation_token: Option<&CancellationToken>,
+    ) -> Option<Self> {
+        if duration.is_none() && cancellation_token.is_none() {
+            return None;
         }
+
+        Some(Self {
+            timeout: duration.map(|duration| (Instant::now(), duration)),
+            cancellation_token: cancellation_token.cloned(),
+            counter: 0,
+        })
     }
 
-    pub fn check_timeout(&mut self) -> bool {
+    pub fn check_timeout(&mut self) -> Option<Interruption> {
         self.counter = self.counter.wrapping_add(1);
-        self.counter % (10 * 1024) == 0 && self.start.elapsed() >= self.duration
+        if self.counter % (10 * 1024) != 0 {
+            return None;
+        }
+
+        if self
+            .cancellation_token
+            .as_ref()
+            .map_or(false, CancellationToken::is_cancelled)
+        {
+            return Some(Interruption::Cancelled);
+        }
+        match self.timeout {
+            Some((start, duration)) if start.elapsed() >= duration => Some(Interruption::Timeout),
+            _ => None,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test_helpers::test_type_traits_non_clonable;
+    use crate::test_helpers::{test_type_traits, test_type_traits_non_clonable};
 
     use super::*;
 
+    #[test]
+    fn test_check_timeout() {
+        let token = CancellationToken::new();
+        let mut checker = TimeoutChecker::new(None, Some(&token)).unwrap();
+        for _ in 0..(20 * 1024) {
+            assert_eq!(checker.check_timeout(), None);
+        }
+
+        token.cancel();
+        let nb_checks = (0..(10 * 1024))
+            .filter(|_| checker.check_timeout() == Some(Interruption::Cancelled))
+            .count();
+        assert_eq!(nb_checks, 1);
+
+        let mut checker = TimeoutChecker::new(Some(Duration::from_secs(0)), None).unwrap();
+        let nb_checks = (0..(10 * 1024))
+            .filter(|_| checker.check_timeout() == Some(Interruption::Timeout))
+            .count();
+        assert_eq!(nb_checks, 1);
+
+        assert!(TimeoutChecker::new(None, None).is_none());
+    }
+
     #[test]
     fn test_types_traits() {
-        test_type_traits_non_clonable(TimeoutChecker::new(Duration::from_secs(1)));
+        test_type_traits_non_clonable(
+            TimeoutChecker::new(Some(Duration::from_secs(1)), None).unwrap(),
+        );
+        test_type_traits(Interruption::Timeout);
     }
 }
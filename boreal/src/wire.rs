@@ -0,0 +1,310 @@
+//! Binary encoding used to save and load compiled rules.
+//!
+//! The encoding is very simple: integers are stored in little-endian, and variable-length
+//! objects (strings, vectors, ...) are prefixed with their length. Enums are prefixed with
+//! a discriminant byte.
+//!
+//! The objects that cannot be serialized as-is (compiled regexes, automatons, ...) are rebuilt
+//! on deserialization from the data they were built from.
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use crate::compiler::module::Module as CompiledModule;
+
+/// Context available when deserializing objects.
+#[derive(Debug, Default)]
+pub(crate) struct DeserializeContext {
+    /// Modules that can be used by the deserialized rules, by name.
+    ///
+    /// This is used to resolve the static functions of modules.
+    pub modules: HashMap<&'static str, Arc<CompiledModule>>,
+}
+
+/// Serialize an object into a byte vector.
+pub(crate) trait Serialize {
+    fn serialize(&self, writer: &mut Vec<u8>);
+}
+
+/// Deserialize an object from a byte slice.
+///
+/// On success, the slice is advanced past the bytes that were consumed.
+pub(crate) trait Deserialize: Sized {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self>;
+}
+
+pub(crate) fn invalid_data<T>(msg: &str) -> io::Result<T> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, msg))
+}
+
+fn read_array<const N: usize>(reader: &mut &[u8]) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    io::Read::read_exact(reader, &mut buf)?;
+    Ok(buf)
+}
+
+macro_rules! impl_integer {
+    ($ty:ty) => {
+        impl Serialize for $ty {
+            fn serialize(&self, writer: &mut Vec<u8>) {
+                writer.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+
+        impl Deserialize for $ty {
+            fn deserialize_reader(
+                _ctx: &DeserializeContext,
+                reader: &mut &[u8],
+            ) -> io::Result<Self> {
+                Ok(<$ty>::from_le_bytes(read_array(reader)?))
+            }
+        }
+    };
+}
+
+impl_integer!(u8);
+impl_integer!(u32);
+impl_integer!(u64);
+impl_integer!(u128);
+impl_integer!(i64);
+impl_integer!(f64);
+
+impl Serialize for usize {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        (*self as u64).serialize(writer);
+    }
+}
+
+impl Deserialize for usize {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        let v = u64::deserialize_reader(ctx, reader)?;
+        match usize::try_from(v) {
+            Ok(v) => Ok(v),
+            Err(_) => invalid_data("integer too big"),
+        }
+    }
+}
+
+impl Serialize for bool {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        writer.push(u8::from(*self));
+    }
+}
+
+impl Deserialize for bool {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        match u8::deserialize_reader(ctx, reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => invalid_data("invalid boolean"),
+        }
+    }
+}
+
+impl Serialize for char {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        u32::from(*self).serialize(writer);
+    }
+}
+
+impl Deserialize for char {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        match char::from_u32(u32::deserialize_reader(ctx, reader)?) {
+            Some(c) => Ok(c),
+            None => invalid_data("invalid char"),
+        }
+    }
+}
+
+impl Serialize for String {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        self.len().serialize(writer);
+        writer.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Deserialize for String {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        let bytes = read_bytes(ctx, reader)?;
+        String::from_utf8(bytes).or_else(|_| invalid_data("invalid utf-8 string"))
+    }
+}
+
+/// Read a length-prefixed byte string.
+///
+/// This is faster than going through the generic `Vec` implementation.
+pub(crate) fn read_bytes(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Vec<u8>> {
+    let len = usize::deserialize_reader(ctx, reader)?;
+    if len > reader.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    Ok(bytes.to_vec())
+}
+
+/// Write a length-prefixed byte string.
+pub(crate) fn write_bytes(bytes: &[u8], writer: &mut Vec<u8>) {
+    bytes.len().serialize(writer);
+    writer.extend_from_slice(bytes);
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        self.len().serialize(writer);
+        for elem in self {
+            elem.serialize(writer);
+        }
+    }
+}
+
+impl<T: Deserialize> Deserialize for Vec<T> {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        let len = usize::deserialize_reader(ctx, reader)?;
+        // Do not trust the length for the allocation, a corrupted length could trigger
+        // a huge allocation.
+        let mut res = Vec::with_capacity(std::cmp::min(len, reader.len()));
+        for _ in 0..len {
+            res.push(T::deserialize_reader(ctx, reader)?);
+        }
+        Ok(res)
+    }
+}
+
+impl<T: Serialize> Serialize for Option<T> {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        match self {
+            None => writer.push(0),
+            Some(v) => {
+                writer.push(1);
+                v.serialize(writer);
+            }
+        }
+    }
+}
+
+impl<T: Deserialize> Deserialize for Option<T> {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        match u8::deserialize_reader(ctx, reader)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::deserialize_reader(ctx, reader)?)),
+            _ => invalid_data("invalid option discriminant"),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Box<T> {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        (**self).serialize(writer);
+    }
+}
+
+impl<T: Deserialize> Deserialize for Box<T> {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        T::deserialize_reader(ctx, reader).map(Box::new)
+    }
+}
+
+impl<A: Serialize, B: Serialize> Serialize for (A, B) {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        self.0.serialize(writer);
+        self.1.serialize(writer);
+    }
+}
+
+impl<A: Deserialize, B: Deserialize> Deserialize for (A, B) {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        let a = A::deserialize_reader(ctx, reader)?;
+        let b = B::deserialize_reader(ctx, reader)?;
+        Ok((a, b))
+    }
+}
+
+impl<A: Serialize, B: Serialize, C: Serialize> Serialize for (A, B, C) {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        self.0.serialize(writer);
+        self.1.serialize(writer);
+        self.2.serialize(writer);
+    }
+}
+
+impl<A: Deserialize, B: Deserialize, C: Deserialize> Deserialize for (A, B, C) {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        let a = A::deserialize_reader(ctx, reader)?;
+        let b = B::deserialize_reader(ctx, reader)?;
+        let c = C::deserialize_reader(ctx, reader)?;
+        Ok((a, b, c))
+    }
+}
+
+impl Serialize for std::ops::Range<usize> {
+    fn serialize(&self, writer: &mut Vec<u8>) {
+        self.start.serialize(writer);
+        self.end.serialize(writer);
+    }
+}
+
+impl Deserialize for std::ops::Range<usize> {
+    fn deserialize_reader(ctx: &DeserializeContext, reader: &mut &[u8]) -> io::Result<Self> {
+        let start = usize::deserialize_reader(ctx, reader)?;
+        let end = usize::deserialize_reader(ctx, reader)?;
+        Ok(start..end)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Serialize then deserialize an object, and check all the bytes were consumed.
+    pub(crate) fn roundtrip<T: Serialize + Deserialize>(ctx: &DeserializeContext, value: &T) -> T {
+        let mut buf = Vec::new();
+        value.serialize(&mut buf);
+        let mut reader = &*buf;
+        let res = T::deserialize_reader(ctx, &mut reader).unwrap();
+        assert!(reader.is_empty());
+        res
+    }
+
+    #[test]
+    fn test_primitives() {
+        let ctx = DeserializeContext::default();
+
+        assert_eq!(roundtrip(&ctx, &0x12_u8), 0x12);
+        assert_eq!(roundtrip(&ctx, &u32::MAX), u32::MAX);
+        assert_eq!(roundtrip(&ctx, &u128::MAX), u128::MAX);
+        assert_eq!(roundtrip(&ctx, &-5_i64), -5);
+        assert_eq!(roundtrip(&ctx, &usize::MAX), usize::MAX);
+        assert_eq!(roundtrip(&ctx, &1.5_f64).to_bits(), 1.5_f64.to_bits());
+        assert!(roundtrip(&ctx, &true));
+        assert_eq!(roundtrip(&ctx, &'é'), 'é');
+        assert_eq!(roundtrip(&ctx, &"abc".to_owned()), "abc");
+        assert_eq!(roundtrip(&ctx, &vec![1_u32, 2]), vec![1, 2]);
+        assert_eq!(roundtrip(&ctx, &Some(3_u8)), Some(3));
+        assert_eq!(roundtrip(&ctx, &None::<u8>), None);
+        assert_eq!(roundtrip(&ctx, &Box::new(2_u8)), Box::new(2));
+        assert_eq!(roundtrip(&ctx, &(1_u8, 2_u64)), (1, 2));
+        assert_eq!(roundtrip(&ctx, &(1_u8, false, 3_u32)), (1, false, 3));
+        assert_eq!(roundtrip(&ctx, &(3..7)), 3..7);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let ctx = DeserializeContext::default();
+
+        // Truncated
+        assert!(u32::deserialize_reader(&ctx, &mut &[0_u8, 1][..]).is_err());
+        assert!(
+            String::deserialize_reader(&ctx, &mut &[5_u8, 0, 0, 0, 0, 0, 0, 0, b'a'][..]).is_err()
+        );
+        // Invalid values
+        assert!(bool::deserialize_reader(&ctx, &mut &[2_u8][..]).is_err());
+        assert!(Option::<u8>::deserialize_reader(&ctx, &mut &[2_u8][..]).is_err());
+        assert!(char::deserialize_reader(&ctx, &mut &[0_u8, 0xD8, 0, 0][..]).is_err());
+        assert!(
+            String::deserialize_reader(&ctx, &mut &[1_u8, 0, 0, 0, 0, 0, 0, 0, 0xFF][..]).is_err()
+        );
+        // Huge length does not cause a huge allocation
+        assert!(Vec::<u8>::deserialize_reader(&ctx, &mut &[0xFF_u8; 8][..]).is_err());
+    }
+}
@@ -57,6 +57,33 @@ fn test_add_rules_str_err() {
     assert!(compiler.add_rules_file_in_namespace("z", "ns").is_err());
 }
 
+// All parsing errors of a file are reported
+#[test]
+fn test_add_rules_str_parse_errors() {
+    let mut compiler = boreal::Compiler::new();
+    let rules = "rule a { condition: }\nrule b { condition: true }\nrule c { condition: 1 + }";
+    let err = compiler.add_rules_str(rules).unwrap_err();
+
+    let desc = err.to_short_description("mem", rules);
+    assert!(desc.starts_with("mem:1:"), "{desc}");
+    assert!(desc.contains("error: syntax error"), "{desc}");
+
+    let diagnostic = err.to_diagnostic();
+    assert_eq!(diagnostic.message, "syntax error");
+    assert_eq!(diagnostic.labels.len(), 2);
+    assert_eq!(diagnostic.labels[1].message, "syntax error");
+    assert!(diagnostic.labels[1].range.start > rules.rfind("rule c").unwrap());
+    assert_eq!(
+        diagnostic.notes,
+        vec!["2 errors were found while parsing the rules".to_owned()]
+    );
+
+    // No rules were added
+    assert!(compiler
+        .add_rules_str("rule a { condition: true } rule b { condition: true }")
+        .is_ok());
+}
+
 #[test]
 fn test_compiler_api() {
     let mut compiler = boreal::Compiler::default();
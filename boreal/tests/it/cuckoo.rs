@@ -0,0 +1,106 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+#[track_caller]
+fn test_with_report(cond: &str, report: Option<&str>, expected: bool) {
+    let mut compiler = boreal::Compiler::new();
+    let _r = compiler
+        .add_rules_str(format!(
+            r#"import "cuckoo"
+rule test {{
+    condition: {cond}
+}}"#
+        ))
+        .unwrap();
+    let scanner = compiler.into_scanner();
+
+    let mut module_data: HashMap<&str, Box<dyn Any + Send + Sync>> = HashMap::new();
+    if let Some(report) = report {
+        let _r = module_data.insert("cuckoo", Box::new(report.to_owned()));
+    }
+    let res = scanner.scan_mem_with_module_data(b"", module_data).unwrap();
+    assert_eq!(res.matched_rules.len(), usize::from(expected), "{cond}");
+}
+
+#[track_caller]
+fn test(path: &str, cond: &str, expected: bool) {
+    let report = std::fs::read_to_string(format!("tests/assets/cuckoo/{path}")).unwrap();
+    test_with_report(cond, Some(&report), expected);
+}
+
+#[test]
+fn test_network() {
+    let r = "report.json";
+
+    test(r, "cuckoo.network.host(/^93\\.184\\./)", true);
+    test(r, "cuckoo.network.host(/^8\\.8\\./)", false);
+
+    test(r, "cuckoo.network.dns_lookup(/evil\\.net$/)", true);
+    test(r, "cuckoo.network.dns_lookup(/evil\\.org$/)", false);
+    test(
+        "report_dns_request.json",
+        "cuckoo.network.dns_lookup(/c2\\.example/)",
+        true,
+    );
+
+    test(r, "cuckoo.network.http_request(/gate\\.php/)", true);
+    test(r, "cuckoo.network.http_request(/admin\\.php/)", false);
+    test(r, "cuckoo.network.http_get(/index\\.html/)", true);
+    test(r, "cuckoo.network.http_get(/gate\\.php/)", false);
+    // The method is compared case insensitively
+    test(r, "cuckoo.network.http_post(/gate\\.php/)", true);
+    test(r, "cuckoo.network.http_post(/index\\.html/)", false);
+
+    test(r, "cuckoo.network.http_user_agent(/^EvilBot/)", true);
+    test(r, "cuckoo.network.http_user_agent(/curl/)", false);
+}
+
+#[test]
+fn test_behavior() {
+    let r = "report.json";
+
+    test(
+        r,
+        r"cuckoo.registry.key_access(/\\CurrentVersion\\Run$/)",
+        true,
+    );
+    test(r, r"cuckoo.registry.key_access(/\\RunOnce$/)", false);
+
+    test(r, r"cuckoo.filesystem.file_access(/\\etc\\hosts$/)", true);
+    test(r, r"cuckoo.filesystem.file_access(/\.dll$/)", false);
+
+    test(r, r"cuckoo.sync.mutex(/^Global\\EvilMutex_été$/)", true);
+    test(r, r"cuckoo.sync.mutex(/Local/)", false);
+
+    test(
+        r,
+        r"cuckoo.process.executed_command(/^cmd\.exe \/c del/)",
+        true,
+    );
+    test(r, r"cuckoo.process.executed_command(/powershell/)", false);
+
+    // Lists missing from the report
+    let r = "report_dns_request.json";
+    test(r, "cuckoo.sync.mutex(/./)", false);
+    test(r, "cuckoo.network.http_request(/./)", false);
+    test(r, "cuckoo.network.host(/./)", false);
+}
+
+#[test]
+fn test_no_report() {
+    test_with_report("cuckoo.network.host(/./)", None, false);
+    test_with_report("not cuckoo.network.host(/./)", None, true);
+
+    // Invalid reports are ignored
+    test_with_report("cuckoo.network.host(/./)", Some(r#"{"network": "#), false);
+    test_with_report(
+        "cuckoo.network.host(/./)",
+        Some(r#"{"network": {"hosts": ["a"]}} {}"#),
+        false,
+    );
+    test_with_report(
+        "cuckoo.network.host(/./)",
+        Some(r#"{"network": {"hosts": ["a"]}}"#),
+        true,
+    );
+}
@@ -0,0 +1,101 @@
+use boreal::module::Dex;
+
+use crate::libyara_compat::util::ELF64_FILE;
+use crate::utils::{check_file, compare_module_values_on_file, compare_module_values_on_mem};
+
+const DEX_FILE: &str = "tests/assets/dex/classes.dex";
+
+#[test]
+fn test_coverage_dex_classes() {
+    compare_module_values_on_file(Dex, DEX_FILE, false, &[]);
+    compare_module_values_on_file(Dex, DEX_FILE, true, &[]);
+}
+
+#[test]
+fn test_coverage_dex_not_dex() {
+    compare_module_values_on_mem(Dex, "ELF64_FILE", ELF64_FILE, false, &[]);
+    compare_module_values_on_mem(Dex, "dex magic", b"dex\n035\0", false, &[]);
+}
+
+#[track_caller]
+fn test(cond: &str, expected: bool) {
+    check_file(
+        &format!(
+            r#"import "dex"
+rule test {{
+    condition: {cond}
+}}"#
+        ),
+        DEX_FILE,
+        expected,
+    );
+}
+
+#[test]
+fn test_header() {
+    test("dex.header.magic == dex.DEX_FILE_MAGIC_035", true);
+    test("dex.header.endian_tag == dex.ENDIAN_CONSTANT", true);
+    test("dex.header.file_size == filesize", true);
+    test("dex.header.string_ids_size == 10", true);
+    test("dex.map_list.size == 11", true);
+    test(
+        "dex.map_list.map_item[0].type == dex.TYPE_HEADER_ITEM",
+        true,
+    );
+}
+
+#[test]
+fn test_class_data() {
+    test("dex.class_defs[0].access_flags == dex.ACC_PUBLIC", true);
+    test(
+        r#"dex.string_ids[dex.class_defs[0].source_file_idx].value == "Foo.java""#,
+        true,
+    );
+
+    test("dex.number_of_fields == 1", true);
+    test(r#"dex.field[0].name == "count""#, true);
+    test(r#"dex.field[0].class_name == "LFoo;""#, true);
+    test(r#"dex.field[0].proto == "I""#, true);
+    test(
+        "dex.field[0].static == 1 and dex.field[0].instance == 0",
+        true,
+    );
+    test(
+        "dex.field[0].access_flags == dex.ACC_PRIVATE | dex.ACC_STATIC",
+        true,
+    );
+
+    test("dex.number_of_methods == 2", true);
+    test(r#"dex.method[0].name == "<init>""#, true);
+    test(r#"dex.method[0].proto == "V""#, true);
+    test("dex.method[0].direct == 1", true);
+    test(
+        "dex.method[0].access_flags == dex.ACC_PUBLIC | dex.ACC_CONSTRUCTOR",
+        true,
+    );
+    test("dex.method[0].code_item.insns_size == 4", true);
+    test(r#"dex.method[1].name == "run""#, true);
+    test(r#"dex.method[1].proto == "VI""#, true);
+    test("dex.method[1].virtual == 1", true);
+    test("dex.method[1].code_off == 0", true);
+    test("defined dex.method[1].code_item.insns_size", false);
+}
+
+#[test]
+fn test_has_method() {
+    test(r#"dex.has_method("run")"#, true);
+    test(r#"dex.has_method("main")"#, false);
+    test(r#"dex.has_method("LFoo;", "<init>")"#, true);
+    test(r#"dex.has_method("Ljava/lang/Object;", "<init>")"#, false);
+    test(r#"dex.has_method(/^r.n$/)"#, true);
+    test(r#"dex.has_method(/Foo/, /init/)"#, true);
+    test(r#"dex.has_method(/Object/, /init/)"#, false);
+}
+
+#[test]
+fn test_has_class() {
+    test(r#"dex.has_class("LFoo;")"#, true);
+    test(r#"dex.has_class("LBar;")"#, false);
+    test(r#"dex.has_class(/^LF/)"#, true);
+    test(r#"dex.has_class(/Bar/)"#, false);
+}
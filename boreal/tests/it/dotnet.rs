@@ -0,0 +1,33 @@
+use boreal::module::Dotnet;
+
+use crate::utils::compare_module_values_on_file;
+
+#[test]
+fn test_coverage_dotnet_libyara_0ca09bde() {
+    let path = "tests/assets/libyara/data/\
+        0ca09bde7602769120fadc4f7a4147347a7a97271370583586c9e587fd396171";
+    compare_module_values_on_file(Dotnet, path, false, &[]);
+    compare_module_values_on_file(Dotnet, path, true, &[]);
+}
+
+#[test]
+fn test_coverage_dotnet_libyara_33fc70f9() {
+    let path = "tests/assets/libyara/data/\
+        33fc70f99be6d2833ae48852d611c8048d0c053ed0b2c626db4dbe902832a08b";
+    compare_module_values_on_file(Dotnet, path, false, &[]);
+    compare_module_values_on_file(Dotnet, path, true, &[]);
+}
+
+#[test]
+fn test_coverage_dotnet_libyara_3b8b9015() {
+    let path = "tests/assets/libyara/data/\
+        3b8b90159fa9b6048cc5410c5d53f116943564e4d05b04a843f9b3d0540d0c1c";
+    compare_module_values_on_file(Dotnet, path, false, &[]);
+    compare_module_values_on_file(Dotnet, path, true, &[]);
+}
+
+#[test]
+fn test_coverage_dotnet_not_dotnet() {
+    compare_module_values_on_file(Dotnet, "tests/assets/libyara/data/tiny", false, &[]);
+    compare_module_values_on_file(Dotnet, "tests/assets/pe/resources_only.dll", false, &[]);
+}
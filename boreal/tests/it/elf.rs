@@ -3,7 +3,12 @@ use boreal::module::Elf;
 use crate::libyara_compat::util::{
     ELF32_FILE, ELF32_MIPS_FILE, ELF32_NOSECTIONS, ELF32_SHAREDOBJ, ELF64_FILE, ELF_X64_FILE,
 };
-use crate::utils::{compare_module_values_on_file, compare_module_values_on_mem, Checker};
+use crate::utils::{
+    check_boreal, compare_module_values_on_file, compare_module_values_on_mem, Checker,
+};
+
+/// Symbol lookup functions, which are not declared in yara.
+const SYMBOL_LOOKUP_FUNCTIONS: &[&str] = &["elf.has_import", "elf.has_export"];
 
 #[test]
 fn test_non_elf() {
@@ -41,64 +46,246 @@ condition:
     );
 }
 
+#[track_caller]
+fn test_boreal(mem: &[u8], condition: &str) {
+    check_boreal(
+        &format!(
+            r#"import "elf"
+rule test {{
+condition:
+    {condition}
+}}"#
+        ),
+        mem,
+        true,
+    );
+}
+
+#[test]
+fn test_linking() {
+    test_boreal(
+        ELF_X64_FILE,
+        r#"elf.interpreter == "/lib/ld-musl-x86_64.so.1" and
+    elf.number_of_needed == 1 and
+    elf.needed[0] == "libc.so" and
+    not defined elf.soname and
+    not defined elf.rpath and
+    not defined elf.runpath and
+    not defined elf.number_of_version_requirements"#,
+    );
+    test_boreal(
+        ELF32_MIPS_FILE,
+        r#"elf.interpreter == "/lib/ld-musl-mips.so.1" and elf.needed[0] == "libc.so""#,
+    );
+    test_boreal(
+        ELF32_SHAREDOBJ,
+        "not defined elf.interpreter and not defined elf.number_of_needed",
+    );
+    test_boreal(ELF32_FILE, "not defined elf.interpreter");
+
+    let contents = std::fs::read("tests/assets/elf/elf_with_imports").unwrap();
+    test_boreal(
+        &contents,
+        r#"elf.number_of_needed == 2 and
+    elf.needed[0] == "libdyn.so" and
+    elf.needed[1] == "libc.so.6" and
+    elf.number_of_version_requirements == 1 and
+    elf.version_requirements[0].file == "libc.so.6" and
+    elf.version_requirements[0].number_of_versions == 1 and
+    elf.version_requirements[0].versions[0] == "GLIBC_2.2.5""#,
+    );
+}
+
+#[test]
+fn test_notes() {
+    test_boreal(
+        ELF32_NOSECTIONS,
+        r#"elf.number_of_notes == 1 and
+    elf.notes[0].name == "GNU" and
+    elf.notes[0].type == 3 and
+    elf.build_id == "725c33a6cded46f2c7a28c1fbd657ad19f0f5180" and
+    not defined elf.abi_tag.os and
+    not defined elf.go_build_id"#,
+    );
+    test_boreal(
+        ELF32_SHAREDOBJ,
+        r#"elf.build_id == "6e969bbc8b0c9d9529fc07041595c5f0b9d5cdae""#,
+    );
+    test_boreal(ELF32_FILE, "not defined elf.number_of_notes");
+
+    let contents = std::fs::read("tests/assets/elf/elf_with_imports").unwrap();
+    test_boreal(
+        &contents,
+        r#"elf.number_of_notes == 2 and
+    elf.build_id == "f8552717600e7b9764eb8b07252b4556be3d8a7a""#,
+    );
+}
+
+#[test]
+fn test_symbols_lookup() {
+    let contents = std::fs::read("tests/assets/elf/elf_with_imports").unwrap();
+    test_boreal(
+        &contents,
+        r#"elf.has_import("foo") and
+    elf.has_import("printf") == 0 and
+    elf.has_import(/^STR/) and
+    elf.has_export("main") and
+    elf.has_export("protected_fun") and
+    not elf.has_export("foo") and
+    not elf.has_import("main")"#,
+    );
+    test_boreal(ELF_X64_FILE, r#"not elf.has_export("unknown")"#);
+    test_boreal(b"", r#"not defined elf.has_import("foo")"#);
+}
+
 #[test]
 fn test_coverage_elf32() {
-    compare_module_values_on_mem(Elf, "ELF32_FILE", ELF32_FILE, false, &[]);
-    compare_module_values_on_mem(Elf, "ELF32_FILE", ELF32_FILE, true, &[]);
+    compare_module_values_on_mem(
+        Elf,
+        "ELF32_FILE",
+        ELF32_FILE,
+        false,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
+    compare_module_values_on_mem(Elf, "ELF32_FILE", ELF32_FILE, true, SYMBOL_LOOKUP_FUNCTIONS);
 }
 
 #[test]
 fn test_coverage_elf64() {
-    compare_module_values_on_mem(Elf, "ELF64_FILE", ELF64_FILE, false, &[]);
-    compare_module_values_on_mem(Elf, "ELF64_FILE", ELF64_FILE, true, &[]);
+    compare_module_values_on_mem(
+        Elf,
+        "ELF64_FILE",
+        ELF64_FILE,
+        false,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
+    compare_module_values_on_mem(Elf, "ELF64_FILE", ELF64_FILE, true, SYMBOL_LOOKUP_FUNCTIONS);
 }
 
 #[test]
 fn test_coverage_elf32_nosections() {
-    compare_module_values_on_mem(Elf, "ELF32_NOSECTIONS", ELF32_NOSECTIONS, false, &[]);
-    compare_module_values_on_mem(Elf, "ELF32_NOSECTIONS", ELF32_NOSECTIONS, true, &[]);
+    // Notes are not parsed by yara
+    let diffs = [
+        "elf.number_of_notes",
+        "elf.notes",
+        "elf.build_id",
+        "elf.has_import",
+        "elf.has_export",
+    ];
+    compare_module_values_on_mem(Elf, "ELF32_NOSECTIONS", ELF32_NOSECTIONS, false, &diffs);
+    compare_module_values_on_mem(Elf, "ELF32_NOSECTIONS", ELF32_NOSECTIONS, true, &diffs);
 }
 
 #[test]
 fn test_coverage_elf32_sharedobj() {
-    compare_module_values_on_mem(Elf, "ELF32_SHAREDOBJ", ELF32_SHAREDOBJ, false, &[]);
-    compare_module_values_on_mem(Elf, "ELF32_SHAREDOBJ", ELF32_SHAREDOBJ, true, &[]);
+    // Notes are not parsed by yara
+    let diffs = [
+        "elf.number_of_notes",
+        "elf.notes",
+        "elf.build_id",
+        "elf.has_import",
+        "elf.has_export",
+    ];
+    compare_module_values_on_mem(Elf, "ELF32_SHAREDOBJ", ELF32_SHAREDOBJ, false, &diffs);
+    compare_module_values_on_mem(
+        Elf,
+        "ELF32_SHAREDOBJ",
+        ELF32_SHAREDOBJ,
+        true,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
 }
 
 #[test]
 fn test_coverage_elf32_mips() {
-    compare_module_values_on_mem(Elf, "ELF32_MIPS_FILE", ELF32_MIPS_FILE, false, &[]);
-    compare_module_values_on_mem(Elf, "ELF32_MIPS_FILE", ELF32_MIPS_FILE, true, &[]);
+    // Linking details are not parsed by yara
+    let diffs = [
+        "elf.interpreter",
+        "elf.number_of_needed",
+        "elf.needed",
+        "elf.has_import",
+        "elf.has_export",
+    ];
+    compare_module_values_on_mem(Elf, "ELF32_MIPS_FILE", ELF32_MIPS_FILE, false, &diffs);
+    compare_module_values_on_mem(Elf, "ELF32_MIPS_FILE", ELF32_MIPS_FILE, true, &diffs);
 }
 
 #[test]
 fn test_coverage_elf_x64_file() {
-    compare_module_values_on_mem(Elf, "ELF_X64_FILE", ELF_X64_FILE, false, &[]);
-    compare_module_values_on_mem(Elf, "ELF_X64_FILE", ELF_X64_FILE, true, &[]);
+    // Linking details are not parsed by yara
+    let diffs = [
+        "elf.interpreter",
+        "elf.number_of_needed",
+        "elf.needed",
+        "elf.has_import",
+        "elf.has_export",
+    ];
+    compare_module_values_on_mem(Elf, "ELF_X64_FILE", ELF_X64_FILE, false, &diffs);
+    compare_module_values_on_mem(Elf, "ELF_X64_FILE", ELF_X64_FILE, true, &diffs);
 }
 
 #[test]
 fn test_coverage_smallest() {
-    compare_module_values_on_file(Elf, "tests/assets/elf/smallest", false, &[]);
-    compare_module_values_on_file(Elf, "tests/assets/elf/smallest", true, &[]);
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/smallest",
+        false,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/smallest",
+        true,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
 }
 
 #[test]
 fn test_coverage_invalid_sections() {
-    compare_module_values_on_file(Elf, "tests/assets/elf/invalid_sections", false, &[]);
-    compare_module_values_on_file(Elf, "tests/assets/elf/invalid_sections", true, &[]);
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/invalid_sections",
+        false,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/invalid_sections",
+        true,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
 }
 
 #[test]
 fn test_coverage_invalid_program_header() {
-    compare_module_values_on_file(Elf, "tests/assets/elf/invalid_program_header", false, &[]);
-    compare_module_values_on_file(Elf, "tests/assets/elf/invalid_program_header", true, &[]);
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/invalid_program_header",
+        false,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/invalid_program_header",
+        true,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
 }
 
 #[test]
 fn test_coverage_invalid_symbols() {
-    compare_module_values_on_file(Elf, "tests/assets/elf/invalid_symbols", false, &[]);
-    compare_module_values_on_file(Elf, "tests/assets/elf/invalid_symbols", true, &[]);
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/invalid_symbols",
+        false,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
+    compare_module_values_on_file(
+        Elf,
+        "tests/assets/elf/invalid_symbols",
+        true,
+        SYMBOL_LOOKUP_FUNCTIONS,
+    );
 }
 
 #[test]
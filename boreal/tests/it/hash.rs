@@ -1,5 +1,5 @@
 use crate::libyara_compat::util::TEXT_1024_BYTES;
-use crate::utils::{check, Checker};
+use crate::utils::{check, check_boreal, Checker};
 
 const TEXT: &[u8] = TEXT_1024_BYTES.as_bytes();
 
@@ -19,6 +19,12 @@ fn test(cond: &str, input: &[u8]) {
     check(&make_rule(cond), input, true);
 }
 
+/// Test functions that are not available in YARA.
+#[track_caller]
+fn test_boreal(cond: &str, input: &[u8]) {
+    check_boreal(&make_rule(cond), input, true);
+}
+
 #[test]
 fn test_md5() {
     test(
@@ -254,6 +260,198 @@ fn test_sha256() {
     checker.check_fragmented(&[(0, Some(&TEXT[0..70])), (80, Some(&TEXT[80..]))], true);
 }
 
+#[test]
+fn test_sha512() {
+    test_boreal(
+        "hash.sha512(0, filesize) == \
+            \"1f40fc92da241694750979ee6cf582f2d5d7d28e18335de05abc54d0560e0f53\
+            02860c652bf08d560252aa5e74210546f369fbbbce8c12cfc7957b2652fe9a75\"",
+        b"a",
+    );
+    test_boreal(
+        "hash.sha512(0, filesize) == \
+            \"8feeece7bdec0aa96bc3ec2c7f2687b9fb816db5e6f3967d048ee0e9fdb610e1\
+            fbf288148771497bc020b2f183befad2a8b0d0a06a1ea8f88df956fe7f7ede07\"",
+        TEXT,
+    );
+    test_boreal(
+        "hash.sha512(\"abcdefghijklmnopqrstuvwxyz\") == \
+            \"4dbff86cc2ca1bae1e16468a05cb9881c97f1753bce3619034898faa1aabe429\
+            955a1bf8ec483d7421fe3c1646613a59ed5441fb0f321389f77f48a879c7b1f1\"",
+        b"",
+    );
+    test_boreal(
+        "hash.sha512(0, filesize * 2) == hash.sha512(0, filesize)",
+        b"abcdefghijklmnopqrstuvwxyz",
+    );
+
+    test_boreal(r#"not defined hash.sha512(0, filesize)"#, b"");
+    test_boreal(r#"not defined hash.sha512(-1, filesize)"#, b"a");
+
+    let mut checker = Checker::new_without_yara(&make_rule(
+        "hash.sha512(50, 100) == \
+            \"b67e38d729ce0ba7f8c840f1a2fa1fa5f1f6c80d543a7a52526797cdfdb05a67\
+            202d4d5c2617fcf2280713828e34df86ab834d4a9f42598b7f54442867bbeea3\"",
+    ));
+    checker.check(TEXT, true);
+    checker.check_fragmented(&[(0, Some(&TEXT[0..75])), (75, Some(&TEXT[75..]))], true);
+}
+
+#[test]
+fn test_sha3_256() {
+    test_boreal(
+        "hash.sha3_256(0, filesize) == \
+            \"80084bf2fba02475726feb2cab2d8215eab14bc6bdd8bfb2c8151257032ecd8b\"",
+        b"a",
+    );
+    test_boreal(
+        "hash.sha3_256(0, filesize) == \
+            \"1cd2633333be64349f42ae4dde899b48c1d0380e498b5002bf8d2176f06044e1\"",
+        TEXT,
+    );
+    test_boreal(
+        "hash.sha3_256(\"abcdefghijklmnopqrstuvwxyz\") == \
+            \"7cab2dc765e21b241dbc1c255ce620b29f527c6d5e7f5f843e56288f0d707521\"",
+        b"",
+    );
+    test_boreal(
+        "hash.sha3_256(0, filesize * 2) == hash.sha3_256(0, filesize)",
+        b"abcdefghijklmnopqrstuvwxyz",
+    );
+
+    test_boreal(r#"not defined hash.sha3_256(0, filesize)"#, b"");
+    test_boreal(r#"not defined hash.sha3_256(-1, filesize)"#, b"a");
+
+    let mut checker = Checker::new_without_yara(&make_rule(
+        "hash.sha3_256(50, 100) == \
+            \"569d48f1f77a2cf9e5e286b9d86dbbaa209ad17138edc6e2cd5062eae83faec2\"",
+    ));
+    checker.check(TEXT, true);
+    checker.check_fragmented(&[(0, Some(&TEXT[0..75])), (75, Some(&TEXT[75..]))], true);
+}
+
+#[test]
+fn test_blake3() {
+    test_boreal(
+        "hash.blake3(\"\") == \
+            \"af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262\"",
+        b"",
+    );
+    test_boreal(
+        "hash.blake3(\"abc\") == \
+            \"6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85\"",
+        b"",
+    );
+    test_boreal(
+        "hash.blake3(0, filesize) == hash.blake3(\"abcdefghijklmnopqrstuvwxyz\")",
+        b"abcdefghijklmnopqrstuvwxyz",
+    );
+    test_boreal(
+        "hash.blake3(0, filesize * 2) == hash.blake3(0, filesize)",
+        b"abcdefghijklmnopqrstuvwxyz",
+    );
+
+    test_boreal(r#"not defined hash.blake3(0, filesize)"#, b"");
+    test_boreal(r#"not defined hash.blake3(-1, filesize)"#, b"a");
+
+    let mut checker = Checker::new_without_yara(&make_rule(
+        "hash.blake3(1, 3) == \
+            \"6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85\"",
+    ));
+    checker.check(b"_abc_", true);
+    checker.check_fragmented(&[(0, Some(b"_a")), (2, Some(b"bc_"))], true);
+}
+
+/// Pseudo-random bytes, so that fuzzy hashes can be computed on them.
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut state: u32 = 1;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16).to_le_bytes()[0]
+        })
+        .collect()
+}
+
+#[test]
+fn test_ssdeep() {
+    let data = random_bytes(4096);
+
+    test_boreal("hash.ssdeep(\"\") == \"3::\"", b"");
+    test_boreal("hash.ssdeep(0, filesize) == hash.ssdeep(\"abc\")", b"abc");
+    test_boreal(
+        "hash.ssdeep(0, filesize * 2) == hash.ssdeep(0, filesize)",
+        &data,
+    );
+    test_boreal(r#"not defined hash.ssdeep(0, filesize)"#, b"");
+    test_boreal(r#"not defined hash.ssdeep(-1, filesize)"#, b"a");
+
+    test_boreal(
+        "hash.ssdeep_compare(hash.ssdeep(0, filesize), hash.ssdeep(0, filesize)) == 100",
+        &data,
+    );
+    test_boreal(
+        "hash.ssdeep_compare(hash.ssdeep(0, filesize), hash.ssdeep(0, 2048)) < 100",
+        &data,
+    );
+    test_boreal(
+        r#"hash.ssdeep_compare("3:abcdefghijk:abc", "3:lmnopqrstuv:lmn") == 0"#,
+        b"",
+    );
+    test_boreal(r#"not defined hash.ssdeep_compare("3:abc", "abc")"#, b"");
+
+    // Test that fragmented memory still works if chunks are contiguous
+    let mut checker = Checker::new_without_yara(&make_rule("defined hash.ssdeep(0, 4096)"));
+    checker.check_fragmented(
+        &[(0, Some(&data[..1000])), (1000, Some(&data[1000..]))],
+        true,
+    );
+    let mut checker = Checker::new_without_yara(&make_rule("not defined hash.ssdeep(0, 4096)"));
+    checker.check_fragmented(
+        &[(0, Some(&data[..1000])), (2000, Some(&data[2000..]))],
+        true,
+    );
+}
+
+#[test]
+fn test_tlsh() {
+    let data = random_bytes(4096);
+
+    test_boreal(
+        "hash.tlsh(0, filesize) == hash.tlsh(0, filesize * 2)",
+        &data,
+    );
+    // Not enough data to compute a hash
+    test_boreal(r#"not defined hash.tlsh(0, filesize)"#, b"abc");
+    test_boreal(r#"not defined hash.tlsh("abc")"#, b"");
+    test_boreal(r#"not defined hash.tlsh(-1, filesize)"#, &data);
+
+    test_boreal(
+        "hash.tlsh_diff(hash.tlsh(0, filesize), hash.tlsh(0, filesize)) == 0",
+        &data,
+    );
+    test_boreal(
+        "hash.tlsh_diff(hash.tlsh(0, filesize), hash.tlsh(0, 2048)) > 0",
+        &data,
+    );
+    test_boreal(
+        r#"not defined hash.tlsh_diff(hash.tlsh(0, filesize), "T1abc")"#,
+        &data,
+    );
+
+    // Test that fragmented memory still works if chunks are contiguous
+    let mut checker = Checker::new_without_yara(&make_rule("defined hash.tlsh(0, 4096)"));
+    checker.check_fragmented(
+        &[(0, Some(&data[..1000])), (1000, Some(&data[1000..]))],
+        true,
+    );
+    let mut checker = Checker::new_without_yara(&make_rule("not defined hash.tlsh(0, 4096)"));
+    checker.check_fragmented(
+        &[(0, Some(&data[..1000])), (2000, Some(&data[2000..]))],
+        true,
+    );
+}
+
 #[test]
 fn test_checksum32() {
     test("hash.checksum32(0, filesize) == 97", b"a");
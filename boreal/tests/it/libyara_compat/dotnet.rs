@@ -0,0 +1,61 @@
+use crate::utils::check_file;
+
+const DOTNET_FILE: &str =
+    "tests/assets/libyara/data/0ca09bde7602769120fadc4f7a4147347a7a97271370583586c9e587fd396171";
+
+#[test]
+fn test_dotnet() {
+    check_file(
+        "import \"dotnet\"
+      rule test {
+        condition:
+          dotnet.assembly.name == \"hpjsoaputility.Sv.resources\"
+      }",
+        DOTNET_FILE,
+        true,
+    );
+
+    check_file(
+        "import \"dotnet\"
+      rule test {
+        condition:
+          dotnet.is_dotnet == 1 and
+          dotnet.version == \"v2.0.50727\" and
+          dotnet.module_name == \"hpjsoaputility.Sv.resources.dll\"
+      }",
+        DOTNET_FILE,
+        true,
+    );
+
+    check_file(
+        "import \"dotnet\"
+      rule test {
+        condition:
+          for any r in dotnet.resources: (
+            r.name == \"hpjsoaputility.XmlStreamSoapExtension.pt.resources\"
+          )
+      }",
+        DOTNET_FILE,
+        true,
+    );
+
+    check_file(
+        "import \"dotnet\"
+      rule test {
+        condition:
+          for any r in dotnet.assembly_refs: (r.name == \"mscorlib\")
+      }",
+        DOTNET_FILE,
+        true,
+    );
+
+    check_file(
+        "import \"dotnet\"
+      rule test {
+        condition:
+          dotnet.is_dotnet == 0
+      }",
+        "tests/assets/libyara/data/tiny",
+        true,
+    );
+}
@@ -364,4 +364,92 @@ fn test_macho() {
         "tests/assets/libyara/data/tiny-universal",
         true,
     );
+
+    // Dylibs
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.number_of_dylibs == 1 and
+    macho.dylibs[0].name == \"/usr/lib/libSystem.B.dylib\" and
+    macho.dylibs[0].timestamp == 2 and
+    macho.dylibs[0].compatibility_version == \"1.0.0\" and
+    macho.dylibs[0].current_version == \"1213.0.0\" }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.has_dylib(\"/usr/lib/libSystem.B.dylib\") }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.has_dylib(\"/usr/lib/libc.dylib\") }",
+        MACHO_X86_FILE,
+        false,
+    );
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.number_of_dylibs == 2 and
+    macho.dylibs[0].name == \"fact_x86_64.dylib\" and
+    macho.dylibs[1].name == \"/usr/lib/libSystem.B.dylib\" }",
+        MACHO_X86_64_DYLIB_FILE,
+        true,
+    );
+
+    // Rpaths
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.number_of_rpaths == 0 and not macho.has_rpath(\"@loader_path/../lib\") }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    // Dynamic linker, uuid and versions
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.dynamic_linker == \"/usr/lib/dyld\" }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.uuid == \"5FB5950F-4025-3D4F-A8FB-9648C1740790\" }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.min_version.device == 0x24 and
+    macho.min_version.version == \"10.9.0\" and
+    macho.min_version.sdk == \"10.10.0\" }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.source_version == \"0.0.0.0.0\" }",
+        MACHO_X86_FILE,
+        true,
+    );
+
+    // Entitlements, imports and exports
+
+    check(
+        "import \"macho\" rule test { condition:
+    macho.has_entitlement(\"com.apple.security.get-task-allow\") or
+    macho.has_import(\"_printf\") or
+    macho.has_export(\"_main\") }",
+        MACHO_PPC_FILE,
+        false,
+    );
 }
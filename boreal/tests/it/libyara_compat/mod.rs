@@ -1,4 +1,6 @@
 #[cfg(feature = "object")]
+mod dotnet;
+#[cfg(feature = "object")]
 mod elf;
 #[cfg(feature = "object")]
 mod macho;
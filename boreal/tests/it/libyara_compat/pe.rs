@@ -308,7 +308,7 @@ fn test_pe() {
         true
     ) ;
 
-    #[cfg(feature = "authenticode")]
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
     check_file(
         "import \"pe\"
         rule test {
@@ -410,7 +410,7 @@ fn test_pe() {
         true
     );
 
-    #[cfg(feature = "authenticode")]
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
     check_file(
         "import \"pe\"
       rule test {
@@ -421,7 +421,7 @@ fn test_pe() {
         true,
     );
 
-    #[cfg(feature = "authenticode")]
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
     check_file(
         "import \"pe\"
       rule test {
@@ -432,7 +432,7 @@ fn test_pe() {
         true,
     );
 
-    #[cfg(feature = "authenticode")]
+    #[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
     check_file(
         "import \"pe\"
         rule test {
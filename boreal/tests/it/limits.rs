@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use boreal::scanner::{ScanError, ScanParams};
+use boreal::scanner::{CancellationToken, ScanError, ScanParams};
 
 use crate::utils::{Checker, Compiler};
 
@@ -283,6 +283,59 @@ rule second {{
     assert!(matches!(checker.last_err, Some(ScanError::Timeout)));
 }
 
+#[test]
+fn test_cancellation() {
+    let token = CancellationToken::new();
+
+    // Cancel a scan in the middle of the evaluation of a rule.
+    let mut compiler = Compiler::new_without_yara();
+    compiler.add_rules(
+        r#"
+rule first { condition: true }
+
+rule second {
+    strings:
+        $var = "var"
+    condition:
+        for all i in (#var..9223372036854775807) : (
+            for all j in (0..9223372036854775807) : (
+                i + j >= 0
+            )
+        )
+}"#,
+    );
+    let mut checker = compiler.into_checker();
+    checker.set_scan_params(ScanParams::default().cancellation_token(Some(token.clone())));
+    checker.assert_success = false;
+
+    let cancel_thread = {
+        let token = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            token.cancel();
+        })
+    };
+    checker.check_rule_matches(b"", &["default:first"]);
+    cancel_thread.join().unwrap();
+    assert!(matches!(checker.last_err, Some(ScanError::Cancelled)));
+    assert_eq!(checker.last_err.unwrap().to_string(), "scan cancelled");
+
+    // Cancel a scan during the string scan.
+    let mut checker = Checker::new_without_yara(
+        r#"
+rule a {
+    strings:
+        $a = { 00 00 [0-] 00 }
+    condition:
+        $a
+}"#,
+    );
+    checker.set_scan_params(ScanParams::default().cancellation_token(Some(token)));
+    checker.assert_success = false;
+    checker.check_rule_matches(&vec![0; 1024 * 1024], &[]);
+    assert!(matches!(checker.last_err, Some(ScanError::Cancelled)));
+}
+
 #[test]
 fn test_max_split_match_length_hex_string() {
     let mut checker = Checker::new(
@@ -0,0 +1,144 @@
+use crate::libyara_compat::util::PE32_FILE;
+use crate::utils::check_boreal;
+
+const LNK_FILE: &str = "tests/assets/lnk/shortcut.lnk";
+
+#[track_caller]
+fn test_mem(mem: &[u8], cond: &str, expected: bool) {
+    check_boreal(
+        &format!(
+            r#"import "lnk"
+rule test {{
+    condition: {cond}
+}}"#
+        ),
+        mem,
+        expected,
+    );
+}
+
+#[track_caller]
+fn test(cond: &str, expected: bool) {
+    test_mem(&std::fs::read(LNK_FILE).unwrap(), cond, expected);
+}
+
+#[test]
+fn test_header() {
+    test("lnk.is_lnk == 1", true);
+    test("lnk.is_malformed == 0", true);
+    test("lnk.creation_time == 1600000000", true);
+    test("lnk.access_time == 1600000100", true);
+    test("lnk.write_time == 1600000200", true);
+    test("lnk.file_size == 12345", true);
+    test("lnk.icon_index == -3", true);
+    test("lnk.show_command == lnk.SW_SHOWMINNOACTIVE", true);
+    test(
+        "lnk.file_attributes_flags & lnk.FILE_ATTRIBUTE_ARCHIVE != 0",
+        true,
+    );
+    test(
+        "lnk.file_attributes_flags & lnk.FILE_ATTRIBUTE_HIDDEN != 0",
+        false,
+    );
+    test("lnk.link_flags & lnk.HasArguments != 0", true);
+    test("lnk.link_flags & lnk.RunAsUser != 0", false);
+
+    test("lnk.has_hotkey == 1", true);
+    test(r#"lnk.hotkey == "A""#, true);
+    test(
+        "lnk.hotkey_modifier_flags == lnk.HOTKEYF_CONTROL | lnk.HOTKEYF_ALT",
+        true,
+    );
+}
+
+#[test]
+fn test_link_target_id_list() {
+    test("lnk.link_target_id_list_size == 29", true);
+    test("lnk.number_of_item_ids == 2", true);
+    test("lnk.item_id_list[0].size == 20", true);
+    test(r#"lnk.item_id_list[1].data == "/C:\\\x00""#, true);
+}
+
+#[test]
+fn test_link_info() {
+    test("lnk.link_info_header_size == 0x24", true);
+    test("lnk.link_info_flags == lnk.VolumeIDAndLocalBasePath", true);
+    test("lnk.volume_id.drive_type == lnk.DRIVE_FIXED", true);
+    test("lnk.volume_id.drive_serial_number == 0xDEADBEEF", true);
+    test(r#"lnk.volume_id.data == "DATA""#, true);
+    test(
+        r#"lnk.local_base_path == "C:\\Windows\\System32\\cmd.exe""#,
+        true,
+    );
+    test(
+        r#"lnk.local_base_path_unicode == "C:\\Windows\\System32\\cmd.exe""#,
+        true,
+    );
+    test(r#"lnk.common_path_suffix == """#, true);
+    test("defined lnk.common_network_relative_link.size", false);
+}
+
+#[test]
+fn test_string_data() {
+    test(r#"lnk.name_string == "Invoice""#, true);
+    test(
+        r#"lnk.relative_path == "..\\..\\Windows\\System32\\cmd.exe""#,
+        true,
+    );
+    test(r#"lnk.working_dir == "%TEMP%""#, true);
+    test(
+        r#"lnk.command_line_arguments == "/c powershell -enc SQBFAFgA""#,
+        true,
+    );
+    test(
+        r#"lnk.icon_location == "%SystemRoot%\\System32\\shell32.dll""#,
+        true,
+    );
+}
+
+#[test]
+fn test_extra_data() {
+    test(
+        r#"lnk.environment_variables_data.target_ansi == "%windir%\\system32\\cmd.exe""#,
+        true,
+    );
+    test(
+        r#"lnk.environment_variables_data.target_unicode == "%windir%\\system32\\cmd.exe""#,
+        true,
+    );
+    test(
+        r#"lnk.known_folder_data.known_folder_id == "1ac14e77-02e7-4e5d-b744-2eb1ae5198b7""#,
+        true,
+    );
+    test("lnk.known_folder_data.offset == 0xD1", true);
+    test(r#"lnk.tracker_data.machine_id == "desktop-evil01""#, true);
+    test("lnk.tracker_data.version == 0", true);
+    test(
+        r#"lnk.tracker_data.droid_file_identifier == "e1a9d4a0-5a52-11ee-8c99-0242ac120002""#,
+        true,
+    );
+    test("defined lnk.special_folder_data.offset", false);
+}
+
+#[test]
+fn test_malformed() {
+    let mut mem = std::fs::read(LNK_FILE).unwrap();
+    // Truncated in the string data
+    mem.truncate(0x120);
+    test_mem(&mem, "lnk.is_lnk == 1 and lnk.is_malformed == 1", true);
+    test_mem(&mem, "lnk.file_size == 12345", true);
+    test_mem(&mem, "defined lnk.icon_location", false);
+
+    // Only the header
+    mem.truncate(0x4C);
+    test_mem(&mem, "lnk.is_lnk == 1 and lnk.is_malformed == 1", true);
+    mem.truncate(0x4B);
+    test_mem(&mem, "lnk.is_lnk == 0", true);
+}
+
+#[test]
+fn test_not_lnk() {
+    test_mem(PE32_FILE, "lnk.is_lnk == 0", true);
+    test_mem(PE32_FILE, "defined lnk.file_size", false);
+    test_mem(b"", "lnk.is_lnk == 0", true);
+}
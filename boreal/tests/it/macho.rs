@@ -7,6 +7,22 @@ use crate::utils::{
     check_boreal, check_file, compare_module_values_on_file, compare_module_values_on_mem,
 };
 
+/// Code signature constants, which are not declared in yara.
+const CODE_SIGNATURE_CONSTANTS: &[&str] = &[
+    "macho.CS_ADHOC",
+    "macho.CS_HARD",
+    "macho.CS_KILL",
+    "macho.CS_RESTRICT",
+    "macho.CS_ENFORCEMENT",
+    "macho.CS_REQUIRE_LV",
+    "macho.CS_RUNTIME",
+    "macho.CS_LINKER_SIGNED",
+    "macho.CS_HASHTYPE_SHA1",
+    "macho.CS_HASHTYPE_SHA256",
+    "macho.CS_HASHTYPE_SHA256_TRUNCATED",
+    "macho.CS_HASHTYPE_SHA384",
+];
+
 #[track_caller]
 fn test_cond(file: &str, cond: &str) {
     check_file(
@@ -106,85 +122,202 @@ fn test_entry_point_for_arch() {
     );
 }
 
+#[test]
+fn test_code_signature() {
+    let mem = std::fs::read("tests/assets/macho/signed").unwrap();
+    let test = |cond: &str| {
+        check_boreal(
+            &format!("import \"macho\" rule test {{ condition: {cond} }}"),
+            &mem,
+            true,
+        );
+    };
+
+    test("macho.code_signature_data.dataoff == 256 and macho.code_signature_data.datasize == 440");
+    test("macho.number_of_signatures == 1");
+    test(
+        r#"macho.signatures[0].identifier == "com.example.signed" and
+           not defined macho.signatures[0].team_id"#,
+    );
+    test("macho.signatures[0].flags & macho.CS_ADHOC");
+    test("macho.signatures[0].flags & macho.CS_LINKER_SIGNED");
+    test("not (macho.signatures[0].flags & macho.CS_RUNTIME)");
+    test(
+        "macho.signatures[0].hash_type == macho.CS_HASHTYPE_SHA256 and
+         macho.signatures[0].hash_size == 32 and
+         macho.signatures[0].page_size == 4096 and
+         macho.signatures[0].number_of_special_slots == 2 and
+         macho.signatures[0].number_of_code_slots == 1 and
+         macho.signatures[0].code_limit == 4096",
+    );
+    #[cfg(feature = "hash")]
+    test(r#"macho.signatures[0].cdhash == "1564651fcd9b921cd2cf2982e7d7399ab9b9dab8""#);
+    test(
+        r#"macho.number_of_requirements == 1 and
+           macho.requirements[0].type == 3 and
+           macho.requirements[0].data contains "com.example.signed""#,
+    );
+    test(
+        r#"macho.number_of_entitlements == 1 and
+           macho.has_entitlement("com.apple.security.get-task-allow")"#,
+    );
+    // The certificates are only parsed when a backend is available, and this signature has none.
+    #[cfg(all(feature = "authenticode", not(feature = "authenticode-pure")))]
+    test("not defined macho.number_of_certificates");
+    #[cfg(feature = "authenticode-pure")]
+    test("macho.number_of_certificates == 0");
+}
+
 #[test]
 fn test_coverage_non_macho() {
-    compare_module_values_on_mem(MachO, "ELF32_FILE", ELF32_FILE, false, &[]);
-    compare_module_values_on_mem(MachO, "ELF32_FILE", ELF32_FILE, true, &[]);
+    compare_module_values_on_mem(
+        MachO::default(),
+        "ELF32_FILE",
+        ELF32_FILE,
+        false,
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_mem(
+        MachO::default(),
+        "ELF32_FILE",
+        ELF32_FILE,
+        true,
+        CODE_SIGNATURE_CONSTANTS,
+    );
 }
 
 #[test]
 fn test_coverage_macho_x86() {
-    compare_module_values_on_mem(MachO, "MACHO_X86_FILE", MACHO_X86_FILE, false, &[]);
-    compare_module_values_on_mem(MachO, "MACHO_X86_FILE", MACHO_X86_FILE, true, &[]);
+    compare_module_values_on_mem(
+        MachO::default(),
+        "MACHO_X86_FILE",
+        MACHO_X86_FILE,
+        false,
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_mem(
+        MachO::default(),
+        "MACHO_X86_FILE",
+        MACHO_X86_FILE,
+        true,
+        CODE_SIGNATURE_CONSTANTS,
+    );
 }
 
 #[test]
 fn test_coverage_macho_ppc() {
-    compare_module_values_on_mem(MachO, "MACHO_PPC_FILE", MACHO_PPC_FILE, false, &[]);
-    compare_module_values_on_mem(MachO, "MACHO_PPC_FILE", MACHO_PPC_FILE, true, &[]);
+    compare_module_values_on_mem(
+        MachO::default(),
+        "MACHO_PPC_FILE",
+        MACHO_PPC_FILE,
+        false,
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_mem(
+        MachO::default(),
+        "MACHO_PPC_FILE",
+        MACHO_PPC_FILE,
+        true,
+        CODE_SIGNATURE_CONSTANTS,
+    );
 }
 
 #[test]
 fn test_coverage_macho_x86_object() {
     compare_module_values_on_mem(
-        MachO,
+        MachO::default(),
         "MACHO_X86_OBJECT_FILE",
         MACHO_X86_OBJECT_FILE,
         false,
-        &[],
+        CODE_SIGNATURE_CONSTANTS,
     );
     compare_module_values_on_mem(
-        MachO,
+        MachO::default(),
         "MACHO_X86_OBJECT_FILE",
         MACHO_X86_OBJECT_FILE,
         true,
-        &[],
+        CODE_SIGNATURE_CONSTANTS,
     );
 }
 
 #[test]
 fn test_coverage_macho_x64_dylib() {
     compare_module_values_on_mem(
-        MachO,
+        MachO::default(),
         "MACHO_X86_64_DYLIB_FILE",
         MACHO_X86_64_DYLIB_FILE,
         false,
-        &[],
+        CODE_SIGNATURE_CONSTANTS,
     );
     compare_module_values_on_mem(
-        MachO,
+        MachO::default(),
         "MACHO_X86_64_DYLIB_FILE",
         MACHO_X86_64_DYLIB_FILE,
         true,
-        &[],
+        CODE_SIGNATURE_CONSTANTS,
     );
 }
 
 #[test]
 fn test_coverage_macho_tiny_macho() {
-    compare_module_values_on_file(MachO, "tests/assets/libyara/data/tiny-macho", false, &[]);
-    compare_module_values_on_file(MachO, "tests/assets/libyara/data/tiny-macho", true, &[]);
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/libyara/data/tiny-macho",
+        false,
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/libyara/data/tiny-macho",
+        true,
+        CODE_SIGNATURE_CONSTANTS,
+    );
 }
 
 #[test]
 fn test_coverage_macho_tiny_universal() {
     compare_module_values_on_file(
-        MachO,
+        MachO::default(),
         "tests/assets/libyara/data/tiny-universal",
         false,
-        &[],
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/libyara/data/tiny-universal",
+        true,
+        CODE_SIGNATURE_CONSTANTS,
     );
-    compare_module_values_on_file(MachO, "tests/assets/libyara/data/tiny-universal", true, &[]);
 }
 
 #[test]
 fn test_coverage_macho_entry_points() {
-    compare_module_values_on_file(MachO, "tests/assets/macho/entry_points", false, &[]);
-    compare_module_values_on_file(MachO, "tests/assets/macho/entry_points", true, &[]);
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/macho/entry_points",
+        false,
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/macho/entry_points",
+        true,
+        CODE_SIGNATURE_CONSTANTS,
+    );
 }
 
 #[test]
 fn test_coverage_macho_fat64() {
-    compare_module_values_on_file(MachO, "tests/assets/macho/fat64", false, &[]);
-    compare_module_values_on_file(MachO, "tests/assets/macho/fat64", true, &[]);
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/macho/fat64",
+        false,
+        CODE_SIGNATURE_CONSTANTS,
+    );
+    compare_module_values_on_file(
+        MachO::default(),
+        "tests/assets/macho/fat64",
+        true,
+        CODE_SIGNATURE_CONSTANTS,
+    );
 }
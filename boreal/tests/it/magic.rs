@@ -0,0 +1,158 @@
+use crate::libyara_compat::util::{ELF32_SHAREDOBJ, ELF64_FILE, MACHO_X86_FILE, PE32_FILE};
+use crate::utils::{check_boreal, Checker};
+
+fn make_rule(cond: &str) -> String {
+    format!(
+        r#"
+import "magic"
+
+rule test {{
+    condition: {cond}
+}}"#
+    )
+}
+
+#[track_caller]
+fn test(input: &[u8], type_: &str, mime_type: &str) {
+    check_boreal(
+        &make_rule(&format!(
+            r#"magic.type() == "{type_}" and magic.mime_type() == "{mime_type}""#
+        )),
+        input,
+        true,
+    );
+}
+
+#[test]
+fn test_executables() {
+    let tiny = std::fs::read("tests/assets/libyara/data/tiny").unwrap();
+    test(
+        &tiny,
+        "PE32 executable (console) Intel 80386, for MS Windows",
+        "application/x-dosexec",
+    );
+    check_boreal(
+        &make_rule(r#"magic.type() startswith "PE32 executable""#),
+        PE32_FILE,
+        true,
+    );
+    test(b"MZ\0\0", "MS-DOS executable", "application/x-dosexec");
+
+    test(
+        ELF64_FILE,
+        "ELF 64-bit LSB executable, x86-64, version 1 (SYSV)",
+        "application/x-executable",
+    );
+    test(
+        ELF32_SHAREDOBJ,
+        "ELF 32-bit LSB shared object, Intel 80386, version 1 (SYSV)",
+        "application/x-sharedlib",
+    );
+    test(
+        MACHO_X86_FILE,
+        "Mach-O i386 executable",
+        "application/x-mach-binary",
+    );
+}
+
+#[test]
+fn test_documents() {
+    test(
+        b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n",
+        "PDF document, version 1.7",
+        "application/pdf",
+    );
+    test(
+        b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1\x00\x00",
+        "Composite Document File V2 Document",
+        "application/CDFV2",
+    );
+
+    let mut docx = b"PK\x03\x04\x14\x00\x06\x00\x08\x00\x00\x00!\x00\x00\x00\x00\x00\
+        \x00\x00\x00\x00\x00\x00\x00\x00\x13\x00\x00\x00[Content_Types].xml"
+        .to_vec();
+    docx.extend(b"<Types></Types>PK\x03\x04word/document.xml");
+    test(
+        &docx,
+        "Microsoft Word 2007+",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    );
+}
+
+#[test]
+fn test_archives() {
+    test(
+        b"PK\x03\x04\x14\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+        \x00\x00\x00\x00\x00\x00\x00\x00\x05\x00\x00\x00a.txt",
+        "Zip archive data, at least v2.0 to extract",
+        "application/zip",
+    );
+    test(
+        b"\x1F\x8B\x08\x00\x00\x00\x00\x00",
+        "gzip compressed data",
+        "application/gzip",
+    );
+    test(
+        b"BZh91AY&SY",
+        "bzip2 compressed data, block size = 900k",
+        "application/x-bzip2",
+    );
+    test(
+        b"7z\xBC\xAF\x27\x1C\x00\x04",
+        "7-zip archive data",
+        "application/x-7z-compressed",
+    );
+
+    let mut tar = vec![0; 512];
+    tar[..5].copy_from_slice(b"a.txt");
+    tar[257..263].copy_from_slice(b"ustar\0");
+    test(&tar, "POSIX tar archive", "application/x-tar");
+}
+
+#[test]
+fn test_images() {
+    test(
+        b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR\x00\x00\x03\x20\x00\x00\x02\x58",
+        "PNG image data, 800 x 600",
+        "image/png",
+    );
+    test(
+        b"\xFF\xD8\xFF\xE0\x00\x10JFIF\x00\x01\x01",
+        "JPEG image data, JFIF standard 1.01",
+        "image/jpeg",
+    );
+    test(
+        b"GIF89a\x10\x00\x20\x00",
+        "GIF image data, version 89a, 16 x 32",
+        "image/gif",
+    );
+    test(
+        b"RIFF\x00\x00\x00\x00WEBPVP8 ",
+        "RIFF (little-endian) data, Web/P image",
+        "image/webp",
+    );
+}
+
+#[test]
+fn test_text() {
+    test(b"", "empty", "application/x-empty");
+    test(b"some text\n", "ASCII text", "text/plain");
+    test(
+        b"#!/bin/bash\nrm -rf /tmp/a\n",
+        "POSIX shell script, ASCII text executable",
+        "text/x-shellscript",
+    );
+    test(b"<?php echo 1; ?>", "PHP script, ASCII text", "text/x-php");
+    test(b"\x00\x01\x02\x03", "data", "application/octet-stream");
+}
+
+#[test]
+fn test_fragmented() {
+    let mut checker = Checker::new_without_yara(&make_rule(r#"magic.type() == "ASCII text""#));
+    checker.check_fragmented(&[(0, Some(b"some text".as_slice()))], true);
+
+    // Only the start of the memory is used, so nothing can be detected if there is no
+    // region starting at 0.
+    let mut checker = Checker::new_without_yara(&make_rule("not defined magic.type()"));
+    checker.check_fragmented(&[(100, Some(b"some text".as_slice()))], true);
+}
@@ -23,10 +23,14 @@ mod modules;
 mod namespaces;
 mod undefined;
 mod variables;
+mod with_expression;
 
 // Tests related to scanning of fragmented memory
 mod fragmented;
 
+// Tests related to scanning of streams
+mod stream;
+
 // Tests related to different limits set.
 mod limits;
 
@@ -36,10 +40,16 @@ mod limits;
 mod process;
 
 // Tests related to modules
+mod cuckoo;
+mod dex;
+#[cfg(feature = "object")]
+mod dotnet;
 #[cfg(feature = "object")]
 mod elf;
+mod lnk;
 #[cfg(feature = "object")]
 mod macho;
+mod magic;
 mod math;
 #[cfg(feature = "object")]
 mod pe;
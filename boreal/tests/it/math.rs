@@ -1,6 +1,6 @@
 use crate::{
     libyara_compat::util::ELF32_MIPS_FILE,
-    utils::{check, check_err, Checker},
+    utils::{check, check_boreal, check_err, Checker},
 };
 
 fn make_rule(cond: &str) -> String {
@@ -19,6 +19,12 @@ fn test(cond: &str, input: &[u8]) {
     check(&make_rule(cond), input, true);
 }
 
+/// Test functions that are not available in YARA.
+#[track_caller]
+fn test_boreal(cond: &str, input: &[u8]) {
+    check_boreal(&make_rule(cond), input, true);
+}
+
 #[track_caller]
 fn test_err(cond: &str, expected_err: &str) {
     check_err(&make_rule(cond), expected_err);
@@ -676,3 +682,107 @@ fn test_math_to_string() {
         "mem:5:30: error: invalid arguments types: [regex]",
     );
 }
+
+#[test]
+fn test_math_chi_square() {
+    let all_bytes: Vec<u8> = (0..=255).collect();
+
+    test_boreal("math.chi_square(0, filesize) == 0.0", &all_bytes);
+    test_boreal("math.chi_square(0, filesize) == 65280.0", &[0; 256]);
+    test_boreal(
+        r#"math.in_range(math.chi_square("AC"), 253.9999, 254.0001)"#,
+        b"",
+    );
+    test_boreal(
+        "math.chi_square(10, 2) == math.chi_square(\"\\x0a\\x0b\")",
+        &all_bytes,
+    );
+
+    test_boreal(r#"not defined math.chi_square("")"#, b"");
+    test_boreal("not defined math.chi_square(-1, 2)", b"ab");
+    test_boreal("not defined math.chi_square(5, 2)", b"ab");
+
+    test_err(
+        "math.chi_square(1) == 1",
+        "mem:5:31: error: invalid arguments types: [integer]",
+    );
+}
+
+#[test]
+fn test_math_distribution_distance() {
+    test_boreal(r#"math.distribution_distance("AC", "CA") == 0.0"#, b"");
+    test_boreal(r#"math.distribution_distance("AB", "CD") == 1.0"#, b"");
+    test_boreal(
+        r#"math.in_range(math.distribution_distance("AAB", "AB"), 0.16666, 0.16667)"#,
+        b"",
+    );
+    test_boreal(
+        r#"math.distribution_distance(0, 4, "BAAB") == 0.0"#,
+        b"ABAB",
+    );
+    test_boreal(
+        "math.distribution_distance(0, 2, 2, 2) == 0.0 and \
+         math.distribution_distance(0, 2, 4, 2) == 1.0",
+        b"ABBACD",
+    );
+
+    test_boreal(r#"not defined math.distribution_distance("", "A")"#, b"");
+    test_boreal(
+        r#"not defined math.distribution_distance(5, 2, "A")"#,
+        b"ab",
+    );
+    test_boreal("not defined math.distribution_distance(0, 2, -1, 2)", b"ab");
+
+    test_err(
+        r#"math.distribution_distance("A") == 1"#,
+        "mem:5:42: error: invalid arguments types: [bytes]",
+    );
+}
+
+#[test]
+fn test_math_max_entropy() {
+    test_boreal(
+        r#"math.in_range(math.max_entropy("AAAAABCDAAAA", 4), 1.99999, 2.00001)"#,
+        b"",
+    );
+    test_boreal(r#"math.max_entropy("AAAAAAAA", 4) == 0.0"#, b"");
+    // Window bigger than the input
+    test_boreal(r#"math.max_entropy("AC", 4) == 1.0"#, b"");
+    test_boreal(
+        "math.in_range(math.max_entropy(0, filesize, 4), 1.99999, 2.00001) and \
+         math.in_range(math.max_entropy(0, 8, 4), 0.99999, 1.00001)",
+        b"AAAAAABBABCDAA",
+    );
+    test_boreal(
+        "math.in_range(math.max_entropy(0, filesize, filesize), 3.71766, 3.71767)",
+        ELF32_MIPS_FILE,
+    );
+
+    test_boreal(r#"not defined math.max_entropy("", 4)"#, b"");
+    test_boreal(r#"not defined math.max_entropy("AC", 0)"#, b"");
+    test_boreal("not defined math.max_entropy(-1, 2, 2)", b"ab");
+    test_boreal("not defined math.max_entropy(0, 2, -2)", b"ab");
+
+    test_err(
+        "math.max_entropy(0, 1) == 1",
+        "mem:5:32: error: invalid arguments types: [integer, integer]",
+    );
+
+    // Test that fragmented memory still works if chunks are contiguous
+    let mut checker = Checker::new_without_yara(&make_rule(
+        "math.in_range(math.max_entropy(2, 10, 4), 1.99999, 2.00001)",
+    ));
+    checker.check_fragmented(
+        &[
+            (0, Some(b"AAAAAA")),
+            (6, Some(b"BBAB")),
+            (10, Some(b"CDAA")),
+        ],
+        true,
+    );
+
+    // Missing starting bytes of holes means undefined
+    let mut checker =
+        Checker::new_without_yara(&make_rule("not defined math.max_entropy(2, 10, 4)"));
+    checker.check_fragmented(&[(0, Some(b"AAAAAA")), (8, Some(b"ABCDAA"))], true);
+}
@@ -1,3 +1,6 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
 use crate::utils::{check_err, Compiler};
 
 // An import is reused in the same namespace
@@ -364,3 +367,141 @@ rule f {
         ],
     );
 }
+
+#[test]
+fn test_include_callback() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+
+    let mut compiler = Compiler::new_without_yara();
+    let calls2 = Arc::clone(&calls);
+    compiler
+        .compiler
+        .set_include_callback(move |path, current_path, namespace| {
+            calls2.lock().unwrap().push((
+                path.to_owned(),
+                current_path.map(Path::to_path_buf),
+                namespace.map(ToOwned::to_owned),
+            ));
+            match path {
+                "a.yar" => Ok("include \"dir/b.yar\"\nrule a { condition: b }".to_owned()),
+                "dir/b.yar" => Ok("rule b { condition: true }".to_owned()),
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "unknown rules",
+                )),
+            }
+        });
+
+    let _status = compiler
+        .compiler
+        .add_rules_str_in_namespace("include \"a.yar\"\nrule root { condition: a }", "ns")
+        .unwrap();
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            ("a.yar".to_owned(), None, Some("ns".to_owned())),
+            (
+                "dir/b.yar".to_owned(),
+                Some(PathBuf::from("a.yar")),
+                Some("ns".to_owned())
+            ),
+        ]
+    );
+
+    // Errors returned by the callback are reported on the include
+    let rules = "include \"c.yar\"";
+    let err = compiler.compiler.add_rules_str(rules).unwrap_err();
+    assert!(err.path.is_none());
+    assert_eq!(
+        err.to_short_description("mem", rules).trim_end(),
+        "mem:1:1: error: cannot include `c.yar`: unknown rules"
+    );
+
+    let mut checker = compiler.into_checker();
+    checker.check_rule_matches(b"", &["ns:root", "ns:a", "ns:b"]);
+}
+
+#[test]
+fn test_include_cycle() {
+    // Cycle through the filesystem
+    let test_dir = tempfile::TempDir::new().unwrap();
+    let path = test_dir.path().join("a.yar");
+    std::fs::write(&path, "include \"b.yar\"\nrule a { condition: true }").unwrap();
+    std::fs::write(
+        test_dir.path().join("b.yar"),
+        "include \"a.yar\"\nrule b { condition: true }",
+    )
+    .unwrap();
+
+    let mut compiler = boreal::Compiler::new();
+    let err = compiler.add_rules_file(&path).unwrap_err();
+    assert_eq!(err.path.as_ref().unwrap().file_name().unwrap(), "b.yar");
+    assert!(err.to_diagnostic().message.starts_with("include cycle: `"));
+
+    // Cycle through the include callback
+    let mut compiler = boreal::Compiler::new();
+    compiler.set_include_callback(|path, _, _| match path {
+        "a.yar" => Ok("include \"b.yar\"".to_owned()),
+        _ => Ok("include \"a.yar\"".to_owned()),
+    });
+    let err = compiler.add_rules_str("include \"a.yar\"").unwrap_err();
+    // Paths given to the callback are not resolved, so the cycle is only detected once the
+    // same path is included again from the same file.
+    assert_eq!(err.path, Some(PathBuf::from("a.yar")));
+    assert_eq!(
+        err.to_diagnostic().message,
+        "include cycle: `b.yar` is already being included"
+    );
+
+    // The same path included from different files is not a cycle.
+    let mut compiler = Compiler::new_without_yara();
+    compiler
+        .compiler
+        .set_include_callback(|path, current_path, _| {
+            let contents = match (current_path.and_then(Path::to_str), path) {
+                (None, "a/rules.yar") => "include \"common.yar\"\nrule a { condition: true }",
+                (Some("a/rules.yar"), "common.yar") => {
+                    "include \"../b/rules.yar\"\nrule common_a { condition: true }"
+                }
+                (Some("common.yar"), "../b/rules.yar") => {
+                    "include \"common.yar\"\nrule b { condition: true }"
+                }
+                (Some("../b/rules.yar"), "common.yar") => "rule common_b { condition: true }",
+                _ => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "unknown rules",
+                    ))
+                }
+            };
+            Ok(contents.to_owned())
+        });
+    let _status = compiler
+        .compiler
+        .add_rules_str("include \"a/rules.yar\"")
+        .unwrap();
+    let mut checker = compiler.into_checker();
+    checker.check_rule_matches(
+        b"",
+        &[
+            "default:a",
+            "default:common_a",
+            "default:b",
+            "default:common_b",
+        ],
+    );
+}
+
+#[test]
+fn test_includes_disabled() {
+    let mut compiler = boreal::Compiler::new();
+    compiler.set_params(boreal::compiler::CompilerParams::default().disable_includes(true));
+    compiler.set_include_callback(|_, _, _| Ok("rule b { condition: true }".to_owned()));
+
+    let rules = "rule a { condition: true }\ninclude \"b.yar\"";
+    let err = compiler.add_rules_str(rules).unwrap_err();
+    assert_eq!(
+        err.to_short_description("mem", rules).trim_end(),
+        "mem:2:1: error: includes are disabled"
+    );
+}
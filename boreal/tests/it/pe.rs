@@ -1,6 +1,22 @@
 use boreal::module::Pe;
 
-use crate::utils::{check_file, compare_module_values_on_file};
+use crate::utils::{check_boreal, check_file, compare_module_values_on_file};
+
+/// Guard flags constants, which are not declared in yara.
+const GUARD_FLAGS: &[&str] = &[
+    "pe.IMAGE_GUARD_CF_INSTRUMENTED",
+    "pe.IMAGE_GUARD_CFW_INSTRUMENTED",
+    "pe.IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT",
+    "pe.IMAGE_GUARD_SECURITY_COOKIE_UNUSED",
+    "pe.IMAGE_GUARD_PROTECT_DELAYLOAD_IAT",
+    "pe.IMAGE_GUARD_DELAYLOAD_IAT_IN_ITS_OWN_SECTION",
+    "pe.IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT",
+    "pe.IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION",
+    "pe.IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT",
+    "pe.IMAGE_GUARD_RF_INSTRUMENTED",
+    "pe.IMAGE_GUARD_RF_ENABLE",
+    "pe.IMAGE_GUARD_RF_STRICT",
+];
 
 #[test]
 fn test_rva_to_offset() {
@@ -366,7 +382,7 @@ rule test {
     );
 }
 
-#[cfg(feature = "authenticode")]
+#[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
 #[test]
 fn test_signatures_valid_on() {
     fn check_valid_on(value: i64, expected_res: bool) {
@@ -395,7 +411,7 @@ fn test_signatures_valid_on() {
     check_valid_on(1559692800, false);
 }
 
-#[cfg(feature = "authenticode")]
+#[cfg(any(feature = "authenticode", feature = "authenticode-pure"))]
 #[test]
 fn test_signatures_nested() {
     check_file(
@@ -428,341 +444,764 @@ rule test {
     );
 }
 
+#[test]
+fn test_tls_callbacks() {
+    let mem = std::fs::read("tests/assets/libyara/data/tiny").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        pe.number_of_tls_callbacks == 2 and
+        pe.tls_callbacks[0] == 0x4018c0 and
+        pe.tls_callbacks[1] == 0x401870
+}"#,
+        &mem,
+        true,
+    );
+
+    // TLS directory without any callbacks
+    let mem = std::fs::read("tests/assets/yara_1561/x64/FileTest.exe").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        not defined pe.number_of_tls_callbacks and
+        not defined pe.tls_callbacks[0]
+}"#,
+        &mem,
+        true,
+    );
+}
+
+#[test]
+fn test_load_config() {
+    let mem = std::fs::read("tests/assets/libyara/data/mtxex.dll").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        pe.load_config.size == 264 and
+        pe.load_config.time_date_stamp == 0 and
+        pe.load_config.security_cookie == 0x180003008 and
+        pe.load_config.se_handler_table == 0 and
+        pe.load_config.se_handler_count == 0 and
+        pe.load_config.guard_cf_check_function_pointer == 0x1800021e8 and
+        pe.load_config.guard_cf_dispatch_function_pointer == 0x1800021f0 and
+        pe.load_config.guard_cf_function_table == 0x180002220 and
+        pe.load_config.guard_cf_function_count == 5 and
+        pe.load_config.guard_flags == 0x10017500 and
+        pe.load_config.guard_flags & pe.IMAGE_GUARD_CF_INSTRUMENTED and
+        pe.load_config.guard_flags & pe.IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT and
+        pe.load_config.guard_flags & pe.IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT and
+        not (pe.load_config.guard_flags & pe.IMAGE_GUARD_CFW_INSTRUMENTED)
+}"#,
+        &mem,
+        true,
+    );
+
+    // 32 bits, with a SafeSEH table.
+    let mem = std::fs::read("tests/assets/pe/ord_and_delay.exe").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        pe.load_config.size == 188 and
+        pe.load_config.security_cookie == 0x413004 and
+        pe.load_config.se_handler_table == 0x411d80 and
+        pe.load_config.se_handler_count == 2 and
+        pe.load_config.guard_cf_check_function_pointer == 0x40d124 and
+        pe.load_config.guard_cf_function_count == 0 and
+        pe.load_config.guard_flags == pe.IMAGE_GUARD_CF_INSTRUMENTED
+}"#,
+        &mem,
+        true,
+    );
+
+    let mem = std::fs::read("tests/assets/libyara/data/tiny").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        not defined pe.load_config.size
+}"#,
+        &mem,
+        true,
+    );
+}
+
+#[test]
+fn test_runtime_functions() {
+    let test = |path, cond| {
+        let mem = std::fs::read(path).unwrap();
+        check_boreal(
+            &format!("import \"pe\" rule test {{ condition: {cond} }}"),
+            &mem,
+            true,
+        );
+    };
+
+    test(
+        "tests/assets/libyara/data/mtxex.dll",
+        "pe.number_of_runtime_functions == 22",
+    );
+    test(
+        "tests/assets/yara_1561/x64/FileTest.exe",
+        "pe.number_of_runtime_functions == 1161",
+    );
+    test(
+        "tests/assets/libyara/data/pe_mingw",
+        "pe.number_of_runtime_functions == 50",
+    );
+    // No exception directory on x86
+    test(
+        "tests/assets/pe/ord_and_delay.exe",
+        "not defined pe.number_of_runtime_functions",
+    );
+}
+
+#[test]
+fn test_base_relocations() {
+    let mem = std::fs::read("tests/assets/pe/ord_and_delay.exe").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        pe.number_of_base_relocation_blocks == 19 and
+        pe.base_relocation_blocks[0].virtual_address == 0x1000 and
+        pe.base_relocation_blocks[0].number_of_entries == 172 and
+        pe.base_relocation_blocks[3].virtual_address == 0x4000 and
+        pe.base_relocation_blocks[3].number_of_entries == 86
+}"#,
+        &mem,
+        true,
+    );
+
+    let mem = std::fs::read("tests/assets/libyara/data/mtxex.dll").unwrap();
+    check_boreal(
+        r#"import "pe"
+rule test {
+    condition:
+        pe.number_of_base_relocation_blocks == 1 and
+        pe.base_relocation_blocks[0].virtual_address == 0x2000 and
+        pe.base_relocation_blocks[0].number_of_entries == 10
+}"#,
+        &mem,
+        true,
+    );
+}
+
+#[test]
+fn test_bound_imports() {
+    let rule = r#"import "pe"
+rule test {
+    condition:
+        pe.number_of_bound_imports == 1 and
+        pe.bound_imports[0].library_name == "KERNEL32.dll" and
+        pe.bound_imports[0].timestamp == 0x12345678
+}"#;
+
+    let mut mem = std::fs::read("tests/assets/libyara/data/tiny").unwrap();
+    check_boreal(rule, &mem, false);
+
+    // Add a bound import directory in the padding after the section headers. The address
+    // of this directory is a file offset.
+    mem[0x150..0x158].copy_from_slice(&[0x90, 0x02, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00]);
+    mem[0x290..0x298].copy_from_slice(&[0x78, 0x56, 0x34, 0x12, 0x10, 0x00, 0x00, 0x00]);
+    mem[0x2a0..0x2ad].copy_from_slice(b"KERNEL32.dll\0");
+    check_boreal(rule, &mem, true);
+}
+
 #[test]
 fn test_coverage_pe_ord_and_delay() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
     ];
     let path = "tests/assets/pe/ord_and_delay.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_resources_only() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/pe/resources_only.dll";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_079a472d() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
     ];
     let path = "tests/assets/libyara/data/\
         079a472d22290a94ebb212aa8015cdc8dd28a968c6b4d3b88acdd58ce2d3b885";
 
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_079a472d_upx() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
     ];
     let path = "tests/assets/libyara/data/\
         079a472d22290a94ebb212aa8015cdc8dd28a968c6b4d3b88acdd58ce2d3b885.upx";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_0ca09bde() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
     ];
     let path = "tests/assets/libyara/data/\
         0ca09bde7602769120fadc4f7a4147347a7a97271370583586c9e587fd396171";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_33fc70f9() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/libyara/data/\
         33fc70f99be6d2833ae48852d611c8048d0c053ed0b2c626db4dbe902832a08b";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_3b8b9015() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/libyara/data/\
         3b8b90159fa9b6048cc5410c5d53f116943564e4d05b04a843f9b3d0540d0c1c";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_ca21e1c32() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/libyara/data/\
         ca21e1c32065352d352be6cde97f89c141d7737ea92434831f998080783d5386";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_mtxex() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/libyara/data/mtxex.dll";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_mtxex_modified() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/libyara/data/mtxex_modified_rsrc_rva.dll";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &[]);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(Pe::default(), path, true, GUARD_FLAGS);
 }
 
 #[test]
 fn test_coverage_pe_libyara_pe_imports() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
     ];
     let path = "tests/assets/libyara/data/pe_imports";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_pe_mingw() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_tls_callbacks",
+        "pe.tls_callbacks",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/libyara/data/pe_mingw";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_tiny() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_tls_callbacks",
+        "pe.tls_callbacks",
     ];
     let path = "tests/assets/libyara/data/tiny";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_tiny_51ff() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_tls_callbacks",
+        "pe.tls_callbacks",
     ];
     let path = "tests/assets/libyara/data/tiny-idata-51ff";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_tiny_5200() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_tls_callbacks",
+        "pe.tls_callbacks",
     ];
     let path = "tests/assets/libyara/data/tiny-idata-5200";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_libyara_tiny_overlay() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_tls_callbacks",
+        "pe.tls_callbacks",
     ];
     let path = "tests/assets/libyara/data/tiny-overlay";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_1561_std() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/yara_1561/x64/FileTest.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_1561_align_40() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/yara_1561/x64/FileTest_alignment_40.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_1561_32_align_40() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
     ];
     let path = "tests/assets/yara_1561/Win32/FileTest_Alignment_40.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_1561_32_section1() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/yara_1561/Win32/FileTest_Section1_Starts_at_header.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_c6f9709f() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/libyara/data/\
          c6f9709feccf42f2d9e22057182fe185f177fb9daaa2649b4669a24f2ee7e3ba_0h_410h";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_long_name_exporter() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
     ];
     let path = "tests/assets/pe/long_name_exporter.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_long_dll_name() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/pe/long_dll_name.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_long_name_importer() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.number_of_runtime_functions",
     ];
     let path = "tests/assets/pe/long_name_importer.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
 fn test_coverage_pe_invalid_dll_names() {
     let diffs = [
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.number_of_signatures",
-        #[cfg(not(feature = "authenticode"))]
+        #[cfg(not(any(feature = "authenticode", feature = "authenticode-pure")))]
         "pe.is_signed",
+        "pe.load_config",
+        "pe.number_of_base_relocation_blocks",
+        "pe.base_relocation_blocks",
     ];
     let path = "tests/assets/pe/invalid_dll_names.exe";
-    compare_module_values_on_file(Pe::default(), path, false, &diffs);
-    compare_module_values_on_file(Pe::default(), path, true, &diffs);
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        false,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
+    compare_module_values_on_file(
+        Pe::default(),
+        path,
+        true,
+        &[&diffs[..], GUARD_FLAGS].concat(),
+    );
 }
 
 #[test]
@@ -0,0 +1,176 @@
+use std::io::Read;
+
+use boreal::scanner::{ScanError, ScanParams};
+
+use crate::utils::get_boreal_full_matches;
+
+/// Reader returning at most a few bytes on each read.
+struct SlowReader<'a> {
+    mem: &'a [u8],
+    max_read: usize,
+}
+
+impl Read for SlowReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = std::cmp::min(buf.len(), self.max_read);
+        self.mem.read(&mut buf[..len])
+    }
+}
+
+/// Reader failing after some bytes have been read.
+struct FailingReader<'a> {
+    mem: &'a [u8],
+}
+
+impl Read for FailingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.mem.is_empty() {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "broken"))
+        } else {
+            self.mem.read(buf)
+        }
+    }
+}
+
+fn build_scanner(rules: &str, params: ScanParams) -> boreal::Scanner {
+    let mut compiler = boreal::Compiler::new();
+    let _r = compiler.add_rules_str(rules).unwrap();
+    let mut scanner = compiler.into_scanner();
+    scanner.set_scan_params(params);
+    scanner
+}
+
+#[test]
+fn test_stream_scan_chunk_boundaries() {
+    let rules = r#"
+rule a {
+    strings:
+        $a = "boreal"
+        $b = { 61 62 [0-20] 63 }
+        $c = /x[a-z]{10,30}y/
+        $d = "word" fullword
+        $e = "wide" wide
+        $f = /\bab[^.]+?cd/
+    condition:
+        any of them
+}"#;
+
+    // Place the strings everywhere in the memory, so that all kinds of chunk boundaries
+    // are tested.
+    let tokens: &[&[u8]] = &[
+        b"boreal",
+        b"ab0123456789c",
+        b"xabcdefghijklmnopy",
+        b"word",
+        b"swords",
+        b"w\0i\0d\0e\0",
+        b"abzzzzzzzzzzcd",
+    ];
+    let mut mem = Vec::new();
+    let mut i = 0;
+    while mem.len() < 40_000 {
+        mem.extend(std::iter::repeat(b'.').take(i % 61));
+        mem.extend_from_slice(tokens[i % tokens.len()]);
+        i += 1;
+    }
+
+    for chunk_size in [
+        None,
+        Some(1),
+        Some(4096),
+        Some(5000),
+        Some(8193),
+        Some(12345),
+    ] {
+        let scanner = build_scanner(
+            rules,
+            ScanParams::default()
+                .compute_full_matches(true)
+                .memory_chunk_size(chunk_size),
+        );
+        let expected = scanner.scan_mem(&mem).unwrap();
+        let expected = get_boreal_full_matches(&expected);
+
+        let res = scanner.scan_reader(&*mem).unwrap();
+        assert_eq!(get_boreal_full_matches(&res), expected, "{chunk_size:?}");
+
+        let res = scanner
+            .scan_reader(SlowReader {
+                mem: &mem,
+                max_read: 7,
+            })
+            .unwrap();
+        assert_eq!(get_boreal_full_matches(&res), expected, "{chunk_size:?}");
+    }
+}
+
+#[test]
+fn test_stream_scan_semantics() {
+    let scanner = build_scanner(
+        r#"
+rule filesize_defined {
+    condition:
+        filesize == 10000
+}
+
+rule offset_undefined {
+    condition:
+        not defined uint8(0) and not defined entrypoint
+}
+
+rule offsets {
+    strings:
+        $a = "abc"
+    condition:
+        #a == 2 and @a[1] == 3 and @a[2] == 9990 and !a[2] == 3
+}
+"#,
+        ScanParams::default().memory_chunk_size(Some(4096)),
+    );
+
+    let mut mem = vec![0; 10_000];
+    mem[3..6].copy_from_slice(b"abc");
+    mem[9990..9993].copy_from_slice(b"abc");
+    let res = scanner.scan_reader(&*mem).unwrap();
+    let names: Vec<_> = res.matched_rules.iter().map(|r| r.name).collect();
+    assert_eq!(names, ["filesize_defined", "offset_undefined", "offsets"]);
+
+    // Empty stream
+    let res = scanner.scan_reader(std::io::empty()).unwrap();
+    let names: Vec<_> = res.matched_rules.iter().map(|r| r.name).collect();
+    assert_eq!(names, ["offset_undefined"]);
+}
+
+#[test]
+fn test_stream_scan_max_nb_matches() {
+    let scanner = build_scanner(
+        r#"
+rule a {
+    strings:
+        $a = "a"
+    condition:
+        #a == 3
+}"#,
+        ScanParams::default()
+            .string_max_nb_matches(3)
+            .memory_chunk_size(Some(4096)),
+    );
+
+    let res = scanner.scan_reader(&[b'a'; 10_000][..]).unwrap();
+    assert_eq!(res.matched_rules.len(), 1);
+    assert_eq!(res.matched_rules[0].matches[0].matches.len(), 3);
+}
+
+#[test]
+fn test_stream_scan_error() {
+    let scanner = build_scanner(
+        "rule a { strings: $a = \"abc\" condition: $a }",
+        ScanParams::default(),
+    );
+
+    let (err, res) = scanner
+        .scan_reader(FailingReader { mem: b"abc" })
+        .unwrap_err();
+    assert!(matches!(err, ScanError::CannotReadStream(_)));
+    assert!(res.matched_rules.is_empty());
+}
@@ -0,0 +1,184 @@
+use crate::utils::{build_rule, check, check_err, Checker};
+
+#[test]
+fn test_with_expression_errors() {
+    // Identifier declared twice
+    check_err(
+        &build_rule("with a = 1, a = 2 : (a == 1)"),
+        "mem:12:21: error: duplicated loop identifier a",
+    );
+
+    // Identifier already bound by a for expression
+    check_err(
+        &build_rule("for any i in (1, 2): (with i = 3 : (i == 3))"),
+        "mem:12:36: error: duplicated loop identifier i",
+    );
+
+    // Identifiers are only bound in the body
+    check_err(
+        &build_rule("(with b = 1 : (b == 1)) and b == 1"),
+        "mem:12:37: error: unknown identifier \"b\"",
+    );
+    check_err(
+        &build_rule("with b = b : (true)"),
+        "mem:12:18: error: unknown identifier \"b\"",
+    );
+
+    // Wrong type use of bounded identifier
+    check_err(
+        &build_rule("with b = 1 : (b == \"a\")"),
+        "error: expressions have invalid types",
+    );
+    check_err(
+        &build_rule("with b = tests.struct_array[0] : (b == 1)"),
+        "mem:12:43: error: wrong use of identifier",
+    );
+    check_err(
+        &build_rule("with b = tests.struct_array[0] : (b.j == 1)"),
+        "mem:12:44: error: unknown field \"j\"",
+    );
+
+    // Missing body
+    check_err(
+        &build_rule("with b = 1 : true"),
+        "mem:12:22: error: syntax error",
+    );
+}
+
+#[test]
+fn test_with_expression() {
+    check(
+        &build_rule("with one = 1, two = 2 : (one + two == 3)"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("with s = \"foo\", b = true : (b and s contains \"oo\")"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("with f = tests.fsum(1.0, 2.5) : (f == 3.5)"),
+        b"",
+        true,
+    );
+
+    // Declarations can use previous declarations
+    check(
+        &build_rule("with a = 2, b = a * 3, c = b + a : (c == 8)"),
+        b"",
+        true,
+    );
+
+    // Nested in other expressions
+    check(
+        &build_rule("with a = 1 : (with b = a + 1 : (b == 2 and a == 1))"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("for all i in (1..3): (with j = i * 2 : (j > i))"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("with n = 3 : (for all i in (1..n): (i <= n))"),
+        b"",
+        true,
+    );
+
+    // Bounded identifier shadows a rule name.
+    let mut checker = Checker::new(
+        r#"
+rule a { condition: true }
+rule b { condition: with a = false : (not a) }
+rule c { condition: with c = 1 : (c == 1 and a and b) }
+"#,
+    );
+    checker.check_count(b"", 3);
+}
+
+#[test]
+fn test_with_expression_modules() {
+    // Bind objects
+    check(
+        &build_rule(
+            r#"with s = tests.struct_dict["foo"] : (
+            s.i == 1 and s.s == "foo"
+        )"#,
+        ),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("with s = tests.struct_array[1], i = s.i : (i == 1)"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule(
+            "for any i in (0..1): (with s = tests.struct_array[i] : (s.i == 1 and i == 1))",
+        ),
+        b"",
+        true,
+    );
+
+    // Bind primitives
+    check(
+        &build_rule("with s = tests.string_array[1] : (s == \"bar\")"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule(
+            r#"with one = tests.constants.one, str = tests.constants.str : (
+            one == 1 and str == "str"
+        )"#,
+        ),
+        b"",
+        true,
+    );
+}
+
+#[test]
+fn test_with_expression_variables() {
+    let mut checker = Checker::new(&build_rule("with n = #a0 : (n == 2)"));
+    checker.check(b"", false);
+    checker.check(b"a0", false);
+    checker.check(b"a0 a0", true);
+
+    let mut checker = Checker::new(&build_rule("with a = @a1[1], b = @a2[1] : (a < b)"));
+    checker.check(b"", false);
+    checker.check(b"a1 a2", true);
+    checker.check(b"a2 a1", false);
+}
+
+#[test]
+fn test_with_expression_undefined() {
+    // An undefined value can be bound, the body is evaluated as usual.
+    check(
+        &build_rule("with u = tests.undefined.i : (true)"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("with u = tests.undefined.i : (u == 1)"),
+        b"",
+        false,
+    );
+    check(
+        &build_rule("with u = tests.undefined.i : (not defined u)"),
+        b"",
+        true,
+    );
+    check(
+        &build_rule("defined (with u = tests.undefined.i : (u == 1))"),
+        b"",
+        false,
+    );
+    check(
+        &build_rule("with s = tests.struct_array[5] : (not defined s.i)"),
+        b"",
+        true,
+    );
+}